@@ -5,18 +5,425 @@ use std::io::{self, Write};
 use std::process;
 use std::str::FromStr;
 use tracing::{info, warn};
-use winetricks_lib::{Config, Executor, Result, VerbCategory, VerbRegistry, WinetricksError};
+use winetricks_lib::{
+    Config, Executor, HealthSeverity, OutputFormat, Result, VerbCategory, VerbMetadata,
+    VerbRegistry, WinetricksError,
+};
+
+/// Columns used when printing verbs as JSON or CSV
+const VERB_COLUMNS: [&str; 3] = ["name", "title", "category"];
+
+/// Columns of list-cached --sizes as JSON or CSV (size in bytes)
+const CACHED_SIZE_COLUMNS: [&str; 4] = ["name", "title", "category", "size"];
+
+/// Command keywords that can show up in winetricks.log but aren't verbs
+const KNOWN_COMMANDS: &[&str] = &[
+    "list",
+    "list-installed",
+    "list-all",
+    "list-cached",
+    "list-download",
+    "list-manual-download",
+    "info",
+    "list-conflicts",
+    "list-files",
+    "find-verb",
+    "preload",
+    "clean-verb",
+    "export-verbs",
+    "generate-docs",
+    "import-verbs",
+    "metadata",
+    "apps",
+    "dlls",
+    "fonts",
+    "settings",
+    "benchmarks",
+    "codecs",
+    "runtimes",
+    "annihilate",
+    "prefix",
+    "export-prefix",
+    "import-prefix",
+    "folder",
+    "winecfg",
+    "regedit",
+    "taskmgr",
+    "list-processes",
+    "kill-processes",
+    "explorer",
+    "uninstaller",
+    "shell",
+    "winecmd",
+    "help",
+    "uninstall",
+    "reinstall",
+];
+
+/// Usage of the prefix create/delete/info command
+const PREFIX_USAGE: &str =
+    "Usage: winetricks prefix create <name> [--arch=32|64] [--windows-version=VERSION]
+       winetricks prefix delete <name>
+       winetricks prefix info <name>
+       winetricks prefix check <name>
+       winetricks prefix list";
 
 async fn install_verb(config: &Config, verb_name: &str) -> Result<()> {
     let mut executor = Executor::new(config.clone()).await?;
     executor.install_verb(verb_name).await
 }
 
+/// Delete a wineprefix and the .desktop entries pointing into it, asking first unless unattended
+/// Returns false if the user cancelled or the prefix didn't exist
+fn delete_prefix(wineprefix: &std::path::Path, unattended: bool) -> Result<bool> {
+    // Ask for confirmation unless unattended
+    if !unattended {
+        eprintln!("WARNING: This will DELETE ALL DATA AND APPLICATIONS inside:");
+        eprintln!("  {}", wineprefix.display());
+        eprintln!("This action cannot be undone!");
+        print!("Are you sure you want to continue? [y/N] ");
+        io::stdout().flush().unwrap();
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer).unwrap();
+
+        if !answer.trim().to_lowercase().starts_with('y') {
+            println!("Cancelled.");
+            return Ok(false);
+        }
+    } else {
+        eprintln!(
+            "WARNING: Unattended mode, deleting: {}",
+            wineprefix.display()
+        );
+    }
+
+    // Delete wineprefix
+    if wineprefix.exists() {
+        info!("Deleting wineprefix: {:?}", wineprefix);
+        std::fs::remove_dir_all(wineprefix)
+            .map_err(|e| WinetricksError::Verb(format!("Failed to delete wineprefix: {}", e)))?;
+    } else {
+        eprintln!("Wineprefix does not exist: {}", wineprefix.display());
+        return Ok(false);
+    }
+
+    // Clean up .desktop files in XDG_DATA_HOME/applications
+    if let Ok(data_home) = std::env::var("XDG_DATA_HOME") {
+        let apps_dir = std::path::Path::new(&data_home).join("applications");
+        if apps_dir.exists() {
+            // Find and remove .desktop files referencing this wineprefix
+            if let Ok(entries) = std::fs::read_dir(&apps_dir) {
+                for entry in entries.flatten() {
+                    if let Some(file_name) = entry.file_name().to_str() {
+                        if file_name.ends_with(".desktop") {
+                            if let Ok(content) = std::fs::read_to_string(entry.path()) {
+                                if content.contains(wineprefix.to_str().unwrap_or("")) {
+                                    let _ = std::fs::remove_file(entry.path());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Clean up desktop items
+    // Try XDG_DESKTOP_DIR env var first
+    let desktop_path = if let Ok(desktop_dir) = std::env::var("XDG_DESKTOP_DIR") {
+        std::path::PathBuf::from(desktop_dir)
+    } else {
+        // Try reading from user-dirs.dirs config file
+        let mut desktop_dir = None;
+        if let Ok(config_home) = std::env::var("XDG_CONFIG_HOME") {
+            let user_dirs = std::path::Path::new(&config_home).join("user-dirs.dirs");
+            if user_dirs.exists() {
+                if let Ok(content) = std::fs::read_to_string(&user_dirs) {
+                    for line in content.lines() {
+                        if line.starts_with("XDG_DESKTOP_DIR=") {
+                            let value = line
+                                .trim_start_matches("XDG_DESKTOP_DIR=\"")
+                                .trim_end_matches("\"");
+                            // Expand $HOME if present
+                            if let Ok(home) = std::env::var("HOME") {
+                                desktop_dir = Some(value.replace("$HOME", &home));
+                            } else {
+                                desktop_dir = Some(value.to_string());
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        // Fall back to ~/Desktop
+        desktop_dir
+            .unwrap_or_else(|| {
+                std::env::var("HOME")
+                    .map(|h| format!("{}/Desktop", h))
+                    .unwrap_or_else(|_| "~/Desktop".to_string())
+            })
+            .into()
+    };
+
+    if desktop_path.exists() {
+        if let Ok(entries) = std::fs::read_dir(&desktop_path) {
+            for entry in entries.flatten() {
+                if let Some(file_name) = entry.file_name().to_str() {
+                    if file_name.ends_with(".desktop") {
+                        if let Ok(content) = std::fs::read_to_string(entry.path()) {
+                            if content.contains(wineprefix.to_str().unwrap_or("")) {
+                                let _ = std::fs::remove_file(entry.path());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    println!("Wineprefix deleted: {}", wineprefix.display());
+    Ok(true)
+}
+
+/// Print a readiness report for a verb and its prerequisites (--check-prereqs)
+/// Returns whether everything is ready to install
+async fn check_prereqs(config: &Config, verb_name: &str) -> Result<bool> {
+    let executor = Executor::new(config.clone()).await?;
+    let reports = executor.check_prereqs(verb_name).await?;
+    let ready = reports.iter().all(|r| r.is_ready());
+
+    if config.output_format != OutputFormat::Text {
+        let records: Vec<Vec<String>> = reports
+            .iter()
+            .map(|r| {
+                vec![
+                    r.verb.clone(),
+                    r.is_ready().to_string(),
+                    r.installed.to_string(),
+                    r.download_bytes.to_string(),
+                    r.problems.join("; "),
+                ]
+            })
+            .collect();
+        print_records(
+            config.output_format,
+            &["verb", "ready", "installed", "download_bytes", "problems"],
+            &records,
+        );
+        return Ok(ready);
+    }
+
+    println!("Prerequisite check for {}:", verb_name);
+    for report in &reports {
+        let status = if !report.is_ready() {
+            "NOT READY".to_string()
+        } else if report.installed {
+            "already installed".to_string()
+        } else if report.download_bytes > 0 {
+            format!("ready ({} to download)", format_size(report.download_bytes))
+        } else {
+            "ready".to_string()
+        };
+        println!("  {:<20} {}", report.verb, status);
+        for problem in &report.problems {
+            println!("      - {}", problem);
+        }
+    }
+
+    let total: u64 = reports.iter().map(|r| r.download_bytes).sum();
+    if total > 0 {
+        println!("Total download: {}", format_size(total));
+    }
+    if ready {
+        println!("{} is ready to install", verb_name);
+    } else {
+        println!(
+            "{} can't be installed until the problems above are fixed",
+            verb_name
+        );
+    }
+    Ok(ready)
+}
+
+/// Install a verb into a throwaway tmpfs prefix and print the result (--isolate-sandbox)
+/// Returns whether the verb installed cleanly
+async fn verify_in_sandbox(config: &Config, verb_name: &str) -> Result<bool> {
+    let result = winetricks_lib::sandbox::verify_in_sandbox(config, verb_name).await?;
+
+    if config.output_format != OutputFormat::Text {
+        let records = vec![vec![
+            result.verb.clone(),
+            result.passed().to_string(),
+            result.success.to_string(),
+            result.installed.to_string(),
+            result.duration.as_secs().to_string(),
+            result.exit_code().to_string(),
+            result.error.clone().unwrap_or_default(),
+        ]];
+        print_records(
+            config.output_format,
+            &[
+                "verb",
+                "passed",
+                "success",
+                "installed",
+                "duration_secs",
+                "exit_code",
+                "error",
+            ],
+            &records,
+        );
+        return Ok(result.passed());
+    }
+
+    println!("Sandbox verification for {}:", result.verb);
+    println!(
+        "  Install:   {}",
+        if result.success { "ok" } else { "FAILED" }
+    );
+    println!(
+        "  Installed: {}",
+        if result.installed { "yes" } else { "no" }
+    );
+    println!("  Duration:  {}s", result.duration.as_secs());
+    if let Some(ref error) = result.error {
+        println!("  Error:     {}", error);
+    }
+    println!("  Exit code: {}", result.exit_code());
+    Ok(result.passed())
+}
+
 async fn uninstall_verb(config: &Config, verb_name: &str) -> Result<()> {
     let mut executor = Executor::new(config.clone()).await?;
     executor.uninstall_verb(verb_name).await
 }
 
+/// Quote a CSV field if it contains separators, quotes or newlines
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Print records as a JSON array of objects or as CSV with a header line
+fn print_records(format: OutputFormat, columns: &[&str], records: &[Vec<String>]) {
+    match format {
+        OutputFormat::Json => {
+            let objects: Vec<serde_json::Value> = records
+                .iter()
+                .map(|record| {
+                    let mut object = serde_json::Map::new();
+                    for (column, value) in columns.iter().zip(record) {
+                        object.insert(column.to_string(), serde_json::Value::from(value.as_str()));
+                    }
+                    serde_json::Value::Object(object)
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&objects).unwrap_or_else(|_| "[]".to_string())
+            );
+        }
+        OutputFormat::Csv => {
+            println!("{}", columns.join(","));
+            for record in records {
+                let fields: Vec<String> = record.iter().map(|v| csv_field(v)).collect();
+                println!("{}", fields.join(","));
+            }
+        }
+        OutputFormat::Text => {
+            for record in records {
+                println!("{}", record.join(" "));
+            }
+        }
+    }
+}
+
+/// Print verbs in the selected output format (text prints one name per line)
+fn print_verbs(format: OutputFormat, verbs: &[&VerbMetadata]) {
+    if format == OutputFormat::Text {
+        for verb in verbs {
+            println!("{}", verb.name);
+        }
+        return;
+    }
+
+    let records: Vec<Vec<String>> = verbs
+        .iter()
+        .map(|verb| {
+            vec![
+                verb.name.clone(),
+                verb.title.clone(),
+                verb.category.as_str().to_string(),
+            ]
+        })
+        .collect();
+    print_records(format, &VERB_COLUMNS, &records);
+}
+
+/// URL as a terminal hyperlink (OSC 8) when stdout is a terminal, plain text otherwise
+fn hyperlink(url: &str) -> String {
+    use std::io::IsTerminal;
+    if std::io::stdout().is_terminal() {
+        format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, url)
+    } else {
+        url.to_string()
+    }
+}
+
+/// Human-readable file size (1.5 MiB)
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Human-readable age of a timestamp (3 days ago)
+fn format_age(time: std::time::SystemTime) -> String {
+    let secs = time.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+    let (count, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86399 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+    format!(
+        "{} {}{} ago",
+        count,
+        unit,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+/// Print an error message for a verb in the selected output format
+fn print_error(format: OutputFormat, verb_name: &str, message: &str) {
+    match format {
+        OutputFormat::Json => {
+            let error = serde_json::json!({ "verb": verb_name, "error": message });
+            eprintln!("{}", error);
+        }
+        OutputFormat::Csv => {
+            eprintln!("verb,error");
+            eprintln!("{},{}", csv_field(verb_name), csv_field(message));
+        }
+        OutputFormat::Text => {
+            eprintln!("{}", message);
+        }
+    }
+}
+
 fn print_help() {
     println!(
         r#"Winetricks - Package manager for Wine
@@ -28,25 +435,43 @@ Executes verbs to install applications, DLLs, fonts, or change Wine settings.
 
 COMMANDS:
     list                  List categories
+    list --tag=TAG        List verbs tagged TAG (e.g. directx, gaming, runtime)
     list-all              List all categories and their verbs
     list-installed        List already-installed verbs
+    list-conflicts        List installed verbs that conflict with each other
+    list-files VERB       List files a verb placed in the wineprefix
+    find-verb FILE        List verbs that install FILE (e.g. a DLL named in a Windows error)
+    preload VERB...       Download the files of the given verbs into the cache
+    clean-verb VERB...    Delete the cached downloads of the given verbs
+    clean-verb --all      Delete cached downloads of verbs not installed in any prefix
+    export-verbs          Print all verb metadata as a JSON array
+    generate-docs         Print a Markdown reference of all verbs (e.g. > VERBS.md)
+    import-verbs FILE     Add verbs from a JSON array, replacing verbs with the same name
+    metadata update       Download the latest verb metadata, show what changed and replace the cache
     list-cached           List verbs with cached files
+    list-cached --sizes   List cached verbs with their disk usage (--sort=size: largest first)
     list-download         List verbs that auto-download (media=download)
-    list-manual-download  List verbs that require manual download
+    list-manual-download  List verbs that require manual download (or a DVD/CD-ROM)
+    info VERB             Show a verb's details and where to get manually downloaded files
     
     apps list             List verbs in category 'applications'
     benchmarks list       List verbs in category 'benchmarks'
+    codecs list           List verbs in category 'codecs'
     dlls list             List verbs in category 'dlls'
     fonts list            List verbs in category 'fonts'
+    runtimes list         List verbs in category 'runtimes'
     settings list         List verbs in category 'settings'
     
     reinstall VERB        Force reinstall a verb (removes from log, reinstalls)
     uninstall VERB        Uninstall a verb (removes from log, attempts cleanup)
     help                  Open winetricks wiki in browser
+    help VERB             Open the homepage of the software a verb installs
     folder                Open WINEPREFIX in file manager
     winecfg               Run Wine configuration GUI
     regedit               Run Windows registry editor
     taskmgr               Run Windows task manager
+    list-processes        List the processes running in WINEPREFIX
+    kill-processes        Kill every process running in WINEPREFIX
     explorer              Run Windows explorer
     uninstaller           Run Windows uninstaller
     shell                 Open interactive Wine shell
@@ -55,23 +480,46 @@ COMMANDS:
     arch=32|64            Set wine architecture (win32/win64)
     renderer=opengl|vulkan Set Wine D3D renderer (opengl or vulkan)
     annihilate            Delete WINEPREFIX (WARNING: deletes all data!)
+    annihilate --prefix=NAME  Delete the named prefix without switching to it
+    prefix create NAME [--arch=32|64] [--windows-version=VER]
+                          Create a named prefix and print its path
+    prefix delete NAME    Delete a named prefix (like annihilate --prefix=NAME)
+    prefix info NAME      Show architecture, Wine version, verb count and disk usage
+    prefix check NAME     Check a prefix for corruption (registry, drive_c, architecture)
+    prefix list           List prefixes of winetricks, Lutris, Bottles and Steam
+    export-prefix NAME FILE
+                          Back up prefix NAME to FILE (.tar.gz, with installed verb list)
+    import-prefix FILE NAME
+                          Restore a prefix exported with export-prefix as NAME
     
     VERB_NAME             Install a verb (e.g., dotnet48, vcrun2019, corefonts)
 
 OPTIONS:
     --country=CC          Set country code to CC
+    --output-format=FMT   Output format for list commands: text, json, or csv (also --format)
+    --concurrency=N       Maximum number of simultaneous downloads (default: 3)
+    --retries=N           Retry failed downloads N times (default: 3)
+    --cdrom=PATH          Mount point of the install disc for DVD/CD-ROM verbs
+    --timeout=SECONDS     Kill installers that run longer than SECONDS (default: no limit)
+    --reinstall-if-older-than=DAYS
+                          Reinstall verbs that were installed more than DAYS days ago
+    --steam-app-id=APPID  Use the Proton prefix of Steam app APPID (steamapps/compatdata)
+    --check-prereqs       Check that verbs and all their prerequisites can be installed
+    --isolate-sandbox     Test-install verbs into a throwaway tmpfs prefix, then discard it
     -f, --force           Don't check whether packages were already installed
     --gui                 Show GUI diagnostics (GUI not yet implemented)
     --gui=OPT             Set GUI engine (kdialog or zenity)
     --isolate             Install each app in its own WINEPREFIX
     --no-clean            Don't delete temp directories
     --no-isolate          Don't isolate apps (use shared prefix)
+    --no-wine-check       Don't look for Wine (for testing without Wine installed)
     --optin               Opt in to reporting
     --optout              Opt out of reporting
     -q, --unattended      Don't ask any questions, install automatically
-    --self-update         Update this application (coming soon)
-    --update-rollback     Rollback last self update (coming soon)
-    -t, --torify          Run downloads under torify, if available
+    --safe                Refuse to uninstall verbs that other installed verbs depend on
+    --self-update         Update this application to the latest GitHub release
+    --update-rollback     Rollback last self update
+    -t, --torify          Download through Tor (needs torsocks or torify)
     --verify              Run automated GUI tests (coming soon)
     -v, --verbose         Echo all commands as they are executed
     -vv, --really-verbose Really verbose mode
@@ -86,9 +534,13 @@ EXAMPLES:
     winetricks --force corefonts             # Force reinstall
     winetricks reinstall dotnet48            # Force reinstall (same as --force)
     winetricks uninstall dotnet48            # Uninstall a verb
+    winetricks preload dotnet48 vcrun2019    # Fill the download cache for offline use
+    winetricks export-verbs > my-verbs.json  # Back up verb metadata
+    winetricks import-verbs my-verbs.json    # Install a shared verb bundle
     winetricks list-installed                # Show installed verbs with details
     winetricks help                          # Open wiki in browser
     winetricks annihilate                    # Delete WINEPREFIX (with confirmation)
+    winetricks annihilate --prefix=old       # Delete the prefix named 'old'
     winetricks prefix=myprefix dotnet48      # Install to custom prefix
     winetricks renderer=vulkan dotnet48      # Install with Vulkan renderer
     winetricks wayland=wayland dotnet48      # Install using Wayland display driver
@@ -102,32 +554,51 @@ NOTE: This is a pure Rust rewrite of winetricks.
 #[derive(Parser)]
 #[command(name = "winetricks")]
 #[command(about = "A fast, modern package manager for Wine")]
-#[command(version)]
+// -h/--help and -V/--version are handled by main() (print_help)
+#[command(version, disable_help_flag = true, disable_version_flag = true)]
 #[command(long_about = r#"Winetricks - Package manager for Wine
 
 Executes verbs to install applications, DLLs, fonts, or change Wine settings.
 
 COMMANDS:
     list                  List categories
+    list --tag=TAG        List verbs tagged TAG (e.g. directx, gaming, runtime)
     list-all              List all categories and their verbs
     list-installed        List already-installed verbs
+    list-conflicts        List installed verbs that conflict with each other
+    list-files VERB       List files a verb placed in the wineprefix
+    find-verb FILE        List verbs that install FILE (e.g. a DLL named in a Windows error)
+    preload VERB...       Download the files of the given verbs into the cache
+    clean-verb VERB...    Delete the cached downloads of the given verbs
+    clean-verb --all      Delete cached downloads of verbs not installed in any prefix
+    export-verbs          Print all verb metadata as a JSON array
+    generate-docs         Print a Markdown reference of all verbs (e.g. > VERBS.md)
+    import-verbs FILE     Add verbs from a JSON array, replacing verbs with the same name
+    metadata update       Download the latest verb metadata, show what changed and replace the cache
     list-cached           List verbs with cached files
+    list-cached --sizes   List cached verbs with their disk usage (--sort=size: largest first)
     list-download         List verbs that auto-download (media=download)
-    list-manual-download  List verbs that require manual download
+    list-manual-download  List verbs that require manual download (or a DVD/CD-ROM)
+    info VERB             Show a verb's details and where to get manually downloaded files
     
     apps list             List verbs in category 'applications'
     benchmarks list       List verbs in category 'benchmarks'
+    codecs list           List verbs in category 'codecs'
     dlls list             List verbs in category 'dlls'
     fonts list            List verbs in category 'fonts'
+    runtimes list         List verbs in category 'runtimes'
     settings list         List verbs in category 'settings'
     
     reinstall VERB        Force reinstall a verb (removes from log, reinstalls)
     uninstall VERB        Uninstall a verb (removes from log, attempts cleanup)
     help                  Open winetricks wiki in browser
+    help VERB             Open the homepage of the software a verb installs
     folder                Open WINEPREFIX in file manager
     winecfg               Run Wine configuration GUI
     regedit               Run Windows registry editor
     taskmgr               Run Windows task manager
+    list-processes        List the processes running in WINEPREFIX
+    kill-processes        Kill every process running in WINEPREFIX
     explorer              Run Windows explorer
     uninstaller           Run Windows uninstaller
     shell                 Open interactive Wine shell
@@ -136,6 +607,17 @@ COMMANDS:
     arch=32|64            Set wine architecture (win32/win64)
     renderer=opengl|vulkan Set Wine D3D renderer (opengl or vulkan)
     annihilate            Delete WINEPREFIX (WARNING: deletes all data!)
+    annihilate --prefix=NAME  Delete the named prefix without switching to it
+    prefix create NAME [--arch=32|64] [--windows-version=VER]
+                          Create a named prefix and print its path
+    prefix delete NAME    Delete a named prefix (like annihilate --prefix=NAME)
+    prefix info NAME      Show architecture, Wine version, verb count and disk usage
+    prefix check NAME     Check a prefix for corruption (registry, drive_c, architecture)
+    prefix list           List prefixes of winetricks, Lutris, Bottles and Steam
+    export-prefix NAME FILE
+                          Back up prefix NAME to FILE (.tar.gz, with installed verb list)
+    import-prefix FILE NAME
+                          Restore a prefix exported with export-prefix as NAME
     
     VERB_NAME             Install a verb (e.g., dotnet48, vcrun2019, corefonts)
 
@@ -147,8 +629,12 @@ EXAMPLES:
     winetricks --force corefonts             # Force reinstall
     winetricks reinstall dotnet48            # Force reinstall (same as --force)
     winetricks uninstall dotnet48            # Uninstall a verb
+    winetricks preload dotnet48 vcrun2019    # Fill the download cache for offline use
+    winetricks export-verbs > my-verbs.json  # Back up verb metadata
+    winetricks import-verbs my-verbs.json    # Install a shared verb bundle
     winetricks help                          # Open wiki in browser
     winetricks annihilate                    # Delete WINEPREFIX (with confirmation)
+    winetricks annihilate --prefix=old       # Delete the prefix named 'old'
     winetricks prefix=myprefix dotnet48      # Install to custom prefix"#)]
 struct Cli {
     /// Commands or verbs to execute
@@ -163,6 +649,51 @@ struct Cli {
     #[arg(short = 'f', long)]
     force: bool,
 
+    /// Output format for list commands and errors (text, json, or csv)
+    #[arg(
+        long,
+        visible_alias = "format",
+        value_name = "FORMAT",
+        default_value = "text"
+    )]
+    output_format: OutputFormat,
+
+    /// Maximum number of simultaneous downloads
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    concurrency: usize,
+
+    /// How often a failed download is retried
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    retries: u8,
+
+    /// Mount point of the install disc for DVD/CD-ROM verbs
+    #[arg(long, value_name = "PATH")]
+    cdrom: Option<std::path::PathBuf>,
+
+    /// Kill installers that run longer than this many seconds (0: no limit)
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Reinstall verbs that were installed more than this many days ago
+    #[arg(long, value_name = "DAYS")]
+    reinstall_if_older_than: Option<u32>,
+
+    /// Use the Proton prefix (steamapps/compatdata/APPID/pfx) of this Steam app
+    #[arg(long, value_name = "APPID")]
+    steam_app_id: Option<u32>,
+
+    /// Check that verbs and everything they install first can be installed, without installing
+    #[arg(long)]
+    check_prereqs: bool,
+
+    /// Don't look for Wine (for testing download and verb logic without Wine installed)
+    #[arg(long)]
+    no_wine_check: bool,
+
+    /// Install verbs into a throwaway tmpfs prefix and report whether they installed
+    #[arg(long)]
+    isolate_sandbox: bool,
+
     /// Show gui diagnostics even when driven by commandline
     #[arg(long)]
     gui: bool,
@@ -195,6 +726,10 @@ struct Cli {
     #[arg(short = 'q', long)]
     unattended: bool,
 
+    /// Refuse to uninstall verbs that other installed verbs depend on
+    #[arg(long)]
+    safe: bool,
+
     /// Update this application to the last version
     #[arg(long)]
     self_update: bool,
@@ -203,7 +738,7 @@ struct Cli {
     #[arg(long)]
     update_rollback: bool,
 
-    /// Run downloads under torify, if available
+    /// Download through Tor (needs torsocks or torify)
     #[arg(short = 't', long)]
     torify: bool,
 
@@ -211,13 +746,13 @@ struct Cli {
     #[arg(long)]
     verify: bool,
 
-    /// Echo all commands as they are executed
-    #[arg(short = 'v', long)]
-    verbose: bool,
+    /// Echo all commands as they are executed (-vv: really verbose)
+    #[arg(short = 'v', long, action = clap::ArgAction::Count)]
+    verbose: u8,
 
     /// Really verbose (set -x equivalent)
-    #[arg(short = 'v', long = "really-verbose", action = clap::ArgAction::Count)]
-    really_verbose: u8,
+    #[arg(long = "really-verbose")]
+    really_verbose: bool,
 
     /// Display this message and exit
     #[arg(short = 'h', long)]
@@ -230,9 +765,10 @@ struct Cli {
 
 // Commands are parsed from the commands vector
 // They can be: list, list-all, list-cached, list-download, list-manual-download,
-// list-installed, apps list, dlls list, fonts list, settings list, benchmarks list,
-// arch=32|64, prefix=foobar, annihilate, folder, winecfg, regedit, taskmgr, explorer,
-// uninstaller, shell, winecmd, or verb names
+// list-installed, info VERB, apps list, dlls list, fonts list, settings list, benchmarks list,
+// codecs list, runtimes list,
+// arch=32|64, prefix=foobar, annihilate, folder, winecfg, regedit, taskmgr, list-processes,
+// kill-processes, explorer, uninstaller, shell, winecmd, or verb names
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -294,9 +830,9 @@ async fn main() -> Result<()> {
 
     // Determine verbosity level
     // If unattended (-q), suppress all logging unless verbose is explicitly set
-    let verbosity = if cli.really_verbose > 0 {
+    let verbosity = if cli.really_verbose || cli.verbose > 1 {
         2
-    } else if cli.verbose {
+    } else if cli.verbose > 0 {
         1
     } else {
         0
@@ -310,18 +846,33 @@ async fn main() -> Result<()> {
         _ => "trace",
     };
 
+    // Logs go to stderr so command output (export-verbs, --output-format json) can be redirected
     tracing_subscriber::fmt()
         .with_env_filter(format!("winetricks={}", log_level))
+        .with_writer(std::io::stderr)
         .init();
 
     // Load configuration
-    let mut config = Config::new()?;
+    let mut config = Config::from_env_strict()?;
     config.verbosity = verbosity;
     config.force = cli.force;
     config.unattended = cli.unattended;
     config.torify = cli.torify;
+    config.country = cli.country.clone();
     config.isolate = cli.isolate;
     config.no_clean = cli.no_clean;
+    config.safe = cli.safe;
+    config.output_format = cli.output_format;
+    config.download_concurrency = cli.concurrency;
+    config.download_retries = cli.retries;
+    config.cdrom_path = cli.cdrom.clone();
+    if let Some(secs) = cli.timeout {
+        // --timeout overrides wine_timeout from config.toml
+        config.wine_timeout = (secs > 0).then(|| std::time::Duration::from_secs(secs));
+    }
+    config.reinstall_if_older_than = cli.reinstall_if_older_than;
+    config.steam_app_id = cli.steam_app_id;
+    config.skip_wine_check = cli.no_wine_check;
 
     // --no-isolate overrides --isolate if both are set
     if cli.no_isolate {
@@ -329,7 +880,17 @@ async fn main() -> Result<()> {
     }
 
     // Handle WINEPREFIX from environment or prefix= command
-    if let Ok(prefix) = std::env::var("WINEPREFIX") {
+    // (--steam-app-id takes precedence over WINEPREFIX)
+    if let Some(prefix) = config.steam_app_prefix() {
+        std::env::set_var("WINEPREFIX", &prefix);
+        config.wineprefix = Some(prefix);
+    } else if let Some(app_id) = config.steam_app_id {
+        eprintln!(
+            "Error: --steam-app-id={} given, but no Steam installation was found",
+            app_id
+        );
+        std::process::exit(1);
+    } else if let Ok(prefix) = std::env::var("WINEPREFIX") {
         config.wineprefix = Some(prefix.into());
     }
 
@@ -386,7 +947,10 @@ async fn main() -> Result<()> {
     config.ensure_dirs()?;
 
     // Initialize cache from source JSON files if needed (or download from GitHub)
-    config.ensure_cache_initialized().await?;
+    // --no-wine-check (tests) uses whatever metadata is already there, offline
+    if !config.skip_wine_check {
+        config.ensure_cache_initialized().await?;
+    }
 
     // Show startup message
     info!("Winetricks starting...");
@@ -400,11 +964,26 @@ async fn main() -> Result<()> {
         cli.force, cli.unattended
     );
 
+    // Handle self-update and rollback early (before other processing)
+    if cli.self_update {
+        return handle_self_update(config.torify).await;
+    }
+
+    if cli.update_rollback {
+        return handle_update_rollback().await;
+    }
+
     // Parse commands
     // If only flags were provided without commands, show help
     // (GUI should only launch when NO arguments at all, which is handled earlier)
     if cli.commands.is_empty() {
-        if cli.force || cli.unattended || cli.verbose || cli.torify || cli.isolate || cli.no_clean {
+        if cli.force
+            || cli.unattended
+            || cli.verbose > 0
+            || cli.torify
+            || cli.isolate
+            || cli.no_clean
+        {
             eprintln!("Error: Flags provided but no command/verb specified.");
             eprintln!("Usage: winetricks [FLAGS] <command|verb>");
             eprintln!("Example: winetricks --force -q dotnet48");
@@ -415,6 +994,10 @@ async fn main() -> Result<()> {
     }
 
     // Process commands in order - handle arch= and prefix= first
+    // Cleared by --check-prereqs when a verb isn't ready (exit status 1)
+    let mut prereqs_ok = true;
+    // Cleared by --isolate-sandbox when a verb fails to install (exit status 1)
+    let mut sandbox_ok = true;
     let mut i = 0;
     while i < cli.commands.len() {
         let cmd = &cli.commands[i];
@@ -510,7 +1093,7 @@ async fn main() -> Result<()> {
                 _ => {} // Auto - don't modify
             }
 
-            // Also set in wineprefix registry for persistence (or clear if auto)
+            // Also set in wineprefix registry for persistence (auto keeps the current value)
             let wayland_for_registry = if wayland == "auto" {
                 None
             } else {
@@ -529,30 +1112,20 @@ async fn main() -> Result<()> {
         }
 
         if let Some(prefix_name) = cmd.strip_prefix("prefix=") {
-            let prefix_path = config.prefixes_root.join(prefix_name);
+            let prefix_path = config.resolve_prefix(prefix_name)?;
             config.wineprefix = Some(prefix_path.clone());
             std::env::set_var("WINEPREFIX", prefix_path.to_str().unwrap());
 
             // If WINEARCH is set and prefix doesn't exist, initialize it
             if let Some(ref arch) = config.winearch {
-                if !prefix_path.exists() {
+                if !winetricks_lib::Wine::prefix_exists(&prefix_path) {
                     info!(
                         "Creating WINEPREFIX \"{}\" with WINEARCH={}",
                         prefix_path.display(),
                         arch
                     );
-                    // Initialize prefix with wineboot
                     let wine = winetricks_lib::Wine::detect()?;
-                    std::process::Command::new(&wine.wine_bin)
-                        .arg("wineboot")
-                        .env("WINEPREFIX", prefix_path.to_str().unwrap())
-                        .env("WINEARCH", arch)
-                        .status()?;
-                    // Wait for wineserver to finish
-                    std::process::Command::new(&wine.wineserver_bin)
-                        .arg("-w")
-                        .env("WINEPREFIX", prefix_path.to_str().unwrap())
-                        .status()?;
+                    wine.create_prefix(&prefix_path, arch).await?;
                 }
             }
             i += 1;
@@ -577,7 +1150,11 @@ async fn main() -> Result<()> {
                         println!("Successfully reinstalled {}", verb_name);
                     }
                     Err(e) => {
-                        eprintln!("Error reinstalling {}: {}", verb_name, e);
+                        print_error(
+                            config.output_format,
+                            verb_name,
+                            &format!("Error reinstalling {}: {}", verb_name, e),
+                        );
                         std::process::exit(1);
                     }
                 }
@@ -585,25 +1162,66 @@ async fn main() -> Result<()> {
                 continue; // Continue to next iteration
             }
             "list" => {
-                println!("Categories: apps benchmarks dlls fonts settings");
+                // list --tag=TAG: verbs with that tag instead of the categories
+                if let Some(tag) = cli
+                    .commands
+                    .get(i + 1)
+                    .and_then(|arg| arg.strip_prefix("--tag="))
+                {
+                    let metadata_dir = config.metadata_dir();
+                    if metadata_dir.exists() {
+                        let registry = VerbRegistry::load_with_overlay(
+                            metadata_dir,
+                            config.imported_verbs_dir(),
+                        )?;
+                        let verbs = registry.list_by_tag(tag);
+                        if verbs.is_empty() && config.output_format == OutputFormat::Text {
+                            eprintln!(
+                                "No verbs tagged '{}'. Known tags: {}",
+                                tag,
+                                registry.list_tags().join(" ")
+                            );
+                        }
+                        print_verbs(config.output_format, &verbs);
+                    }
+                    i += 2;
+                    continue;
+                }
+
+                let categories = [
+                    "apps",
+                    "benchmarks",
+                    "codecs",
+                    "dlls",
+                    "fonts",
+                    "runtimes",
+                    "settings",
+                ];
+                if config.output_format == OutputFormat::Text {
+                    println!("Categories: {}", categories.join(" "));
+                } else {
+                    let records: Vec<Vec<String>> =
+                        categories.iter().map(|c| vec![c.to_string()]).collect();
+                    print_records(config.output_format, &["category"], &records);
+                }
             }
             "list-all" => {
                 let metadata_dir = config.metadata_dir();
                 if metadata_dir.exists() {
-                    let registry = VerbRegistry::load_from_dir(metadata_dir)?;
-                    let categories = [
-                        VerbCategory::Apps,
-                        VerbCategory::Benchmarks,
-                        VerbCategory::Dlls,
-                        VerbCategory::Fonts,
-                        VerbCategory::Settings,
-                    ];
-                    for category in categories {
-                        println!("===== {} =====", category.as_str());
-                        let verbs = registry.list_by_category(category);
-                        for verb in verbs {
-                            println!("{}", verb.name);
+                    let registry =
+                        VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())?;
+                    if config.output_format == OutputFormat::Text {
+                        for category in VerbCategory::ALL {
+                            let verbs: Vec<_> = registry.iter_category(category).collect();
+                            if verbs.is_empty() {
+                                continue;
+                            }
+                            println!("===== {} =====", category.as_str());
+                            print_verbs(config.output_format, &verbs);
                         }
+                    } else {
+                        // Structured formats carry the category per row instead of headers
+                        print_verbs(config.output_format, &registry.list_all());
                     }
                 }
             }
@@ -617,50 +1235,131 @@ async fn main() -> Result<()> {
                     return Ok(());
                 }
 
-                let registry = VerbRegistry::load_from_dir(metadata_dir)?;
+                let registry =
+                    VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())?;
                 let mut cached_verbs = Vec::new();
 
                 // Check each verb to see if its files are cached
-                for category in [
-                    VerbCategory::Apps,
-                    VerbCategory::Benchmarks,
-                    VerbCategory::Dlls,
-                    VerbCategory::Fonts,
-                    VerbCategory::Settings,
-                ] {
-                    for verb_metadata in registry.list_by_category(category) {
-                        // A verb is cached if all its files exist in cache
-                        let mut all_cached = true;
-                        if !verb_metadata.files.is_empty() {
-                            for file in &verb_metadata.files {
-                                // Check if file is cached - original winetricks uses verb_name/filename structure
-                                // For now, check both verb_name/filename and just filename
-                                let cache_file = config
-                                    .cache_dir
-                                    .join(&verb_metadata.name)
-                                    .join(&file.filename);
-                                let cache_file_alt = config.cache_dir.join(&file.filename);
-
-                                if !cache_file.exists() && !cache_file_alt.exists() {
-                                    all_cached = false;
-                                    break;
-                                }
+                for verb_metadata in registry.list_all() {
+                    // A verb is cached if all its files exist in cache
+                    let mut all_cached = true;
+                    if !verb_metadata.files.is_empty() {
+                        for file in &verb_metadata.files {
+                            // Check if file is cached - original winetricks uses verb_name/filename structure
+                            // For now, check both verb_name/filename and just filename
+                            let cache_file = config
+                                .cache_dir
+                                .join(&verb_metadata.name)
+                                .join(&file.filename);
+                            let cache_file_alt = config.cache_dir.join(&file.filename);
+
+                            if !cache_file.exists() && !cache_file_alt.exists() {
+                                all_cached = false;
+                                break;
                             }
-                        } else {
-                            // Verb has no files to download, skip it
-                            all_cached = false;
                         }
+                    } else {
+                        // Verb has no files to download, skip it
+                        all_cached = false;
+                    }
 
-                        if all_cached {
-                            cached_verbs.push(verb_metadata.name.clone());
-                        }
+                    if all_cached {
+                        cached_verbs.push(verb_metadata);
                     }
                 }
 
-                cached_verbs.sort();
-                for verb_name in cached_verbs {
-                    println!("{}", verb_name);
+                // list-cached --sizes [--sort=size]: one line per verb with its disk usage
+                let options: Vec<&str> = cli.commands[i + 1..]
+                    .iter()
+                    .take_while(|c| *c == "--sizes" || c.starts_with("--sort="))
+                    .map(|c| c.as_str())
+                    .collect();
+                let sort_by_size = match options.iter().find_map(|o| o.strip_prefix("--sort=")) {
+                    None | Some("name") => false,
+                    Some("size") => true,
+                    Some(other) => {
+                        eprintln!(
+                            "Error: unknown sort order '{}' (expected name or size)",
+                            other
+                        );
+                        std::process::exit(1);
+                    }
+                };
+                let show_sizes = sort_by_size || options.contains(&"--sizes");
+
+                if show_sizes {
+                    let downloader =
+                        winetricks_lib::download::DownloadManager::new(config.cache_dir.clone())?
+                            .with_torify(config.torify)?;
+                    let mut sizes = Vec::new();
+                    for verb_metadata in &cached_verbs {
+                        let size: u64 = downloader
+                            .get_cached_file_metadata(&verb_metadata.name)?
+                            .iter()
+                            .map(|f| f.size)
+                            .sum();
+                        sizes.push((*verb_metadata, size));
+                    }
+                    if sort_by_size {
+                        sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+                    }
+
+                    if config.output_format == OutputFormat::Text {
+                        let width = sizes
+                            .iter()
+                            .map(|(verb, _)| verb.name.len())
+                            .max()
+                            .unwrap_or(0);
+                        for (verb, size) in &sizes {
+                            println!(
+                                "{:<width$}  {:>10}",
+                                verb.name,
+                                format_size(*size),
+                                width = width
+                            );
+                        }
+                        println!(
+                            "{:<width$}  {:>10}",
+                            "Total",
+                            format_size(downloader.cache_size()?),
+                            width = width
+                        );
+                    } else {
+                        let records: Vec<Vec<String>> = sizes
+                            .iter()
+                            .map(|(verb, size)| {
+                                vec![
+                                    verb.name.clone(),
+                                    verb.title.clone(),
+                                    verb.category.as_str().to_string(),
+                                    size.to_string(),
+                                ]
+                            })
+                            .collect();
+                        print_records(config.output_format, &CACHED_SIZE_COLUMNS, &records);
+                    }
+                } else if config.output_format == OutputFormat::Text {
+                    // Show each cached file with its size and age
+                    let downloader =
+                        winetricks_lib::download::DownloadManager::new(config.cache_dir.clone())?
+                            .with_torify(config.torify)?;
+                    for verb_metadata in cached_verbs {
+                        println!("{}", verb_metadata.name);
+                        for file in downloader.get_cached_file_metadata(&verb_metadata.name)? {
+                            println!(
+                                "    {}  {}  {}",
+                                file.filename,
+                                format_size(file.size),
+                                format_age(file.modified)
+                            );
+                        }
+                    }
+                } else {
+                    print_verbs(config.output_format, &cached_verbs);
                 }
+
+                i += options.len() + 1;
+                continue;
             }
             "list-download" => {
                 let metadata_dir = config.metadata_dir();
@@ -672,30 +1371,254 @@ async fn main() -> Result<()> {
                     return Ok(());
                 }
 
-                let registry = VerbRegistry::load_from_dir(metadata_dir)?;
-                let mut download_verbs = Vec::new();
+                let registry =
+                    VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())?;
+                let download_verbs =
+                    registry.list_by_media_type(winetricks_lib::MediaType::Download);
+                print_verbs(config.output_format, &download_verbs);
+            }
+            "list-manual-download" => {
+                let metadata_dir = config.metadata_dir();
+                if !metadata_dir.exists() {
+                    eprintln!(
+                        "Error: metadata directory not found: {}",
+                        metadata_dir.display()
+                    );
+                    return Ok(());
+                }
 
-                // List verbs with media=download
-                for category in [
-                    VerbCategory::Apps,
-                    VerbCategory::Benchmarks,
-                    VerbCategory::Dlls,
-                    VerbCategory::Fonts,
-                    VerbCategory::Settings,
+                let registry =
+                    VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())?;
+                // Disc-based verbs can't be downloaded either
+                let mut manual_download_verbs = registry.get_verbs_needing_manual_download();
+                for media in [
+                    winetricks_lib::MediaType::Dvd,
+                    winetricks_lib::MediaType::CdRom,
                 ] {
-                    for verb_metadata in registry.list_by_category(category) {
-                        if verb_metadata.media == winetricks_lib::MediaType::Download {
-                            download_verbs.push(verb_metadata.name.clone());
+                    manual_download_verbs.extend(registry.list_by_media_type(media));
+                }
+                manual_download_verbs.sort_by(|a, b| a.name.cmp(&b.name));
+                print_verbs(config.output_format, &manual_download_verbs);
+            }
+            "info" => {
+                // info VERB: show a verb's metadata and where to get manual downloads
+                let Some(verb_name) = cli.commands.get(i + 1) else {
+                    eprintln!("Error: info requires a verb name");
+                    std::process::exit(1);
+                };
+                let metadata_dir = config.metadata_dir();
+                if !metadata_dir.exists() {
+                    eprintln!(
+                        "Error: metadata directory not found: {}",
+                        metadata_dir.display()
+                    );
+                    return Ok(());
+                }
+
+                let registry =
+                    VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())?;
+                let Some(verb) = registry.get(verb_name) else {
+                    let suggestions = registry.suggest(verb_name, 3);
+                    let message = if suggestions.is_empty() {
+                        format!("Verb '{}' not found", verb_name)
+                    } else {
+                        format!(
+                            "Verb '{}' not found (did you mean: {}?)",
+                            verb_name,
+                            suggestions.join(", ")
+                        )
+                    };
+                    print_error(config.output_format, verb_name, &message);
+                    std::process::exit(1);
+                };
+                let manual_download = registry.get_manual_download_info(verb_name);
+                let cache_dir = config.cache_dir.join(&verb.name);
+
+                if config.output_format != OutputFormat::Text {
+                    let files: Vec<&str> = verb.files.iter().map(|f| f.filename.as_str()).collect();
+                    let (purchase_url, instructions) = manual_download
+                        .map(|m| {
+                            (
+                                m.purchase_url.unwrap_or_default(),
+                                m.instructions.unwrap_or_default(),
+                            )
+                        })
+                        .unwrap_or_default();
+                    print_records(
+                        config.output_format,
+                        &[
+                            "name",
+                            "title",
+                            "category",
+                            "publisher",
+                            "year",
+                            "homepage",
+                            "media",
+                            "files",
+                            "purchase_url",
+                            "instructions",
+                            "cache_dir",
+                        ],
+                        &[vec![
+                            verb.name.clone(),
+                            verb.title.clone(),
+                            verb.category.as_str().to_string(),
+                            verb.publisher.clone().unwrap_or_default(),
+                            verb.year.clone().unwrap_or_default(),
+                            verb.homepage.clone().unwrap_or_default(),
+                            verb.media.as_str().to_string(),
+                            files.join(" "),
+                            purchase_url,
+                            instructions,
+                            cache_dir.display().to_string(),
+                        ]],
+                    );
+                } else {
+                    println!("Verb:        {}", verb.name);
+                    println!("Title:       {}", verb.title);
+                    println!("Category:    {}", verb.category.as_str());
+                    if let Some(ref publisher) = verb.publisher {
+                        println!("Publisher:   {}", publisher);
+                    }
+                    if let Some(ref year) = verb.year {
+                        println!("Year:        {}", year);
+                    }
+                    if let Some(ref homepage) = verb.homepage {
+                        println!("Homepage:    {}", hyperlink(homepage));
+                    }
+                    println!("Media:       {}", verb.media.as_str());
+                    if let Some(ref description) = verb.description {
+                        println!("Description: {}", description);
+                    }
+                    for file in &verb.files {
+                        match file.size {
+                            Some(size) => {
+                                println!("File:        {} ({})", file.filename, format_size(size))
+                            }
+                            None => println!("File:        {}", file.filename),
+                        }
+                    }
+
+                    if let Some(info) = manual_download {
+                        println!();
+                        println!("This verb's files must be downloaded manually.");
+                        if let Some(ref url) = info.purchase_url {
+                            println!("Get them from: {}", hyperlink(url));
+                        }
+                        if let Some(ref instructions) = info.instructions {
+                            println!("{}", instructions);
+                        }
+                        println!("Then save them to: {}", cache_dir.display());
+                        for file in &info.files {
+                            println!("  {}", file.filename);
                         }
                     }
                 }
 
-                download_verbs.sort();
-                for verb_name in download_verbs {
-                    println!("{}", verb_name);
+                i += 2;
+                continue;
+            }
+            "list-conflicts" => {
+                let metadata_dir = config.metadata_dir();
+                if !metadata_dir.exists() {
+                    eprintln!(
+                        "Error: metadata directory not found: {}",
+                        metadata_dir.display()
+                    );
+                    return Ok(());
+                }
+
+                let registry =
+                    VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())?;
+
+                // Load installed verbs from winetricks.log
+                let installed = Executor::read_installed_verbs(&config.wineprefix())?;
+
+                let conflicts = registry.find_conflicts(&installed);
+                let title_of = |name: &str| {
+                    registry
+                        .get(name)
+                        .map(|m| m.title.clone())
+                        .unwrap_or_default()
+                };
+
+                if config.output_format != OutputFormat::Text {
+                    let records: Vec<Vec<String>> = conflicts
+                        .iter()
+                        .map(|(a, b)| vec![a.clone(), title_of(a), b.clone(), title_of(b)])
+                        .collect();
+                    print_records(
+                        config.output_format,
+                        &["verb", "verb_title", "conflicting", "conflicting_title"],
+                        &records,
+                    );
+                } else if conflicts.is_empty() {
+                    println!("No conflicting verbs installed in this wineprefix");
+                } else {
+                    println!("Conflicting installed verbs ({}):", conflicts.len());
+                    println!("{}", "=".repeat(50));
+                    for (a, b) in &conflicts {
+                        println!("  {} <-> {}", a, b);
+                        println!("    {}: {}", a, title_of(a));
+                        println!("    {}: {}", b, title_of(b));
+                    }
+                    println!();
+                    println!(
+                        "These verbs replace the same files or settings; uninstall one of them."
+                    );
                 }
             }
-            "list-manual-download" => {
+            "list-files" => {
+                // list-files command: list-files VERB_NAME
+                if i + 1 >= cli.commands.len() {
+                    eprintln!("Error: list-files requires a verb name");
+                    eprintln!("Usage: winetricks list-files <verb-name>");
+                    std::process::exit(1);
+                }
+
+                let verb_name = &cli.commands[i + 1];
+                let executor = Executor::new(config.clone()).await?;
+                let files = match executor.list_installed_files(verb_name) {
+                    Ok(files) => files,
+                    Err(e) => {
+                        print_error(config.output_format, verb_name, &format!("Error: {}", e));
+                        std::process::exit(1);
+                    }
+                };
+
+                if config.output_format != OutputFormat::Text {
+                    let records: Vec<Vec<String>> = files
+                        .iter()
+                        .map(|f| {
+                            vec![
+                                f.path.to_string_lossy().to_string(),
+                                f.size.to_string(),
+                                f.sha256.clone().unwrap_or_default(),
+                            ]
+                        })
+                        .collect();
+                    print_records(config.output_format, &["path", "size", "sha256"], &records);
+                } else if files.is_empty() {
+                    println!("No files found for {} in this wineprefix", verb_name);
+                } else {
+                    println!("Files placed by {} ({}):", verb_name, files.len());
+                    println!("{}", "=".repeat(50));
+                    for file in &files {
+                        println!("  {} ({} bytes)", file.path.display(), file.size);
+                        if let Some(ref sha256) = file.sha256 {
+                            println!("    sha256: {}", sha256);
+                        }
+                    }
+                }
+                i += 1; // Skip the verb name
+            }
+            "find-verb" => {
+                // find-verb FILE: which verbs install a file (e.g. msvcp140.dll)
+                let Some(filename) = cli.commands.get(i + 1) else {
+                    eprintln!("Error: find-verb requires a file name");
+                    eprintln!("Usage: winetricks find-verb <file-name>");
+                    std::process::exit(1);
+                };
                 let metadata_dir = config.metadata_dir();
                 if !metadata_dir.exists() {
                     eprintln!(
@@ -705,53 +1628,347 @@ async fn main() -> Result<()> {
                     return Ok(());
                 }
 
-                let registry = VerbRegistry::load_from_dir(metadata_dir)?;
-                let mut manual_download_verbs = Vec::new();
+                let registry =
+                    VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())?;
+                let verbs = registry.get_by_installed_file(filename);
+                if config.output_format != OutputFormat::Text {
+                    print_verbs(config.output_format, &verbs);
+                } else if verbs.is_empty() {
+                    println!("No verb is known to install {}", filename);
+                } else {
+                    let names: Vec<&str> = verbs.iter().map(|v| v.name.as_str()).collect();
+                    println!(
+                        "{} ({} is installed by {})",
+                        names.join(", "),
+                        filename,
+                        if names.len() == 1 {
+                            "this verb"
+                        } else {
+                            "these verbs"
+                        }
+                    );
+                }
+                i += 1; // Skip the file name
+            }
+            "preload" => {
+                // preload VERB...: download verb files to the cache without installing
+                let verbs: Vec<&str> = cli.commands[i + 1..]
+                    .iter()
+                    .take_while(|c| !c.starts_with('-') && !c.contains('='))
+                    .map(|c| c.as_str())
+                    .collect();
+                if verbs.is_empty() {
+                    eprintln!("Error: preload requires at least one verb name");
+                    eprintln!("Usage: winetricks preload <verb-name> <verb-name> ...");
+                    std::process::exit(1);
+                }
 
-                // List verbs with media=manual_download
-                for category in [
-                    VerbCategory::Apps,
-                    VerbCategory::Benchmarks,
-                    VerbCategory::Dlls,
-                    VerbCategory::Fonts,
-                    VerbCategory::Settings,
-                ] {
-                    for verb_metadata in registry.list_by_category(category) {
-                        if verb_metadata.media == winetricks_lib::MediaType::ManualDownload {
-                            manual_download_verbs.push(verb_metadata.name.clone());
+                let metadata_dir = config.metadata_dir();
+                if !metadata_dir.exists() {
+                    eprintln!(
+                        "Error: metadata directory not found: {}",
+                        metadata_dir.display()
+                    );
+                    std::process::exit(1);
+                }
+
+                let registry =
+                    VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())?;
+                let downloader = winetricks_lib::download::DownloadManager::with_concurrency(
+                    config.cache_dir.clone(),
+                    config.download_concurrency,
+                )?
+                .with_retries(config.download_retries)
+                .with_torify(config.torify)?;
+
+                println!(
+                    "Preloading {} verb(s) into {}",
+                    verbs.len(),
+                    config.cache_dir.display()
+                );
+                let results = downloader
+                    .preload_verbs(&verbs, &registry, config.country.as_deref())
+                    .await;
+
+                let mut failed = Vec::new();
+                for (verb_name, result) in verbs.iter().zip(results) {
+                    match result {
+                        Ok(()) => println!("  {}: cached", verb_name),
+                        Err(e) => {
+                            eprintln!("  {}: {}", verb_name, e);
+                            failed.push(*verb_name);
+                        }
+                    }
+                }
+                if !failed.is_empty() {
+                    eprintln!("\nFailed to preload: {}", failed.join(", "));
+                    std::process::exit(1);
+                }
+
+                i += verbs.len() + 1;
+                continue;
+            }
+            "clean-verb" => {
+                // clean-verb VERB...: delete cached downloads (clean-verb --all: every unused verb)
+                let args: Vec<&str> = cli.commands[i + 1..]
+                    .iter()
+                    .take_while(|c| *c == "--all" || (!c.starts_with('-') && !c.contains('=')))
+                    .map(|c| c.as_str())
+                    .collect();
+                if args.is_empty() {
+                    eprintln!("Error: clean-verb requires a verb name or --all");
+                    eprintln!("Usage: winetricks clean-verb <verb-name> ...");
+                    eprintln!("       winetricks clean-verb --all");
+                    std::process::exit(1);
+                }
+
+                let executor = Executor::new(config.clone()).await?;
+                if args.contains(&"--all") {
+                    let freed = executor.cleanup_all_caches()?;
+                    println!(
+                        "Removed cached downloads of verbs not installed in any prefix ({} freed)",
+                        format_size(freed)
+                    );
+                } else {
+                    let mut failed = false;
+                    for verb_name in &args {
+                        match executor.cleanup_verb_cache(verb_name) {
+                            Ok(freed) => println!("{}: {} freed", verb_name, format_size(freed)),
+                            Err(e) => {
+                                eprintln!("{}: {}", verb_name, e);
+                                failed = true;
+                            }
                         }
                     }
+                    if failed {
+                        std::process::exit(1);
+                    }
+                }
+
+                i += args.len() + 1;
+                continue;
+            }
+            "export-verbs" => {
+                let registry = VerbRegistry::load_with_overlay(
+                    config.metadata_dir(),
+                    config.imported_verbs_dir(),
+                )?;
+                println!("{}", registry.export_json()?);
+            }
+            "generate-docs" => {
+                // generate-docs > VERBS.md
+                let registry = VerbRegistry::load_with_overlay(
+                    config.metadata_dir(),
+                    config.imported_verbs_dir(),
+                )?;
+                print!("{}", registry.to_markdown());
+            }
+            "import-verbs" => {
+                // import-verbs FILE: imported verbs replace built-in verbs of the same name
+                let Some(path) = cli.commands.get(i + 1) else {
+                    eprintln!("Error: import-verbs requires a JSON file");
+                    eprintln!("Usage: winetricks import-verbs <file.json>");
+                    std::process::exit(1);
+                };
+
+                let content = std::fs::read_to_string(path)?;
+                let imported = VerbRegistry::import_json(&content)?;
+                // Imports go to their own directory, loaded on top of the metadata cache,
+                // so a metadata refresh (which rewrites the cache) can't remove them
+                let imported_dir = config.imported_verbs_dir();
+                let metadata_dir = config.metadata_dir();
+                let existing = if metadata_dir.exists() {
+                    VerbRegistry::load_with_overlay(&metadata_dir, &imported_dir)?
+                } else {
+                    VerbRegistry::new()
+                };
+                let mut merged = if imported_dir.is_dir() {
+                    VerbRegistry::load_from_dir(&imported_dir)?
+                } else {
+                    VerbRegistry::new()
+                };
+
+                let imported_count = imported.list().len();
+                let overridden = imported
+                    .list()
+                    .iter()
+                    .filter(|verb| existing.exists(&verb.name))
+                    .count();
+                merged.merge(imported);
+                merged.write_to_dir(&imported_dir)?;
+
+                println!(
+                    "Imported {} verb(s) into {} ({} replaced existing verbs)",
+                    imported_count,
+                    imported_dir.display(),
+                    overridden
+                );
+                i += 1; // Skip the file name
+            }
+            "metadata" => {
+                // metadata update: fetch the bundle, show the diff, confirm, replace the cache
+                if cli.commands.get(i + 1).map(String::as_str) != Some("update") {
+                    eprintln!("Error: unknown metadata command");
+                    eprintln!("Usage: winetricks metadata update");
+                    std::process::exit(1);
+                }
+
+                let metadata_dir = config.metadata_dir();
+                let current = VerbRegistry::load_from_dir(&metadata_dir).unwrap_or_default();
+                eprintln!(
+                    "Downloading {}",
+                    winetricks_lib::config::METADATA_BUNDLE_URL
+                );
+                let latest = VerbRegistry::load_from_url(
+                    winetricks_lib::config::METADATA_BUNDLE_URL,
+                    config.torify,
+                )
+                .await?;
+
+                let diff = VerbRegistry::diff(&current, &latest);
+                if diff.is_empty() {
+                    println!(
+                        "Verb metadata is up to date ({} verbs)",
+                        current.list().len()
+                    );
+                    i += 2;
+                    continue;
+                }
+
+                println!(
+                    "{} added, {} removed, {} changed",
+                    diff.added.len(),
+                    diff.removed.len(),
+                    diff.changed.len()
+                );
+                for name in &diff.added {
+                    println!("  + {}", name);
                 }
+                for name in &diff.removed {
+                    println!("  - {}", name);
+                }
+                for (name, change) in &diff.changed {
+                    println!("  ~ {} ({})", name, change);
+                }
+
+                if !config.unattended {
+                    print!(
+                        "Replace the verb metadata in {}? [y/N] ",
+                        metadata_dir.display()
+                    );
+                    io::stdout().flush().unwrap();
 
-                manual_download_verbs.sort();
-                for verb_name in manual_download_verbs {
-                    println!("{}", verb_name);
+                    let mut answer = String::new();
+                    io::stdin().read_line(&mut answer).unwrap();
+                    if !answer.trim().to_lowercase().starts_with('y') {
+                        println!("Cancelled.");
+                        i += 2;
+                        continue;
+                    }
                 }
+
+                // Also drops the files of removed verbs and of verbs that moved category
+                latest.write_to_dir(&metadata_dir)?;
+                println!(
+                    "Updated verb metadata in {} ({} verbs)",
+                    metadata_dir.display(),
+                    latest.list().len()
+                );
+
+                i += 2;
+                continue;
             }
             "list-installed" => {
                 let wineprefix = config.wineprefix();
                 let log_file = wineprefix.join("winetricks.log");
                 if log_file.exists() {
-                    let content = std::fs::read_to_string(&log_file)?;
-                    let installed: Vec<&str> = content.lines()
-                        .map(|l| l.trim())
+                    let entries = winetricks_lib::install_log::read_log(&wineprefix)?;
+                    let installed: Vec<&str> = entries
+                        .iter()
+                        .map(|e| e.verb.as_str())
                         .filter(|l| {
-                            // Filter out empty lines, flags (starting with -), comments, and command keywords
-                            !l.is_empty()
-                            && !l.starts_with('-')  // Flags like -q, --force
-                            && !l.starts_with('#')  // Comments
-                            && !l.starts_with("//") // Comments
-                            && !l.contains('=')     // Commands like prefix=, arch=
-                            && l != &"list" && l != &"list-installed" && l != &"list-all" 
-                            && l != &"list-cached" && l != &"list-download" && l != &"list-manual-download"
-                            && l != &"apps" && l != &"dlls" && l != &"fonts" && l != &"settings" && l != &"benchmarks"
-                            && l != &"annihilate" && l != &"folder" && l != &"winecfg" && l != &"regedit"
-                            && l != &"taskmgr" && l != &"explorer" && l != &"uninstaller" && l != &"shell"
-                            && l != &"winecmd" && l != &"help" && l != &"uninstall" && l != &"reinstall"
+                            // Filter out command keywords (empty lines, flags, comments and
+                            // commands like prefix= are skipped by read_log)
+                            !KNOWN_COMMANDS.contains(l)
                         })
                         .collect();
 
-                    if installed.is_empty() {
+                    if config.output_format != OutputFormat::Text {
+                        // Structured output: CSV has name, title and category (empty if unknown)
+                        let metadata_dir = config.metadata_dir();
+                        let registry = if metadata_dir.exists() {
+                            VerbRegistry::load_with_overlay(
+                                metadata_dir,
+                                config.imported_verbs_dir(),
+                            )
+                            .ok()
+                        } else {
+                            None
+                        };
+
+                        if config.output_format == OutputFormat::Json {
+                            // Full metadata plus when and with which Wine each verb was
+                            // installed (null for entries of the original winetricks)
+                            let objects: Vec<serde_json::Value> = entries
+                                .iter()
+                                .filter(|e| installed.contains(&e.verb.as_str()))
+                                .map(|entry| {
+                                    let mut object = registry
+                                        .as_ref()
+                                        .and_then(|r| r.get(&entry.verb))
+                                        .and_then(|m| serde_json::to_value(m).ok())
+                                        .and_then(|v| match v {
+                                            serde_json::Value::Object(map) => Some(map),
+                                            _ => None,
+                                        })
+                                        .unwrap_or_default();
+                                    object.insert("name".to_string(), entry.verb.clone().into());
+                                    object.insert(
+                                        "installed_at".to_string(),
+                                        entry
+                                            .installed_at
+                                            .map(winetricks_lib::install_log::format_timestamp)
+                                            .into(),
+                                    );
+                                    object.insert(
+                                        "wine_version".to_string(),
+                                        entry
+                                            .wine_version
+                                            .as_deref()
+                                            .map(|v| v.strip_prefix("wine-").unwrap_or(v))
+                                            .into(),
+                                    );
+                                    serde_json::Value::Object(object)
+                                })
+                                .collect();
+                            println!(
+                                "{}",
+                                serde_json::to_string_pretty(&objects)
+                                    .unwrap_or_else(|_| "[]".to_string())
+                            );
+                        } else {
+                            let records: Vec<Vec<String>> = installed
+                                .iter()
+                                .map(|verb_name| {
+                                    match registry.as_ref().and_then(|r| r.get(verb_name)) {
+                                        Some(metadata) => vec![
+                                            verb_name.to_string(),
+                                            metadata.title.clone(),
+                                            metadata.category.as_str().to_string(),
+                                        ],
+                                        None => {
+                                            vec![
+                                                verb_name.to_string(),
+                                                String::new(),
+                                                String::new(),
+                                            ]
+                                        }
+                                    }
+                                })
+                                .collect();
+                            print_records(config.output_format, &VERB_COLUMNS, &records);
+                        }
+                    } else if installed.is_empty() {
                         println!("No verbs installed in this wineprefix");
                     } else {
                         println!("Installed verbs ({}):", installed.len());
@@ -760,28 +1977,63 @@ async fn main() -> Result<()> {
                         // Try to show metadata if available
                         let metadata_dir = config.metadata_dir();
                         let registry = if metadata_dir.exists() {
-                            VerbRegistry::load_from_dir(metadata_dir).ok()
+                            VerbRegistry::load_with_overlay(
+                                metadata_dir,
+                                config.imported_verbs_dir(),
+                            )
+                            .ok()
                         } else {
                             None
                         };
 
+                        // With --verbose, also show the DLL overrides belonging to each verb
+                        let overrides = if config.verbosity >= 1 {
+                            winetricks_lib::Wine::detect()
+                                .and_then(|wine| wine.list_dll_overrides(&wineprefix))
+                                .map_err(|e| warn!("Could not read DLL overrides: {}", e))
+                                .unwrap_or_default()
+                        } else {
+                            Default::default()
+                        };
+
                         for verb_name in &installed {
-                            if let Some(registry) = &registry {
-                                if let Some(metadata) = registry.get(verb_name) {
-                                    println!(
-                                        "  {} - {} ({})",
-                                        verb_name,
-                                        metadata.title,
-                                        metadata.category.as_str()
-                                    );
-                                } else {
-                                    println!("  {}", verb_name);
-                                }
+                            let metadata = registry.as_ref().and_then(|r| r.get(verb_name));
+                            if let Some(metadata) = metadata {
+                                println!(
+                                    "  {} - {} ({})",
+                                    verb_name,
+                                    metadata.title,
+                                    metadata.category.as_str()
+                                );
                             } else {
                                 println!("  {}", verb_name);
                             }
+
+                            // A verb's DLLs: its own name (d3dx9_43) and any .dll it ships
+                            let mut dlls = vec![verb_name.to_lowercase()];
+                            if let Some(metadata) = metadata {
+                                dlls.extend(metadata.files.iter().filter_map(|f| {
+                                    f.filename
+                                        .to_lowercase()
+                                        .strip_suffix(".dll")
+                                        .map(String::from)
+                                }));
+                            }
+                            let mut verb_overrides: Vec<(&String, &String)> = overrides
+                                .iter()
+                                .filter(|(dll, _)| {
+                                    // Overrides may be written as *dllname
+                                    dlls.contains(&dll.trim_start_matches('*').to_lowercase())
+                                })
+                                .collect();
+                            verb_overrides.sort();
+                            for (dll, value) in verb_overrides {
+                                println!("      override: {}={}", dll, value);
+                            }
                         }
                     }
+                } else if config.output_format != OutputFormat::Text {
+                    print_records(config.output_format, &VERB_COLUMNS, &[]);
                 } else {
                     println!("No installation log found at {:?}", log_file);
                     println!("No verbs have been installed in this wineprefix.");
@@ -816,6 +2068,8 @@ async fn main() -> Result<()> {
                         "fonts",
                         "settings",
                         "benchmarks",
+                        "codecs",
+                        "runtimes",
                         "prefix=",
                         "arch=",
                         "renderer=",
@@ -850,17 +2104,18 @@ async fn main() -> Result<()> {
 
                 continue; // Already incremented i
             }
-            "apps" | "benchmarks" | "dlls" | "fonts" | "settings" => {
+            "apps" | "benchmarks" | "codecs" | "dlls" | "fonts" | "runtimes" | "settings" => {
                 // Check if next command is "list"
                 if i + 1 < cli.commands.len() && cli.commands[i + 1] == "list" {
                     if let Ok(category) = VerbCategory::from_str(cmd) {
                         let metadata_dir = config.metadata_dir();
                         if metadata_dir.exists() {
-                            let registry = VerbRegistry::load_from_dir(metadata_dir)?;
-                            let verbs = registry.list_by_category(category);
-                            for verb in verbs {
-                                println!("{}", verb.name);
-                            }
+                            let registry = VerbRegistry::load_with_overlay(
+                                metadata_dir,
+                                config.imported_verbs_dir(),
+                            )?;
+                            let verbs: Vec<_> = registry.iter_category(category).collect();
+                            print_verbs(config.output_format, &verbs);
                         }
                     }
                     i += 1; // Skip "list"
@@ -869,14 +2124,40 @@ async fn main() -> Result<()> {
                 }
             }
             "help" => {
-                // Open winetricks wiki in browser
-                let url = "https://github.com/Winetricks/winetricks/wiki";
+                // Open winetricks wiki in browser, or with help VERB the verb's homepage
+                let mut url = "https://github.com/Winetricks/winetricks/wiki".to_string();
+                if let Some(verb_name) = cli
+                    .commands
+                    .get(i + 1)
+                    .filter(|c| !c.starts_with('-') && !c.contains('='))
+                {
+                    let metadata_dir = config.metadata_dir();
+                    if !metadata_dir.exists() {
+                        eprintln!(
+                            "Error: metadata directory not found: {}",
+                            metadata_dir.display()
+                        );
+                        std::process::exit(1);
+                    }
+                    let registry =
+                        VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())?;
+                    let Some(verb) = registry.get(verb_name) else {
+                        eprintln!("Error: Verb '{}' not found", verb_name);
+                        std::process::exit(1);
+                    };
+                    let Some(ref homepage) = verb.homepage else {
+                        eprintln!("No homepage known for {}", verb_name);
+                        std::process::exit(1);
+                    };
+                    url = homepage.clone();
+                    i += 1;
+                }
                 let browsers = ["xdg-open", "sdtwebclient", "cygstart", "open", "firefox"];
 
                 let mut opened = false;
                 for browser in &browsers {
                     if std::process::Command::new(browser)
-                        .arg(url)
+                        .arg(&url)
                         .status()
                         .is_ok()
                     {
@@ -890,119 +2171,272 @@ async fn main() -> Result<()> {
                     std::process::exit(1);
                 }
             }
-            "annihilate" => {
-                // DANGEROUS: Delete entire WINEPREFIX
-                let wineprefix = config.wineprefix();
-
-                // Ask for confirmation unless unattended
-                if !config.unattended {
-                    eprintln!("WARNING: This will DELETE ALL DATA AND APPLICATIONS inside:");
-                    eprintln!("  {}", wineprefix.display());
-                    eprintln!("This action cannot be undone!");
-                    print!("Are you sure you want to continue? [y/N] ");
-                    io::stdout().flush().unwrap();
-
-                    let mut answer = String::new();
-                    io::stdin().read_line(&mut answer).unwrap();
-
-                    if !answer.trim().to_lowercase().starts_with('y') {
-                        println!("Cancelled.");
-                        return Ok(());
+            "prefix" => {
+                // prefix list: prefixes under prefixes_root and those of Lutris/Bottles/Steam
+                if cli.commands.get(i + 1).map(String::as_str) == Some("list") {
+                    let mut records = Vec::new();
+                    for root in config.discover_prefix_roots() {
+                        let root_name = config.prefix_root_name(&root);
+                        for prefix in winetricks_lib::Wine::prefixes_in(&root) {
+                            // Proton prefixes are <appid>/pfx, name them by the app id
+                            let named = if prefix.ends_with("pfx") {
+                                prefix.parent().unwrap_or(&prefix)
+                            } else {
+                                &prefix
+                            };
+                            let name = named
+                                .file_name()
+                                .map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            records.push(vec![
+                                root_name.to_string(),
+                                name,
+                                prefix.display().to_string(),
+                            ]);
+                        }
                     }
-                } else {
-                    eprintln!(
-                        "WARNING: Unattended annihilate will delete: {}",
-                        wineprefix.display()
-                    );
-                }
-
-                // Delete wineprefix
-                if wineprefix.exists() {
-                    info!("Deleting wineprefix: {:?}", wineprefix);
-                    std::fs::remove_dir_all(&wineprefix).map_err(|e| {
-                        WinetricksError::Verb(format!("Failed to delete wineprefix: {}", e))
-                    })?;
-                } else {
-                    eprintln!("Wineprefix does not exist: {}", wineprefix.display());
-                    return Ok(());
-                }
 
-                // Clean up .desktop files in XDG_DATA_HOME/applications
-                if let Ok(data_home) = std::env::var("XDG_DATA_HOME") {
-                    let apps_dir = std::path::Path::new(&data_home).join("applications");
-                    if apps_dir.exists() {
-                        // Find and remove .desktop files referencing this wineprefix
-                        if let Ok(entries) = std::fs::read_dir(&apps_dir) {
-                            for entry in entries.flatten() {
-                                if let Some(file_name) = entry.file_name().to_str() {
-                                    if file_name.ends_with(".desktop") {
-                                        if let Ok(content) = std::fs::read_to_string(entry.path()) {
-                                            if content.contains(wineprefix.to_str().unwrap_or("")) {
-                                                let _ = std::fs::remove_file(entry.path());
-                                            }
-                                        }
-                                    }
-                                }
+                    if config.output_format != OutputFormat::Text {
+                        print_records(config.output_format, &["root", "name", "path"], &records);
+                    } else {
+                        let mut current_root = "";
+                        for record in &records {
+                            if record[0] != current_root {
+                                current_root = &record[0];
+                                println!("{}:", current_root);
                             }
+                            println!("  {:<24} {}", record[1], record[2]);
                         }
                     }
+                    i += 2;
+                    continue;
                 }
 
-                // Clean up desktop items
-                // Try XDG_DESKTOP_DIR env var first
-                let desktop_path = if let Ok(desktop_dir) = std::env::var("XDG_DESKTOP_DIR") {
-                    std::path::PathBuf::from(desktop_dir)
-                } else {
-                    // Try reading from user-dirs.dirs config file
-                    let mut desktop_dir = None;
-                    if let Ok(config_home) = std::env::var("XDG_CONFIG_HOME") {
-                        let user_dirs = std::path::Path::new(&config_home).join("user-dirs.dirs");
-                        if user_dirs.exists() {
-                            if let Ok(content) = std::fs::read_to_string(&user_dirs) {
-                                for line in content.lines() {
-                                    if line.starts_with("XDG_DESKTOP_DIR=") {
-                                        let value = line
-                                            .trim_start_matches("XDG_DESKTOP_DIR=\"")
-                                            .trim_end_matches("\"");
-                                        // Expand $HOME if present
-                                        if let Ok(home) = std::env::var("HOME") {
-                                            desktop_dir = Some(value.replace("$HOME", &home));
-                                        } else {
-                                            desktop_dir = Some(value.to_string());
-                                        }
-                                        break;
-                                    }
-                                }
+                // prefix create|delete|info NAME: manage named prefixes under prefixes_root
+                let (Some(action), Some(name)) = (cli.commands.get(i + 1), cli.commands.get(i + 2))
+                else {
+                    eprintln!("Error: prefix requires an action and a prefix name");
+                    eprintln!("{}", PREFIX_USAGE);
+                    std::process::exit(1);
+                };
+                let options: Vec<&str> = cli.commands[i + 3..]
+                    .iter()
+                    .take_while(|c| c.starts_with("--arch=") || c.starts_with("--windows-version="))
+                    .map(|c| c.as_str())
+                    .collect();
+                let prefix_path = config.resolve_prefix(name)?;
+
+                match action.as_str() {
+                    "create" => {
+                        let arch = match options.iter().find_map(|o| o.strip_prefix("--arch=")) {
+                            Some("32") | Some("win32") => "win32".to_string(),
+                            Some("64") | Some("win64") => "win64".to_string(),
+                            Some(other) => {
+                                eprintln!(
+                                    "Error: unknown architecture '{}' (expected 32 or 64)",
+                                    other
+                                );
+                                std::process::exit(1);
                             }
+                            None => config
+                                .winearch
+                                .clone()
+                                .unwrap_or_else(|| "win64".to_string()),
+                        };
+
+                        if winetricks_lib::Wine::prefix_exists(&prefix_path) {
+                            return Err(WinetricksError::Config(format!(
+                                "Wineprefix already exists: {}",
+                                prefix_path.display()
+                            )));
                         }
-                    }
-                    // Fall back to ~/Desktop
-                    desktop_dir
-                        .unwrap_or_else(|| {
-                            std::env::var("HOME")
-                                .map(|h| format!("{}/Desktop", h))
-                                .unwrap_or_else(|_| "~/Desktop".to_string())
-                        })
-                        .into()
-                };
 
-                if desktop_path.exists() {
-                    if let Ok(entries) = std::fs::read_dir(&desktop_path) {
-                        for entry in entries.flatten() {
-                            if let Some(file_name) = entry.file_name().to_str() {
-                                if file_name.ends_with(".desktop") {
-                                    if let Ok(content) = std::fs::read_to_string(entry.path()) {
-                                        if content.contains(wineprefix.to_str().unwrap_or("")) {
-                                            let _ = std::fs::remove_file(entry.path());
-                                        }
-                                    }
-                                }
+                        eprintln!(
+                            "Creating WINEPREFIX \"{}\" with WINEARCH={}",
+                            prefix_path.display(),
+                            arch
+                        );
+                        let wine = winetricks_lib::Wine::detect()?;
+                        wine.create_prefix(&prefix_path, &arch).await?;
+
+                        if let Some(version) = options
+                            .iter()
+                            .find_map(|o| o.strip_prefix("--windows-version="))
+                        {
+                            let mut prefix_config = config.clone();
+                            prefix_config.wineprefix = Some(prefix_path.clone());
+                            prefix_config.winearch = Some(arch.clone());
+                            let executor = Executor::new(prefix_config).await?;
+                            executor.set_windows_version(version)?;
+                        }
+
+                        println!("{}", prefix_path.display());
+                    }
+                    "delete" => {
+                        delete_prefix(&prefix_path, config.unattended)?;
+                    }
+                    "check" => {
+                        let mut prefix_config = config.clone();
+                        prefix_config.wineprefix = Some(prefix_path.clone());
+                        let executor = Executor::new(prefix_config).await?;
+                        let issues = executor.check_prefix_health().await?;
+
+                        if config.output_format != OutputFormat::Text {
+                            let records: Vec<Vec<String>> = issues
+                                .iter()
+                                .map(|issue| {
+                                    vec![
+                                        issue.severity.as_str().to_string(),
+                                        issue.message.clone(),
+                                        issue.fix_hint.clone(),
+                                    ]
+                                })
+                                .collect();
+                            print_records(
+                                config.output_format,
+                                &["severity", "message", "fix_hint"],
+                                &records,
+                            );
+                        } else if issues.is_empty() {
+                            println!("{}: no problems found", prefix_path.display());
+                        } else {
+                            println!("{}:", prefix_path.display());
+                            for issue in &issues {
+                                println!("  {:<8} {}", issue.severity.as_str(), issue.message);
+                                println!("           Fix: {}", issue.fix_hint);
                             }
                         }
+
+                        if issues
+                            .iter()
+                            .any(|issue| issue.severity == HealthSeverity::Error)
+                        {
+                            std::process::exit(1);
+                        }
+                    }
+                    "info" => {
+                        if !winetricks_lib::Wine::prefix_exists(&prefix_path) {
+                            return Err(WinetricksError::Config(format!(
+                                "Wineprefix does not exist: {}",
+                                prefix_path.display()
+                            )));
+                        }
+
+                        let arch = winetricks_lib::Wine::prefix_arch(&prefix_path)?;
+                        let wine_version = winetricks_lib::Wine::detect()
+                            .map(|w| w.version)
+                            .unwrap_or_else(|_| "unknown (wine not found)".to_string());
+                        let installed = Executor::read_installed_verbs(&prefix_path)?;
+                        let size = winetricks_lib::Wine::prefix_size(&prefix_path);
+
+                        if config.output_format != OutputFormat::Text {
+                            print_records(
+                                config.output_format,
+                                &[
+                                    "name",
+                                    "path",
+                                    "arch",
+                                    "wine_version",
+                                    "installed_verbs",
+                                    "size_bytes",
+                                ],
+                                &[vec![
+                                    name.clone(),
+                                    prefix_path.display().to_string(),
+                                    arch,
+                                    wine_version,
+                                    installed.len().to_string(),
+                                    size.to_string(),
+                                ]],
+                            );
+                        } else {
+                            println!("Prefix:          {}", name);
+                            println!("Path:            {}", prefix_path.display());
+                            println!("Architecture:    {}", arch);
+                            println!("Wine version:    {}", wine_version);
+                            println!("Installed verbs: {}", installed.len());
+                            println!("Disk usage:      {}", format_size(size));
+                        }
+                    }
+                    other => {
+                        eprintln!("Error: unknown prefix action '{}'", other);
+                        eprintln!("{}", PREFIX_USAGE);
+                        std::process::exit(1);
                     }
                 }
 
-                println!("Wineprefix deleted: {}", wineprefix.display());
+                i += options.len() + 3;
+                continue;
+            }
+            "export-prefix" => {
+                // export-prefix NAME FILE: NAME is a prefix under prefixes_root or a path
+                let (Some(name), Some(output)) = (cli.commands.get(i + 1), cli.commands.get(i + 2))
+                else {
+                    eprintln!("Error: export-prefix requires a prefix name and an output file");
+                    eprintln!("Usage: winetricks export-prefix <name> <output.tar.gz>");
+                    std::process::exit(1);
+                };
+                let prefix_path = if std::path::Path::new(name).is_absolute() {
+                    std::path::PathBuf::from(name)
+                } else {
+                    config.resolve_prefix(name)?
+                };
+
+                eprintln!("Exporting {} to {}", prefix_path.display(), output);
+                let wine = winetricks_lib::Wine::detect()?;
+                let manifest = wine.export_prefix(&prefix_path, std::path::Path::new(output))?;
+                println!(
+                    "Exported {} ({}, {} installed verbs) to {}",
+                    name,
+                    manifest.arch,
+                    manifest.verbs.len(),
+                    output
+                );
+
+                i += 3;
+                continue;
+            }
+            "import-prefix" => {
+                // import-prefix FILE NAME
+                let (Some(input), Some(name)) = (cli.commands.get(i + 1), cli.commands.get(i + 2))
+                else {
+                    eprintln!("Error: import-prefix requires an export file and a prefix name");
+                    eprintln!("Usage: winetricks import-prefix <input.tar.gz> <name>");
+                    std::process::exit(1);
+                };
+                let prefix_path = config.resolve_prefix(name)?;
+
+                let manifest =
+                    winetricks_lib::Wine::import_prefix(std::path::Path::new(input), &prefix_path)?;
+                println!(
+                    "Imported {} ({}, exported {} with {})",
+                    prefix_path.display(),
+                    manifest.arch,
+                    manifest.exported_at,
+                    manifest.wine_version
+                );
+                if !manifest.verbs.is_empty() {
+                    println!("Installed verbs: {}", manifest.verbs.join(", "));
+                }
+
+                i += 3;
+                continue;
+            }
+            "annihilate" => {
+                // DANGEROUS: Delete entire WINEPREFIX
+                // annihilate --prefix=NAME deletes a named prefix without switching to it
+                let wineprefix = match cli
+                    .commands
+                    .get(i + 1)
+                    .and_then(|c| c.strip_prefix("--prefix="))
+                {
+                    Some(prefix_name) => config.resolve_prefix(prefix_name)?,
+                    None => config.wineprefix(),
+                };
+
+                if !delete_prefix(&wineprefix, config.unattended)? {
+                    return Ok(());
+                }
                 std::process::exit(0);
             }
             "folder" => {
@@ -1056,6 +2490,31 @@ async fn main() -> Result<()> {
                     .spawn()?;
                 // Don't wait for completion (background process)
             }
+            "list-processes" => {
+                let processes = winetricks_lib::Wine::list_running_processes(&config.wineprefix())?;
+                if config.output_format == OutputFormat::Text {
+                    if processes.is_empty() {
+                        println!("No processes running in {}", config.wineprefix().display());
+                    }
+                    for process in &processes {
+                        println!("{:>8}  {:<24}  {}", process.pid, process.name, process.exe);
+                    }
+                } else {
+                    let records: Vec<Vec<String>> = processes
+                        .iter()
+                        .map(|p| vec![p.pid.to_string(), p.name.clone(), p.exe.clone()])
+                        .collect();
+                    print_records(config.output_format, &["pid", "name", "exe"], &records);
+                }
+            }
+            "kill-processes" => {
+                let killed = winetricks_lib::Wine::kill_prefix_processes(&config.wineprefix())?;
+                println!(
+                    "Killed {} process(es) in {}",
+                    killed,
+                    config.wineprefix().display()
+                );
+            }
             "explorer" => {
                 // Run Windows explorer (background)
                 let wine = winetricks_lib::Wine::detect()?;
@@ -1179,11 +2638,12 @@ async fn main() -> Result<()> {
                     if let Ok(category) = VerbCategory::from_str(parts[0]) {
                         let metadata_dir = config.metadata_dir();
                         if metadata_dir.exists() {
-                            let registry = VerbRegistry::load_from_dir(metadata_dir)?;
-                            let verbs = registry.list_by_category(category);
-                            for verb in verbs {
-                                println!("{}", verb.name);
-                            }
+                            let registry = VerbRegistry::load_with_overlay(
+                                metadata_dir,
+                                config.imported_verbs_dir(),
+                            )?;
+                            let verbs: Vec<_> = registry.iter_category(category).collect();
+                            print_verbs(config.output_format, &verbs);
                         }
                     }
                 } else {
@@ -1199,18 +2659,84 @@ async fn main() -> Result<()> {
                         std::process::exit(1);
                     }
 
+                    if cli.check_prereqs {
+                        match check_prereqs(&config, cmd).await {
+                            Ok(true) => {}
+                            Ok(false) => prereqs_ok = false,
+                            Err(e) => {
+                                print_error(
+                                    config.output_format,
+                                    cmd,
+                                    &format!("Error checking {}: {}", cmd, e),
+                                );
+                                std::process::exit(1);
+                            }
+                        }
+                        i += 1;
+                        continue;
+                    }
+
+                    if cli.isolate_sandbox {
+                        match verify_in_sandbox(&config, cmd).await {
+                            Ok(true) => {}
+                            Ok(false) => sandbox_ok = false,
+                            Err(e) => {
+                                print_error(
+                                    config.output_format,
+                                    cmd,
+                                    &format!("Error setting up sandbox for {}: {}", cmd, e),
+                                );
+                                std::process::exit(1);
+                            }
+                        }
+                        i += 1;
+                        continue;
+                    }
+
                     match install_verb(&config, cmd).await {
                         Ok(_) => {
                             // Success - already printed by executor
                         }
-                        Err(WinetricksError::VerbNotFound(_)) => {
-                            eprintln!("Error: Verb '{}' not found", cmd);
-                            eprintln!("Use 'winetricks list' to see available verbs.");
+                        Err(WinetricksError::VerbNotFound { suggestions, .. }) => {
+                            if config.output_format == OutputFormat::Text {
+                                eprintln!("Error: Verb '{}' not found", cmd);
+                                if !suggestions.is_empty() {
+                                    eprintln!("Did you mean: {}?", suggestions.join(", "));
+                                }
+                                eprintln!("Use 'winetricks list' to see available verbs.");
+                            } else if suggestions.is_empty() {
+                                print_error(
+                                    config.output_format,
+                                    cmd,
+                                    &format!("Verb '{}' not found", cmd),
+                                );
+                            } else {
+                                print_error(
+                                    config.output_format,
+                                    cmd,
+                                    &format!(
+                                        "Verb '{}' not found (did you mean: {}?)",
+                                        cmd,
+                                        suggestions.join(", ")
+                                    ),
+                                );
+                            }
                             std::process::exit(1);
                         }
                         Err(e) => {
-                            eprintln!("Error installing {}: {}", cmd, e);
-                            std::process::exit(1);
+                            print_error(
+                                config.output_format,
+                                cmd,
+                                &format!("Error installing {}: {}", cmd, e),
+                            );
+                            // Distinct exit code so scripts can tell conflicts from failures
+                            let code =
+                                if matches!(e.root_cause(), WinetricksError::VerbConflict { .. }) {
+                                    2
+                                } else {
+                                    1
+                                };
+                            std::process::exit(code);
                         }
                     }
                 }
@@ -1219,19 +2745,14 @@ async fn main() -> Result<()> {
         i += 1;
     }
 
-    // Handle self-update and rollback early (before other processing)
-    if cli.self_update {
-        return handle_self_update().await;
-    }
-
-    if cli.update_rollback {
-        return handle_update_rollback().await;
+    if !prereqs_ok || !sandbox_ok {
+        std::process::exit(1);
     }
 
     Ok(())
 }
 
-async fn handle_self_update() -> Result<()> {
+async fn handle_self_update(torify: bool) -> Result<()> {
     use std::env;
     use std::process;
 
@@ -1275,13 +2796,34 @@ async fn handle_self_update() -> Result<()> {
         process::exit(1);
     }
 
-    eprintln!("Self-update for Rust winetricks is not yet fully implemented.");
-    eprintln!("For now, please update by:");
-    eprintln!("  1. Pulling latest changes: git pull");
-    eprintln!("  2. Rebuilding: cargo build --release");
-    eprintln!("  3. Reinstalling the binary");
-    eprintln!();
-    eprintln!("Future versions will support downloading pre-built binaries from GitHub releases.");
+    println!("Checking for updates...");
+    let release = winetricks_lib::self_update::latest_release(torify).await?;
+    let current_version = env!("CARGO_PKG_VERSION");
+    if !winetricks_lib::self_update::is_newer(release.version(), current_version) {
+        println!(
+            "winetricks {} is up to date (latest release: {})",
+            current_version,
+            release.version()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "Updating winetricks {} to {}...",
+        current_version,
+        release.version()
+    );
+    if let Err(e) =
+        winetricks_lib::self_update::install_release(&release, &current_exe, torify).await
+    {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+    println!(
+        "Updated to {} (previous version kept as {}, undo with --update-rollback)",
+        release.version(),
+        winetricks_lib::self_update::backup_path(&current_exe).display()
+    );
 
     Ok(())
 }
@@ -1294,7 +2836,7 @@ async fn handle_update_rollback() -> Result<()> {
         WinetricksError::Config(format!("Could not determine executable path: {}", e))
     })?;
 
-    let rollback_file = current_exe.with_extension("bak");
+    let rollback_file = winetricks_lib::self_update::backup_path(&current_exe);
 
     if !rollback_file.exists() {
         eprintln!("No backup found. Nothing to rollback.");
@@ -1302,18 +2844,15 @@ async fn handle_update_rollback() -> Result<()> {
         process::exit(1);
     }
 
-    eprintln!("Rollback for Rust winetricks is not yet fully implemented.");
-    eprintln!("To rollback manually:");
-    eprintln!("  1. Backup file exists at: {}", rollback_file.display());
-    eprintln!("  2. Copy it to replace current executable");
-    eprintln!();
-    eprintln!("Example:");
-    eprintln!(
-        "  sudo cp {} {}",
-        rollback_file.display(),
-        current_exe.display()
+    if let Err(e) = winetricks_lib::self_update::rollback(&current_exe) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+    println!(
+        "Rolled back {} (the replaced version is now {})",
+        current_exe.display(),
+        rollback_file.display()
     );
-    eprintln!("  sudo chmod +x {}", current_exe.display());
 
     Ok(())
 }
@@ -0,0 +1,224 @@
+//! Runs the winetricks binary with --no-wine-check against a throwaway home directory,
+//! so download, registry and verb-selection logic is tested on machines without Wine
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Home directory with a one-verb metadata cache (testdll) and an empty wineprefix
+struct Home {
+    dir: tempfile::TempDir,
+}
+
+impl Home {
+    fn new(file_url: &str) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let dlls = dir.path().join("config/winetricks/dlls");
+        std::fs::create_dir_all(&dlls).unwrap();
+        std::fs::write(
+            dlls.join("testdll.json"),
+            format!(
+                r#"{{"name": "testdll", "category": "dlls", "title": "Test DLL",
+                    "files": [{{"filename": "testdll.zip", "url": "{}"}}]}}"#,
+                file_url
+            ),
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("prefix/drive_c/windows/system32")).unwrap();
+        Self { dir }
+    }
+
+    fn path(&self, relative: &str) -> PathBuf {
+        self.dir.path().join(relative)
+    }
+
+    fn winetricks(&self, args: &[&str]) -> Output {
+        let home = self.dir.path();
+        Command::new(env!("CARGO_BIN_EXE_winetricks"))
+            .arg("--no-wine-check")
+            .args(args)
+            .env_clear()
+            .env("PATH", "/usr/bin:/bin")
+            .env("HOME", home)
+            .env("XDG_CONFIG_HOME", home.join("config"))
+            .env("XDG_CACHE_HOME", home.join("cache"))
+            .env("XDG_DATA_HOME", home.join("data"))
+            .env("WINEPREFIX", home.join("prefix"))
+            .output()
+            .unwrap()
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Serve `body` for every request on a local port; returns the base URL
+fn serve(body: &'static [u8]) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(header.as_bytes());
+            if !request.starts_with(b"HEAD") {
+                let _ = stream.write_all(body);
+            }
+        }
+    });
+    url
+}
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "winetricks failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn lists_verbs_from_the_metadata_cache() {
+    let home = Home::new("http://127.0.0.1:9/testdll.zip");
+    let output = home.winetricks(&["dlls", "list"]);
+    assert_success(&output);
+    assert!(stdout(&output)
+        .lines()
+        .any(|line| line.starts_with("testdll")));
+}
+
+#[test]
+fn unknown_verbs_suggest_close_names() {
+    let home = Home::new("http://127.0.0.1:9/testdll.zip");
+    let output = home.winetricks(&["testdl"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Verb 'testdl' not found"), "{}", stderr);
+    assert!(stderr.contains("testdll"), "{}", stderr);
+}
+
+#[test]
+fn list_files_finds_dlls_in_system32() {
+    let home = Home::new("http://127.0.0.1:9/testdll.zip");
+    let dll = home.path("prefix/drive_c/windows/system32/testdll.dll");
+    std::fs::write(&dll, b"MZ").unwrap();
+
+    let output = home.winetricks(&["list-files", "testdll"]);
+    assert_success(&output);
+    assert!(stdout(&output).contains(&dll.display().to_string()));
+}
+
+#[test]
+fn preload_downloads_into_the_cache() {
+    let url = format!("{}/testdll.zip", serve(b"zip contents"));
+    let home = Home::new(&url);
+
+    let output = home.winetricks(&["preload", "testdll"]);
+    assert_success(&output);
+    assert!(stdout(&output).contains("testdll: cached"));
+    assert_eq!(
+        std::fs::read(home.path("cache/winetricks/testdll/testdll.zip")).unwrap(),
+        b"zip contents"
+    );
+}
+
+#[test]
+fn annihilate_refuses_prefix_names_outside_the_prefixes_root() {
+    let home = Home::new("http://127.0.0.1:9/testdll.zip");
+    std::fs::create_dir_all(home.path("data/wineprefixes/game/drive_c")).unwrap();
+
+    for name in ["", ".", "..", "../..", "/tmp"] {
+        let output = home.winetricks(&["-q", "annihilate", &format!("--prefix={}", name)]);
+        assert!(!output.status.success(), "--prefix={} was accepted", name);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Invalid prefix name"), "{}", stderr);
+    }
+    assert!(home.path("data/wineprefixes/game/drive_c").is_dir());
+}
+
+#[test]
+fn prefix_actions_refuse_names_outside_the_prefixes_root() {
+    let home = Home::new("http://127.0.0.1:9/testdll.zip");
+    std::fs::create_dir_all(home.path("data/wineprefixes/game/drive_c")).unwrap();
+
+    for action in ["delete", "create", "info", "check"] {
+        let output = home.winetricks(&["-q", "prefix", action, ".."]);
+        assert!(
+            !output.status.success(),
+            "prefix {} .. was accepted",
+            action
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Invalid prefix name"), "{}", stderr);
+    }
+    assert!(home.path("data/wineprefixes/game/drive_c").is_dir());
+}
+
+#[test]
+fn imported_verbs_are_kept_apart_from_the_metadata_cache() {
+    let home = Home::new("http://127.0.0.1:9/testdll.zip");
+    let import = home.path("import.json");
+    std::fs::write(
+        &import,
+        r#"[{"name": "testdll", "category": "dlls", "title": "Imported DLL"},
+            {"name": "mydll", "category": "dlls", "title": "My DLL"}]"#,
+    )
+    .unwrap();
+
+    let output = home.winetricks(&["import-verbs", import.to_str().unwrap()]);
+    assert_success(&output);
+    assert!(stdout(&output).contains("1 replaced existing verbs"));
+    assert!(home
+        .path("data/winetricks/imported-verbs/dlls/mydll.json")
+        .is_file());
+    // The metadata cache (rewritten by metadata refreshes) is left alone
+    assert!(!home.path("config/winetricks/dlls/mydll.json").exists());
+
+    let output = home.winetricks(&["--output-format=csv", "dlls", "list"]);
+    assert_success(&output);
+    let listed = stdout(&output);
+    assert!(listed.contains("testdll,Imported DLL,dlls"), "{}", listed);
+    assert!(listed.contains("mydll,My DLL,dlls"), "{}", listed);
+}
+
+#[test]
+fn list_cached_sizes_are_part_of_json_and_csv_output() {
+    let home = Home::new("http://127.0.0.1:9/testdll.zip");
+    std::fs::write(
+        home.path("config/winetricks/dlls/bigdll.json"),
+        r#"{"name": "bigdll", "category": "dlls", "title": "Big DLL",
+            "files": [{"filename": "bigdll.zip", "url": "http://127.0.0.1:9/bigdll.zip"}]}"#,
+    )
+    .unwrap();
+    for (verb, size) in [("testdll", 10), ("bigdll", 2000)] {
+        let dir = home.path(&format!("cache/winetricks/{}", verb));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(format!("{}.zip", verb)), vec![0; size]).unwrap();
+    }
+
+    let output = home.winetricks(&["--output-format=csv", "list-cached", "--sort=size"]);
+    assert_success(&output);
+    assert_eq!(
+        stdout(&output),
+        "name,title,category,size\nbigdll,Big DLL,dlls,2000\ntestdll,Test DLL,dlls,10\n"
+    );
+
+    let output = home.winetricks(&["--output-format=json", "list-cached", "--sizes"]);
+    assert_success(&output);
+    let records: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(records[0]["name"], "bigdll");
+    assert_eq!(records[0]["size"], "2000");
+    assert_eq!(records[1]["name"], "testdll");
+    assert_eq!(records[1]["size"], "10");
+}
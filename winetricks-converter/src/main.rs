@@ -5,35 +5,112 @@ use clap::Parser;
 use regex::Regex;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use winetricks_lib::{MediaType, VerbCategory, VerbFile, VerbMetadata};
+use winetricks_lib::{
+    ArchSupport, MediaType, VerbCategory, VerbFile, VerbMetadata, VerbRegistry, WineBugWorkaround,
+};
 
 #[derive(Parser)]
 #[command(name = "winetricks-converter")]
 #[command(about = "Convert original winetricks script to JSON metadata")]
 struct Cli {
     /// Input winetricks script file
-    #[arg(short, long)]
-    input: PathBuf,
+    #[arg(short, long, required_unless_present_any = ["validate", "bundle"])]
+    input: Option<PathBuf>,
 
     /// Output directory for JSON metadata files
     #[arg(short, long, default_value = "files/json")]
     output: PathBuf,
+
+    /// Check the output directory for unknown dependencies/conflicts, bad URLs and
+    /// bad SHA256 checksums (after converting, if --input is given)
+    #[arg(long)]
+    validate: bool,
+
+    /// Also write all verbs of the output directory to this file as one JSON array
+    /// (the metadata bundle fetched by winetricks when it has no local verb files)
+    #[arg(long)]
+    bundle: Option<PathBuf>,
+}
+
+/// Problem found by validate_registry
+#[derive(Debug)]
+struct ValidationError {
+    verb: String,
+    field: String,
+    message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.verb, self.field, self.message)
+    }
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    println!("Reading winetricks script: {:?}", cli.input);
-    let content = fs::read_to_string(&cli.input)
-        .with_context(|| format!("Failed to read {:?}", cli.input))?;
+    if let Some(input) = &cli.input {
+        convert(input, &cli.output)?;
+    }
+
+    if cli.validate {
+        println!("Validating {:?}...", cli.output);
+        let errors = validate_registry(&cli.output);
+        for error in &errors {
+            eprintln!("{}", error);
+        }
+        if !errors.is_empty() {
+            eprintln!("{} validation error(s)", errors.len());
+            std::process::exit(1);
+        }
+        println!("No validation errors");
+    }
+
+    if let Some(bundle) = &cli.bundle {
+        let registry = VerbRegistry::load_from_dir(&cli.output)
+            .with_context(|| format!("Failed to load verbs from {:?}", cli.output))?;
+        fs::write(bundle, registry.export_json()?)
+            .with_context(|| format!("Failed to write {:?}", bundle))?;
+        println!("Wrote {} verbs to {:?}", registry.list().len(), bundle);
+    }
+    Ok(())
+}
+
+fn convert(input: &Path, output: &Path) -> Result<()> {
+    println!("Reading winetricks script: {:?}", input);
+    let content =
+        fs::read_to_string(input).with_context(|| format!("Failed to read {:?}", input))?;
 
     // First, parse all load_* functions to extract download URLs and SHA256 hashes
     println!("Extracting download URLs and SHA256 hashes from load_* functions...");
     let downloads = extract_downloads(&content)?;
     println!("Found {} download entries", downloads.len());
 
+    // Extract w_workaround_wine_bug annotations from load_* functions
+    println!("Extracting Wine bug workarounds from load_* functions...");
+    let workarounds = extract_workarounds(&content)?;
+    println!("Found workarounds for {} verbs", workarounds.len());
+
+    // Extract w_call / w_try_uninstall_from_registry verb references from load_* functions
+    println!("Extracting verb calls and conflicts from load_* functions...");
+    let references = extract_verb_references(&content)?;
+    println!("Found verb references for {} verbs", references.len());
+
+    // Extract w_download_manual pages and "# Manual download:" comments from load_* functions
+    println!("Extracting manual download instructions from load_* functions...");
+    let manual_downloads = extract_manual_downloads(&content)?;
+    println!(
+        "Found manual download instructions for {} verbs",
+        manual_downloads.len()
+    );
+
+    // Extract "# Homepage:" comments from load_* functions
+    println!("Extracting homepages from load_* functions...");
+    let homepages = extract_homepages(&content)?;
+    println!("Found homepages for {} verbs", homepages.len());
+
     // Create output directories for each category
     let categories = [
         "apps",
@@ -41,11 +118,13 @@ fn main() -> Result<()> {
         "fonts",
         "settings",
         "benchmarks",
+        "codecs",
+        "runtimes",
         "download",
         "manual-download",
     ];
     for cat in &categories {
-        fs::create_dir_all(cli.output.join(cat))?;
+        fs::create_dir_all(output.join(cat))?;
     }
 
     // Pattern to match w_metadata calls
@@ -57,6 +136,10 @@ fn main() -> Result<()> {
     let mut in_metadata = false;
     let mut metadata_lines = Vec::new();
     let mut verbs = Vec::new();
+    // "# Notes:" comment block following the metadata declaration
+    let mut looking_for_notes = false;
+    let mut in_notes = false;
+    let mut notes_lines: Vec<String> = Vec::new();
 
     for line in content.lines() {
         // Check if this is a metadata declaration start
@@ -66,6 +149,14 @@ fn main() -> Result<()> {
                 if let Ok(mut verb) = parse_metadata(&verb_name, &category, &metadata_lines) {
                     // Enrich with download URLs from load_* functions
                     enrich_with_downloads(&mut verb, &downloads);
+                    detect_file_arches(&mut verb.files);
+                    if let Some(verb_workarounds) = workarounds.get(&verb.name) {
+                        verb.wine_bug_workarounds = verb_workarounds.clone();
+                    }
+                    add_verb_references(&mut verb, &references);
+                    add_manual_download(&mut verb, &manual_downloads);
+                    verb.homepage = homepages.get(&verb.name).cloned();
+                    verb.description = notes_description(&notes_lines);
                     verbs.push(verb);
                 }
             }
@@ -73,6 +164,8 @@ fn main() -> Result<()> {
             verb_name = caps[1].to_string();
             category = caps[2].to_string();
             metadata_lines.clear();
+            notes_lines.clear();
+            in_notes = false;
             in_metadata = true;
 
             // Get continuation lines
@@ -82,11 +175,30 @@ fn main() -> Result<()> {
             } else {
                 // Single line metadata (unlikely but possible)
                 in_metadata = false;
+                looking_for_notes = true;
             }
         } else if in_metadata {
             metadata_lines.push(line.to_string());
             if !line.trim().ends_with('\\') {
                 in_metadata = false;
+                looking_for_notes = true;
+            }
+        } else if looking_for_notes {
+            let trimmed = line.trim();
+            if in_notes {
+                // Notes continue until the first non-comment line
+                if let Some(note) = trimmed.strip_prefix('#') {
+                    notes_lines.push(note.trim().to_string());
+                    continue;
+                }
+                in_notes = false;
+                looking_for_notes = false;
+            } else if let Some(note) = trimmed.strip_prefix("# Notes:") {
+                notes_lines.push(note.trim().to_string());
+                in_notes = true;
+            } else if !trimmed.is_empty() {
+                // Only blank lines may separate the metadata from its notes
+                looking_for_notes = false;
             }
         }
     }
@@ -96,6 +208,14 @@ fn main() -> Result<()> {
         if let Ok(mut verb) = parse_metadata(&verb_name, &category, &metadata_lines) {
             // Enrich with download URLs from load_* functions
             enrich_with_downloads(&mut verb, &downloads);
+            detect_file_arches(&mut verb.files);
+            if let Some(verb_workarounds) = workarounds.get(&verb.name) {
+                verb.wine_bug_workarounds = verb_workarounds.clone();
+            }
+            add_verb_references(&mut verb, &references);
+            add_manual_download(&mut verb, &manual_downloads);
+            verb.homepage = homepages.get(&verb.name).cloned();
+            verb.description = notes_description(&notes_lines);
             verbs.push(verb);
         }
     }
@@ -104,7 +224,7 @@ fn main() -> Result<()> {
 
     // Write JSON files
     for verb in verbs {
-        let cat_dir = cli.output.join(verb.category.as_str());
+        let cat_dir = output.join(verb.category.as_str());
         let json_file = cat_dir.join(format!("{}.json", verb.name));
 
         let json = serde_json::to_string_pretty(&verb)?;
@@ -117,9 +237,90 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Check the converted verbs in dir (<category>/<verb>.json) against each other:
+/// dependencies and conflicts must name existing verbs, download URLs must parse and
+/// SHA256 checksums must be 64 hex digits
+fn validate_registry(dir: &Path) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|category| category.path())
+        .filter(|path| path.is_dir())
+        .flat_map(|category_dir| fs::read_dir(category_dir).into_iter().flatten().flatten())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    files.sort();
+
+    let mut verbs = Vec::new();
+    for path in &files {
+        let name = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<VerbMetadata>(&content).map_err(|e| e.to_string())
+            });
+        match parsed {
+            Ok(verb) => verbs.push((name, verb)),
+            Err(e) => errors.push(ValidationError {
+                verb: name,
+                field: "json".to_string(),
+                message: format!("{} could not be loaded: {}", path.display(), e),
+            }),
+        }
+    }
+
+    let known: std::collections::HashSet<&str> =
+        verbs.iter().map(|(name, _)| name.as_str()).collect();
+    let sha256_re = Regex::new(r"^[0-9a-fA-F]{64}$").expect("valid regex");
+
+    for (name, verb) in &verbs {
+        let mut error = |field: &str, message: String| {
+            errors.push(ValidationError {
+                verb: name.clone(),
+                field: field.to_string(),
+                message,
+            })
+        };
+
+        for dependency in &verb.dependencies {
+            if !known.contains(dependency.as_str()) {
+                error("dependencies", format!("unknown verb {:?}", dependency));
+            }
+        }
+        for conflict in &verb.conflicts {
+            if !known.contains(conflict.as_str()) {
+                error("conflicts", format!("unknown verb {:?}", conflict));
+            }
+        }
+        for file in &verb.files {
+            if let Some(url) = &file.url {
+                if let Err(e) = url::Url::parse(url) {
+                    error("url", format!("{:?} ({}): {}", url, file.filename, e));
+                }
+            }
+            if let Some(sha256) = &file.sha256 {
+                if !sha256_re.is_match(sha256) {
+                    error(
+                        "sha256",
+                        format!("{:?} ({}) is not 64 hex digits", sha256, file.filename),
+                    );
+                }
+            }
+        }
+    }
+
+    errors
+}
+
 fn parse_metadata(name: &str, cat: &str, lines: &[String]) -> Result<VerbMetadata> {
-    let category = VerbCategory::from_str(cat)
-        .map_err(|e| anyhow::anyhow!("Unknown category: {} ({})", cat, e))?;
+    let category = reclassify_category(name, infer_category(name, cat));
 
     let mut title = name.to_string();
     let mut publisher = None;
@@ -151,16 +352,25 @@ fn parse_metadata(name: &str, cat: &str, lines: &[String]) -> Result<VerbMetadat
             media = match media_val.as_str() {
                 "download" => MediaType::Download,
                 "manual_download" => MediaType::ManualDownload,
+                "dvd" => MediaType::Dvd,
+                "cd" => MediaType::CdRom,
                 _ => MediaType::Download,
             };
         } else if line.starts_with("file") && line.contains("=") {
             // Handle file1=, file2=, file3=, etc.
+            let key = line.split('=').next().unwrap_or_default();
+            let index = key.trim_start_matches("file").parse::<usize>().unwrap_or(0);
             let filename = extract_value(line);
-            files.push(VerbFile {
-                filename,
-                url: None,    // Will be extracted from load function
-                sha256: None, // Will be extracted from load function
-            });
+            files.push((
+                index,
+                VerbFile {
+                    filename,
+                    url: None,    // Will be extracted from load function
+                    sha256: None, // Will be extracted from load function
+                    size: None,
+                    arch: None,
+                },
+            ));
         } else if line.starts_with("installed_file") && line.contains("=") {
             // Handle installed_file1=, installed_file2=, etc. (use first one)
             if installed_file.is_none() {
@@ -180,20 +390,210 @@ fn parse_metadata(name: &str, cat: &str, lines: &[String]) -> Result<VerbMetadat
         }
     }
 
+    // Keep files in fileN= order so they line up with the w_download calls
+    files.sort_by_key(|(index, _)| *index);
+    let files: Vec<VerbFile> = files.into_iter().map(|(_, file)| file).collect();
+
     Ok(VerbMetadata {
         name: name.to_string(),
         category,
         title,
+        description: None,
         publisher,
         year,
+        homepage: None,
         media,
+        // x86/x64 file names don't restrict the prefix (WoW64 runs 32-bit installers
+        // in 64-bit prefixes), so only hand-written metadata narrows this down
+        architecture: ArchSupport::Both,
         files,
         installed_file,
         installed_exe,
         conflicts,
+        dependencies: Vec::new(),
+        calls: Vec::new(),
+        calls_after: Vec::new(),
+        tags: infer_tags(name, category),
+        purchase_url: None,
+        download_instructions: None,
+        broken_wine_versions: Vec::new(),
+        wine_bug_workarounds: Vec::new(),
+        msi_features: Vec::new(),
     })
 }
 
+/// Set VerbFile::arch for verbs that ship separate 32-bit and 64-bit files
+/// (foo_x86.msi + foo_x64.msi); a lone foo_x86.exe stays None since it is the
+/// verb's installer in every prefix
+fn detect_file_arches(files: &mut [VerbFile]) {
+    let arch_of = |file: &VerbFile| {
+        let name = file.filename.to_lowercase().replace("x86_64", "x64");
+        let mut x86 = false;
+        let mut x64 = false;
+        for token in name.split(|c: char| !c.is_ascii_alphanumeric()) {
+            match token {
+                "x86" | "i386" | "i686" => x86 = true,
+                "x64" | "amd64" => x64 = true,
+                _ => {}
+            }
+        }
+        // Combined installers (NDP48-x86-x64-AllOS-ENU.exe) are for both
+        match (x86, x64) {
+            (true, false) => Some("x86"),
+            (false, true) => Some("x64"),
+            _ => None,
+        }
+    };
+
+    let arches: Vec<Option<&str>> = files.iter().map(arch_of).collect();
+    if !(arches.contains(&Some("x86")) && arches.contains(&Some("x64"))) {
+        return;
+    }
+    for (file, arch) in files.iter_mut().zip(arches) {
+        file.arch = arch.map(str::to_string);
+    }
+}
+
+/// Join a "# Notes:" comment block into a single description
+fn notes_description(lines: &[String]) -> Option<String> {
+    let description = lines
+        .iter()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if description.is_empty() {
+        None
+    } else {
+        Some(description)
+    }
+}
+
+/// Category from the w_metadata line, or guessed from the verb name when the
+/// category isn't one we know (so the verb isn't dropped)
+fn infer_category(name: &str, explicit: &str) -> VerbCategory {
+    if let Ok(category) = VerbCategory::from_str(explicit) {
+        return category;
+    }
+
+    let category = if ["dotnet", "vcrun", "vbrun"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    {
+        VerbCategory::Dlls
+    } else if name.ends_with("font") || name.ends_with("fonts") {
+        VerbCategory::Fonts
+    } else if name.contains("bench") {
+        VerbCategory::Benchmarks
+    } else {
+        VerbCategory::Apps
+    };
+    eprintln!(
+        "Warning: Unknown category '{}' for {}, using {}",
+        explicit,
+        name,
+        category.as_str()
+    );
+    category
+}
+
+/// Move runtimes and codecs out of the catch-all dlls category (by verb name)
+fn reclassify_category(name: &str, category: VerbCategory) -> VerbCategory {
+    if category != VerbCategory::Dlls {
+        return category;
+    }
+
+    if ["dotnet", "vcrun", "vbrun"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    {
+        VerbCategory::Runtimes
+    } else if ["xvid", "divx", "wmp", "wmv"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    {
+        VerbCategory::Codecs
+    } else {
+        category
+    }
+}
+
+/// Tags implied by verb name prefixes: (tags, prefixes)
+const TAG_RULES: &[(&[&str], &[&str])] = &[
+    // Microsoft DirectX components
+    (
+        &["directx", "microsoft"],
+        &[
+            "d3d",
+            "dinput",
+            "directmusic",
+            "directplay",
+            "directshow",
+            "directx",
+            "dpvoice",
+            "dsound",
+            "dswave",
+            "dx8vb",
+            "dxdiag",
+            "mdx",
+            "xact",
+            "xaudio",
+            "xinput",
+        ],
+    ),
+    // DirectX on top of Vulkan
+    (&["directx", "vulkan"], &["dxvk", "vkd3d"]),
+    (
+        &["gaming"],
+        &[
+            "binkw32",
+            "cnc_ddraw",
+            "dinput",
+            "dxvk",
+            "faudio",
+            "galliumnine",
+            "gfw",
+            "glidewrapper",
+            "openal",
+            "physx",
+            "sdl",
+            "vkd3d",
+            "xact",
+            "xaudio",
+            "xinput",
+            "xna",
+        ],
+    ),
+    // Microsoft runtimes
+    (
+        &["microsoft", "runtime"],
+        &["dotnet", "mfc", "msvc", "ucrtbase", "vcrun", "vjrun", "xna"],
+    ),
+];
+
+/// Pre-populate tags from the verb name (all d3dx* get "directx", dotnet* get
+/// "microsoft" and "runtime", ...)
+fn infer_tags(name: &str, category: VerbCategory) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for (rule_tags, prefixes) in TAG_RULES {
+        if prefixes.iter().any(|prefix| name.starts_with(prefix)) {
+            tags.extend(rule_tags.iter().map(|t| t.to_string()));
+        }
+    }
+
+    // DirectMusic DLLs (dmband, dmime, ...) and Visual Basic runtimes (vb6run)
+    if category == VerbCategory::Dlls && name.starts_with("dm") {
+        tags.extend(["directx", "microsoft"].map(String::from));
+    }
+    if name.starts_with("vb") && name.ends_with("run") {
+        tags.extend(["microsoft", "runtime"].map(String::from));
+    }
+
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
 fn extract_value(line: &str) -> String {
     // Extract value from key="value" or key=value
     if let Some(eq_pos) = line.find('=') {
@@ -205,89 +605,373 @@ fn extract_value(line: &str) -> String {
     }
 }
 
-/// Extract download URLs and SHA256 hashes from load_* functions
-/// Returns a map of verb name to (filename, url, sha256)
-fn extract_downloads(content: &str) -> Result<HashMap<String, Vec<(String, String, String)>>> {
-    let mut downloads: HashMap<String, Vec<(String, String, String)>> = HashMap::new();
-
+/// Call `visit` with the name and body lines of every load_<verb>() function
+/// The body runs from the line after the definition to the line closing its braces;
+/// braces in comment lines aren't counted
+fn for_each_load_function(
+    content: &str,
+    mut visit: impl FnMut(&str, &[&str]) -> Result<()>,
+) -> Result<()> {
     // Pattern to match load_<verb_name>() function
     let load_func_re = Regex::new(r"^load_(\w+)\(\)")?;
-    // Pattern to match w_download calls: w_download <url> <sha256>
-    let w_download_re = Regex::new(r"^\s+w_download\s+(\S+)\s+(\S+)")?;
-    // Pattern to match w_download_to calls: w_download_to <cache_dir> "<url>" <sha256>
-    // We'll use a simpler regex and fallback to manual parsing
-    let w_download_to_re = Regex::new(r"^\s+w_download_to")?;
 
-    let lines: Vec<&str> = content.lines().collect();
-    let mut current_verb: Option<String> = None;
-    let mut in_function = false;
+    let mut current: Option<(String, Vec<&str>)> = None;
     let mut brace_depth = 0;
 
-    for line in lines.iter() {
-        // Check if this is a load_* function definition
+    for line in content.lines() {
+        // A new definition also ends a function whose braces never closed
         if let Some(caps) = load_func_re.captures(line) {
-            current_verb = Some(caps[1].to_string());
-            in_function = true;
-            brace_depth = 0;
+            if let Some((verb_name, body)) = current.take() {
+                visit(&verb_name, &body)?;
+            }
+            current = Some((caps[1].to_string(), Vec::new()));
+            brace_depth = line.matches('{').count() as i32 - line.matches('}').count() as i32;
+            continue;
+        }
 
-            // Count opening braces on this line
-            brace_depth += line.matches('{').count();
-            brace_depth -= line.matches('}').count();
+        let Some((_, ref mut body)) = current else {
             continue;
+        };
+        body.push(line);
+
+        // Count braces to track function scope
+        if !line.trim_start().starts_with('#') {
+            brace_depth += line.matches('{').count() as i32;
+            brace_depth -= line.matches('}').count() as i32;
         }
 
-        if let Some(ref verb_name) = current_verb {
-            if in_function {
-                // Count braces to track function scope
-                brace_depth += line.matches('{').count();
-                brace_depth -= line.matches('}').count();
+        // Function ended
+        if brace_depth <= 0 {
+            if let Some((verb_name, body)) = current.take() {
+                visit(&verb_name, &body)?;
+            }
+        }
+    }
+    if let Some((verb_name, body)) = current {
+        visit(&verb_name, &body)?;
+    }
 
-                // Check for w_download calls
-                if let Some(caps) = w_download_re.captures(line) {
-                    let url = caps.get(1).unwrap().as_str().to_string();
-                    let sha256 = caps.get(2).unwrap().as_str().to_string();
+    Ok(())
+}
 
-                    // Try to extract filename from URL or previous file1= assignment
-                    let filename = extract_filename_from_url(&url);
+/// Extract download URLs and SHA256 hashes from load_* functions
+/// Returns a map of verb name to (filename, url, sha256)
+fn extract_downloads(content: &str) -> Result<HashMap<String, Vec<(String, String, String)>>> {
+    let mut downloads: HashMap<String, Vec<(String, String, String)>> = HashMap::new();
 
+    // Pattern to match w_download calls: w_download <url> <sha256> [filename]
+    let w_download_re = Regex::new(r"^\s+w_download\s+\S+\s+\S+")?;
+    // Pattern to match w_download_to calls: w_download_to <cache_dir> "<url>" <sha256> [filename]
+    // We'll use a simpler regex and fallback to manual parsing
+    let w_download_to_re = Regex::new(r"^\s+w_download_to\s")?;
+
+    for_each_load_function(content, |verb_name, body| {
+        for line in body {
+            // Check for w_download calls: w_download <url> <sha256> [filename]
+            if w_download_re.is_match(line) {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if let Some(download) = parse_download_args(&parts[1..]) {
                     downloads
-                        .entry(verb_name.clone())
-                        .or_insert_with(Vec::new)
-                        .push((filename, url, sha256));
+                        .entry(verb_name.to_string())
+                        .or_default()
+                        .push(download);
                 }
+            }
 
-                // Check for w_download_to calls (used by fonts: w_download_to corefonts "url" sha256)
-                // Format: w_download_to <cache_dir> "<url>" <sha256>
-                if w_download_to_re.is_match(line) {
-                    // Parse manually: w_download_to corefonts "https://..." sha256
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 4 {
-                        // parts[0] = "w_download_to"
-                        // parts[1] = cache_dir (e.g., "corefonts")
-                        // parts[2] = url (may be quoted)
-                        // parts[3] = sha256
-                        let mut url = parts[2].to_string();
-                        url = url.trim_matches('"').trim_matches('\'').to_string();
-                        let sha256 = parts[3].to_string();
-                        let filename = extract_filename_from_url(&url);
-
+            // Check for w_download_to calls (used by fonts: w_download_to corefonts "url" sha256)
+            // Format: w_download_to <cache_dir> "<url>" <sha256> [filename]
+            if w_download_to_re.is_match(line) {
+                // parts[0] = "w_download_to", parts[1] = cache_dir (e.g., "corefonts")
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 4 {
+                    if let Some(download) = parse_download_args(&parts[2..]) {
                         downloads
-                            .entry(verb_name.clone())
-                            .or_insert_with(Vec::new)
-                            .push((filename, url, sha256));
+                            .entry(verb_name.to_string())
+                            .or_default()
+                            .push(download);
                     }
                 }
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok(downloads)
+}
+
+/// Extract w_workaround_wine_bug calls from load_* functions
+/// Returns a map of verb name to workarounds (bug number, description, version ranges)
+fn extract_workarounds(content: &str) -> Result<HashMap<String, Vec<WineBugWorkaround>>> {
+    let mut workarounds: HashMap<String, Vec<WineBugWorkaround>> = HashMap::new();
 
-                // Function ended
-                if brace_depth <= 0 {
-                    in_function = false;
-                    current_verb = None;
+    // Pattern to match: w_workaround_wine_bug <bug> ["description"] [range ...]
+    // Usually wrapped in an if: if w_workaround_wine_bug 12345 "desc" ,5.0; then
+    let workaround_re = Regex::new(
+        r#"w_workaround_wine_bug\s+"?(\d+)"?(?:\s+(?:"([^"]*)"|'([^']*)'))?((?:\s+"?[\d.]*,[\d.]*"?)*)"#,
+    )?;
+
+    for_each_load_function(content, |verb_name, body| {
+        for line in body {
+            let Some(caps) = workaround_re.captures(line) else {
+                continue;
+            };
+            let bug_number: u32 = caps[1].parse()?;
+            let description = caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default();
+            let ranges: Vec<String> = caps
+                .get(4)
+                .map(|m| {
+                    m.as_str()
+                        .split_whitespace()
+                        .map(|r| r.trim_matches('"').to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            let affected_versions = if ranges.is_empty() {
+                None
+            } else {
+                Some(ranges.join(" "))
+            };
+
+            workarounds
+                .entry(verb_name.to_string())
+                .or_default()
+                .push(WineBugWorkaround {
+                    bug_number,
+                    description,
+                    affected_versions,
+                });
+        }
+        Ok(())
+    })?;
+
+    Ok(workarounds)
+}
+
+/// Verbs a load_* function refers to
+#[derive(Debug, Default)]
+struct VerbReferences {
+    /// w_call <verb> before the function's own installation step (prerequisites)
+    calls: Vec<String>,
+    /// w_call <verb> after the function's own installation step
+    calls_after: Vec<String>,
+    /// w_try_uninstall_from_registry <verb>: verbs this one replaces
+    conflicts: Vec<String>,
+}
+
+/// Extract w_call and w_try_uninstall_from_registry calls from load_* functions
+/// Arguments that aren't plain verb names (variables, flags) are skipped. The first
+/// w_try* command other than w_try_cd/w_try_uninstall_from_registry is taken as the
+/// function's own installation step; w_calls after it go to calls_after
+fn extract_verb_references(content: &str) -> Result<HashMap<String, VerbReferences>> {
+    let mut references: HashMap<String, VerbReferences> = HashMap::new();
+
+    // Pattern to match: w_call [--force] <verb> / w_try_uninstall_from_registry <verb>
+    let call_re = Regex::new(
+        r#"\b(w_call|w_try_uninstall_from_registry)\s+(?:--?\w+\s+)*"?([A-Za-z0-9_.\-]+)"?(?:\s|;|$)"#,
+    )?;
+    // Pattern to match the commands that install something (w_try, w_try_ms_installer, ...)
+    let install_re = Regex::new(r"\bw_try(_\w+)?\b")?;
+
+    for_each_load_function(content, |verb_name, body| {
+        let mut installed = false;
+        for line in body {
+            // Skip commented out calls
+            let code = line.split('#').next().unwrap_or("");
+            for caps in call_re.captures_iter(code) {
+                let referenced = caps[2].to_string();
+                if referenced == verb_name {
+                    continue;
+                }
+                let entry = references.entry(verb_name.to_string()).or_default();
+                let list = if &caps[1] != "w_call" {
+                    &mut entry.conflicts
+                } else if installed {
+                    &mut entry.calls_after
+                } else {
+                    &mut entry.calls
+                };
+                if !list.contains(&referenced) {
+                    list.push(referenced);
                 }
             }
+            installed |= install_re.captures_iter(code).any(|caps| {
+                !matches!(
+                    caps.get(1).map(|m| m.as_str()),
+                    Some("_cd" | "_uninstall_from_registry")
+                )
+            });
+        }
+        Ok(())
+    })?;
+
+    Ok(references)
+}
+
+/// Add the calls and conflicts found in a verb's load_* function to its metadata
+fn add_verb_references(verb: &mut VerbMetadata, references: &HashMap<String, VerbReferences>) {
+    let Some(verb_references) = references.get(&verb.name) else {
+        return;
+    };
+    verb.calls = verb_references.calls.clone();
+    verb.calls_after = verb_references.calls_after.clone();
+    for conflict in &verb_references.conflicts {
+        if !verb.conflicts.contains(conflict) {
+            verb.conflicts.push(conflict.clone());
         }
     }
+}
 
-    Ok(downloads)
+/// Where and how to get the files of a manual download verb
+#[derive(Debug, Default)]
+struct ManualDownload {
+    /// First argument of w_download_manual
+    purchase_url: Option<String>,
+    /// "# Manual download:" comment, including the comment lines following it
+    instructions: Option<String>,
+}
+
+/// Extract w_download_manual URLs and "# Manual download:" comments from load_* functions
+fn extract_manual_downloads(content: &str) -> Result<HashMap<String, ManualDownload>> {
+    let mut manual_downloads: HashMap<String, ManualDownload> = HashMap::new();
+
+    // Pattern to match: w_download_manual <url> <filename> [sha256]
+    let download_manual_re = Regex::new(r#"^\s*w_download_manual\s+["']?(https?://[^"'\s]+)"#)?;
+
+    for_each_load_function(content, |verb_name, body| {
+        // Lines of the "# Manual download:" comment being read
+        let mut instruction_lines: Option<Vec<String>> = None;
+
+        for line in body {
+            let trimmed = line.trim();
+            // The comment continues until the first non-comment line
+            if let Some(ref mut lines) = instruction_lines {
+                if let Some(comment) = trimmed.strip_prefix('#') {
+                    lines.push(comment.trim().to_string());
+                    continue;
+                }
+                let instructions = lines.join(" ").trim().to_string();
+                if !instructions.is_empty() {
+                    manual_downloads
+                        .entry(verb_name.to_string())
+                        .or_default()
+                        .instructions = Some(instructions);
+                }
+                instruction_lines = None;
+            } else if let Some(comment) = trimmed.strip_prefix("# Manual download") {
+                instruction_lines = Some(vec![comment.trim_start_matches(':').trim().to_string()]);
+                continue;
+            }
+
+            if let Some(caps) = download_manual_re.captures(line) {
+                let entry = manual_downloads.entry(verb_name.to_string()).or_default();
+                if entry.purchase_url.is_none() {
+                    entry.purchase_url = Some(caps[1].to_string());
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok(manual_downloads)
+}
+
+/// Add the purchase page and instructions found in a verb's load_* function to its metadata
+fn add_manual_download(
+    verb: &mut VerbMetadata,
+    manual_downloads: &HashMap<String, ManualDownload>,
+) {
+    let Some(manual_download) = manual_downloads.get(&verb.name) else {
+        return;
+    };
+    verb.purchase_url = manual_download.purchase_url.clone();
+    verb.download_instructions = manual_download.instructions.clone();
+}
+
+/// Homepages of verbs whose load_* function has no "# Homepage:" comment
+const KNOWN_HOMEPAGES: &[(&str, &str)] = &[
+    ("7zip", "https://www.7-zip.org/"),
+    (
+        "dotnet48",
+        "https://dotnet.microsoft.com/download/dotnet-framework/net48",
+    ),
+    ("dxvk", "https://github.com/doitsujin/dxvk"),
+    ("faudio", "https://github.com/FNA-XNA/FAudio"),
+    ("firefox", "https://www.mozilla.org/firefox/"),
+    ("steam", "https://store.steampowered.com/"),
+    (
+        "vcrun2019",
+        "https://learn.microsoft.com/en-us/cpp/windows/latest-supported-vc-redist",
+    ),
+    (
+        "vcrun2022",
+        "https://learn.microsoft.com/en-us/cpp/windows/latest-supported-vc-redist",
+    ),
+    ("vkd3d", "https://github.com/HansKristian-Work/vkd3d-proton"),
+    ("vlc", "https://www.videolan.org/vlc/"),
+];
+
+/// Homepage from KNOWN_HOMEPAGES
+fn known_homepage(name: &str) -> Option<&'static str> {
+    KNOWN_HOMEPAGES
+        .iter()
+        .find(|(verb, _)| *verb == name)
+        .map(|(_, homepage)| *homepage)
+}
+
+/// Extract "# Homepage: <url>" comments from load_* functions, falling back to
+/// KNOWN_HOMEPAGES for functions without one
+fn extract_homepages(content: &str) -> Result<HashMap<String, String>> {
+    let mut homepages = HashMap::new();
+
+    let homepage_re = Regex::new(r"^\s*#\s*Homepage:\s*(\S+)")?;
+
+    for_each_load_function(content, |verb_name, body| {
+        let homepage = body
+            .iter()
+            .find_map(|line| homepage_re.captures(line))
+            .map(|caps| caps[1].to_string())
+            .or_else(|| known_homepage(verb_name).map(String::from));
+        if let Some(homepage) = homepage {
+            homepages.insert(verb_name.to_string(), homepage);
+        }
+        Ok(())
+    })?;
+
+    Ok(homepages)
+}
+
+/// Parse w_download arguments: <url> <sha256> [filename]
+/// Returns (filename, url, sha256); filename falls back to the last URL component
+fn parse_download_args(args: &[&str]) -> Option<(String, String, String)> {
+    let unquote = |s: &str| s.trim_matches('"').trim_matches('\'').to_string();
+
+    let url = unquote(args.first()?);
+    let sha256 = unquote(args.get(1)?);
+    // Stop at shell syntax following the call (e.g. "|| return", "; then")
+    let filename = args
+        .get(2)
+        .filter(|arg| !arg.starts_with(['|', '&', ';', '#']))
+        .map(|arg| unquote(arg))
+        .unwrap_or_else(|| extract_filename_from_url(&url));
+
+    Some((filename, url, sha256))
+}
+
+/// Replace ${fileN}/$fileN references with the verb's fileN= declarations
+fn resolve_file_vars(value: &str, files: &[VerbFile]) -> String {
+    let mut resolved = value.to_string();
+    // Highest index first so $file1 doesn't clobber $file10
+    for (index, file) in files.iter().enumerate().rev() {
+        let n = index + 1;
+        resolved = resolved
+            .replace(&format!("${{file{}}}", n), &file.filename)
+            .replace(&format!("$file{}", n), &file.filename);
+    }
+    resolved
 }
 
 /// Extract filename from URL or guess based on URL structure
@@ -306,47 +990,220 @@ fn extract_filename_from_url(url: &str) -> String {
 }
 
 /// Enrich verb metadata with download URLs and SHA256 hashes
+/// Downloads are matched to fileN= entries by filename first, then in call order
 fn enrich_with_downloads(
     verb: &mut VerbMetadata,
     downloads: &HashMap<String, Vec<(String, String, String)>>,
 ) {
-    if let Some(download_list) = downloads.get(&verb.name) {
-        // Match downloads to files based on filename
-        for file in &mut verb.files {
-            if file.url.is_none() {
-                // Try to find matching download by filename
-                for (filename, url, sha256) in download_list {
-                    if filename == &file.filename
-                        || file.filename.contains(filename)
-                        || filename.contains(&file.filename)
-                        || filename == "unknown"
-                    {
-                        file.url = Some(url.clone());
-                        file.sha256 = Some(sha256.clone());
-                        break;
-                    }
-                }
+    let Some(download_list) = downloads.get(&verb.name) else {
+        return;
+    };
 
-                // If still no match and we have downloads, use the first one
-                if file.url.is_none() && !download_list.is_empty() {
-                    let (filename, url, sha256) = &download_list[0];
-                    if file.filename.is_empty() || file.filename == "unknown" {
-                        file.filename = filename.clone();
-                    }
-                    file.url = Some(url.clone());
-                    file.sha256 = Some(sha256.clone());
-                }
+    // Resolve ${fileN} references in URLs and filenames
+    let download_list: Vec<(String, String, String)> = download_list
+        .iter()
+        .map(|(filename, url, sha256)| {
+            (
+                resolve_file_vars(filename, &verb.files),
+                resolve_file_vars(url, &verb.files),
+                sha256.clone(),
+            )
+        })
+        .collect();
+    let mut used = vec![false; download_list.len()];
+
+    // First pass: match downloads to files by filename
+    for file in &mut verb.files {
+        if file.url.is_some() {
+            continue;
+        }
+        if let Some(index) = download_list
+            .iter()
+            .zip(&used)
+            .position(|((filename, _, _), used)| !used && filename == &file.filename)
+        {
+            let (_, url, sha256) = &download_list[index];
+            file.url = Some(url.clone());
+            file.sha256 = Some(sha256.clone());
+            used[index] = true;
+        }
+    }
+
+    // Second pass: remaining files take the remaining downloads in order (fileN <-> Nth call)
+    for file in &mut verb.files {
+        if file.url.is_some() {
+            continue;
+        }
+        if let Some(index) = used.iter().position(|u| !u) {
+            let (filename, url, sha256) = &download_list[index];
+            if file.filename.is_empty() || file.filename == "unknown" {
+                file.filename = filename.clone();
             }
+            file.url = Some(url.clone());
+            file.sha256 = Some(sha256.clone());
+            used[index] = true;
         }
+    }
 
-        // If verb has no files defined but we have downloads, add them
-        if verb.files.is_empty() {
-            for (filename, url, sha256) in download_list {
-                verb.files.push(VerbFile {
-                    filename: filename.clone(),
-                    url: Some(url.clone()),
-                    sha256: Some(sha256.clone()),
-                });
+    // Downloads without a fileN= declaration are added as extra files
+    for (index, (filename, url, sha256)) in download_list.iter().enumerate() {
+        if !used[index] {
+            verb.files.push(VerbFile {
+                filename: filename.clone(),
+                url: Some(url.clone()),
+                sha256: Some(sha256.clone()),
+                size: None,
+                arch: None,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infer_tags_from_name_prefixes() {
+        assert_eq!(
+            infer_tags("d3dx9_43", VerbCategory::Dlls),
+            ["directx", "microsoft"]
+        );
+        assert_eq!(
+            infer_tags("dotnet48", VerbCategory::Runtimes),
+            ["microsoft", "runtime"]
+        );
+        assert_eq!(
+            infer_tags("dxvk2071", VerbCategory::Dlls),
+            ["directx", "gaming", "vulkan"]
+        );
+        assert!(infer_tags("7zip", VerbCategory::Apps).is_empty());
+    }
+
+    #[test]
+    fn w_calls_keep_their_side_of_the_installer() {
+        let script = r#"
+load_dotnet48()
+{
+    w_call remove_mono
+    w_call dotnet40
+    w_try_cd "${W_CACHE}/${W_PACKAGE}"
+    w_try_ms_installer "${WINE}" "${file1}" ${W_OPT_UNATTENDED:+/q}
+    w_call dotnet48_fixup
+}
+
+load_corefonts()
+{
+    w_call andale
+    w_call arial
+}
+"#;
+        let references = extract_verb_references(script).unwrap();
+        let dotnet = &references["dotnet48"];
+        assert_eq!(dotnet.calls, ["remove_mono", "dotnet40"]);
+        assert_eq!(dotnet.calls_after, ["dotnet48_fixup"]);
+        let corefonts = &references["corefonts"];
+        assert_eq!(corefonts.calls, ["andale", "arial"]);
+        assert!(corefonts.calls_after.is_empty());
+    }
+
+    #[test]
+    fn homepage_comments_take_precedence_over_known_homepages() {
+        let script = r#"
+load_vlc()
+{
+    w_call vcrun2019
+}
+
+load_firefox()
+{
+    # Homepage: https://www.mozilla.org/firefox/new/
+    w_download https://example.com/firefox.exe
+}
+
+load_pipes()
+{
+    w_try "${WINE}" pipes.exe
+}
+"#;
+        let homepages = extract_homepages(script).unwrap();
+        assert_eq!(homepages["vlc"], known_homepage("vlc").unwrap());
+        assert_eq!(homepages["firefox"], "https://www.mozilla.org/firefox/new/");
+        assert!(!homepages.contains_key("pipes"));
+    }
+
+    #[test]
+    fn converted_verbs_round_trip_to_markdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("winetricks");
+        fs::write(
+            &script,
+            r#"
+w_metadata vcrun2019 dlls \
+    title="Visual C++ 2015-2019 libraries" \
+    publisher="Microsoft" \
+    year="2019" \
+    media="download" \
+    file1="vc_redist.x86.exe" \
+    installed_file1="${W_SYSTEM32_DLLS_WIN}/vcruntime140.dll"
+
+load_vcrun2019()
+{
+    w_download https://aka.ms/vs/16/release/vc_redist.x86.exe 4c5dd2e4f8b1b5fa0c2d8c1d1d1d8c6cbf1e9d6b1c5a3f0e7c9d6b2a1f0e9d8c
+    w_try "${WINE}" vc_redist.x86.exe
+}
+
+w_metadata pipes apps \
+    title="Pipes | Tubes" \
+    publisher="Pipe Co" \
+    year="2001" \
+    media="manual_download" \
+    file1="pipes.exe"
+"#,
+        )
+        .unwrap();
+        let output = dir.path().join("json");
+        convert(&script, &output).unwrap();
+
+        let registry = VerbRegistry::load_from_dir(&output).unwrap();
+        let vcrun = registry.get("vcrun2019").unwrap();
+        assert_eq!(vcrun.category, VerbCategory::Runtimes);
+        assert_eq!(
+            vcrun.files[0].url.as_deref(),
+            Some("https://aka.ms/vs/16/release/vc_redist.x86.exe")
+        );
+        assert_eq!(
+            registry.to_markdown(),
+            "# Winetricks verbs\n\
+             \n## apps\n\n\
+             | Name | Title | Publisher | Year | Media |\n\
+             |------|-------|-----------|------|-------|\n\
+             | pipes | Pipes \\| Tubes | Pipe Co | 2001 | manual_download |\n\
+             \n## runtimes\n\n\
+             | Name | Title | Publisher | Year | Media |\n\
+             |------|-------|-----------|------|-------|\n\
+             | vcrun2019 | Visual C++ 2015-2019 libraries | Microsoft | 2019 | download |\n"
+        );
+    }
+
+    #[test]
+    fn checked_in_tags_and_homepages_match_converter() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../files/json");
+        let registry = VerbRegistry::load_from_dir(&dir).expect("files/json loads");
+        for verb in registry.iter() {
+            assert_eq!(
+                verb.tags,
+                infer_tags(&verb.name, verb.category),
+                "tags of {} differ from the converter's",
+                verb.name
+            );
+            if let Some(homepage) = known_homepage(&verb.name) {
+                assert_eq!(
+                    verb.homepage.as_deref(),
+                    Some(homepage),
+                    "homepage of {} differs from the converter's",
+                    verb.name
+                );
             }
         }
     }
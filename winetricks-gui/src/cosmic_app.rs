@@ -172,7 +172,8 @@ pub mod cosmic_impl {
             let metadata_dir = config.metadata_dir();
 
             let registry = if metadata_dir.exists() {
-                VerbRegistry::load_from_dir(metadata_dir).unwrap_or_else(|_| VerbRegistry::new())
+                VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())
+                    .unwrap_or_else(|_| VerbRegistry::new())
             } else {
                 VerbRegistry::new()
             };
@@ -682,23 +683,36 @@ pub mod cosmic_impl {
             let all_active = self.selected_category.is_none();
             let apps_active = self.selected_category == Some(VerbCategory::Apps);
             let dlls_active = self.selected_category == Some(VerbCategory::Dlls);
+            let runtimes_active = self.selected_category == Some(VerbCategory::Runtimes);
+            let codecs_active = self.selected_category == Some(VerbCategory::Codecs);
             let fonts_active = self.selected_category == Some(VerbCategory::Fonts);
             let settings_active = self.selected_category == Some(VerbCategory::Settings);
 
             let all_btn = self.category_button("All", all_active, None);
             let apps_btn = self.category_button("Apps", apps_active, Some(VerbCategory::Apps));
             let dlls_btn = self.category_button("DLLs", dlls_active, Some(VerbCategory::Dlls));
+            let runtimes_btn =
+                self.category_button("Runtimes", runtimes_active, Some(VerbCategory::Runtimes));
+            let codecs_btn =
+                self.category_button("Codecs", codecs_active, Some(VerbCategory::Codecs));
             let fonts_btn = self.category_button("Fonts", fonts_active, Some(VerbCategory::Fonts));
             let settings_btn =
                 self.category_button("Settings", settings_active, Some(VerbCategory::Settings));
 
-            let category_row =
-                row(vec![all_btn, apps_btn, dlls_btn, fonts_btn, settings_btn]).spacing(8);
+            let category_row = row(vec![
+                all_btn,
+                apps_btn,
+                dlls_btn,
+                runtimes_btn,
+                codecs_btn,
+                fonts_btn,
+                settings_btn,
+            ])
+            .spacing(8);
 
             let verbs: Vec<_> = if let Some(category) = self.selected_category {
                 self.registry
-                    .list_by_category(category)
-                    .into_iter()
+                    .iter_category(category)
                     .filter(|v| {
                         self.search_query.is_empty()
                             || v.name.contains(&self.search_query)
@@ -708,24 +722,17 @@ pub mod cosmic_impl {
                     })
                     .collect()
             } else {
-                [
-                    VerbCategory::Apps,
-                    VerbCategory::Dlls,
-                    VerbCategory::Fonts,
-                    VerbCategory::Settings,
-                    VerbCategory::Benchmarks,
-                ]
-                .iter()
-                .flat_map(|cat| self.registry.list_by_category(*cat))
-                .filter(|v| {
-                    self.search_query.is_empty()
-                        || v.name.contains(&self.search_query)
-                        || v.title
-                            .to_lowercase()
-                            .contains(&self.search_query.to_lowercase())
-                })
-                .take(100)
-                .collect()
+                self.registry
+                    .iter()
+                    .filter(|v| {
+                        self.search_query.is_empty()
+                            || v.name.contains(&self.search_query)
+                            || v.title
+                                .to_lowercase()
+                                .contains(&self.search_query.to_lowercase())
+                    })
+                    .take(100)
+                    .collect()
             };
 
             let verb_list: Vec<Element<Message>> = verbs
@@ -4,14 +4,28 @@
 
 #[cfg(feature = "cosmic")]
 mod cosmic_app;
+#[cfg(feature = "iced")]
+mod preferences;
 
 #[cfg(feature = "iced")]
 use iced::widget::{
-    button, checkbox, column, container, pick_list, progress_bar, row, scrollable, text, text_input,
+    button, checkbox, column, container, mouse_area, pick_list, progress_bar, row, scrollable,
+    text, text_input, tooltip,
+};
+#[cfg(feature = "iced")]
+use iced::{
+    Alignment, Application, Color, Command, Element, Length, Pixels, Settings, Subscription, Theme,
 };
 #[cfg(feature = "iced")]
-use iced::{Alignment, Color, Element, Length, Pixels, Sandbox, Settings, Theme};
-use winetricks_lib::{Config, VerbCategory, VerbRegistry};
+use preferences::{GuiPreferences, ThemeChoice};
+#[cfg(feature = "iced")]
+use std::sync::Arc;
+#[cfg(feature = "iced")]
+use tokio::sync::{mpsc, Mutex};
+use winetricks_lib::download::{CachedFileMetadata, DownloadManager};
+use winetricks_lib::{
+    Config, InstallProgress, ManualDownloadInfo, VerbCategory, VerbMetadata, VerbRegistry,
+};
 
 #[cfg(feature = "iced")]
 fn main() -> iced::Result {
@@ -71,6 +85,10 @@ fn main() {
     std::process::exit(1);
 }
 
+/// Progress updates of the running installation, tagged with the verb name
+#[cfg(feature = "iced")]
+type ProgressReceiver = Arc<Mutex<mpsc::UnboundedReceiver<(String, InstallProgress)>>>;
+
 #[cfg(feature = "iced")]
 struct WinetricksApp {
     config: Config,
@@ -78,6 +96,8 @@ struct WinetricksApp {
     current_view: View,
     search_query: String,
     selected_category: Option<VerbCategory>,
+    // Tag picked in the Browse tag cloud (narrows the category/search results)
+    selected_tag: Option<String>,
     installed_verbs: Vec<String>,
     // Preference state
     wineprefix_input: String,
@@ -87,6 +107,31 @@ struct WinetricksApp {
     wayland_selection: Option<WaylandDisplay>,
     // Operation status
     operation_status: Option<OperationStatus>,
+    // Verb currently being installed and its progress (0.0 - 1.0)
+    installing: Option<(String, f32)>,
+    // stderr lines of the running installation (shown in the output drawer)
+    install_output: Vec<String>,
+    // Progress updates from the running installation, tagged with the verb name
+    install_progress_rx: Option<ProgressReceiver>,
+    // Verbs waiting to be installed, in order
+    install_queue: Vec<String>,
+    // Index of the queue item currently being dragged
+    queue_drag: Option<usize>,
+    // Cached download files per verb (loaded when Preferences is opened)
+    cache_entries: Vec<(String, Vec<CachedFileMetadata>)>,
+    // Verb shown in the Browse detail panel
+    expanded_verb: Option<String>,
+    // Browse view shows checkboxes for installing several verbs at once
+    select_mode: bool,
+    // Verbs checked in select mode, in the order they were checked
+    selected_verbs: Vec<String>,
+    // Wineprefixes offered by the sidebar prefix selector
+    prefixes: Vec<PrefixOption>,
+    // Contents of the prefix's winetricks.log (loaded when the log view is opened)
+    install_log: String,
+    theme_choice: ThemeChoice,
+    // Keyboard focus in the Browse view
+    keyboard_focus: KeyboardFocus,
 }
 
 #[derive(Debug, Clone)]
@@ -101,8 +146,53 @@ enum View {
     Installed,
     Preferences,
     WineTools,
+    // Installation log, opened from the Wine Tools list
+    InstallLog,
 }
 
+/// Keyboard focus in the Browse view; Tab moves search box -> categories -> verbs
+/// iced has no accessibility tree or focusable buttons yet, so the app tracks and
+/// draws the focus itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyboardFocus {
+    Search,
+    // Index into BROWSE_CATEGORIES
+    Category(usize),
+    // Index into the visible verbs
+    Verb(usize),
+}
+
+/// Category buttons of the Browse view, in display (and Tab) order
+#[cfg(feature = "iced")]
+const BROWSE_CATEGORIES: [(&str, Option<VerbCategory>); 7] = [
+    ("All", None),
+    ("Apps", Some(VerbCategory::Apps)),
+    ("DLLs", Some(VerbCategory::Dlls)),
+    ("Runtimes", Some(VerbCategory::Runtimes)),
+    ("Codecs", Some(VerbCategory::Codecs)),
+    ("Fonts", Some(VerbCategory::Fonts)),
+    ("Settings", Some(VerbCategory::Settings)),
+];
+
+#[cfg(feature = "iced")]
+fn search_input_id() -> text_input::Id {
+    text_input::Id::new("search")
+}
+
+#[cfg(feature = "iced")]
+fn verb_list_id() -> scrollable::Id {
+    scrollable::Id::new("verb-list")
+}
+
+#[cfg(feature = "iced")]
+fn install_output_id() -> scrollable::Id {
+    scrollable::Id::new("install-output")
+}
+
+/// Installer output lines shown in the drawer (Copy Log copies all of them)
+#[cfg(feature = "iced")]
+const INSTALL_OUTPUT_LINES: usize = 20;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum WineArch {
     Auto,
@@ -139,6 +229,40 @@ impl Renderer {
     }
 }
 
+/// A wineprefix in the sidebar prefix selector
+#[derive(Debug, Clone)]
+struct PrefixOption {
+    path: std::path::PathBuf,
+    /// Tool managing the prefix root (Lutris, Bottles, Steam; "winetricks" for our own)
+    group: &'static str,
+}
+
+// The same prefix is the same option whichever root it was found in
+impl PartialEq for PrefixOption {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl std::fmt::Display for PrefixOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.group != "winetricks" {
+            write!(f, "{}: ", self.group)?;
+        }
+        // Proton prefixes (<appid>/pfx) are named by their app id
+        let named = if self.path.ends_with("pfx") {
+            self.path.parent().unwrap_or(&self.path)
+        } else {
+            &self.path
+        };
+        // Named prefixes show their name, anything else its full path
+        match named.file_name().and_then(|n| n.to_str()) {
+            Some(name) if !name.starts_with('.') => write!(f, "{}", name),
+            _ => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
 impl std::fmt::Display for Renderer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -180,11 +304,34 @@ impl std::fmt::Display for WaylandDisplay {
 enum Message {
     ViewChanged(View),
     SearchChanged(String),
+    // Enter in the search box moves the focus to the first result
+    SearchSubmitted,
+    // Keys not used by a widget (keyboard navigation)
+    KeyPressed(iced::keyboard::Key, iced::keyboard::Modifiers),
     CategorySelected(VerbCategory),
+    // Clicking the selected tag again clears it
+    TagSelected(String),
+    // Verb detail panel (clicking the open verb again closes it)
+    VerbSelected(String),
+    CloseVerbDetail,
+    // Bulk selection in the Browse view (leaving select mode clears the selection)
+    SelectModeToggled,
+    VerbChecked(String, bool),
+    SelectAll(Vec<String>, bool),
+    InstallSelected,
+    // Purchase page of a manual download verb
+    OpenUrl(String),
     InstallVerb(String),
     UninstallVerb(String),
     // Wine Tools messages
     RunWineTool(String),
+    // Installation log view
+    CopyInstallLog,
+    ExportInstallLog,
+    // Output drawer of the running installation
+    CopyInstallOutput,
+    // Prefix selector in the sidebar
+    PrefixChanged(std::path::PathBuf),
     // Preference settings messages
     WineprefixChanged(String),
     BrowseWineprefix,
@@ -198,42 +345,113 @@ enum Message {
     IsolateToggled(bool),
     NoCleanToggled(bool),
     VerbosityChanged(u8),
+    ThemeChanged(ThemeChoice),
     // Operation status updates
     OperationStatusUpdate(Option<OperationStatus>),
+    // Installation progress (None once the progress channel is closed)
+    InstallProgressed(Option<(String, InstallProgress)>),
+    InstallFinished(String, Result<(), String>),
+    // Installation queue
+    QueueAdd(String),
+    QueueRemove(usize),
+    QueueRun,
+    QueueDragStarted(usize),
+    QueueDragEntered(usize),
+    QueueDragEnded,
+    // Download cache (Preferences view)
+    CacheLoaded(Vec<(String, Vec<CachedFileMetadata>)>),
+    DeleteCachedFile(std::path::PathBuf),
 }
 
-// Modern dark theme colors
+// Modern theme colors, dark or light to match the theme preference
 mod colors {
-    use iced::Color;
-
-    pub const BACKGROUND: Color = Color::from_rgb(0.08, 0.08, 0.1);
-    pub const SURFACE: Color = Color::from_rgb(0.12, 0.12, 0.15);
-    pub const SURFACE_HOVER: Color = Color::from_rgb(0.16, 0.16, 0.2);
-    pub const PRIMARY: Color = Color::from_rgb(0.4, 0.7, 1.0);
-    pub const PRIMARY_HOVER: Color = Color::from_rgb(0.5, 0.75, 1.0);
-    pub const TEXT_PRIMARY: Color = Color::from_rgb(1.0, 1.0, 1.0);
-    pub const TEXT_SECONDARY: Color = Color::from_rgb(0.7, 0.7, 0.75);
-    pub const TEXT_DIM: Color = Color::from_rgb(0.5, 0.5, 0.55);
+    use iced::{Color, Theme};
+
+    pub struct Palette {
+        pub background: Color,
+        pub surface: Color,
+        pub surface_hover: Color,
+        pub primary: Color,
+        pub primary_hover: Color,
+        pub text_primary: Color,
+        pub text_secondary: Color,
+        pub text_dim: Color,
+    }
+
+    pub const DARK: Palette = Palette {
+        background: Color::from_rgb(0.08, 0.08, 0.1),
+        surface: Color::from_rgb(0.12, 0.12, 0.15),
+        surface_hover: Color::from_rgb(0.16, 0.16, 0.2),
+        primary: Color::from_rgb(0.4, 0.7, 1.0),
+        primary_hover: Color::from_rgb(0.5, 0.75, 1.0),
+        text_primary: Color::from_rgb(1.0, 1.0, 1.0),
+        text_secondary: Color::from_rgb(0.7, 0.7, 0.75),
+        text_dim: Color::from_rgb(0.5, 0.5, 0.55),
+    };
+
+    pub const LIGHT: Palette = Palette {
+        background: Color::from_rgb(0.96, 0.96, 0.97),
+        surface: Color::from_rgb(1.0, 1.0, 1.0),
+        surface_hover: Color::from_rgb(0.9, 0.9, 0.93),
+        primary: Color::from_rgb(0.15, 0.45, 0.85),
+        primary_hover: Color::from_rgb(0.25, 0.52, 0.9),
+        text_primary: Color::from_rgb(0.1, 0.1, 0.12),
+        text_secondary: Color::from_rgb(0.35, 0.35, 0.4),
+        text_dim: Color::from_rgb(0.5, 0.5, 0.55),
+    };
+
+    /// Palette for a theme (light for Theme::Light, dark otherwise)
+    pub fn of(theme: &Theme) -> &'static Palette {
+        if theme.extended_palette().is_dark {
+            &DARK
+        } else {
+            &LIGHT
+        }
+    }
 }
 
 #[cfg(feature = "iced")]
-impl Sandbox for WinetricksApp {
+impl Application for WinetricksApp {
+    type Executor = iced::executor::Default;
     type Message = Message;
+    type Theme = Theme;
+    type Flags = ();
 
-    fn new() -> Self {
+    fn new(_flags: ()) -> (Self, Command<Message>) {
         // Initialize configuration
         let mut config = Config::new().unwrap_or_else(|_| Config::default());
+
+        // Restore preferences saved by the previous session
+        // (WINEPREFIX/WINEARCH from the environment still take precedence)
+        let saved = GuiPreferences::load();
+        if saved.wineprefix.is_some() && std::env::var_os("WINEPREFIX").is_none() {
+            config.wineprefix = saved.wineprefix.clone();
+        }
+        if saved.winearch.is_some() && std::env::var_os("WINEARCH").is_none() {
+            config.winearch = saved.winearch.clone();
+        }
+        config.force = saved.force;
+        config.unattended = saved.unattended;
+        config.torify = saved.torify;
+        let country = saved.country.trim();
+        config.country = (!country.is_empty()).then(|| country.to_string());
+        config.isolate = saved.isolate;
+        config.no_clean = saved.no_clean;
+        config.verbosity = saved.verbosity;
+
         let metadata_dir = config.metadata_dir();
 
         // Load verb registry
         let registry = if metadata_dir.exists() {
-            VerbRegistry::load_from_dir(metadata_dir).unwrap_or_else(|_| VerbRegistry::new())
+            VerbRegistry::load_with_overlay(metadata_dir, config.imported_verbs_dir())
+                .unwrap_or_else(|_| VerbRegistry::new())
         } else {
             VerbRegistry::new()
         };
 
         // Load installed verbs
         let installed_verbs = load_installed_verbs(&config);
+        let prefixes = list_prefix_options(&config);
 
         // Initialize preference state
         let wineprefix_input = config.wineprefix().to_string_lossy().to_string();
@@ -267,40 +485,276 @@ impl Sandbox for WinetricksApp {
                     _ => None,
                 });
 
-        Self {
-            config,
-            registry,
-            current_view: View::Browse,
-            search_query: String::new(),
-            selected_category: None,
-            installed_verbs,
-            wineprefix_input,
-            country_input: String::new(),
-            winearch_selection,
-            renderer_selection,
-            wayland_selection,
-            operation_status: None,
-        }
+        (
+            Self {
+                config,
+                registry,
+                current_view: View::Browse,
+                search_query: String::new(),
+                selected_category: None,
+                selected_tag: None,
+                installed_verbs,
+                wineprefix_input,
+                country_input: saved.country,
+                winearch_selection,
+                renderer_selection,
+                wayland_selection,
+                operation_status: None,
+                installing: None,
+                install_output: Vec::new(),
+                install_progress_rx: None,
+                install_queue: Vec::new(),
+                queue_drag: None,
+                cache_entries: Vec::new(),
+                expanded_verb: None,
+                select_mode: false,
+                selected_verbs: Vec::new(),
+                prefixes,
+                install_log: String::new(),
+                theme_choice: saved.theme,
+                keyboard_focus: KeyboardFocus::Search,
+            },
+            text_input::focus(search_input_id()),
+        )
     }
 
     fn title(&self) -> String {
         "Winetricks".to_string()
     }
 
-    fn update(&mut self, message: Message) {
+    fn update(&mut self, message: Message) -> Command<Message> {
+        let changes_preferences = matches!(
+            message,
+            Message::WineprefixChanged(_)
+                | Message::PrefixChanged(_)
+                | Message::BrowseWineprefix
+                | Message::CountryChanged(_)
+                | Message::WinearchChanged(_)
+                | Message::ForceToggled(_)
+                | Message::UnattendedToggled(_)
+                | Message::TorifyToggled(_)
+                | Message::IsolateToggled(_)
+                | Message::NoCleanToggled(_)
+                | Message::VerbosityChanged(_)
+                | Message::ThemeChanged(_)
+        );
+
         match message {
             Message::ViewChanged(view) => {
                 self.current_view = view;
+                if view == View::Preferences {
+                    return load_cache_entries(self.config.cache_dir.clone(), self.config.torify);
+                }
+                if view == View::InstallLog {
+                    self.install_log = read_install_log(&self.config);
+                }
             }
             Message::SearchChanged(query) => {
                 self.search_query = query;
+                self.keyboard_focus = KeyboardFocus::Search;
+            }
+            Message::SearchSubmitted => {
+                if !self.visible_verbs().is_empty() {
+                    return self.focus(KeyboardFocus::Verb(0));
+                }
+            }
+            Message::KeyPressed(key, modifiers) => {
+                return self.handle_key(key, modifiers);
             }
             Message::CategorySelected(category) => {
                 self.selected_category = Some(category);
             }
+            Message::TagSelected(tag) => {
+                if self.selected_tag.as_ref() == Some(&tag) {
+                    self.selected_tag = None;
+                } else {
+                    self.selected_tag = Some(tag);
+                }
+            }
+            Message::VerbSelected(verb_name) => {
+                if self.expanded_verb.as_ref() == Some(&verb_name) {
+                    self.expanded_verb = None;
+                } else {
+                    self.expanded_verb = Some(verb_name);
+                }
+            }
+            Message::CloseVerbDetail => {
+                self.expanded_verb = None;
+            }
+            Message::OpenUrl(url) => {
+                open_url(&url);
+            }
+            Message::SelectModeToggled => {
+                self.select_mode = !self.select_mode;
+                self.selected_verbs.clear();
+            }
+            Message::VerbChecked(verb_name, checked) => {
+                if !checked {
+                    self.selected_verbs.retain(|v| v != &verb_name);
+                } else if !self.selected_verbs.contains(&verb_name) {
+                    self.selected_verbs.push(verb_name);
+                }
+            }
+            Message::SelectAll(verb_names, checked) => {
+                for verb_name in verb_names {
+                    let _ = self.update(Message::VerbChecked(verb_name, checked));
+                }
+            }
+            Message::InstallSelected => {
+                // Selected verbs go through the queue, so they wait for a running
+                // installation and are installed together with install_many
+                for verb_name in std::mem::take(&mut self.selected_verbs) {
+                    let _ = self.update(Message::QueueAdd(verb_name));
+                }
+                self.select_mode = false;
+                return self.update(Message::QueueRun);
+            }
             Message::InstallVerb(verb_name) => {
+                // Only one installation at a time (installs share the wineprefix),
+                // so anything requested meanwhile waits in the queue
+                if self.installing.is_some() {
+                    eprintln!("Installation already in progress, queueing: {}", verb_name);
+                    return self.update(Message::QueueAdd(verb_name));
+                }
                 eprintln!("Install verb: {}", verb_name);
-                // TODO: Implement async installation
+                self.installing = Some((verb_name.clone(), 0.0));
+                self.install_output.clear();
+
+                // Progress updates are funnelled back through a channel
+                let (tx, rx) = mpsc::unbounded_channel();
+                let rx = Arc::new(Mutex::new(rx));
+                self.install_progress_rx = Some(rx.clone());
+
+                let config = self.config.clone();
+                let verb = verb_name.clone();
+                let install = Command::perform(
+                    async move {
+                        let mut executor = winetricks_lib::Executor::new(config)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        executor
+                            .install_verb_with_progress(&verb, {
+                                let verb = verb.clone();
+                                move |progress| {
+                                    let _ = tx.send((verb.clone(), progress));
+                                }
+                            })
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    move |result| Message::InstallFinished(verb_name.clone(), result),
+                );
+
+                return Command::batch([install, next_install_progress(rx)]);
+            }
+            Message::InstallProgressed(progress) => {
+                let Some(progress) = progress else {
+                    // Channel closed - installation finished
+                    self.install_progress_rx = None;
+                    return Command::none();
+                };
+                // Queue runs move on to the next verb without a new InstallVerb
+                // (late updates after InstallFinished are ignored)
+                let (verb_name, progress) = progress;
+                let Some(ref rx) = self.install_progress_rx else {
+                    return Command::none();
+                };
+                // Keep listening for the next update
+                let next = next_install_progress(rx.clone());
+                if self.installing.is_none() {
+                    return next;
+                }
+                self.installing = Some((verb_name, progress.fraction()));
+                if let InstallProgress::Log(line) = progress {
+                    self.install_output.push(line);
+                    // Follow the tail of the output
+                    return Command::batch([
+                        next,
+                        scrollable::snap_to(install_output_id(), scrollable::RelativeOffset::END),
+                    ]);
+                }
+                return next;
+            }
+            Message::InstallFinished(verb_name, result) => {
+                match result {
+                    Ok(()) => eprintln!("Successfully installed: {}", verb_name),
+                    Err(e) => eprintln!("Error installing {}: {}", verb_name, e),
+                }
+                self.installing = None;
+                self.installed_verbs = load_installed_verbs(&self.config);
+            }
+            Message::QueueAdd(verb_name) => {
+                if !self.install_queue.contains(&verb_name) {
+                    self.install_queue.push(verb_name);
+                }
+            }
+            Message::QueueRemove(index) => {
+                if index < self.install_queue.len() {
+                    self.install_queue.remove(index);
+                }
+            }
+            Message::QueueRun => {
+                if self.installing.is_some() || self.install_queue.is_empty() {
+                    return Command::none();
+                }
+                let verbs = std::mem::take(&mut self.install_queue);
+                eprintln!("Installing queue: {}", verbs.join(" "));
+                self.installing = Some((verbs[0].clone(), 0.0));
+                self.install_output.clear();
+
+                let (tx, rx) = mpsc::unbounded_channel();
+                let rx = Arc::new(Mutex::new(rx));
+                self.install_progress_rx = Some(rx.clone());
+
+                let config = self.config.clone();
+                let label = verbs.join(", ");
+                let install = Command::perform(
+                    async move {
+                        let mut executor = winetricks_lib::Executor::new(config)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        executor
+                            .install_many_with_progress(&verbs, move |verb, progress| {
+                                let _ = tx.send((verb.to_string(), progress));
+                            })
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    move |result| Message::InstallFinished(label.clone(), result),
+                );
+
+                return Command::batch([install, next_install_progress(rx)]);
+            }
+            Message::QueueDragStarted(index) => {
+                self.queue_drag = Some(index);
+            }
+            Message::QueueDragEntered(index) => {
+                // Move the dragged item as soon as it crosses another one
+                if let Some(from) = self.queue_drag {
+                    if from != index && from < self.install_queue.len() {
+                        let verb_name = self.install_queue.remove(from);
+                        let to = index.min(self.install_queue.len());
+                        self.install_queue.insert(to, verb_name);
+                        self.queue_drag = Some(to);
+                    }
+                }
+            }
+            Message::QueueDragEnded => {
+                self.queue_drag = None;
+            }
+            Message::CacheLoaded(entries) => {
+                self.cache_entries = entries;
+            }
+            Message::DeleteCachedFile(path) => {
+                eprintln!("Deleting cached file: {}", path.display());
+                if let Err(e) = std::fs::remove_file(&path) {
+                    eprintln!("Failed to delete {}: {}", path.display(), e);
+                }
+                // Drop the entry (and the verb once it has no files left)
+                for (_, files) in &mut self.cache_entries {
+                    files.retain(|f| f.path != path);
+                }
+                self.cache_entries.retain(|(_, files)| !files.is_empty());
             }
             Message::UninstallVerb(verb_name) => {
                 eprintln!("Uninstalling verb: {}", verb_name);
@@ -367,7 +821,25 @@ impl Sandbox for WinetricksApp {
                 eprintln!("Running Wine tool: {}", tool);
                 run_wine_tool(&self.config, &tool);
             }
+            Message::CopyInstallLog => {
+                return iced::clipboard::write(self.install_log.clone());
+            }
+            Message::CopyInstallOutput => {
+                return iced::clipboard::write(self.install_output.join("\n"));
+            }
+            Message::ExportInstallLog => {
+                if let Some(path) = pick_save_file("winetricks.log") {
+                    eprintln!("Exporting installation log to {}", path.display());
+                    if let Err(e) = std::fs::write(&path, &self.install_log) {
+                        eprintln!("Failed to export log to {}: {}", path.display(), e);
+                    }
+                }
+            }
             // Preference settings updates
+            Message::PrefixChanged(path) => {
+                self.wineprefix_input = path.to_string_lossy().to_string();
+                self.switch_prefix(path);
+            }
             Message::WineprefixChanged(value) => {
                 self.wineprefix_input = value.clone();
                 // Update config with new wineprefix
@@ -376,31 +848,7 @@ impl Sandbox for WinetricksApp {
                 } else {
                     std::path::PathBuf::from(&value)
                 };
-                self.config.wineprefix = Some(new_path);
-
-                // Load renderer from new wineprefix
-                self.config.load_renderer_from_prefix();
-                // Update renderer selection UI to match detected value
-                self.renderer_selection = self.config.renderer.as_ref().and_then(|renderer| {
-                    match renderer.to_lowercase().as_str() {
-                        "opengl" | "gl" => Some(Renderer::OpenGL),
-                        "vulkan" | "vk" | "v" => Some(Renderer::Vulkan),
-                        _ => None,
-                    }
-                });
-
-                // Load wayland setting from new wineprefix
-                self.config.load_wayland_from_prefix();
-                self.wayland_selection = self.config.wayland.as_ref().and_then(|wayland| {
-                    match wayland.to_lowercase().as_str() {
-                        "wayland" => Some(WaylandDisplay::Wayland),
-                        "xwayland" | "x11" => Some(WaylandDisplay::XWayland),
-                        _ => None,
-                    }
-                });
-
-                // Reload installed verbs when prefix changes
-                self.installed_verbs = load_installed_verbs(&self.config);
+                self.switch_prefix(new_path);
             }
             Message::BrowseWineprefix => {
                 // Open native folder picker dialog
@@ -467,7 +915,8 @@ impl Sandbox for WinetricksApp {
 
                             // Reload installed verbs for the new prefix
                             self.installed_verbs = load_installed_verbs(&self.config);
-                            return;
+                            self.save_preferences();
+                            return Command::none();
                         }
                     }
                 }
@@ -518,7 +967,8 @@ impl Sandbox for WinetricksApp {
 
                             // Reload installed verbs for the new prefix
                             self.installed_verbs = load_installed_verbs(&self.config);
-                            return;
+                            self.save_preferences();
+                            return Command::none();
                         }
                     }
                 }
@@ -569,7 +1019,8 @@ impl Sandbox for WinetricksApp {
 
                             // Reload installed verbs for the new prefix
                             self.installed_verbs = load_installed_verbs(&self.config);
-                            return;
+                            self.save_preferences();
+                            return Command::none();
                         }
                     }
                 }
@@ -586,6 +1037,8 @@ impl Sandbox for WinetricksApp {
                 }
             }
             Message::CountryChanged(value) => {
+                let country = value.trim();
+                self.config.country = (!country.is_empty()).then(|| country.to_string());
                 self.country_input = value;
             }
             Message::WinearchChanged(arch) => {
@@ -619,7 +1072,7 @@ impl Sandbox for WinetricksApp {
                 };
                 self.config.wayland = wayland_str.map(|s| s.to_string());
 
-                // Set or clear in wineprefix registry for persistence
+                // Set in wineprefix registry for persistence (Auto keeps the current value)
                 if let Err(e) = self.config.set_wayland_in_registry(wayland_str) {
                     eprintln!("Warning: Failed to set Graphics driver in registry: {}", e);
                 } else {
@@ -659,11 +1112,27 @@ impl Sandbox for WinetricksApp {
             Message::VerbosityChanged(level) => {
                 self.config.verbosity = level;
             }
+            Message::ThemeChanged(choice) => {
+                self.theme_choice = choice;
+            }
+        }
+
+        if changes_preferences {
+            self.save_preferences();
         }
+
+        Command::none()
     }
 
     fn view(&self) -> Element<'_, Message> {
-        let main_content = container(row![self.sidebar(), self.content()].spacing(0))
+        let mut layout = column![row![self.sidebar(), self.content()]
+            .spacing(0)
+            .height(Length::Fill)];
+        // Output drawer along the bottom while an installation runs
+        if let Some((ref verb_name, _)) = self.installing {
+            layout = layout.push(self.install_output_drawer(verb_name));
+        }
+        let main_content = container(layout)
             .width(Length::Fill)
             .height(Length::Fill)
             .style(iced::theme::Container::Custom(Box::new(
@@ -694,12 +1163,211 @@ impl Sandbox for WinetricksApp {
     }
 
     fn theme(&self) -> Theme {
-        Theme::Dark
+        if self.theme_choice.is_dark() {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        // Keys the focused widget ignored (e.g. Tab, or anything outside the search box)
+        let keys = iced::event::listen_with(|event, status| match (event, status) {
+            (
+                iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. }),
+                iced::event::Status::Ignored,
+            ) => Some(Message::KeyPressed(key, modifiers)),
+            _ => None,
+        });
+
+        // Releasing the mouse anywhere ends a queue drag
+        if self.queue_drag.is_some() {
+            Subscription::batch([
+                keys,
+                iced::event::listen_with(|event, _status| match event {
+                    iced::Event::Mouse(iced::mouse::Event::ButtonReleased(
+                        iced::mouse::Button::Left,
+                    )) => Some(Message::QueueDragEnded),
+                    _ => None,
+                }),
+            ])
+        } else {
+            keys
+        }
     }
 }
 
 #[cfg(feature = "iced")]
 impl WinetricksApp {
+    /// Colors of the current theme
+    fn colors(&self) -> &'static colors::Palette {
+        colors::of(&self.theme())
+    }
+
+    /// Keyboard navigation: Escape closes panels everywhere; Tab/Shift+Tab, the arrow
+    /// keys and Enter/Space work in the Browse view
+    fn handle_key(
+        &mut self,
+        key: iced::keyboard::Key,
+        modifiers: iced::keyboard::Modifiers,
+    ) -> Command<Message> {
+        use iced::keyboard::key::Named;
+        use iced::keyboard::Key;
+
+        if key == Key::Named(Named::Escape) {
+            self.expanded_verb = None;
+            if self.select_mode {
+                self.select_mode = false;
+                self.selected_verbs.clear();
+            }
+            return Command::none();
+        }
+        if self.current_view != View::Browse {
+            return Command::none();
+        }
+
+        let verb_count = self.visible_verbs().len();
+        let last_category = BROWSE_CATEGORIES.len() - 1;
+        let target = match (key.as_ref(), self.keyboard_focus) {
+            (Key::Named(Named::Tab), focus) if modifiers.shift() => match focus {
+                KeyboardFocus::Search if verb_count > 0 => KeyboardFocus::Verb(verb_count - 1),
+                KeyboardFocus::Search => KeyboardFocus::Category(last_category),
+                KeyboardFocus::Category(0) => KeyboardFocus::Search,
+                KeyboardFocus::Category(i) => KeyboardFocus::Category(i - 1),
+                KeyboardFocus::Verb(0) => KeyboardFocus::Category(last_category),
+                KeyboardFocus::Verb(i) => KeyboardFocus::Verb(i - 1),
+            },
+            (Key::Named(Named::Tab), focus) => match focus {
+                KeyboardFocus::Search => KeyboardFocus::Category(0),
+                KeyboardFocus::Category(i) if i < last_category => KeyboardFocus::Category(i + 1),
+                KeyboardFocus::Category(_) if verb_count > 0 => KeyboardFocus::Verb(0),
+                KeyboardFocus::Verb(i) if i + 1 < verb_count => KeyboardFocus::Verb(i + 1),
+                _ => KeyboardFocus::Search,
+            },
+            (Key::Named(Named::ArrowDown), KeyboardFocus::Verb(i)) if i + 1 < verb_count => {
+                KeyboardFocus::Verb(i + 1)
+            }
+            (Key::Named(Named::ArrowDown), KeyboardFocus::Search | KeyboardFocus::Category(_))
+                if verb_count > 0 =>
+            {
+                KeyboardFocus::Verb(0)
+            }
+            (Key::Named(Named::ArrowUp), KeyboardFocus::Verb(0)) => KeyboardFocus::Search,
+            (Key::Named(Named::ArrowUp), KeyboardFocus::Verb(i)) => KeyboardFocus::Verb(i - 1),
+            (Key::Named(Named::ArrowLeft), KeyboardFocus::Category(i)) if i > 0 => {
+                KeyboardFocus::Category(i - 1)
+            }
+            (Key::Named(Named::ArrowRight), KeyboardFocus::Category(i)) if i < last_category => {
+                KeyboardFocus::Category(i + 1)
+            }
+            (Key::Named(Named::Enter | Named::Space), focus) => {
+                return self.activate(focus);
+            }
+            _ => return Command::none(),
+        };
+        self.focus(target)
+    }
+
+    /// Move the keyboard focus, focusing the search box or scrolling a verb into view
+    fn focus(&mut self, target: KeyboardFocus) -> Command<Message> {
+        self.keyboard_focus = target;
+        match target {
+            KeyboardFocus::Search => text_input::focus(search_input_id()),
+            KeyboardFocus::Category(_) | KeyboardFocus::Verb(_) => {
+                // Focusing an id no widget has takes the focus away from the search box
+                let blur = text_input::focus(text_input::Id::unique());
+                let KeyboardFocus::Verb(index) = target else {
+                    return blur;
+                };
+                let last = self.visible_verbs().len().saturating_sub(1).max(1);
+                Command::batch([
+                    blur,
+                    scrollable::snap_to(
+                        verb_list_id(),
+                        scrollable::RelativeOffset {
+                            x: 0.0,
+                            y: index as f32 / last as f32,
+                        },
+                    ),
+                ])
+            }
+        }
+    }
+
+    /// Enter/Space: select the focused category, or install/uninstall the focused verb
+    fn activate(&mut self, focus: KeyboardFocus) -> Command<Message> {
+        match focus {
+            KeyboardFocus::Search => text_input::focus(search_input_id()),
+            KeyboardFocus::Category(i) => {
+                self.selected_category = BROWSE_CATEGORIES[i].1;
+                Command::none()
+            }
+            KeyboardFocus::Verb(i) => {
+                let message = self
+                    .visible_verbs()
+                    .get(i)
+                    .and_then(|verb| self.verb_action_message(&verb.name));
+                match message {
+                    Some(message) => self.update(message),
+                    None => Command::none(),
+                }
+            }
+        }
+    }
+
+    /// Use another wineprefix: reload its renderer/wayland settings and installed verbs
+    fn switch_prefix(&mut self, path: std::path::PathBuf) {
+        self.config.wineprefix = Some(path);
+
+        // Load renderer from new wineprefix
+        self.config.load_renderer_from_prefix();
+        // Update renderer selection UI to match detected value
+        self.renderer_selection =
+            self.config.renderer.as_ref().and_then(|renderer| {
+                match renderer.to_lowercase().as_str() {
+                    "opengl" | "gl" => Some(Renderer::OpenGL),
+                    "vulkan" | "vk" | "v" => Some(Renderer::Vulkan),
+                    _ => None,
+                }
+            });
+
+        // Load wayland setting from new wineprefix
+        self.config.load_wayland_from_prefix();
+        self.wayland_selection = self.config.wayland.as_ref().and_then(|wayland| {
+            match wayland.to_lowercase().as_str() {
+                "wayland" => Some(WaylandDisplay::Wayland),
+                "xwayland" | "x11" => Some(WaylandDisplay::XWayland),
+                _ => None,
+            }
+        });
+
+        // Reload installed verbs when prefix changes
+        self.installed_verbs = load_installed_verbs(&self.config);
+        self.prefixes = list_prefix_options(&self.config);
+        if self.current_view == View::InstallLog {
+            self.install_log = read_install_log(&self.config);
+        }
+    }
+
+    /// Save the current preference state to gui.toml
+    fn save_preferences(&self) {
+        let preferences = GuiPreferences {
+            wineprefix: self.config.wineprefix.clone(),
+            winearch: self.config.winearch.clone(),
+            country: self.country_input.clone(),
+            force: self.config.force,
+            unattended: self.config.unattended,
+            torify: self.config.torify,
+            isolate: self.config.isolate,
+            no_clean: self.config.no_clean,
+            verbosity: self.config.verbosity,
+            theme: self.theme_choice,
+        };
+        if let Err(e) = preferences.save() {
+            eprintln!("Warning: Failed to save preferences: {}", e);
+        }
+    }
+
     fn operation_status_overlay(&self, status: &OperationStatus) -> Element<'_, Message> {
         let (title, message) = match status {
             OperationStatus::Uninstalling { verb_name } => (
@@ -717,10 +1385,10 @@ impl WinetricksApp {
             column![
                 text(&title)
                     .size(18)
-                    .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
                 text(&message)
                     .size(14)
-                    .style(iced::theme::Text::Color(colors::TEXT_SECONDARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_secondary)),
                 progress_bar(0.0..=100.0, 100.0) // Indeterminate progress
                     .width(Length::Fixed(300.0))
                     .height(Length::Fixed(6.0)),
@@ -736,11 +1404,55 @@ impl WinetricksApp {
         .into()
     }
 
+    /// Live tail of the running installation's stderr output
+    fn install_output_drawer(&self, verb_name: &str) -> Element<'_, Message> {
+        let tail = &self.install_output[self
+            .install_output
+            .len()
+            .saturating_sub(INSTALL_OUTPUT_LINES)..];
+        let lines: Vec<Element<Message>> = tail
+            .iter()
+            .map(|line| {
+                text(line)
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(iced::theme::Text::Color(self.colors().text_secondary))
+                    .into()
+            })
+            .collect();
+
+        container(
+            column![
+                row![
+                    text(format!("Installing {}", verb_name))
+                        .size(14)
+                        .style(iced::theme::Text::Color(self.colors().text_primary))
+                        .width(Length::Fill),
+                    self.action_button("Copy Log", false, Message::CopyInstallOutput),
+                ]
+                .spacing(16)
+                .align_items(Alignment::Center),
+                scrollable(column(lines).padding([0, 16, 0, 0]))
+                    .id(install_output_id())
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+            ]
+            .spacing(8),
+        )
+        .padding(16)
+        .width(Length::Fill)
+        .height(Length::Fixed(240.0))
+        .style(iced::theme::Container::Custom(Box::new(
+            SidebarContainerStyle,
+        )))
+        .into()
+    }
+
     fn sidebar(&self) -> Element<'_, Message> {
         let is_browse = self.current_view == View::Browse;
         let is_installed = self.current_view == View::Installed;
         let is_prefs = self.current_view == View::Preferences;
-        let is_tools = self.current_view == View::WineTools;
+        let is_tools = matches!(self.current_view, View::WineTools | View::InstallLog);
 
         let browse_btn =
             self.sidebar_button("Browse", is_browse, Message::ViewChanged(View::Browse));
@@ -765,13 +1477,15 @@ impl WinetricksApp {
                 container(
                     text("Winetricks")
                         .size(22)
-                        .style(iced::theme::Text::Color(colors::TEXT_PRIMARY))
+                        .style(iced::theme::Text::Color(self.colors().text_primary))
                 )
-                .padding([20, 16, 24, 16]),
+                .padding([20, 16, 12, 16]),
+                self.prefix_selector(),
                 browse_btn,
                 installed_btn,
                 prefs_btn,
                 tools_btn,
+                self.queue_panel(),
             ]
             .spacing(4),
         )
@@ -783,15 +1497,107 @@ impl WinetricksApp {
         .into()
     }
 
+    /// Dropdown switching between the known wineprefixes
+    fn prefix_selector(&self) -> Element<'_, Message> {
+        let current = PrefixOption {
+            path: self.config.wineprefix(),
+            group: "winetricks",
+        };
+        container(
+            column![
+                text("Wineprefix")
+                    .size(12)
+                    .style(iced::theme::Text::Color(self.colors().text_dim)),
+                pick_list(self.prefixes.clone(), Some(current), |option| {
+                    Message::PrefixChanged(option.path)
+                })
+                .text_size(13)
+                .padding(8)
+                .width(Length::Fill),
+            ]
+            .spacing(4),
+        )
+        .padding([0, 16, 16, 16])
+        .into()
+    }
+
+    /// Queued verbs (drag to reorder) and the Run Queue button
+    fn queue_panel(&self) -> Element<'_, Message> {
+        if self.install_queue.is_empty() {
+            return column![].into();
+        }
+
+        let items: Vec<Element<Message>> = self
+            .install_queue
+            .iter()
+            .enumerate()
+            .map(|(index, verb_name)| {
+                let dragging = self.queue_drag == Some(index);
+                mouse_area(
+                    container(
+                        row![
+                            text(verb_name)
+                                .size(13)
+                                .style(iced::theme::Text::Color(if dragging {
+                                    self.colors().primary
+                                } else {
+                                    self.colors().text_secondary
+                                }))
+                                .width(Length::Fill),
+                            button(text("x").size(12))
+                                .padding([2, 8])
+                                .style(iced::theme::Button::Custom(Box::new(SidebarButtonStyle {
+                                    _active: false
+                                },)))
+                                .on_press(Message::QueueRemove(index)),
+                        ]
+                        .align_items(Alignment::Center),
+                    )
+                    .padding([6, 8])
+                    .style(iced::theme::Container::Custom(Box::new(CardContainerStyle))),
+                )
+                .on_press(Message::QueueDragStarted(index))
+                .on_enter(Message::QueueDragEntered(index))
+                .into()
+            })
+            .collect();
+
+        let run_button = button(text("Run Queue").size(13))
+            .width(Length::Fill)
+            .padding([8, 12])
+            .style(iced::theme::Button::Custom(Box::new(ActionButtonStyle {
+                primary: true,
+            })));
+        // Disabled while another installation is running
+        let run_button = if self.installing.is_none() {
+            run_button.on_press(Message::QueueRun)
+        } else {
+            run_button
+        };
+
+        container(
+            column![
+                text(format!("Queue ({})", self.install_queue.len()))
+                    .size(14)
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
+                column(items).spacing(4),
+                run_button,
+            ]
+            .spacing(8),
+        )
+        .padding([24, 16, 0, 16])
+        .into()
+    }
+
     fn sidebar_button<'a>(&self, label: &str, active: bool, msg: Message) -> Element<'a, Message> {
         container(
             button(
                 text(label)
                     .size(14)
                     .style(iced::theme::Text::Color(if active {
-                        colors::PRIMARY
+                        self.colors().primary
                     } else {
-                        colors::TEXT_SECONDARY
+                        self.colors().text_secondary
                     })),
             )
             .width(Length::Fill)
@@ -811,6 +1617,7 @@ impl WinetricksApp {
             View::Installed => self.installed_view(),
             View::Preferences => self.preferences_view(),
             View::WineTools => self.wine_tools_view(),
+            View::InstallLog => self.install_log_view(),
         })
         .width(Length::Fill)
         .height(Length::Fill)
@@ -820,127 +1627,265 @@ impl WinetricksApp {
 
     fn browse_view(&self) -> Element<'_, Message> {
         let search_bar = text_input("Search verbs...", &self.search_query)
+            .id(search_input_id())
             .on_input(Message::SearchChanged)
+            .on_submit(Message::SearchSubmitted)
             .padding(12)
             .style(iced::theme::TextInput::Custom(Box::new(SearchInputStyle)));
 
         // Category buttons
-        let all_active = self.selected_category.is_none();
-        let apps_active = self.selected_category == Some(VerbCategory::Apps);
-        let dlls_active = self.selected_category == Some(VerbCategory::Dlls);
-        let fonts_active = self.selected_category == Some(VerbCategory::Fonts);
-        let settings_active = self.selected_category == Some(VerbCategory::Settings);
-
-        let all_btn = self.category_button("All", all_active, None);
-        let apps_btn = self.category_button("Apps", apps_active, Some(VerbCategory::Apps));
-        let dlls_btn = self.category_button("DLLs", dlls_active, Some(VerbCategory::Dlls));
-        let fonts_btn = self.category_button("Fonts", fonts_active, Some(VerbCategory::Fonts));
-        let settings_btn =
-            self.category_button("Settings", settings_active, Some(VerbCategory::Settings));
-
-        let category_row = row![all_btn, apps_btn, dlls_btn, fonts_btn, settings_btn].spacing(8);
-
-        // Verb list
-        let verbs: Vec<_> = if let Some(category) = self.selected_category {
-            self.registry
-                .list_by_category(category)
-                .into_iter()
-                .filter(|v| {
-                    self.search_query.is_empty()
-                        || v.name.contains(&self.search_query)
-                        || v.title
-                            .to_lowercase()
-                            .contains(&self.search_query.to_lowercase())
-                })
-                .collect()
-        } else {
-            // Show all verbs
-            [
-                VerbCategory::Apps,
-                VerbCategory::Dlls,
-                VerbCategory::Fonts,
-                VerbCategory::Settings,
-                VerbCategory::Benchmarks,
-            ]
+        let category_buttons: Vec<Element<Message>> = BROWSE_CATEGORIES
             .iter()
-            .flat_map(|cat| self.registry.list_by_category(*cat))
-            .filter(|v| {
-                self.search_query.is_empty()
-                    || v.name.contains(&self.search_query)
-                    || v.title
-                        .to_lowercase()
-                        .contains(&self.search_query.to_lowercase())
+            .enumerate()
+            .map(|(i, (label, category))| {
+                self.category_button(
+                    label,
+                    self.selected_category == *category,
+                    *category,
+                    self.keyboard_focus == KeyboardFocus::Category(i),
+                )
             })
-            .take(100)
-            .collect()
-        };
+            .collect();
+        let mut category_row = row(category_buttons)
+            .spacing(8)
+            .align_items(Alignment::Center);
+
+        let verbs = self.visible_verbs();
+
+        // Select mode toggle, and in select mode (un)checking all visible verbs
+        category_row =
+            category_row
+                .push(iced::widget::horizontal_space())
+                .push(self.action_button(
+                    if self.select_mode { "Done" } else { "Select" },
+                    false,
+                    Message::SelectModeToggled,
+                ));
+        if self.select_mode {
+            let visible: Vec<String> = verbs.iter().map(|v| v.name.clone()).collect();
+            let all_checked = visible.iter().all(|v| self.selected_verbs.contains(v));
+            category_row = category_row.push(self.action_button(
+                if all_checked {
+                    "Deselect All"
+                } else {
+                    "Select All"
+                },
+                false,
+                Message::SelectAll(visible, !all_checked),
+            ));
+        }
 
         let verb_list: Vec<Element<Message>> = verbs
             .iter()
-            .map(|verb| {
-                let is_installed = self.installed_verbs.contains(&verb.name);
-                let action_msg = if is_installed {
-                    Message::UninstallVerb(verb.name.clone())
+            .enumerate()
+            .map(|(index, verb)| {
+                let summary = column![
+                    text(&verb.title)
+                        .size(16)
+                        .style(iced::theme::Text::Color(self.colors().text_primary)),
+                    if let Some(ref desc) = verb.publisher {
+                        text(desc)
+                            .size(12)
+                            .style(iced::theme::Text::Color(self.colors().text_dim))
+                    } else {
+                        text("")
+                            .size(12)
+                            .style(iced::theme::Text::Color(self.colors().text_dim))
+                    }
+                ]
+                .spacing(4)
+                .width(Length::Fill);
+
+                // Show the longer description when hovering the verb title
+                let summary: Element<Message> = if let Some(ref description) = verb.description {
+                    tooltip(
+                        summary,
+                        container(
+                            text(description)
+                                .size(12)
+                                .style(iced::theme::Text::Color(self.colors().text_secondary)),
+                        )
+                        .padding(10)
+                        .max_width(400.0)
+                        .style(iced::theme::Container::Custom(Box::new(
+                            OperationStatusStyle,
+                        ))),
+                        tooltip::Position::Bottom,
+                    )
+                    .into()
                 } else {
-                    Message::InstallVerb(verb.name.clone())
+                    summary.into()
                 };
 
-                container(
-                    row![
-                        column![
-                            text(&verb.title)
-                                .size(16)
-                                .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
-                            if let Some(ref desc) = verb.publisher {
-                                text(desc)
-                                    .size(12)
-                                    .style(iced::theme::Text::Color(colors::TEXT_DIM))
-                            } else {
-                                text("")
-                                    .size(12)
-                                    .style(iced::theme::Text::Color(colors::TEXT_DIM))
-                            }
-                        ]
-                        .spacing(4)
-                        .width(Length::Fill),
-                        self.action_button(
-                            if is_installed { "Uninstall" } else { "Install" },
-                            !is_installed,
-                            action_msg
-                        )
-                    ]
-                    .spacing(16)
-                    .align_items(Alignment::Center)
-                    .padding(16),
-                )
-                .style(iced::theme::Container::Custom(Box::new(CardContainerStyle)))
-                .into()
+                let mut card = row![].spacing(16).align_items(Alignment::Center);
+                if self.select_mode {
+                    let verb_name = verb.name.clone();
+                    card = card.push(
+                        checkbox("", self.selected_verbs.contains(&verb.name)).on_toggle(
+                            move |checked| Message::VerbChecked(verb_name.clone(), checked),
+                        ),
+                    );
+                }
+                let card = card.push(summary).push(self.verb_action(&verb.name));
+
+                // Clicking the card (outside the button) opens the detail panel
+                let card = container(card.padding(16));
+                let card = if self.keyboard_focus == KeyboardFocus::Verb(index) {
+                    card.style(iced::theme::Container::Custom(Box::new(
+                        FocusedCardContainerStyle,
+                    )))
+                } else {
+                    card.style(iced::theme::Container::Custom(Box::new(CardContainerStyle)))
+                };
+                mouse_area(card)
+                    .on_press(Message::VerbSelected(verb.name.clone()))
+                    .into()
             })
             .collect();
 
-        scrollable(
+        let list = scrollable(
             column![
                 text("Browse Verbs")
                     .size(32)
-                    .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
                 text("Search and install applications, DLLs, fonts, and more")
                     .size(14)
-                    .style(iced::theme::Text::Color(colors::TEXT_SECONDARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_secondary)),
                 search_bar.width(Length::Fill),
                 category_row,
+                self.tag_cloud(),
                 column(verb_list).spacing(8),
             ]
             .spacing(20)
             .width(Length::Fill),
         )
+        .id(verb_list_id());
+
+        // Install button pinned below the list while anything is checked
+        let list: Element<Message> = if self.selected_verbs.is_empty() {
+            list.into()
+        } else {
+            column![
+                list.height(Length::Fill),
+                button(text(format!("Install Selected ({})", self.selected_verbs.len())).size(14))
+                    .width(Length::Fill)
+                    .padding([12, 20])
+                    .style(iced::theme::Button::Custom(Box::new(ActionButtonStyle {
+                        primary: true,
+                    })))
+                    .on_press(Message::InstallSelected),
+            ]
+            .spacing(12)
+            .into()
+        };
+
+        let Some(verb) = self
+            .expanded_verb
+            .as_ref()
+            .and_then(|name| self.registry.get(name))
+        else {
+            return list;
+        };
+
+        let panel = VerbDetailPanel {
+            verb,
+            is_installed: self.installed_verbs.contains(&verb.name),
+            manual_download: self.registry.get_manual_download_info(&verb.name),
+            cache_dir: self.config.cache_dir.join(&verb.name),
+            palette: self.colors(),
+        };
+        row![
+            container(list).width(Length::Fill),
+            panel.view(self.verb_action(&verb.name)),
+        ]
+        .spacing(16)
         .into()
     }
 
+    /// Verbs shown in the Browse list (category, search and tag filters applied)
+    fn visible_verbs(&self) -> Vec<&VerbMetadata> {
+        let matches_filters = |v: &&VerbMetadata| {
+            let matches_search = self.search_query.is_empty()
+                || v.name.contains(&self.search_query)
+                || v.title
+                    .to_lowercase()
+                    .contains(&self.search_query.to_lowercase());
+            let matches_tag = self
+                .selected_tag
+                .as_ref()
+                .map(|tag| v.tags.contains(tag))
+                .unwrap_or(true);
+            matches_search && matches_tag
+        };
+        if let Some(category) = self.selected_category {
+            self.registry
+                .iter_category(category)
+                .filter(matches_filters)
+                .collect()
+        } else {
+            // Show all verbs
+            self.registry
+                .list_all()
+                .into_iter()
+                .filter(matches_filters)
+                .take(100)
+                .collect()
+        }
+    }
+
+    /// Install/Uninstall button for a verb, or its progress while it is being installed
+    fn verb_action<'a>(&self, verb_name: &str) -> Element<'a, Message> {
+        if let Some((ref name, fraction)) = self.installing {
+            if name == verb_name {
+                return column![
+                    text(format!("Installing... {:.0}%", fraction * 100.0))
+                        .size(12)
+                        .style(iced::theme::Text::Color(self.colors().text_secondary)),
+                    progress_bar(0.0..=1.0, fraction)
+                        .width(Length::Fixed(160.0))
+                        .height(Length::Fixed(6.0)),
+                ]
+                .spacing(6)
+                .align_items(Alignment::Center)
+                .into();
+            }
+        }
+
+        let is_installed = self.installed_verbs.iter().any(|v| v == verb_name);
+        let (label, msg) = match self.verb_action_message(verb_name) {
+            Some(msg @ Message::UninstallVerb(_)) => ("Uninstall", msg),
+            Some(msg @ Message::QueueAdd(_)) => ("Add to Queue", msg),
+            Some(msg) => ("Install", msg),
+            None => return text("").into(),
+        };
+        self.action_button(label, !is_installed, msg)
+    }
+
+    /// What the verb's action button (or Enter on the focused verb) does, None while
+    /// the verb is being installed
+    fn verb_action_message(&self, verb_name: &str) -> Option<Message> {
+        if self.installing.as_ref().map(|(name, _)| name.as_str()) == Some(verb_name) {
+            return None;
+        }
+
+        let is_installed = self.installed_verbs.iter().any(|v| v == verb_name);
+        // Once something is queued, Install adds to the queue instead
+        let queueing = !is_installed && !self.install_queue.is_empty();
+        Some(if is_installed {
+            Message::UninstallVerb(verb_name.to_string())
+        } else if queueing {
+            Message::QueueAdd(verb_name.to_string())
+        } else {
+            Message::InstallVerb(verb_name.to_string())
+        })
+    }
+
     fn category_button<'a>(
         &self,
         label: &str,
         active: bool,
         category: Option<VerbCategory>,
+        focused: bool,
     ) -> Element<'a, Message> {
         let msg = category.map(Message::CategorySelected);
 
@@ -951,7 +1896,7 @@ impl WinetricksApp {
                     .style(iced::theme::Text::Color(if active {
                         Color::BLACK
                     } else {
-                        colors::TEXT_SECONDARY
+                        self.colors().text_secondary
                     })),
             )
             .padding([8, 16])
@@ -961,11 +1906,53 @@ impl WinetricksApp {
             .on_press_maybe(msg),
         )
         .style(iced::theme::Container::Custom(Box::new(
-            CategoryContainerStyle { active },
+            CategoryContainerStyle { active, focused },
         )))
         .into()
     }
 
+    /// Buttons for every verb tag, below the category buttons
+    fn tag_cloud(&self) -> Element<'_, Message> {
+        const TAGS_PER_ROW: usize = 10;
+
+        let tags = self.registry.list_tags();
+        let rows: Vec<Element<Message>> = tags
+            .chunks(TAGS_PER_ROW)
+            .map(|chunk| {
+                let buttons: Vec<Element<Message>> = chunk
+                    .iter()
+                    .map(|tag| {
+                        let active = self.selected_tag.as_deref() == Some(*tag);
+                        container(
+                            button(text(format!("#{}", tag)).size(12).style(
+                                iced::theme::Text::Color(if active {
+                                    Color::BLACK
+                                } else {
+                                    self.colors().text_dim
+                                }),
+                            ))
+                            .padding([4, 10])
+                            .style(iced::theme::Button::Custom(Box::new(CategoryButtonStyle {
+                                _active: active,
+                            })))
+                            .on_press(Message::TagSelected(tag.to_string())),
+                        )
+                        .style(iced::theme::Container::Custom(Box::new(
+                            CategoryContainerStyle {
+                                active,
+                                focused: false,
+                            },
+                        )))
+                        .into()
+                    })
+                    .collect();
+                row(buttons).spacing(6).into()
+            })
+            .collect();
+
+        column(rows).spacing(6).into()
+    }
+
     fn action_button<'a>(&self, label: &str, primary: bool, msg: Message) -> Element<'a, Message> {
         button(
             text(label)
@@ -973,7 +1960,7 @@ impl WinetricksApp {
                 .style(iced::theme::Text::Color(if primary {
                     Color::WHITE
                 } else {
-                    colors::TEXT_SECONDARY
+                    self.colors().text_secondary
                 })),
         )
         .padding([10, 20])
@@ -993,7 +1980,7 @@ impl WinetricksApp {
                     row![
                         text(verb_name)
                             .size(16)
-                            .style(iced::theme::Text::Color(colors::TEXT_PRIMARY))
+                            .style(iced::theme::Text::Color(self.colors().text_primary))
                             .width(Length::Fill),
                         self.action_button(
                             "Uninstall",
@@ -1014,10 +2001,10 @@ impl WinetricksApp {
             column![
                 text("Installed Verbs")
                     .size(32)
-                    .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
                 text("View and manage installed verbs in your wineprefix")
                     .size(14)
-                    .style(iced::theme::Text::Color(colors::TEXT_SECONDARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_secondary)),
                 column(installed_list).spacing(8),
             ]
             .spacing(20),
@@ -1030,10 +2017,10 @@ impl WinetricksApp {
             column![
                 text("Preferences")
                     .size(32)
-                    .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
                 text("Configure Winetricks settings and options")
                     .size(14)
-                    .style(iced::theme::Text::Color(colors::TEXT_SECONDARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_secondary)),
                 // Wine Prefix Section
                 self.settings_section(
                     "Wine Prefix",
@@ -1203,6 +2190,30 @@ impl WinetricksApp {
                     ]
                     .into()
                 ),
+                // Appearance Section
+                self.settings_section(
+                    "Appearance",
+                    "Configure how Winetricks looks",
+                    column![self.setting_row(
+                        "Theme",
+                        "Dark, light, or follow the desktop (GTK) theme",
+                        pick_list(
+                            ThemeChoice::all(),
+                            Some(self.theme_choice),
+                            Message::ThemeChanged
+                        )
+                        .padding(10)
+                        .width(Length::Fill)
+                        .into()
+                    )]
+                    .into()
+                ),
+                // Download Cache Section
+                self.settings_section(
+                    "Download Cache",
+                    "Files downloaded for each verb",
+                    self.cache_entries_view()
+                ),
                 // Information Section
                 self.settings_section(
                     "Information",
@@ -1234,10 +2245,10 @@ impl WinetricksApp {
             column![
                 text(title)
                     .size(20)
-                    .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
                 text(description)
                     .size(13)
-                    .style(iced::theme::Text::Color(colors::TEXT_SECONDARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_secondary)),
                 container(content).padding(16)
             ]
             .spacing(12)
@@ -1257,10 +2268,10 @@ impl WinetricksApp {
             column![
                 text(title)
                     .size(15)
-                    .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
                 text(description)
                     .size(12)
-                    .style(iced::theme::Text::Color(colors::TEXT_SECONDARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_secondary)),
             ]
             .spacing(4)
             .width(Length::Fill),
@@ -1283,10 +2294,10 @@ impl WinetricksApp {
             column![
                 text(title)
                     .size(15)
-                    .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
                 text(description)
                     .size(12)
-                    .style(iced::theme::Text::Color(colors::TEXT_SECONDARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_secondary)),
             ]
             .spacing(4)
             .width(Length::Fill),
@@ -1298,15 +2309,56 @@ impl WinetricksApp {
         .into()
     }
 
+    fn cache_entries_view(&self) -> Element<'_, Message> {
+        if self.cache_entries.is_empty() {
+            return text("No cached downloads")
+                .size(13)
+                .style(iced::theme::Text::Color(self.colors().text_dim))
+                .into();
+        }
+
+        let mut entries = column![].spacing(4);
+        for (verb, files) in &self.cache_entries {
+            entries = entries.push(
+                text(verb)
+                    .size(15)
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
+            );
+            for file in files {
+                entries = entries.push(
+                    row![
+                        text(&file.filename)
+                            .size(13)
+                            .style(iced::theme::Text::Color(self.colors().text_secondary))
+                            .width(Length::Fill),
+                        text(format!("{:.1} MiB", file.size as f64 / (1024.0 * 1024.0)))
+                            .size(13)
+                            .style(iced::theme::Text::Color(self.colors().text_dim)),
+                        button(text("Delete").size(12))
+                            .on_press(Message::DeleteCachedFile(file.path.clone()))
+                            .padding([4, 10])
+                            .style(iced::theme::Button::Custom(Box::new(ActionButtonStyle {
+                                primary: false,
+                            }))),
+                    ]
+                    .spacing(16)
+                    .align_items(Alignment::Center)
+                    .padding([4, 0, 4, 12]),
+                );
+            }
+        }
+        entries.into()
+    }
+
     fn info_row<'a>(&self, title: &str, value: &str) -> Element<'a, Message> {
         row![
             text(title)
                 .size(14)
-                .style(iced::theme::Text::Color(colors::TEXT_SECONDARY))
+                .style(iced::theme::Text::Color(self.colors().text_secondary))
                 .width(Length::Fill),
             text(value)
                 .size(13)
-                .style(iced::theme::Text::Color(colors::TEXT_DIM)),
+                .style(iced::theme::Text::Color(self.colors().text_dim)),
         ]
         .spacing(16)
         .padding([8, 0])
@@ -1318,10 +2370,10 @@ impl WinetricksApp {
             column![
                 text("Wine Tools")
                     .size(32)
-                    .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_primary)),
                 text("Quick access to Wine utilities")
                     .size(14)
-                    .style(iced::theme::Text::Color(colors::TEXT_SECONDARY)),
+                    .style(iced::theme::Text::Color(self.colors().text_secondary)),
                 self.tool_card(
                     "Wine Configuration",
                     "Configure Wine settings, libraries, and applications",
@@ -1353,6 +2405,11 @@ impl WinetricksApp {
                     "Open Windows command prompt (cmd.exe)",
                     "winecmd"
                 ),
+                self.tool_card_with_message(
+                    "Installation Log",
+                    "View the verbs installed in this prefix and when",
+                    Message::ViewChanged(View::InstallLog)
+                ),
                 self.tool_card(
                     "Open Prefix Folder",
                     "Open WINEPREFIX directory in file manager",
@@ -1370,15 +2427,25 @@ impl WinetricksApp {
     }
 
     fn tool_card<'a>(&self, title: &str, description: &str, tool: &str) -> Element<'a, Message> {
+        self.tool_card_with_message(title, description, Message::RunWineTool(tool.to_string()))
+    }
+
+    /// Tool card that sends an arbitrary message instead of running a wine tool
+    fn tool_card_with_message<'a>(
+        &self,
+        title: &str,
+        description: &str,
+        msg: Message,
+    ) -> Element<'a, Message> {
         container(
             button(
                 column![
                     text(title)
                         .size(16)
-                        .style(iced::theme::Text::Color(colors::TEXT_PRIMARY)),
+                        .style(iced::theme::Text::Color(self.colors().text_primary)),
                     text(description)
                         .size(13)
-                        .style(iced::theme::Text::Color(colors::TEXT_SECONDARY)),
+                        .style(iced::theme::Text::Color(self.colors().text_secondary)),
                 ]
                 .spacing(4)
                 .align_items(Alignment::Start),
@@ -1386,11 +2453,85 @@ impl WinetricksApp {
             .width(Length::Fill)
             .padding(20)
             .style(iced::theme::Button::Custom(Box::new(ToolButtonStyle)))
-            .on_press(Message::RunWineTool(tool.to_string())),
+            .on_press(msg),
         )
         .style(iced::theme::Container::Custom(Box::new(CardContainerStyle)))
         .into()
     }
+
+    /// winetricks.log of the current prefix, one entry per row with the install date dimmed
+    fn install_log_view(&self) -> Element<'_, Message> {
+        use winetricks_lib::install_log::{format_timestamp, LogEntry};
+
+        let entries: Vec<Element<Message>> = self
+            .install_log
+            .lines()
+            .filter_map(LogEntry::parse)
+            .map(|entry| {
+                let date = entry
+                    .installed_at
+                    .map(format_timestamp)
+                    .map(|timestamp| timestamp.replacen('T', " ", 1))
+                    .unwrap_or_default();
+                row![
+                    text(date)
+                        .size(13)
+                        .font(iced::Font::MONOSPACE)
+                        .style(iced::theme::Text::Color(self.colors().text_dim))
+                        .width(Length::Fixed(170.0)),
+                    text(entry.verb)
+                        .size(14)
+                        .font(iced::Font::MONOSPACE)
+                        .style(iced::theme::Text::Color(self.colors().text_primary))
+                        .width(Length::Fill),
+                    text(entry.wine_version.unwrap_or_default())
+                        .size(13)
+                        .style(iced::theme::Text::Color(self.colors().text_dim)),
+                ]
+                .spacing(16)
+                .padding([4, 0])
+                .into()
+            })
+            .collect();
+
+        let log_body: Element<Message> = if entries.is_empty() {
+            text("No verbs have been installed in this prefix yet")
+                .size(14)
+                .style(iced::theme::Text::Color(self.colors().text_secondary))
+                .into()
+        } else {
+            scrollable(column(entries).padding([0, 16, 0, 0]))
+                .height(Length::Fill)
+                .into()
+        };
+
+        column![
+            row![
+                self.action_button("Back", false, Message::ViewChanged(View::WineTools)),
+                text("Installation Log")
+                    .size(32)
+                    .style(iced::theme::Text::Color(self.colors().text_primary))
+                    .width(Length::Fill),
+                self.action_button("Copy to Clipboard", false, Message::CopyInstallLog),
+                self.action_button("Export...", true, Message::ExportInstallLog),
+            ]
+            .spacing(16)
+            .align_items(Alignment::Center),
+            text(format!(
+                "winetricks.log in {}",
+                self.config.wineprefix().display()
+            ))
+            .size(14)
+            .style(iced::theme::Text::Color(self.colors().text_secondary)),
+            container(log_body)
+                .padding(16)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(iced::theme::Container::Custom(Box::new(CardContainerStyle))),
+        ]
+        .spacing(20)
+        .into()
+    }
 }
 
 // Custom container styles (Iced only)
@@ -1401,9 +2542,9 @@ struct BackgroundContainerStyle;
 impl container::StyleSheet for BackgroundContainerStyle {
     type Style = iced::Theme;
 
-    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
         container::Appearance {
-            background: Some(colors::BACKGROUND.into()),
+            background: Some(colors::of(style).background.into()),
             ..Default::default()
         }
     }
@@ -1416,9 +2557,9 @@ struct SidebarContainerStyle;
 impl container::StyleSheet for SidebarContainerStyle {
     type Style = iced::Theme;
 
-    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
         container::Appearance {
-            background: Some(colors::SURFACE.into()),
+            background: Some(colors::of(style).surface.into()),
             ..Default::default()
         }
     }
@@ -1431,15 +2572,36 @@ struct CardContainerStyle;
 impl container::StyleSheet for CardContainerStyle {
     type Style = iced::Theme;
 
-    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
         container::Appearance {
-            background: Some(colors::SURFACE.into()),
+            background: Some(colors::of(style).surface.into()),
             border: iced::Border::with_radius(12.0),
             ..Default::default()
         }
     }
 }
 
+// Verb card with the keyboard focus
+#[cfg(feature = "iced")]
+struct FocusedCardContainerStyle;
+
+#[cfg(feature = "iced")]
+impl container::StyleSheet for FocusedCardContainerStyle {
+    type Style = iced::Theme;
+
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(colors::of(style).surface_hover.into()),
+            border: iced::Border {
+                color: colors::of(style).primary,
+                width: 2.0,
+                radius: 12.0.into(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
 #[cfg(feature = "iced")]
 struct OverlayStyle;
 
@@ -1467,9 +2629,9 @@ struct OperationStatusStyle;
 impl container::StyleSheet for OperationStatusStyle {
     type Style = iced::Theme;
 
-    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
         container::Appearance {
-            background: Some(colors::SURFACE.into()),
+            background: Some(colors::of(style).surface.into()),
             border: iced::Border::with_radius(16.0),
             ..Default::default()
         }
@@ -1479,23 +2641,33 @@ impl container::StyleSheet for OperationStatusStyle {
 #[cfg(feature = "iced")]
 struct CategoryContainerStyle {
     active: bool,
+    // Keyboard focus ring
+    focused: bool,
 }
 
 #[cfg(feature = "iced")]
 impl container::StyleSheet for CategoryContainerStyle {
     type Style = iced::Theme;
 
-    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
         container::Appearance {
             background: Some(
                 if self.active {
-                    colors::PRIMARY
+                    colors::of(style).primary
                 } else {
-                    colors::SURFACE
+                    colors::of(style).surface
                 }
                 .into(),
             ),
-            border: iced::Border::with_radius(8.0),
+            border: if self.focused {
+                iced::Border {
+                    color: colors::of(style).primary_hover,
+                    width: 2.0,
+                    radius: 8.0.into(),
+                }
+            } else {
+                iced::Border::with_radius(8.0)
+            },
             ..Default::default()
         }
     }
@@ -1520,9 +2692,9 @@ impl button::StyleSheet for SidebarButtonStyle {
         }
     }
 
-    fn hovered(&self, _style: &Self::Style) -> button::Appearance {
+    fn hovered(&self, style: &Self::Style) -> button::Appearance {
         button::Appearance {
-            background: Some(colors::SURFACE_HOVER.into()),
+            background: Some(colors::of(style).surface_hover.into()),
             border: iced::Border::with_radius(8.0),
             ..Default::default()
         }
@@ -1557,13 +2729,13 @@ struct ActionButtonStyle {
 impl button::StyleSheet for ActionButtonStyle {
     type Style = iced::Theme;
 
-    fn active(&self, _style: &Self::Style) -> button::Appearance {
+    fn active(&self, style: &Self::Style) -> button::Appearance {
         button::Appearance {
             background: Some(
                 if self.primary {
-                    colors::PRIMARY
+                    colors::of(style).primary
                 } else {
-                    colors::SURFACE
+                    colors::of(style).surface
                 }
                 .into(),
             ),
@@ -1572,13 +2744,13 @@ impl button::StyleSheet for ActionButtonStyle {
         }
     }
 
-    fn hovered(&self, _style: &Self::Style) -> button::Appearance {
+    fn hovered(&self, style: &Self::Style) -> button::Appearance {
         button::Appearance {
             background: Some(
                 if self.primary {
-                    colors::PRIMARY_HOVER
+                    colors::of(style).primary_hover
                 } else {
-                    colors::SURFACE_HOVER
+                    colors::of(style).surface_hover
                 }
                 .into(),
             ),
@@ -1603,9 +2775,9 @@ impl button::StyleSheet for ToolButtonStyle {
         }
     }
 
-    fn hovered(&self, _style: &Self::Style) -> button::Appearance {
+    fn hovered(&self, style: &Self::Style) -> button::Appearance {
         button::Appearance {
-            background: Some(colors::SURFACE_HOVER.into()),
+            background: Some(colors::of(style).surface_hover.into()),
             border: iced::Border::with_radius(0.0),
             ..Default::default()
         }
@@ -1619,43 +2791,43 @@ struct SearchInputStyle;
 impl text_input::StyleSheet for SearchInputStyle {
     type Style = iced::Theme;
 
-    fn active(&self, _style: &Self::Style) -> text_input::Appearance {
+    fn active(&self, style: &Self::Style) -> text_input::Appearance {
         text_input::Appearance {
-            background: colors::SURFACE.into(),
+            background: colors::of(style).surface.into(),
             border: iced::Border::with_radius(10.0),
-            icon_color: colors::TEXT_DIM,
+            icon_color: colors::of(style).text_dim,
         }
     }
 
-    fn focused(&self, _style: &Self::Style) -> text_input::Appearance {
+    fn focused(&self, style: &Self::Style) -> text_input::Appearance {
         text_input::Appearance {
-            background: colors::SURFACE.into(),
+            background: colors::of(style).surface.into(),
             border: iced::Border::with_radius(10.0),
-            icon_color: colors::PRIMARY,
+            icon_color: colors::of(style).primary,
         }
     }
 
-    fn placeholder_color(&self, _style: &Self::Style) -> Color {
-        colors::TEXT_DIM
+    fn placeholder_color(&self, style: &Self::Style) -> Color {
+        colors::of(style).text_dim
     }
 
-    fn value_color(&self, _style: &Self::Style) -> Color {
-        colors::TEXT_PRIMARY
+    fn value_color(&self, style: &Self::Style) -> Color {
+        colors::of(style).text_primary
     }
 
-    fn disabled_color(&self, _style: &Self::Style) -> Color {
-        colors::TEXT_DIM
+    fn disabled_color(&self, style: &Self::Style) -> Color {
+        colors::of(style).text_dim
     }
 
-    fn selection_color(&self, _style: &Self::Style) -> Color {
-        colors::PRIMARY
+    fn selection_color(&self, style: &Self::Style) -> Color {
+        colors::of(style).primary
     }
 
-    fn disabled(&self, _style: &Self::Style) -> text_input::Appearance {
+    fn disabled(&self, style: &Self::Style) -> text_input::Appearance {
         text_input::Appearance {
-            background: colors::SURFACE.into(),
+            background: colors::of(style).surface.into(),
             border: iced::Border::with_radius(10.0),
-            icon_color: colors::TEXT_DIM,
+            icon_color: colors::of(style).text_dim,
         }
     }
 }
@@ -1762,15 +2934,7 @@ fn run_wine_tool(config: &Config, tool: &str) {
             );
         }
         "help" => {
-            let url = "https://github.com/Winetricks/winetricks/wiki";
-            let browsers = ["xdg-open", "sdtwebclient", "cygstart", "open", "firefox"];
-
-            for browser in &browsers {
-                if process::Command::new(browser).arg(url).spawn().is_ok() {
-                    return;
-                }
-            }
-            eprintln!("Could not open browser. Please visit: {}", url);
+            open_url("https://github.com/Winetricks/winetricks/wiki");
         }
         _ => {
             eprintln!("Unknown tool: {}", tool);
@@ -1778,24 +2942,359 @@ fn run_wine_tool(config: &Config, tool: &str) {
     }
 }
 
-fn load_installed_verbs(config: &Config) -> Vec<String> {
-    let log_file = config.wineprefix().join("winetricks.log");
+/// Open a web page in the user's browser
+fn open_url(url: &str) {
+    let browsers = ["xdg-open", "sdtwebclient", "cygstart", "open", "firefox"];
+
+    for browser in &browsers {
+        if std::process::Command::new(browser).arg(url).spawn().is_ok() {
+            return;
+        }
+    }
+    eprintln!("Could not open browser. Please visit: {}", url);
+}
+
+/// Side panel in the Browse view showing everything known about a verb
+#[cfg(feature = "iced")]
+struct VerbDetailPanel<'a> {
+    verb: &'a VerbMetadata,
+    is_installed: bool,
+    /// Where to get the files if they can't be downloaded automatically
+    manual_download: Option<ManualDownloadInfo>,
+    /// Cache directory the manually downloaded files go to
+    cache_dir: std::path::PathBuf,
+    palette: &'static colors::Palette,
+}
+
+#[cfg(feature = "iced")]
+impl<'a> VerbDetailPanel<'a> {
+    fn view(self, action: Element<'a, Message>) -> Element<'a, Message> {
+        let verb = self.verb;
+        let palette = self.palette;
+        let mut details = column![
+            row![
+                text(&verb.title)
+                    .size(20)
+                    .style(iced::theme::Text::Color(palette.text_primary))
+                    .width(Length::Fill),
+                button(text("x").size(14))
+                    .on_press(Message::CloseVerbDetail)
+                    .padding([2, 8])
+                    .style(iced::theme::Button::Custom(Box::new(SidebarButtonStyle {
+                        _active: false
+                    }))),
+            ]
+            .align_items(Alignment::Center),
+            text(&verb.name)
+                .size(13)
+                .style(iced::theme::Text::Color(palette.text_dim)),
+        ]
+        .spacing(10);
+
+        details = details
+            .push(Self::field(palette, "Category", verb.category.as_str()))
+            .push(Self::field(
+                palette,
+                "Publisher",
+                verb.publisher.as_deref().unwrap_or("Unknown"),
+            ))
+            .push(Self::field(
+                palette,
+                "Year",
+                verb.year.as_deref().unwrap_or("Unknown"),
+            ))
+            .push(Self::field(
+                palette,
+                "Status",
+                if self.is_installed {
+                    "Installed"
+                } else {
+                    "Not installed"
+                },
+            ));
+
+        if let Some(ref homepage) = verb.homepage {
+            details = details.push(
+                row![
+                    text("Homepage")
+                        .size(13)
+                        .style(iced::theme::Text::Color(palette.text_secondary))
+                        .width(Length::Fixed(90.0)),
+                    Self::link(palette, homepage.clone()),
+                ]
+                .spacing(8),
+            );
+        }
+
+        if let Some(ref description) = verb.description {
+            details = details.push(
+                text(description)
+                    .size(13)
+                    .style(iced::theme::Text::Color(palette.text_secondary)),
+            );
+        }
 
-    if !log_file.exists() {
+        if !verb.files.is_empty() {
+            details = details.push(Self::heading(palette, "Files"));
+            for file in &verb.files {
+                let source = file
+                    .url
+                    .as_deref()
+                    .map(mask_url)
+                    .unwrap_or_else(|| "manual download".to_string());
+                details = details.push(
+                    column![
+                        text(&file.filename)
+                            .size(13)
+                            .style(iced::theme::Text::Color(palette.text_primary)),
+                        text(source)
+                            .size(12)
+                            .style(iced::theme::Text::Color(palette.text_dim)),
+                    ]
+                    .spacing(2),
+                );
+            }
+        }
+
+        if let Some(info) = self.manual_download {
+            details = details.push(Self::heading(palette, "Manual download"));
+            if let Some(instructions) = info.instructions {
+                details = details.push(
+                    text(instructions)
+                        .size(12)
+                        .style(iced::theme::Text::Color(palette.text_secondary)),
+                );
+            }
+            if let Some(url) = info.purchase_url {
+                details = details.push(Self::link(palette, url));
+            }
+            details = details.push(
+                text(format!("Save the files to {}", self.cache_dir.display()))
+                    .size(12)
+                    .style(iced::theme::Text::Color(palette.text_dim)),
+            );
+        }
+
+        if !verb.wine_bug_workarounds.is_empty() || !verb.broken_wine_versions.is_empty() {
+            details = details.push(Self::heading(palette, "Known Wine bugs"));
+            for bug in &verb.wine_bug_workarounds {
+                details = details.push(
+                    text(format!("#{} {}", bug.bug_number, bug.description))
+                        .size(12)
+                        .style(iced::theme::Text::Color(palette.text_secondary)),
+                );
+            }
+            for range in &verb.broken_wine_versions {
+                let min = if range.min.is_unbounded() {
+                    "any".to_string()
+                } else {
+                    range.min.to_string()
+                };
+                let max = range
+                    .max
+                    .as_ref()
+                    .map_or_else(|| "current".to_string(), |max| max.to_string());
+                let versions = format!("Broken in Wine {} - {}", min, max);
+                details = details.push(
+                    text(versions)
+                        .size(12)
+                        .style(iced::theme::Text::Color(palette.text_secondary)),
+                );
+            }
+        }
+
+        details = details.push(action);
+
+        container(scrollable(details.padding(20)))
+            .width(Length::Fixed(360.0))
+            .height(Length::Fill)
+            .style(iced::theme::Container::Custom(Box::new(CardContainerStyle)))
+            .into()
+    }
+
+    /// URL opened in the browser when clicked
+    fn link(palette: &colors::Palette, url: String) -> Element<'a, Message> {
+        button(
+            text(&url)
+                .size(12)
+                .style(iced::theme::Text::Color(palette.primary)),
+        )
+        .on_press(Message::OpenUrl(url))
+        .padding(0)
+        .style(iced::theme::Button::Text)
+        .into()
+    }
+
+    fn heading(palette: &colors::Palette, label: &str) -> Element<'a, Message> {
+        text(label.to_string())
+            .size(15)
+            .style(iced::theme::Text::Color(palette.text_primary))
+            .into()
+    }
+
+    fn field(palette: &colors::Palette, label: &str, value: &str) -> Element<'a, Message> {
+        row![
+            text(label.to_string())
+                .size(13)
+                .style(iced::theme::Text::Color(palette.text_secondary))
+                .width(Length::Fixed(90.0)),
+            text(value.to_string())
+                .size(13)
+                .style(iced::theme::Text::Color(palette.text_primary)),
+        ]
+        .spacing(8)
+        .into()
+    }
+}
+
+/// Shorten a download URL to its host (https://download.microsoft.com/...)
+fn mask_url(url: &str) -> String {
+    let host_start = url.find("://").map(|i| i + 3).unwrap_or(0);
+    match url[host_start..].find('/') {
+        Some(path_start) => format!("{}/...", &url[..host_start + path_start]),
+        None => url.to_string(),
+    }
+}
+
+/// Scan the download cache in the background (hashing large files takes a while)
+#[cfg(feature = "iced")]
+fn load_cache_entries(cache_dir: std::path::PathBuf, torify: bool) -> Command<Message> {
+    Command::perform(
+        async move {
+            tokio::task::spawn_blocking(move || scan_cache_entries(&cache_dir, torify))
+                .await
+                .unwrap_or_default()
+        },
+        Message::CacheLoaded,
+    )
+}
+
+/// Cached files for every verb directory in the cache, sorted by verb name
+fn scan_cache_entries(
+    cache_dir: &std::path::Path,
+    torify: bool,
+) -> Vec<(String, Vec<CachedFileMetadata>)> {
+    let Ok(downloader) = DownloadManager::new(cache_dir.to_path_buf())
+        .and_then(|downloader| downloader.with_torify(torify))
+    else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
         return Vec::new();
+    };
+
+    let mut verbs: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        // .cas is the content store, not a verb
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    verbs.sort();
+
+    verbs
+        .into_iter()
+        .filter_map(|verb| {
+            let files = downloader.get_cached_file_metadata(&verb).ok()?;
+            (!files.is_empty()).then_some((verb, files))
+        })
+        .collect()
+}
+
+/// Wait for the next installation progress update
+#[cfg(feature = "iced")]
+fn next_install_progress(rx: ProgressReceiver) -> Command<Message> {
+    Command::perform(
+        async move { rx.lock().await.recv().await },
+        Message::InstallProgressed,
+    )
+}
+
+/// Prefixes for the sidebar selector, grouped by prefix root (ours first, then
+/// Lutris, Bottles and Steam)
+fn list_prefix_options(config: &Config) -> Vec<PrefixOption> {
+    let mut options: Vec<PrefixOption> = winetricks_lib::Wine::list_prefixes(&config.prefixes_root)
+        .into_iter()
+        .map(|path| PrefixOption {
+            path,
+            group: "winetricks",
+        })
+        .collect();
+
+    for root in config.discover_prefix_roots().into_iter().skip(1) {
+        let group = config.prefix_root_name(&root);
+        for path in winetricks_lib::Wine::prefixes_in(&root) {
+            let option = PrefixOption { path, group };
+            if !options.contains(&option) {
+                options.push(option);
+            }
+        }
     }
+    options
+}
+
+/// Raw contents of the current prefix's winetricks.log (empty if there is none)
+fn read_install_log(config: &Config) -> String {
+    let log_file = config
+        .wineprefix()
+        .join(winetricks_lib::install_log::LOG_FILE_NAME);
+    std::fs::read_to_string(log_file).unwrap_or_default()
+}
+
+/// Ask for a file to save to with a native dialog (zenity, then kdialog)
+/// Returns None if no dialog is available or it was cancelled
+fn pick_save_file(default_name: &str) -> Option<std::path::PathBuf> {
+    use std::process;
 
-    std::fs::read_to_string(&log_file)
+    let start = dirs::home_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("/"))
+        .join(default_name);
+    let start_path = start.to_string_lossy().to_string();
+
+    let dialogs: [(&str, Vec<String>); 2] = [
+        (
+            "zenity",
+            vec![
+                "--file-selection".to_string(),
+                "--save".to_string(),
+                "--confirm-overwrite".to_string(),
+                "--title=Export Installation Log".to_string(),
+                format!("--filename={}", start_path),
+            ],
+        ),
+        (
+            "kdialog",
+            vec![
+                "--getsavefilename".to_string(),
+                start_path.clone(),
+                "--title".to_string(),
+                "Export Installation Log".to_string(),
+            ],
+        ),
+    ];
+
+    for (dialog, args) in &dialogs {
+        let Ok(output) = process::Command::new(dialog).args(args).output() else {
+            // Not installed, try the next one
+            continue;
+        };
+        if !output.status.success() {
+            // Cancelled
+            return None;
+        }
+        let selected = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        return (!selected.is_empty()).then(|| std::path::PathBuf::from(selected));
+    }
+
+    eprintln!("No file dialog found (zenity/kdialog), cannot export");
+    None
+}
+
+fn load_installed_verbs(config: &Config) -> Vec<String> {
+    winetricks_lib::install_log::read_log(&config.wineprefix())
         .unwrap_or_default()
-        .lines()
-        .map(|l| l.trim().to_string())
-        .filter(|l| {
-            !l.is_empty()
-                && !l.starts_with('-')
-                && !l.starts_with('#')
-                && !l.starts_with("//")
-                && !l.contains('=')
-                && !matches!(l.as_str(), "list" | "list-installed" | "list-all")
-        })
+        .into_iter()
+        .map(|entry| entry.verb)
+        .filter(|verb| !matches!(verb.as_str(), "list" | "list-installed" | "list-all"))
         .collect()
 }
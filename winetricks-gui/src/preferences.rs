@@ -0,0 +1,151 @@
+//! GUI preferences persistence (~/.config/winetricks/gui.toml)
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Preference state saved between GUI sessions
+/// Renderer and display driver aren't stored here: they live in the wineprefix registry
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuiPreferences {
+    /// Wineprefix path
+    pub wineprefix: Option<PathBuf>,
+    /// Wine architecture (win32 or win64, None for auto)
+    pub winearch: Option<String>,
+    /// Country code (--country=CC)
+    pub country: String,
+    /// Force reinstall (-f, --force)
+    pub force: bool,
+    /// Unattended mode (-q, --unattended)
+    pub unattended: bool,
+    /// Run downloads under torify (-t, --torify)
+    pub torify: bool,
+    /// Install each app in its own WINEPREFIX (--isolate)
+    pub isolate: bool,
+    /// Keep temp directories (--no-clean)
+    pub no_clean: bool,
+    /// Verbosity level (0-2)
+    pub verbosity: u8,
+    /// Color theme
+    pub theme: ThemeChoice,
+}
+
+/// Color theme of the GUI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeChoice {
+    #[default]
+    Dark,
+    Light,
+    /// Follow the desktop's GTK theme
+    System,
+}
+
+impl ThemeChoice {
+    pub fn all() -> [ThemeChoice; 3] {
+        [ThemeChoice::Dark, ThemeChoice::Light, ThemeChoice::System]
+    }
+
+    /// Whether the theme is dark (System asks the desktop, once per run)
+    pub fn is_dark(self) -> bool {
+        static SYSTEM_DARK: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        match self {
+            ThemeChoice::Dark => true,
+            ThemeChoice::Light => false,
+            ThemeChoice::System => *SYSTEM_DARK.get_or_init(system_prefers_dark),
+        }
+    }
+}
+
+impl std::fmt::Display for ThemeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeChoice::Dark => write!(f, "Dark"),
+            ThemeChoice::Light => write!(f, "Light"),
+            ThemeChoice::System => write!(f, "System"),
+        }
+    }
+}
+
+/// Whether the desktop uses a dark theme: GTK_THEME (e.g. "Adwaita:dark"), then
+/// gtk-application-prefer-dark-theme or gtk-theme-name in ~/.config/gtk-3.0/settings.ini
+/// Dark if neither says otherwise
+fn system_prefers_dark() -> bool {
+    if let Ok(theme) = std::env::var("GTK_THEME") {
+        if !theme.is_empty() {
+            return theme.to_lowercase().contains("dark");
+        }
+    }
+
+    let Some(settings) = dirs::config_dir()
+        .map(|dir| dir.join("gtk-3.0").join("settings.ini"))
+        .and_then(|path| std::fs::read_to_string(path).ok())
+    else {
+        return true;
+    };
+
+    let mut theme_name_dark = None;
+    for line in settings.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().to_lowercase();
+        match key.trim() {
+            "gtk-application-prefer-dark-theme" => return value == "1" || value == "true",
+            "gtk-theme-name" => theme_name_dark = Some(value.contains("dark")),
+            _ => {}
+        }
+    }
+    theme_name_dark.unwrap_or(true)
+}
+
+impl GuiPreferences {
+    /// Path of the preferences file
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| {
+                dirs::home_dir()
+                    .unwrap_or_else(|| PathBuf::from("/"))
+                    .join(".config")
+            })
+            .join("winetricks")
+            .join("gui.toml")
+    }
+
+    /// Load preferences, falling back to defaults if the file is missing or invalid
+    pub fn load() -> Self {
+        let path = Self::path();
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| toml::from_str(&content).map_err(anyhow::Error::from))
+        {
+            Ok(preferences) => preferences,
+            Err(e) => {
+                eprintln!("Warning: Failed to load preferences from {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Save preferences atomically (write to .tmp, then rename over the old file)
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {:?}", parent))?;
+        }
+
+        let content = toml::to_string_pretty(self).context("Failed to serialize preferences")?;
+        let tmp_path = path.with_extension("toml.tmp");
+        std::fs::write(&tmp_path, content)
+            .with_context(|| format!("Failed to write {:?}", tmp_path))?;
+        std::fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Failed to rename {:?} to {:?}", tmp_path, path))?;
+        Ok(())
+    }
+}
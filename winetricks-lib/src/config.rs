@@ -2,11 +2,82 @@
 
 use crate::error::{Result, WinetricksError};
 use dirs;
-use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{info, warn};
 
+/// Community-maintained bundle of all verb metadata (JSON array of VerbMetadata)
+pub const METADATA_BUNDLE_URL: &str =
+    "https://raw.githubusercontent.com/ryzendew/winetricks/master/files/verbs-bundle.json";
+
+/// WINETRICKS_* environment variables known to winetricks (see Config::from_env_strict)
+/// Includes those only the original winetricks reads, so shared shell setups stay valid
+pub const KNOWN_ENV_VARS: &[&str] = &[
+    "WINETRICKS_AUTH",
+    "WINETRICKS_BLACKLIST",
+    "WINETRICKS_STRICT_ENV",
+    // Original winetricks
+    "WINETRICKS_DOWNLOADER",
+    "WINETRICKS_DOWNLOADER_RETRIES",
+    "WINETRICKS_DOWNLOADER_TIMEOUT",
+    "WINETRICKS_GUI",
+    "WINETRICKS_LATEST_VERSION_CHECK",
+    "WINETRICKS_OPT_SHAREDPREFIX",
+    "WINETRICKS_SUPER_QUIET",
+];
+
+/// How long a downloaded metadata bundle is trusted before re-checking its ETag (one week)
+pub const DEFAULT_METADATA_REFRESH_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Revalidation state for the cached metadata bundle
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct BundleState {
+    etag: Option<String>,
+    /// Unix time of the last check against the server (successful or not)
+    checked_at: u64,
+}
+
+/// Output format for listing commands and error reporting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable text (default)
+    #[default]
+    Text,
+    /// JSON array of objects
+    Json,
+    /// Comma-separated values with a header line
+    Csv,
+}
+
+impl OutputFormat {
+    /// Get the format name as a string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!(
+                "Unknown output format: {} (expected text, json, or csv)",
+                s
+            )),
+        }
+    }
+}
+
 /// Winetricks configuration
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -34,6 +105,9 @@ pub struct Config {
     /// Use torify for downloads
     pub torify: bool,
 
+    /// Country code for regional Microsoft download servers (--country)
+    pub country: Option<String>,
+
     /// Wine architecture (win32 or win64)
     pub winearch: Option<String>,
 
@@ -48,6 +122,49 @@ pub struct Config {
 
     /// Don't delete temp directories (--no-clean)
     pub no_clean: bool,
+
+    /// Refuse to uninstall verbs that installed verbs depend on (--safe)
+    pub safe: bool,
+
+    /// Output format for listings and errors (--output-format)
+    pub output_format: OutputFormat,
+
+    /// Maximum number of simultaneous downloads (--concurrency)
+    pub download_concurrency: usize,
+
+    /// How often a failed download is retried (--retries)
+    pub download_retries: u8,
+
+    /// How long to wait for wineserver -w before killing it
+    pub wineserver_timeout: Duration,
+
+    /// Mount point of the install disc for DVD/CD-ROM verbs (--cdrom)
+    pub cdrom_path: Option<PathBuf>,
+
+    /// Kill installers that run longer than this (--timeout, wine_timeout in config.toml)
+    /// None (the default) waits forever
+    pub wine_timeout: Option<Duration>,
+
+    /// Release tag to install per GitHub-based verb (e.g. dxvk = "v2.3") instead of the
+    /// latest release (github_pins table in config.toml)
+    pub github_pins: HashMap<String, String>,
+
+    /// Reinstall verbs installed more than this many days ago (--reinstall-if-older-than)
+    pub reinstall_if_older_than: Option<u32>,
+
+    /// Steam app whose Proton prefix (steamapps/compatdata/<appid>/pfx) to use (--steam-app-id)
+    pub steam_app_id: Option<u32>,
+
+    /// Don't look for Wine, use a placeholder /usr/bin/wine instead (--no-wine-check,
+    /// for testing download and verb logic on systems without Wine)
+    pub skip_wine_check: bool,
+
+    /// How often the metadata bundle is re-checked when there is no local verb source
+    /// (metadata_refresh_days in config.toml)
+    pub metadata_refresh_interval: Duration,
+
+    /// cabextract to run instead of the one found in PATH
+    pub cabextract: Option<PathBuf>,
 }
 
 impl Config {
@@ -65,7 +182,7 @@ impl Config {
             .ok_or_else(|| WinetricksError::Config("Could not determine data directory".into()))?
             .join("wineprefixes");
 
-        Ok(Self {
+        let mut config = Self {
             cache_dir,
             data_dir,
             prefixes_root,
@@ -74,22 +191,198 @@ impl Config {
             force: false,
             unattended: false,
             torify: false,
+            country: None,
             winearch: None,
             renderer: None,
             wayland: None,
             isolate: false,
             no_clean: false,
-        })
+            safe: false,
+            output_format: OutputFormat::default(),
+            download_concurrency: crate::download::DEFAULT_DOWNLOAD_CONCURRENCY,
+            download_retries: crate::download::DEFAULT_DOWNLOAD_RETRIES,
+            wineserver_timeout: crate::wine::DEFAULT_WINESERVER_TIMEOUT,
+            cdrom_path: None,
+            wine_timeout: None,
+            github_pins: HashMap::new(),
+            reinstall_if_older_than: None,
+            steam_app_id: None,
+            skip_wine_check: false,
+            metadata_refresh_interval: DEFAULT_METADATA_REFRESH_INTERVAL,
+            cabextract: None,
+        };
+        config.load_config_file();
+        Ok(config)
+    }
+
+    /// Like new(), but with WINETRICKS_STRICT_ENV=1 set, fails on WINETRICKS_* environment
+    /// variables winetricks doesn't know (typos in shell init files)
+    pub fn from_env_strict() -> Result<Self> {
+        if std::env::var("WINETRICKS_STRICT_ENV").as_deref() == Ok("1") {
+            let mut unknown: Vec<String> = std::env::vars_os()
+                .map(|(name, _)| name.to_string_lossy().into_owned())
+                .filter(|name| name.starts_with("WINETRICKS_"))
+                .filter(|name| !KNOWN_ENV_VARS.contains(&name.as_str()))
+                .collect();
+            unknown.sort();
+            match unknown.len() {
+                0 => {}
+                1 => {
+                    return Err(WinetricksError::Config(format!(
+                        "Unknown environment variable: {}",
+                        unknown[0]
+                    )))
+                }
+                _ => {
+                    return Err(WinetricksError::Config(format!(
+                        "Unknown environment variables: {}",
+                        unknown.join(", ")
+                    )))
+                }
+            }
+        }
+        Self::new()
+    }
+
+    /// Apply defaults from ~/.config/winetricks/config.toml (missing file: built-in defaults)
+    /// Supported keys: wine_timeout (seconds, 0 = no limit), github_pins (verb = "tag"),
+    /// metadata_refresh_days
+    fn load_config_file(&mut self) {
+        let path = self.cached_verbs_dir().join("config.toml");
+        if !path.exists() {
+            return;
+        }
+
+        let settings = match ::config::Config::builder()
+            .add_source(::config::File::from(path.as_path()))
+            .build()
+        {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("Ignoring {:?}: {}", path, e);
+                return;
+            }
+        };
+
+        match settings.get::<u64>("wine_timeout") {
+            Ok(0) => self.wine_timeout = None,
+            Ok(secs) => self.wine_timeout = Some(Duration::from_secs(secs)),
+            Err(::config::ConfigError::NotFound(_)) => {}
+            Err(e) => warn!("Ignoring wine_timeout in {:?}: {}", path, e),
+        }
+
+        match settings.get::<HashMap<String, String>>("github_pins") {
+            Ok(pins) => self.github_pins = pins,
+            Err(::config::ConfigError::NotFound(_)) => {}
+            Err(e) => warn!("Ignoring github_pins in {:?}: {}", path, e),
+        }
+
+        match settings.get::<u64>("metadata_refresh_days") {
+            Ok(days) => self.metadata_refresh_interval = Duration::from_secs(days * 24 * 60 * 60),
+            Err(::config::ConfigError::NotFound(_)) => {}
+            Err(e) => warn!("Ignoring metadata_refresh_days in {:?}: {}", path, e),
+        }
     }
 
     /// Get the wine prefix path (default or configured)
     pub fn wineprefix(&self) -> PathBuf {
         self.wineprefix
             .clone()
+            .or_else(|| self.steam_app_prefix())
             .or_else(|| std::env::var("WINEPREFIX").ok().map(PathBuf::from))
             .unwrap_or_else(|| dirs::home_dir().unwrap().join(".wine"))
     }
 
+    /// Proton prefix of the configured Steam app (None without steam_app_id or Steam)
+    pub fn steam_app_prefix(&self) -> Option<PathBuf> {
+        let app_id = self.steam_app_id?;
+        let steam_root = crate::wine::Wine::steam_root()?;
+        Some(
+            steam_root
+                .join("steamapps")
+                .join("compatdata")
+                .join(app_id.to_string())
+                .join("pfx"),
+        )
+    }
+
+    /// Directories holding wineprefixes: prefixes_root, then the prefix directories of
+    /// Lutris, Bottles and Steam (Proton) if those are installed
+    pub fn discover_prefix_roots(&self) -> Vec<PathBuf> {
+        let mut roots = vec![self.prefixes_root.clone()];
+        for (_, root) in Self::other_prefix_roots() {
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+        roots
+    }
+
+    /// Name of the tool managing a prefix root ("winetricks" for prefixes_root)
+    pub fn prefix_root_name(&self, root: &Path) -> &'static str {
+        Self::other_prefix_roots()
+            .into_iter()
+            .find(|(_, dir)| dir == root)
+            .map(|(name, _)| name)
+            .unwrap_or("winetricks")
+    }
+
+    /// Prefix roots of other Wine managers, detected by their config/data directories
+    fn other_prefix_roots() -> Vec<(&'static str, PathBuf)> {
+        let Some(home) = dirs::home_dir() else {
+            return Vec::new();
+        };
+        let mut roots = Vec::new();
+
+        // Lutris installs games (and their prefixes) into ~/Games by default
+        let lutris_installed = [
+            home.join(".config/lutris"),
+            home.join(".local/share/lutris"),
+            home.join(".var/app/net.lutris.Lutris"),
+        ]
+        .iter()
+        .any(|dir| dir.is_dir());
+        if lutris_installed {
+            roots.push(("Lutris", home.join("Games")));
+        }
+
+        // Bottles keeps one prefix per bottle in <data dir>/bottles
+        for data_dir in [
+            home.join(".var/app/com.usebottles.bottles/data/bottles"),
+            home.join(".local/share/bottles"),
+        ] {
+            if data_dir.is_dir() {
+                roots.push(("Bottles", data_dir.join("bottles")));
+            }
+        }
+
+        // Proton prefixes are compatdata/<appid>/pfx
+        if let Some(steam_root) = crate::wine::Wine::steam_root() {
+            roots.push(("Steam", steam_root.join("steamapps").join("compatdata")));
+        }
+
+        roots.retain(|(_, dir)| dir.is_dir());
+        roots
+    }
+
+    /// Resolve a named prefix (prefix=NAME) to its path under prefixes_root
+    /// Prefixes get deleted by name, so the name must not be able to point anywhere
+    /// else: empty names, absolute paths, "." and ".." and path separators are rejected
+    pub fn resolve_prefix(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty()
+            || name == "."
+            || name.contains(['/', '\\'])
+            || name.contains("..")
+            || Path::new(name).is_absolute()
+        {
+            return Err(WinetricksError::Config(format!(
+                "Invalid prefix name: {:?}",
+                name
+            )));
+        }
+        Ok(self.prefixes_root.join(name))
+    }
+
     /// Get source JSON directory (files/json/ in project, or empty if not found)
     pub fn source_json_dir(&self) -> Option<PathBuf> {
         if let Ok(current_exe) = std::env::current_exe() {
@@ -139,8 +432,18 @@ impl Config {
             return Ok(());
         }
 
-        // No local source - check if we need to download from GitHub
-        if cached_dir.exists() {
+        // No local source - use the community metadata bundle, re-checked weekly
+        match self.refresh_metadata_bundle(&cached_dir).await {
+            Ok(()) if cached_dir.join("verbs-bundle.json").exists() => return Ok(()),
+            Ok(()) => {}
+            Err(e) => warn!("Could not fetch verb metadata bundle: {}", e),
+        }
+
+        // Bundle unavailable - check if we need to download from GitHub
+        let has_categories = crate::VerbCategory::ALL
+            .iter()
+            .any(|category| cached_dir.join(category.as_str()).is_dir());
+        if has_categories {
             // Cache already exists, check if we should update from GitHub
             // For now, skip auto-update from GitHub to avoid breaking changes
             info!("Using existing verb cache at: {:?}", cached_dir);
@@ -155,6 +458,59 @@ impl Config {
         Ok(())
     }
 
+    /// Fetch the verb metadata bundle (see VerbRegistry::load_from_url) into the cache
+    /// The bundle is kept at <cache>/verbs-bundle.json, unpacked into the usual category
+    /// directories, and only re-checked (with If-None-Match) once metadata_refresh_interval
+    /// has passed since the last attempt
+    async fn refresh_metadata_bundle(&self, cached_dir: &Path) -> Result<()> {
+        use crate::VerbRegistry;
+        use std::fs;
+        use std::time::SystemTime;
+
+        let bundle_path = cached_dir.join("verbs-bundle.json");
+        let state_path = cached_dir.join("verbs-bundle.state.json");
+
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut state: BundleState = fs::read_to_string(&state_path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
+        if now.saturating_sub(state.checked_at) < self.metadata_refresh_interval.as_secs() {
+            info!("Verb metadata bundle checked recently, not checking again yet");
+            return Ok(());
+        }
+
+        info!("Checking verb metadata bundle: {}", METADATA_BUNDLE_URL);
+        let etag = state.etag.as_deref().filter(|_| bundle_path.exists());
+        let fetched =
+            VerbRegistry::load_from_url_if_modified(METADATA_BUNDLE_URL, etag, self.torify).await;
+        let result = match fetched {
+            Ok(None) => {
+                info!("Verb metadata bundle is up to date");
+                Ok(())
+            }
+            Ok(Some((registry, etag))) => {
+                registry.write_to_dir(cached_dir)?;
+                fs::write(&bundle_path, registry.export_json()?)?;
+                info!("Verb metadata bundle unpacked into: {:?}", cached_dir);
+                state.etag = etag;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        };
+
+        // Failed attempts count too, so an unreachable server isn't retried on every run
+        state.checked_at = now;
+        fs::create_dir_all(cached_dir)?;
+        fs::write(&state_path, serde_json::to_string(&state)?)?;
+        result
+    }
+
     /// Download JSON files from GitHub repository
     async fn download_json_from_github(&self, cached_dir: &Path) -> Result<()> {
         use std::fs;
@@ -167,10 +523,12 @@ impl Config {
         let categories = [
             "apps",
             "benchmarks",
+            "codecs",
             "dlls",
             "download",
             "fonts",
             "manual-download",
+            "runtimes",
             "settings",
         ];
 
@@ -179,8 +537,7 @@ impl Config {
             GITHUB_REPO, JSON_PATH
         );
 
-        let client = Client::builder()
-            .user_agent("Winetricks-RS/1.0")
+        let client = crate::download::http_client_builder(self.torify)?
             .build()
             .map_err(|e| WinetricksError::Config(format!("Failed to create HTTP client: {}", e)))?;
 
@@ -380,6 +737,15 @@ impl Config {
 
                         let dest_path = cached_category_dir.join(filename);
                         fs::copy(&json_path, &dest_path)?;
+
+                        // A verb that moved category (dlls -> runtimes) would otherwise
+                        // be registered twice
+                        for category in crate::VerbCategory::ALL {
+                            let stale = cached_dir.join(category.as_str()).join(filename);
+                            if category.as_str() != category_name && stale.exists() {
+                                fs::remove_file(&stale)?;
+                            }
+                        }
                     }
                 }
             }
@@ -388,6 +754,15 @@ impl Config {
         Ok(())
     }
 
+    /// Verbs added with import-verbs (~/.local/share/winetricks/imported-verbs/), kept
+    /// apart from metadata_dir() so refreshing the metadata bundle never removes them
+    pub fn imported_verbs_dir(&self) -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| dirs::home_dir().unwrap().join(".local").join("share"))
+            .join("winetricks")
+            .join("imported-verbs")
+    }
+
     /// Get metadata directory (uses cached location, falls back to source)
     /// The cached location is ~/.config/winetricks/ which contains category subdirectories (dlls/, apps/, etc.)
     pub fn metadata_dir(&self) -> PathBuf {
@@ -435,114 +810,111 @@ impl Config {
 
     /// Ensure directories exist
     pub fn ensure_dirs(&self) -> Result<()> {
-        std::fs::create_dir_all(&self.cache_dir)?;
-        std::fs::create_dir_all(&self.data_dir)?;
-        std::fs::create_dir_all(&self.prefixes_root)?;
-        std::fs::create_dir_all(self.metadata_dir())?;
+        Self::create_dir(&self.cache_dir)?;
+        Self::create_dir(&self.data_dir)?;
+        Self::create_dir(&self.prefixes_root)?;
+        Self::create_dir(&self.metadata_dir())?;
         Ok(())
     }
 
+    /// create_dir_all with a hint for the usual permission/read-only problems
+    fn create_dir(path: &Path) -> Result<()> {
+        use std::io::ErrorKind;
+
+        std::fs::create_dir_all(path).map_err(|e| match e.kind() {
+            ErrorKind::PermissionDenied => WinetricksError::Config(format!(
+                "Cannot create directory {:?}: permission denied. Try: chmod +w {:?}",
+                path,
+                path.parent().unwrap_or(path)
+            )),
+            ErrorKind::ReadOnlyFilesystem => WinetricksError::Config(format!(
+                "Cannot create directory {:?}: the filesystem is read-only. \
+                 Remount it read-write (e.g. mount -o remount,rw) or point \
+                 XDG_CACHE_HOME/XDG_DATA_HOME at a writable location",
+                path
+            )),
+            _ => WinetricksError::Config(format!("Cannot create directory {:?}: {}", path, e)),
+        })
+    }
+
     /// Set D3D renderer in wineprefix registry (persistent setting)
     pub fn set_renderer_in_registry(&self, renderer: Option<&str>) -> Result<()> {
         use crate::Wine;
-        use std::fs;
-        use std::io::Write;
         use std::process::Command;
 
         let wineprefix = self.wineprefix();
-
-        // Create temp directory for registry file
-        let temp_dir = dirs::cache_dir()
-            .ok_or_else(|| WinetricksError::Config("Could not determine cache directory".into()))?
-            .join("winetricks");
-        fs::create_dir_all(&temp_dir)?;
-
-        let reg_file = temp_dir.join("set_renderer.reg");
+        let wineprefix_str = wineprefix.to_string_lossy().to_string();
         let wine = Wine::detect()?;
 
         // Convert renderer to Wine format
         let renderer_value = match renderer {
             Some(r) => match r.to_lowercase().as_str() {
-                "opengl" | "gl" | "w" => "gl",
-                "vulkan" | "vk" | "v" => "vulkan",
-                "gdi" => "gdi",
-                "no3d" => "no3d",
-                _ => r,
+                "opengl" | "gl" | "w" => "gl".to_string(),
+                "vulkan" | "vk" | "v" => "vulkan".to_string(),
+                "gdi" => "gdi".to_string(),
+                "no3d" => "no3d".to_string(),
+                _ => r.to_string(),
             },
             None => {
-                // If None, remove the setting (set to empty string or remove key)
-                // For now, we'll just return success without writing
+                // Remove the setting so Wine uses its default renderer
+                // reg delete fails if the value doesn't exist, which is fine
+                let _ = Command::new(&wine.wine_bin)
+                    .arg("reg")
+                    .arg("delete")
+                    .arg("HKEY_CURRENT_USER\\Software\\Wine\\Direct3D")
+                    .arg("/v")
+                    .arg("renderer")
+                    .arg("/f")
+                    .env("WINEPREFIX", &wineprefix_str)
+                    .envs(wine.proton_env(&wineprefix))
+                    .output()
+                    .map_err(|e| {
+                        WinetricksError::command_io(
+                            "wine reg delete HKCU\\Software\\Wine\\Direct3D /v renderer /f",
+                            e,
+                        )
+                    })?;
                 return Ok(());
             }
         };
 
-        // Create registry file
-        let reg_content = format!(
-            r#"REGEDIT4
-
-[HKEY_CURRENT_USER\Software\Wine\Direct3D]
-"renderer"="{}"
-"#,
+        // HKCU\Software\Wine\Direct3D\renderer (REG_SZ) is read by wined3d on startup,
+        // so unlike WINE_D3D_CONFIG it survives wineserver restarts
+        let command = format!(
+            "wine reg add HKCU\\Software\\Wine\\Direct3D /v renderer /t REG_SZ /d {} /f",
             renderer_value
         );
-
-        let mut file = fs::File::create(&reg_file)?;
-        file.write_all(reg_content.as_bytes())?;
-        file.sync_all()?;
-
-        // Convert Unix path to Wine Windows path
-        let wineprefix_str = wineprefix.to_string_lossy().to_string();
-        let reg_file_str = reg_file.to_string_lossy().to_string();
-
-        // Get Windows path for the reg file
         let output = Command::new(&wine.wine_bin)
-            .arg("winepath")
-            .arg("-w")
-            .arg(&reg_file_str)
+            .arg("reg")
+            .arg("add")
+            .arg("HKEY_CURRENT_USER\\Software\\Wine\\Direct3D")
+            .arg("/v")
+            .arg("renderer")
+            .arg("/t")
+            .arg("REG_SZ")
+            .arg("/d")
+            .arg(&renderer_value)
+            .arg("/f")
             .env("WINEPREFIX", &wineprefix_str)
+            .envs(wine.proton_env(&wineprefix))
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine winepath -w {:?}", reg_file_str),
-                error: e.to_string(),
-            })?;
-
-        let reg_file_win = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-        // Import registry file using wine regedit
-        let status = Command::new(&wine.wine_bin)
-            .arg("regedit")
-            .arg("/S") // Silent mode
-            .arg(&reg_file_win)
-            .env("WINEPREFIX", &wineprefix_str)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine regedit /S {:?}", reg_file_win),
-                error: e.to_string(),
-            })?;
-
-        // Clean up temp file
-        let _ = fs::remove_file(&reg_file);
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-        if !status.success() {
-            return Err(WinetricksError::Config(format!(
-                "Failed to set renderer in registry (exit code: {:?})",
-                status.code()
-            )));
+        if !output.status.success() {
+            return Err(WinetricksError::command_failed(command, &output));
         }
 
         Ok(())
     }
 
     /// Get D3D renderer from wineprefix registry
-    pub fn get_renderer_from_registry(&self) -> Option<String> {
+    /// Returns None if the prefix has no renderer value set
+    pub fn get_renderer_from_registry(&self) -> Result<Option<String>> {
         use crate::Wine;
         use std::process::Command;
 
         let wineprefix = self.wineprefix();
-        let wine = match Wine::detect() {
-            Ok(w) => w,
-            Err(_) => return None,
-        };
+        let wine = Wine::detect()?;
 
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
 
@@ -555,11 +927,18 @@ impl Config {
             .arg("/v")
             .arg("renderer")
             .env("WINEPREFIX", &wineprefix_str)
+            .envs(wine.proton_env(&wineprefix))
             .output()
-            .ok()?;
+            .map_err(|e| {
+                WinetricksError::command_io(
+                    "wine reg query HKCU\\Software\\Wine\\Direct3D /v renderer",
+                    e,
+                )
+            })?;
 
+        // reg query fails when the key or value doesn't exist
         if !output.status.success() {
-            return None;
+            return Ok(None);
         }
 
         // Parse output: should contain "renderer" REG_SZ "value"
@@ -573,75 +952,38 @@ impl Config {
                     let value = value.trim().trim_matches('"');
                     if !value.is_empty() {
                         // Convert Wine format back to user-friendly format
-                        match value.to_lowercase().as_str() {
-                            "gl" => return Some("opengl".to_string()),
-                            "vulkan" | "vk" | "v" => return Some("vulkan".to_string()),
-                            "gdi" => return Some("gdi".to_string()),
-                            "no3d" => return Some("no3d".to_string()),
-                            _ => return Some(value.to_string()),
-                        }
+                        let renderer = match value.to_lowercase().as_str() {
+                            "gl" => "opengl".to_string(),
+                            "vulkan" | "vk" | "v" => "vulkan".to_string(),
+                            "gdi" => "gdi".to_string(),
+                            "no3d" => "no3d".to_string(),
+                            _ => value.to_string(),
+                        };
+                        return Ok(Some(renderer));
                     }
                 }
             }
         }
 
-        None
+        Ok(None)
     }
 
     /// Load renderer setting from wineprefix (registry) if available
     pub fn load_renderer_from_prefix(&mut self) {
-        if let Some(renderer) = self.get_renderer_from_registry() {
+        if let Ok(Some(renderer)) = self.get_renderer_from_registry() {
             self.renderer = Some(renderer);
         }
     }
 
-    /// Get Graphics driver from wineprefix registry
-    pub fn get_wayland_from_registry(&self) -> Option<String> {
-        use crate::Wine;
-        use std::process::Command;
-
-        let wineprefix = self.wineprefix();
-        let wine = match Wine::detect() {
-            Ok(w) => w,
-            Err(_) => return None,
-        };
-
-        let wineprefix_str = wineprefix.to_string_lossy().to_string();
-
-        // Query registry for Graphics driver
-        let output = Command::new(&wine.wine_bin)
-            .arg("reg")
-            .arg("query")
-            .arg("HKEY_CURRENT_USER\\Software\\Wine\\Drivers")
-            .arg("/v")
-            .arg("Graphics")
-            .env("WINEPREFIX", &wineprefix_str)
-            .output()
-            .ok()?;
-
-        if !output.status.success() {
-            return None;
-        }
-
-        // Parse output
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines() {
-            if line.contains("Graphics") && line.contains("REG_SZ") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if let Some(value) = parts.last() {
-                    let value = value.trim().trim_matches('"').to_lowercase();
-                    if !value.is_empty() {
-                        match value.as_str() {
-                            "wayland" => return Some("wayland".to_string()),
-                            "x11" | "xwayland" => return Some("xwayland".to_string()),
-                            _ => return Some(value),
-                        }
-                    }
-                }
-            }
-        }
-
-        None
+    /// Get the display driver from the wineprefix registry
+    /// (HKCU\Software\Wine\Drivers\Graphics: "wayland", or "xwayland" for x11)
+    pub fn get_wayland_from_registry(&self) -> Result<Option<String>> {
+        let wine = crate::Wine::detect()?;
+        let driver = wine.get_graphics_driver(&self.wineprefix())?;
+        Ok(driver.map(|driver| match driver.to_lowercase().as_str() {
+            "x11" | "xwayland" => "xwayland".to_string(),
+            other => other.to_string(),
+        }))
     }
 
     /// Detect current display server (Wayland or XWayland)
@@ -665,7 +1007,7 @@ impl Config {
     /// Does NOT fall back to environment detection (preserves Auto setting)
     pub fn load_wayland_from_prefix(&mut self) {
         // First try registry
-        if let Some(wayland) = self.get_wayland_from_registry() {
+        if let Ok(Some(wayland)) = self.get_wayland_from_registry() {
             self.wayland = Some(wayland);
             return;
         }
@@ -678,7 +1020,7 @@ impl Config {
     /// Load wayland setting with environment fallback (for initial detection)
     pub fn load_wayland_from_prefix_with_env(&mut self) {
         // First try registry
-        if let Some(wayland) = self.get_wayland_from_registry() {
+        if let Ok(Some(wayland)) = self.get_wayland_from_registry() {
             self.wayland = Some(wayland);
             return;
         }
@@ -689,122 +1031,31 @@ impl Config {
         }
     }
 
-    /// Set Graphics driver in wineprefix registry (persistent setting)
+    /// Set the display driver in the wineprefix registry (persistent setting)
+    /// "wayland" writes Graphics="wayland", "xwayland"/"x11" Graphics="x11" (with
+    /// `wine reg add`); None (wayland=auto) leaves whatever the prefix has
     pub fn set_wayland_in_registry(&self, wayland: Option<&str>) -> Result<()> {
-        use crate::Wine;
-        use std::fs;
-        use std::io::Write;
-        use std::process::Command;
-
-        let wineprefix = self.wineprefix();
-
-        // Create temp directory for registry file
-        let temp_dir = dirs::cache_dir()
-            .ok_or_else(|| WinetricksError::Config("Could not determine cache directory".into()))?
-            .join("winetricks");
-        fs::create_dir_all(&temp_dir)?;
-
-        let reg_file = temp_dir.join("set_wayland.reg");
-        let wine = Wine::detect()?;
-
-        let wineprefix_str = wineprefix.to_string_lossy().to_string();
-
-        // Handle Auto - delete the registry key to let Wine decide
-        if wayland.is_none() {
-            // Delete the Graphics key using wine reg delete
-            let status = Command::new(&wine.wine_bin)
-                .arg("reg")
-                .arg("delete")
-                .arg("HKEY_CURRENT_USER\\Software\\Wine\\Drivers")
-                .arg("/v")
-                .arg("Graphics")
-                .arg("/f") // Force delete
-                .env("WINEPREFIX", &wineprefix_str)
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!(
-                        "wine reg delete HKEY_CURRENT_USER\\Software\\Wine\\Drivers /v Graphics /f"
-                    ),
-                    error: e.to_string(),
-                })?;
-
-            // It's OK if the key doesn't exist (exit code 1)
-            if !status.success() && status.code() != Some(1) {
-                return Err(WinetricksError::Config(format!(
-                    "Failed to delete Graphics driver from registry (exit code: {:?})",
-                    status.code()
-                )));
+        let Some(wayland) = wayland else {
+            match self.get_wayland_from_registry()? {
+                Some(current) => info!("wayland=auto: keeping Graphics driver {}", current),
+                None => info!("wayland=auto: no Graphics driver set, Wine decides"),
             }
-
             return Ok(());
-        }
+        };
 
-        // Convert wayland option to Wine format
         let graphics_value = match wayland {
-            Some("wayland") => "wayland",
-            Some("xwayland") | Some("x11") => "x11",
+            "wayland" => "wayland",
+            "xwayland" | "x11" => "x11",
             _ => {
                 return Err(WinetricksError::Config(format!(
                     "Invalid wayland value: {}",
-                    wayland.unwrap_or("None")
+                    wayland
                 )));
             }
         };
 
-        // Create registry file
-        let reg_content = format!(
-            r#"REGEDIT4
-
-[HKEY_CURRENT_USER\Software\Wine\Drivers]
-"Graphics"="{}"
-"#,
-            graphics_value
-        );
-
-        let mut file = fs::File::create(&reg_file)?;
-        file.write_all(reg_content.as_bytes())?;
-        file.sync_all()?;
-
-        // Convert Unix path to Wine Windows path
-        let reg_file_str = reg_file.to_string_lossy().to_string();
-
-        // Get Windows path for the reg file
-        let output = Command::new(&wine.wine_bin)
-            .arg("winepath")
-            .arg("-w")
-            .arg(&reg_file_str)
-            .env("WINEPREFIX", &wineprefix_str)
-            .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine winepath -w {:?}", reg_file_str),
-                error: e.to_string(),
-            })?;
-
-        let reg_file_win = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-        // Import registry file using wine regedit
-        let status = Command::new(&wine.wine_bin)
-            .arg("regedit")
-            .arg("/S") // Silent mode
-            .arg(&reg_file_win)
-            .env("WINEPREFIX", &wineprefix_str)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine regedit /S {:?}", reg_file_win),
-                error: e.to_string(),
-            })?;
-
-        // Clean up temp file
-        let _ = fs::remove_file(&reg_file);
-
-        if !status.success() {
-            return Err(WinetricksError::Config(format!(
-                "Failed to set Graphics driver in registry (exit code: {:?})",
-                status.code()
-            )));
-        }
-
-        Ok(())
+        let wine = crate::Wine::detect()?;
+        wine.set_graphics_driver(&self.wineprefix(), graphics_value)
     }
 }
 
@@ -1,40 +1,176 @@
 //! Download system with caching and checksum verification
 
 use crate::error::{Result, WinetricksError};
+use crate::verb::VerbRegistry;
 use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::Client;
+use reqwest::header::{CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+/// Default number of simultaneous downloads
+pub const DEFAULT_DOWNLOAD_CONCURRENCY: usize = 3;
+
+/// Default number of times a failed download is retried
+pub const DEFAULT_DOWNLOAD_RETRIES: u8 = 3;
+
+/// Longest wait between two download attempts
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// SOCKS proxy of a local Tor daemon used with --torify
+/// socks5h so host names are resolved through Tor too
+const TOR_PROXY: &str = "socks5h://127.0.0.1:9050";
+
+/// Builder for the HTTP client of every request winetricks makes
+/// With torify (--torify) all requests go through Tor's SOCKS proxy on 127.0.0.1:9050;
+/// fails if neither torsocks nor torify is installed, rather than silently connecting
+/// without Tor
+pub fn http_client_builder(torify: bool) -> Result<reqwest::ClientBuilder> {
+    let mut builder = Client::builder().user_agent("Winetricks/1.0");
+    if torify {
+        if which::which("torsocks").is_err() && which::which("torify").is_err() {
+            return Err(WinetricksError::Config(
+                "torify requested but torsocks/torify not found in PATH".into(),
+            ));
+        }
+        builder = builder.proxy(reqwest::Proxy::all(TOR_PROXY)?);
+    }
+    Ok(builder)
+}
+
+/// HTTP validators stored next to a cached file (<file>.metadata.json)
+/// Used to revalidate files without a pinned checksum via conditional GET
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CacheMetadata {
+    /// URL the file was downloaded from
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+}
+
+/// A file stored in a verb's download cache directory
+#[derive(Debug, Clone)]
+pub struct CachedFile {
+    pub filename: String,
+    pub path: PathBuf,
+    /// Size in bytes
+    pub size: u64,
+    pub sha256: String,
+    pub modified: SystemTime,
+}
+
+/// A file stored in a verb's download cache directory, without its checksum
+/// (see get_cached_file_metadata)
+#[derive(Debug, Clone)]
+pub struct CachedFileMetadata {
+    pub filename: String,
+    pub path: PathBuf,
+    /// Size in bytes
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// Headers of a remote file, fetched without downloading it (see head_request)
+#[derive(Debug, Clone, Default)]
+pub struct HeadResponse {
+    /// Size in bytes, if the server sent one
+    pub content_length: Option<u64>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Where the URL redirected to, if anywhere
+    pub redirect_url: Option<String>,
+}
 
 /// Download manager
+#[derive(Clone)]
 pub struct DownloadManager {
     client: Client,
     cache_dir: PathBuf,
+    /// Maximum number of simultaneous downloads
+    concurrency: usize,
+    /// Limits active requests (shared between clones)
+    semaphore: Arc<Semaphore>,
+    /// How often a download is retried after a network error or checksum mismatch
+    retries: u8,
 }
 
 impl DownloadManager {
     /// Create a new download manager
     pub fn new(cache_dir: PathBuf) -> Result<Self> {
-        let client = Client::builder().user_agent("Winetricks/1.0").build()?;
+        Self::with_concurrency(cache_dir, DEFAULT_DOWNLOAD_CONCURRENCY)
+    }
+
+    /// Create a new download manager allowing up to `concurrency` simultaneous downloads
+    pub fn with_concurrency(cache_dir: PathBuf, concurrency: usize) -> Result<Self> {
+        let client = http_client_builder(false)?.build()?;
 
         std::fs::create_dir_all(&cache_dir)?;
 
-        Ok(Self { client, cache_dir })
+        // At least one download must be able to run
+        let concurrency = concurrency.max(1);
+
+        Ok(Self {
+            client,
+            cache_dir,
+            concurrency,
+            semaphore: Arc::new(Semaphore::new(concurrency)),
+            retries: DEFAULT_DOWNLOAD_RETRIES,
+        })
+    }
+
+    /// Download through Tor (--torify, see http_client_builder)
+    pub fn with_torify(mut self, torify: bool) -> Result<Self> {
+        if torify {
+            self.client = http_client_builder(true)?.build()?;
+        }
+        Ok(self)
+    }
+
+    /// Retry failed downloads up to `retries` times (0 disables retrying)
+    pub fn with_retries(mut self, retries: u8) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Get the maximum number of simultaneous downloads
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
     }
 
     /// Download a file to cache
+    /// The response is streamed to disk chunk by chunk; `on_progress` is called after each
+    /// chunk with (bytes_received, content_length), content_length being 0 if the server
+    /// didn't send one
     pub async fn download<P: AsRef<Path>>(
         &self,
         url: &str,
         filename: P,
         expected_sha256: Option<&str>,
         progress: bool,
+        on_progress: Option<&(dyn Fn(u64, u64) + Send + Sync)>,
+        country: Option<&str>,
     ) -> Result<PathBuf> {
         let filename = filename.as_ref();
         let cache_file = self.cache_dir.join(filename);
 
+        // A file with a known checksum may already be in the content store
+        // (e.g. the DirectX redistributable shared by all d3dx9_* verbs)
+        if let Some(expected) = expected_sha256 {
+            if !cache_file.exists() && self.link_from_store(expected, &cache_file) {
+                return Ok(cache_file);
+            }
+        }
+
         // Check if already cached
+        let mut validators = None;
         if cache_file.exists() {
             if let Some(expected) = expected_sha256 {
                 if self.verify_checksum(&cache_file, expected)? {
@@ -43,12 +179,108 @@ impl DownloadManager {
                 // Checksum mismatch - re-download
                 std::fs::remove_file(&cache_file)?;
             } else {
-                return Ok(cache_file);
+                // No checksum to verify against - revalidate with the server if we
+                // know the file's ETag/Last-Modified, otherwise trust the cache
+                match Self::load_cache_metadata(&cache_file) {
+                    Some(metadata) if metadata.url == url => validators = Some(metadata),
+                    _ => return Ok(cache_file),
+                }
+            }
+        }
+
+        // Transient network errors are retried with exponential backoff
+        let mut request_country = country;
+        let mut attempt: u32 = 0;
+        loop {
+            // Conditional GET when revalidating a cached file
+            let mut request = self.client.get(regional_url(url, request_country));
+            if let Some(ref metadata) = validators {
+                if let Some(ref etag) = metadata.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(ref last_modified) = metadata.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+            let result = self
+                .download_once(
+                    request,
+                    url,
+                    &cache_file,
+                    expected_sha256,
+                    progress,
+                    on_progress,
+                )
+                .await;
+
+            let retries_left = attempt < u32::from(self.retries);
+            match result {
+                Err(WinetricksError::ChecksumMismatch { .. }) if retries_left => {
+                    // The CDN (or regional mirror) may have served a corrupted file,
+                    // try again right away from the original URL
+                    warn!("Checksum mismatch downloading {}, retrying", url);
+                    request_country = None;
+                }
+                Err(WinetricksError::Http(ref e)) if retries_left && is_transient(e) => {
+                    let delay =
+                        Duration::from_millis(500 * 2u64.pow(attempt.min(6))).min(MAX_RETRY_DELAY);
+                    warn!(
+                        "Downloading {} failed ({}), retrying in {:.1}s",
+                        url,
+                        e,
+                        delay.as_secs_f32()
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
             }
+            attempt += 1;
+        }
+    }
+
+    /// One attempt of download(): send `request` and stream the response into the cache
+    async fn download_once(
+        &self,
+        request: RequestBuilder,
+        url: &str,
+        cache_file: &Path,
+        expected_sha256: Option<&str>,
+        progress: bool,
+        on_progress: Option<&(dyn Fn(u64, u64) + Send + Sync)>,
+    ) -> Result<PathBuf> {
+        // Wait for a free download slot (released when the permit is dropped, also on errors)
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .map_err(|e| WinetricksError::Download(format!("Download slot error: {}", e)))?;
+
+        let mut response = request.send().await?;
+
+        // Cached copy is still current (the file only exists here when revalidating it)
+        if response.status() == StatusCode::NOT_MODIFIED && cache_file.exists() {
+            return Ok(cache_file.to_path_buf());
+        }
+        if !response.status().is_success() {
+            return Err(WinetricksError::Download(format!(
+                "Failed to download {}: HTTP {}",
+                url,
+                response.status()
+            )));
         }
 
-        // Download file
-        let mut response = self.client.get(url).send().await?;
+        let headers = response.headers();
+        let metadata = CacheMetadata {
+            url: url.to_string(),
+            etag: headers
+                .get(ETAG)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from),
+            last_modified: headers
+                .get(LAST_MODIFIED)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from),
+        };
 
         let total_size = response.content_length().unwrap_or(0);
 
@@ -66,16 +298,23 @@ impl DownloadManager {
             None
         };
 
-        let mut file = std::fs::File::create(&cache_file)?;
+        // Write to a temporary file so a failed download doesn't clobber the cached copy
+        let part_file = Self::sidecar_path(cache_file, "part");
+        let mut file = std::fs::File::create(&part_file)?;
         let mut hasher = Sha256::new();
+        let mut received = 0u64;
 
         while let Some(chunk) = response.chunk().await? {
             file.write_all(&chunk)?;
             hasher.update(&chunk);
+            received += chunk.len() as u64;
 
             if let Some(ref pb) = pb {
                 pb.inc(chunk.len() as u64);
             }
+            if let Some(callback) = on_progress {
+                callback(received, total_size);
+            }
         }
 
         if let Some(pb) = pb {
@@ -83,10 +322,10 @@ impl DownloadManager {
         }
 
         // Verify checksum
+        let computed = format!("{:x}", hasher.finalize());
         if let Some(expected) = expected_sha256 {
-            let computed = format!("{:x}", hasher.finalize());
             if computed != expected {
-                std::fs::remove_file(&cache_file)?;
+                std::fs::remove_file(&part_file)?;
                 return Err(WinetricksError::ChecksumMismatch {
                     expected: expected.to_string(),
                     got: computed,
@@ -94,7 +333,281 @@ impl DownloadManager {
             }
         }
 
-        Ok(cache_file)
+        std::fs::rename(&part_file, cache_file)?;
+        Self::save_cache_metadata(cache_file, &metadata);
+        self.add_to_store(&computed, cache_file);
+
+        Ok(cache_file.to_path_buf())
+    }
+
+    /// Send an HTTP HEAD request for a URL (following redirects) and return the file's
+    /// size and cache validators without downloading it
+    pub async fn head_request(&self, url: &str) -> Result<HeadResponse> {
+        let response = self.client.head(url).send().await?;
+        if !response.status().is_success() {
+            return Err(WinetricksError::Download(format!(
+                "HEAD request for {} failed: HTTP {}",
+                url,
+                response.status()
+            )));
+        }
+
+        let headers = response.headers();
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        // response.content_length() is the body's length, which is 0 for HEAD
+        let content_length = header(CONTENT_LENGTH).and_then(|v| v.parse().ok());
+        let final_url = response.url().as_str();
+
+        Ok(HeadResponse {
+            content_length,
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            redirect_url: (final_url != url).then(|| final_url.to_string()),
+        })
+    }
+
+    /// Download every file of the given verbs into the cache (<cache>/<verb>/<file>)
+    /// Files are fetched concurrently, up to the configured concurrency limit, and
+    /// files already in the cache are skipped. Returns one result per verb.
+    pub async fn preload_verbs(
+        &self,
+        verbs: &[&str],
+        registry: &VerbRegistry,
+        country: Option<&str>,
+    ) -> Vec<Result<()>> {
+        let mut results: Vec<Result<()>> = Vec::with_capacity(verbs.len());
+        let mut tasks = tokio::task::JoinSet::new();
+
+        for (index, verb_name) in verbs.iter().enumerate() {
+            let Some(metadata) = registry.get(verb_name) else {
+                results.push(Err(WinetricksError::VerbNotFound {
+                    verb: verb_name.to_string(),
+                    suggestions: registry.suggest(verb_name, 3),
+                }));
+                continue;
+            };
+            results.push(Ok(()));
+
+            for file in &metadata.files {
+                let Some(ref url) = file.url else {
+                    continue;
+                };
+                let relative = Path::new(verb_name).join(&file.filename);
+                if file.sha256.is_none() && self.is_cached(&relative) {
+                    continue;
+                }
+
+                std::fs::create_dir_all(self.cache_dir.join(verb_name)).ok();
+                let manager = self.clone();
+                let url = url.clone();
+                let sha256 = file.sha256.clone();
+                let country = country.map(String::from);
+                tasks.spawn(async move {
+                    let result = manager
+                        .download(
+                            &url,
+                            &relative,
+                            sha256.as_deref(),
+                            false,
+                            None,
+                            country.as_deref(),
+                        )
+                        .await;
+                    (index, result)
+                });
+            }
+        }
+
+        // Report the first failure of each verb
+        while let Some(joined) = tasks.join_next().await {
+            let (index, result) = match joined {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    warn!("Download task failed: {}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = result {
+                if results[index].is_ok() {
+                    results[index] = Err(e);
+                }
+            }
+        }
+
+        results
+    }
+
+    /// Path of a file in the content-addressed store (<cache>/.cas/<sha256[0:2]>/<sha256>)
+    fn store_path(&self, sha256: &str) -> PathBuf {
+        let sha256 = sha256.to_lowercase();
+        self.cache_dir
+            .join(".cas")
+            .join(sha256.get(..2).unwrap_or("00"))
+            .join(&sha256)
+    }
+
+    /// Hard link a stored file into its verb-specific cache path
+    /// Returns false if the store doesn't have it (or linking/copying failed)
+    fn link_from_store(&self, sha256: &str, cache_file: &Path) -> bool {
+        let stored = self.store_path(sha256);
+        if !stored.is_file() {
+            return false;
+        }
+        if let Some(parent) = cache_file.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        // Fall back to a copy where hard links aren't supported
+        std::fs::hard_link(&stored, cache_file).is_ok()
+            || std::fs::copy(&stored, cache_file).is_ok()
+    }
+
+    /// Add a downloaded file to the content-addressed store, or replace it with a link
+    /// to the stored copy if identical content was downloaded before
+    /// Failures only cost disk space, so they are ignored
+    fn add_to_store(&self, sha256: &str, cache_file: &Path) {
+        let stored = self.store_path(sha256);
+        if stored.is_file() {
+            let tmp = Self::sidecar_path(cache_file, "link");
+            if std::fs::hard_link(&stored, &tmp).is_ok() {
+                let _ = std::fs::rename(&tmp, cache_file);
+            }
+        } else if let Some(parent) = stored.parent() {
+            let _ = std::fs::create_dir_all(parent);
+            let _ = std::fs::hard_link(cache_file, &stored);
+        }
+    }
+
+    /// Total size of the download cache in bytes
+    /// Hard-linked copies of a stored file are only counted once
+    pub fn cache_size(&self) -> Result<u64> {
+        use std::collections::HashSet;
+        use std::os::unix::fs::MetadataExt;
+
+        let mut seen = HashSet::new();
+        let mut total = 0;
+        for entry in walkdir::WalkDir::new(&self.cache_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let metadata = entry.metadata().map_err(std::io::Error::from)?;
+            if seen.insert((metadata.dev(), metadata.ino())) {
+                total += metadata.len();
+            }
+        }
+        Ok(total)
+    }
+
+    /// Remove content store entries no cached file links to any more
+    /// Returns the number of bytes freed
+    pub fn prune_store(&self) -> Result<u64> {
+        use std::os::unix::fs::MetadataExt;
+
+        let mut freed = 0;
+        for entry in walkdir::WalkDir::new(self.cache_dir.join(".cas"))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let metadata = entry.metadata().map_err(std::io::Error::from)?;
+            if metadata.nlink() == 1 {
+                std::fs::remove_file(entry.path())?;
+                freed += metadata.len();
+            }
+        }
+        Ok(freed)
+    }
+
+    /// Files cached for a verb (cache_dir/<verb>/) with their SHA256, sorted by name
+    /// This reads every file; use get_cached_file_metadata() when sizes are enough
+    pub fn get_cached_files(&self, verb_name: &str) -> Result<Vec<CachedFile>> {
+        self.get_cached_file_metadata(verb_name)?
+            .into_iter()
+            .map(|file| {
+                let mut hasher = Sha256::new();
+                std::io::copy(&mut std::fs::File::open(&file.path)?, &mut hasher)?;
+                Ok(CachedFile {
+                    filename: file.filename,
+                    path: file.path,
+                    size: file.size,
+                    sha256: format!("{:x}", hasher.finalize()),
+                    modified: file.modified,
+                })
+            })
+            .collect()
+    }
+
+    /// Files cached for a verb (cache_dir/<verb>/), sorted by name, from their
+    /// metadata only (no hashing)
+    /// Download sidecars (.part, .link, .metadata.json) are skipped
+    pub fn get_cached_file_metadata(&self, verb_name: &str) -> Result<Vec<CachedFileMetadata>> {
+        let verb_dir = self.cache_dir.join(verb_name);
+        if !verb_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&verb_dir)? {
+            let entry = entry?;
+            let filename = entry.file_name().to_string_lossy().to_string();
+            if filename.ends_with(".part")
+                || filename.ends_with(".link")
+                || filename.ends_with(".metadata.json")
+            {
+                continue;
+            }
+
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+
+            files.push(CachedFileMetadata {
+                filename,
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+
+        files.sort_by(|a, b| a.filename.cmp(&b.filename));
+        Ok(files)
+    }
+
+    /// Path of a sidecar file next to a cached file (e.g. foo.exe -> foo.exe.metadata.json)
+    fn sidecar_path(cache_file: &Path, suffix: &str) -> PathBuf {
+        let mut name = cache_file.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(suffix);
+        cache_file.with_file_name(name)
+    }
+
+    /// Load the HTTP validators stored for a cached file
+    fn load_cache_metadata(cache_file: &Path) -> Option<CacheMetadata> {
+        let content =
+            std::fs::read_to_string(Self::sidecar_path(cache_file, "metadata.json")).ok()?;
+        let metadata: CacheMetadata = serde_json::from_str(&content).ok()?;
+        if metadata.etag.is_none() && metadata.last_modified.is_none() {
+            return None;
+        }
+        Some(metadata)
+    }
+
+    /// Store the HTTP validators for a cached file (failures only lose revalidation)
+    fn save_cache_metadata(cache_file: &Path, metadata: &CacheMetadata) {
+        let sidecar = Self::sidecar_path(cache_file, "metadata.json");
+        if metadata.etag.is_none() && metadata.last_modified.is_none() {
+            let _ = std::fs::remove_file(&sidecar);
+            return;
+        }
+        if let Ok(json) = serde_json::to_string_pretty(metadata) {
+            let _ = std::fs::write(&sidecar, json);
+        }
     }
 
     /// Verify SHA256 checksum
@@ -117,3 +630,34 @@ impl DownloadManager {
         self.cache_dir.join(filename)
     }
 }
+
+/// Whether a request error is worth retrying: connection problems and timeouts
+/// (HTTP error statuses are reported as WinetricksError::Download, never retried)
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_body()
+}
+
+/// Microsoft download URL with the country query (?mscomtp=1&country=CC) that
+/// selects the regional CDN, like the original winetricks' --country
+/// Other URLs, and all URLs without a country, are returned unchanged
+fn regional_url(url: &str, country: Option<&str>) -> String {
+    let Some(country) = country.filter(|c| !c.is_empty()) else {
+        return url.to_string();
+    };
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+    let is_microsoft = parsed
+        .host_str()
+        .map(|host| host == "microsoft.com" || host.ends_with(".microsoft.com"))
+        .unwrap_or(false);
+    if !is_microsoft {
+        return url.to_string();
+    }
+
+    parsed
+        .query_pairs_mut()
+        .append_pair("mscomtp", "1")
+        .append_pair("country", country);
+    parsed.to_string()
+}
@@ -26,20 +26,70 @@ pub enum WinetricksError {
     #[error("Checksum mismatch: expected {expected}, got {got}")]
     ChecksumMismatch { expected: String, got: String },
 
-    #[error("Verb not found: {0}")]
-    VerbNotFound(String),
+    #[error("Verb not found: {verb}")]
+    VerbNotFound {
+        verb: String,
+        /// Closest known verb names (may be empty)
+        suggestions: Vec<String>,
+    },
 
     #[error("Verb already installed: {0}")]
     VerbAlreadyInstalled(String),
 
-    #[error("Verb conflict: {verb} conflicts with {conflicting}")]
-    VerbConflict { verb: String, conflicting: String },
+    #[error("Verb conflict: {verb} conflicts with {conflicting}{}", resolution_hint(.resolution))]
+    VerbConflict {
+        verb: String,
+        conflicting: String,
+        /// Command line that resolves the conflict (e.g. "winetricks uninstall a && winetricks b")
+        resolution: Option<String>,
+    },
+
+    #[error("Insufficient disk space: {required} bytes required, {available} bytes available")]
+    InsufficientDiskSpace { required: u64, available: u64 },
+
+    #[error("Package ({verb}) is known to be broken in wine-{wine_version}. {workaround_hint}")]
+    KnownBroken {
+        verb: String,
+        wine_version: String,
+        workaround_hint: String,
+    },
+
+    #[error("Installation of {verb} failed and was rolled back: {cause}")]
+    RolledBack {
+        verb: String,
+        #[source]
+        cause: Box<WinetricksError>,
+    },
+
+    #[error("WINEPREFIX {prefix_path:?} is {prefix_arch}, but {requested_arch} was requested. Omit arch= to use the existing prefix, or choose a new prefix (e.g. prefix=NAME) for {requested_arch}")]
+    PrefixArchMismatch {
+        prefix_arch: String,
+        requested_arch: String,
+        prefix_path: std::path::PathBuf,
+    },
+
+    #[error("wineserver did not finish within {timeout_secs} seconds")]
+    WineserverTimeout { timeout_secs: u64 },
+
+    #[error("Installation of {verb} did not finish within {timeout_secs} seconds and was killed")]
+    InstallationTimeout { verb: String, timeout_secs: u64 },
+
+    #[error("Required tools not found: {}\nInstall with: {}", .tools.join(", "), crate::tools::install_hint(.tools))]
+    MissingTools { tools: Vec<String> },
 
     #[error("Invalid wine version: {0}")]
     InvalidWineVersion(String),
 
-    #[error("Command execution failed: {command} - {error}")]
-    CommandExecution { command: String, error: String },
+    #[error("Command execution failed: {command}{}", command_failure_detail(.exit_code, .stderr, .io_error))]
+    CommandExecution {
+        command: String,
+        /// Exit code (None if the command could not be started or was killed by a signal)
+        exit_code: Option<i32>,
+        /// Captured stderr (trimmed, may be empty)
+        stderr: String,
+        /// Error starting or waiting for the command
+        io_error: Option<String>,
+    },
 
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
@@ -49,4 +99,116 @@ pub enum WinetricksError {
 
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
+
+    /// What was being done when an error occurred (see ResultExt::context)
+    #[error("{message} \u{2192} {cause}")]
+    Context {
+        message: String,
+        #[source]
+        cause: Box<WinetricksError>,
+    },
+}
+
+/// Add context to errors, like anyhow::Context
+/// e.g. `.context("While verifying installation")?` turns "cabextract failed" into
+/// "While verifying installation → cabextract failed"
+pub trait ResultExt<T> {
+    /// Wrap the error in a WinetricksError::Context with the given message
+    fn context(self, message: impl std::fmt::Display) -> Result<T>;
+
+    /// Like context(), but only builds the message if there is an error
+    fn with_context<M: std::fmt::Display>(self, message: impl FnOnce() -> M) -> Result<T>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn context(self, message: impl std::fmt::Display) -> Result<T> {
+        self.map_err(|cause| WinetricksError::Context {
+            message: message.to_string(),
+            cause: Box::new(cause),
+        })
+    }
+
+    fn with_context<M: std::fmt::Display>(self, message: impl FnOnce() -> M) -> Result<T> {
+        self.map_err(|cause| WinetricksError::Context {
+            message: message().to_string(),
+            cause: Box::new(cause),
+        })
+    }
+}
+
+impl WinetricksError {
+    /// The innermost error, skipping any Context wrappers
+    pub fn root_cause(&self) -> &WinetricksError {
+        match self {
+            WinetricksError::Context { cause, .. } => cause.root_cause(),
+            other => other,
+        }
+    }
+
+    /// A command that could not be started (or waited for)
+    pub fn command_io(command: impl Into<String>, error: impl ToString) -> Self {
+        WinetricksError::CommandExecution {
+            command: command.into(),
+            exit_code: None,
+            stderr: String::new(),
+            io_error: Some(error.to_string()),
+        }
+    }
+
+    /// A command that ran but exited unsuccessfully
+    /// stdout isn't part of the message but is logged at debug level
+    pub fn command_failed(command: impl Into<String>, output: &std::process::Output) -> Self {
+        let command = command.into();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !stdout.trim().is_empty() {
+            tracing::debug!("{} stdout:\n{}", command, stdout.trim_end());
+        }
+        WinetricksError::CommandExecution {
+            command,
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            io_error: None,
+        }
+    }
+
+    /// A wine command run through Wine::run_and_capture() that exited unsuccessfully
+    pub fn wine_command_failed(
+        command: impl Into<String>,
+        output: &crate::wine::CommandOutput,
+    ) -> Self {
+        WinetricksError::CommandExecution {
+            command: command.into(),
+            exit_code: output.code(),
+            stderr: output.stderr.trim().to_string(),
+            io_error: None,
+        }
+    }
+}
+
+/// " (exit code 1): <stderr>" suffix for CommandExecution messages
+fn command_failure_detail(
+    exit_code: &Option<i32>,
+    stderr: &str,
+    io_error: &Option<String>,
+) -> String {
+    let mut detail = match exit_code {
+        Some(code) => format!(" (exit code {})", code),
+        None if io_error.is_none() => " (killed by signal)".to_string(),
+        None => String::new(),
+    };
+    if let Some(error) = io_error {
+        detail.push_str(&format!(" - {}", error));
+    }
+    if !stderr.is_empty() {
+        detail.push_str(&format!(": {}", stderr));
+    }
+    detail
+}
+
+/// "\nTo resolve: <command>" suffix for VerbConflict messages
+fn resolution_hint(resolution: &Option<String>) -> String {
+    match resolution {
+        Some(resolution) => format!("\nTo resolve: {}", resolution),
+        None => String::new(),
+    }
 }
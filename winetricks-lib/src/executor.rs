@@ -2,19 +2,163 @@
 
 use crate::config::Config;
 use crate::download::DownloadManager;
-use crate::error::{Result, WinetricksError};
+use crate::error::{Result, ResultExt, WinetricksError};
+use crate::install_log::{self, LogEntry};
 use crate::installer::{
-    detect_from_file, detect_installer_type, get_msi_silent_switch, get_silent_switches,
-    InstallerType,
+    detect_archive_type, detect_from_file, detect_installer_type, get_msi_silent_switch,
+    get_silent_switches, ArchiveType, InstallerType,
 };
-use crate::verb::{VerbCategory, VerbMetadata, VerbRegistry};
-use crate::wine::Wine;
+use crate::verb::{
+    ArchSupport, MediaType, VerbCategory, VerbFile, VerbMetadata, VerbRegistry, WineVersionRange,
+};
+use crate::wine::{CommandOutput, Wine};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 use tracing::{info, warn};
 
+/// Prefix state captured before an installation (for rollback on failure)
+struct PrefixSnapshot {
+    /// Verb being installed
+    verb_name: String,
+    /// winetricks.log content (None if the log didn't exist)
+    log_content: Option<String>,
+    /// Registry files and their content (None if the file didn't exist)
+    registry_files: Vec<(PathBuf, Option<Vec<u8>>)>,
+    /// Entries present in cache_dir/<verb> before installation
+    cached_entries: Vec<PathBuf>,
+}
+
+/// Progress updates reported by Executor::install_verb_with_progress()
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallProgress {
+    /// Pre-flight checks passed, installation is starting
+    Started,
+    /// Downloading a file (index is 1-based, total is 0 if the size is unknown)
+    Downloading {
+        filename: String,
+        index: usize,
+        count: usize,
+        received: u64,
+        total: u64,
+    },
+    /// Running the installer
+    Installing,
+    /// A line the installer wrote to stderr
+    Log(String),
+}
+
+impl InstallProgress {
+    /// Approximate overall completion (0.0 - 1.0) for progress bars
+    pub fn fraction(&self) -> f32 {
+        match self {
+            InstallProgress::Started => 0.05,
+            // Downloads account for the first half of the installation
+            InstallProgress::Downloading {
+                index,
+                count,
+                received,
+                total,
+                ..
+            } => {
+                let file_fraction = if *total > 0 {
+                    (*received as f32 / *total as f32).min(1.0)
+                } else {
+                    0.0
+                };
+                0.05 + 0.45 * (*index as f32 - 1.0 + file_fraction) / (*count).max(1) as f32
+            }
+            // Installer output only arrives while the installer runs
+            InstallProgress::Installing | InstallProgress::Log(_) => 0.5,
+        }
+    }
+}
+
+/// A file placed in the wineprefix by a verb
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledFile {
+    /// Unix path inside the wineprefix
+    pub path: PathBuf,
+    /// File size in bytes
+    pub size: u64,
+    /// SHA256 checksum (None if the file couldn't be read)
+    pub sha256: Option<String>,
+}
+
+/// Verb cache directories other verbs download into as well
+/// Executor::cleanup_all_caches() never treats them as unused
+const SHARED_CACHE_DIRS: &[&str] = &["directx9", "vcrun6"];
+
+/// How long a looked up GitHub release download URL is reused (github_releases.json)
+const GITHUB_RELEASE_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// A GitHub release download URL remembered in github_releases.json
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct CachedRelease {
+    url: String,
+    /// Unix time of the API lookup
+    fetched_at: u64,
+}
+
+/// Whether a verb could be installed right now (see Executor::dry_run)
+#[derive(Debug, Clone)]
+pub struct DryRunReport {
+    pub verb: String,
+    /// Already listed in winetricks.log (installing would skip it)
+    pub installed: bool,
+    /// Bytes still to be downloaded (files without a known size are not counted)
+    pub download_bytes: u64,
+    /// Reasons the installation would fail before it starts
+    pub problems: Vec<String>,
+}
+
+impl DryRunReport {
+    pub fn is_ready(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// How serious a problem found by Executor::check_prefix_health() is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthSeverity {
+    /// The prefix works, but something is off
+    Warn,
+    /// The prefix is broken; installations will likely fail
+    Error,
+}
+
+impl HealthSeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HealthSeverity::Warn => "warning",
+            HealthSeverity::Error => "error",
+        }
+    }
+}
+
+/// A problem with a wineprefix (see Executor::check_prefix_health)
+#[derive(Debug, Clone)]
+pub struct HealthIssue {
+    pub severity: HealthSeverity,
+    pub message: String,
+    /// What the user can do about it
+    pub fix_hint: String,
+}
+
+impl HealthIssue {
+    fn new(severity: HealthSeverity, message: String, fix_hint: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message,
+            fix_hint: fix_hint.into(),
+        }
+    }
+}
+
+/// Callback receiving installation progress updates
+type ProgressCallback = Box<dyn Fn(InstallProgress) + Send + Sync>;
+
 /// Verb executor
 pub struct Executor {
     config: Config,
@@ -23,20 +167,38 @@ pub struct Executor {
     registry: VerbRegistry,
     /// Stored Windows version (for restore after installation)
     stored_windows_version: Option<String>,
+    /// Progress callback for the installation in progress (if any)
+    progress: Option<ProgressCallback>,
+    /// Verbs whose w_call'ed verbs are being installed (outermost first), so a cycle
+    /// in the metadata is skipped instead of recursing forever
+    calling: Vec<String>,
 }
 
 impl Executor {
     /// Create a new executor
     pub async fn new(config: Config) -> Result<Self> {
-        let wine = Wine::detect()?;
-        let downloader = DownloadManager::new(config.cache_dir.clone())?;
+        let mut wine = if config.skip_wine_check {
+            Wine::placeholder()
+        } else {
+            Wine::detect()?
+        };
+        wine.verbosity = config.verbosity;
+        let downloader = DownloadManager::with_concurrency(
+            config.cache_dir.clone(),
+            config.download_concurrency,
+        )?
+        .with_retries(config.download_retries)
+        .with_torify(config.torify)?;
 
         // Initialize cache from source JSON files if needed (or download from GitHub)
-        config.ensure_cache_initialized().await?;
+        // Without a Wine check (tests), use whatever metadata is already there, offline
+        if !config.skip_wine_check {
+            config.ensure_cache_initialized().await?;
+        }
 
         // Load verb registry from cached metadata directory
         let registry = if config.metadata_dir().exists() {
-            VerbRegistry::load_from_dir(config.metadata_dir())?
+            VerbRegistry::load_with_overlay(config.metadata_dir(), config.imported_verbs_dir())?
         } else {
             VerbRegistry::new()
         };
@@ -47,6 +209,8 @@ impl Executor {
             downloader,
             registry,
             stored_windows_version: None,
+            progress: None,
+            calling: Vec::new(),
         })
     }
 
@@ -62,14 +226,18 @@ impl Executor {
         let metadata = self
             .registry
             .get(verb_name)
-            .ok_or_else(|| WinetricksError::VerbNotFound(verb_name.to_string()))?
+            .ok_or_else(|| WinetricksError::VerbNotFound {
+                verb: verb_name.to_string(),
+                suggestions: Vec::new(),
+            })?
             .clone();
 
         // Download files if needed
         let cache_dir = self.config.cache_dir.join(verb_name);
         std::fs::create_dir_all(&cache_dir)?;
 
-        for file in &metadata.files {
+        let win64 = self.prefix_is_win64()?;
+        for file in metadata.files.iter().filter(|f| f.applies_to(win64)) {
             if let Some(ref url) = file.url {
                 info!("Downloading {} from {}", file.filename, url);
                 let _downloaded = self
@@ -79,8 +247,11 @@ impl Executor {
                         &cache_dir.join(&file.filename),
                         file.sha256.as_deref(),
                         true,
+                        None,
+                        self.config.country.as_deref(),
                     )
-                    .await?;
+                    .await
+                    .with_context(|| format!("While downloading {}", file.filename))?;
             }
         }
 
@@ -90,7 +261,8 @@ impl Executor {
 
         // Execute verb installation
         self.execute_verb_installation(&metadata, &cache_dir, is_vcrun_internal)
-            .await?;
+            .await
+            .with_context(|| format!("While installing {}", verb_name))?;
 
         // Log installation
         self.log_installation(verb_name)?;
@@ -98,10 +270,103 @@ impl Executor {
         Ok(())
     }
 
+    /// Install a verb, reporting progress updates through a callback
+    /// (used by the GUI to drive progress bars)
+    pub async fn install_verb_with_progress<F>(
+        &mut self,
+        verb_name: &str,
+        progress: F,
+    ) -> Result<()>
+    where
+        F: Fn(InstallProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Box::new(progress));
+        let result = self.install_verb(verb_name).await;
+        self.progress = None;
+        result
+    }
+
+    /// Install a verb into another wineprefix, leaving this executor's config untouched
+    /// (e.g. installing the same verb into many prefixes with one Executor)
+    pub async fn install_verb_to_prefix(&mut self, verb_name: &str, prefix: &Path) -> Result<()> {
+        let mut prefix_config = self.config.clone();
+        prefix_config.wineprefix = Some(prefix.to_path_buf());
+
+        let original = std::mem::replace(&mut self.config, prefix_config);
+        let result = self.install_verb(verb_name).await;
+        self.config = original;
+        result
+    }
+
+    /// Install several verbs one after another, stopping at the first failure
+    pub async fn install_many(&mut self, verb_names: &[String]) -> Result<()> {
+        for verb_name in verb_names {
+            self.install_verb(verb_name).await?;
+        }
+        Ok(())
+    }
+
+    /// Install several verbs one after another, reporting which verb each
+    /// progress update belongs to (used by the GUI installation queue)
+    pub async fn install_many_with_progress<F>(
+        &mut self,
+        verb_names: &[String],
+        progress: F,
+    ) -> Result<()>
+    where
+        F: Fn(&str, InstallProgress) + Send + Sync + 'static,
+    {
+        let progress = std::sync::Arc::new(progress);
+        for verb_name in verb_names {
+            let progress = progress.clone();
+            let name = verb_name.clone();
+            self.install_verb_with_progress(verb_name, move |p| progress(&name, p))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Run an installer, killing it (and everything it started) once it has run
+    /// longer than the configured wine_timeout
+    fn run_installer(
+        &self,
+        cmd: std::process::Command,
+        command: &str,
+        verb_name: &str,
+    ) -> Result<CommandOutput> {
+        let timeout = self.config.wine_timeout;
+        self.wine
+            .capture_command_with_log(cmd, command, timeout, &|line| {
+                self.report_progress(InstallProgress::Log(line.to_string()))
+            })?
+            .ok_or_else(|| WinetricksError::InstallationTimeout {
+                verb: verb_name.to_string(),
+                timeout_secs: timeout.unwrap_or_default().as_secs(),
+            })
+    }
+
+    /// Report installation progress to the registered callback (if any)
+    fn report_progress(&self, progress: InstallProgress) {
+        if let Some(ref callback) = self.progress {
+            callback(progress);
+        }
+    }
+
+    /// Fail early (with install instructions) when a tool installations need is missing,
+    /// rather than halfway through one; listing and checking commands don't need them
+    /// (wine is one of the required tools, so there's nothing to check without it)
+    fn check_required_tools(&self) -> Result<()> {
+        if !self.config.skip_wine_check {
+            crate::tools::check_required_tools()?;
+        }
+        Ok(())
+    }
+
     /// Install a verb using Rust implementation
     pub async fn install_verb(&mut self, verb_name: &str) -> Result<()> {
         let start_time = Instant::now();
         info!("Installing verb: {}", verb_name);
+        self.check_required_tools()?;
 
         // Debug: Log force and unattended flags
         if self.config.force {
@@ -117,27 +382,89 @@ impl Executor {
             if self.config.unattended { "1" } else { "0" },
         );
 
-        // Set WINE_D3D_CONFIG if configured
-        // Wine uses WINE_D3D_CONFIG="renderer=<value>" format
+        // Create the wineprefix up front with the configured architecture, rather than
+        // letting the first wine command create it implicitly (and with Wine's default arch)
+        let wineprefix = self.config.wineprefix();
+        if let Some(ref arch) = self.config.winearch {
+            if !Wine::prefix_exists(&wineprefix) {
+                info!(
+                    "Creating WINEPREFIX \"{}\" with WINEARCH={}",
+                    wineprefix.display(),
+                    arch
+                );
+                self.wine.create_prefix(&wineprefix, arch).await?;
+            } else {
+                // Wine can't convert an existing prefix to another architecture,
+                // and the error it gives when asked to is not very helpful
+                let prefix_arch = Wine::prefix_arch(&wineprefix)?;
+                if prefix_arch != *arch {
+                    return Err(WinetricksError::PrefixArchMismatch {
+                        prefix_arch,
+                        requested_arch: arch.clone(),
+                        prefix_path: wineprefix,
+                    });
+                }
+            }
+        }
+
+        // Apply configured renderer, preferring the prefix registry over WINE_D3D_CONFIG
+        // so the setting survives wineserver restarts
         if let Some(ref renderer) = self.config.renderer {
-            let wine_renderer = match renderer.to_lowercase().as_str() {
-                "opengl" | "gl" | "w" => "gl",
-                "vulkan" | "vk" | "v" => "vulkan",
-                "gdi" => "gdi",
-                "no3d" => "no3d",
-                _ => renderer.as_str(),
-            };
-            std::env::set_var("WINE_D3D_CONFIG", &format!("renderer={}", wine_renderer));
+            let registry_renderer = self.config.get_renderer_from_registry().ok().flatten();
+            let in_registry = registry_renderer.as_deref() == Some(renderer.as_str())
+                || self.config.set_renderer_in_registry(Some(renderer)).is_ok();
+
+            if in_registry {
+                // Registry value is authoritative - don't let a stale env var override it
+                std::env::remove_var("WINE_D3D_CONFIG");
+            } else {
+                // Fall back to the environment variable
+                // Wine uses WINE_D3D_CONFIG="renderer=<value>" format
+                let wine_renderer = match renderer.to_lowercase().as_str() {
+                    "opengl" | "gl" | "w" => "gl",
+                    "vulkan" | "vk" | "v" => "vulkan",
+                    "gdi" => "gdi",
+                    "no3d" => "no3d",
+                    _ => renderer.as_str(),
+                };
+                warn!("Failed to set renderer in registry, using WINE_D3D_CONFIG instead");
+                std::env::set_var("WINE_D3D_CONFIG", format!("renderer={}", wine_renderer));
+            }
         }
 
         // Get verb metadata - must be in registry, no fallback to original winetricks
         let metadata = match self.registry.get(verb_name) {
             Some(m) => m.clone(),
             None => {
-                return Err(WinetricksError::VerbNotFound(verb_name.to_string()));
+                // Suggest close matches for typos like "dotnet4.8" or "vcredist2019"
+                return Err(WinetricksError::VerbNotFound {
+                    verb: verb_name.to_string(),
+                    suggestions: self.registry.suggest(verb_name, 3),
+                });
             }
         };
 
+        // Verbs that only work in one architecture (16-bit tools, old x86-only DLLs)
+        match metadata.architecture {
+            ArchSupport::X86Only if !self.config.force && self.prefix_is_win64()? => {
+                return Err(WinetricksError::Verb(format!(
+                    "{} only works in 32-bit prefixes; use arch=32 with a new prefix (or --force to install anyway)",
+                    verb_name
+                )));
+            }
+            ArchSupport::X64Only if !self.prefix_is_win64()? => {
+                return Err(WinetricksError::Verb(format!(
+                    "{} only works in 64-bit prefixes; use arch=64 with a new prefix",
+                    verb_name
+                )));
+            }
+            _ => {}
+        }
+
+        // Log entry of an earlier installation being redone; removed only once the
+        // prefix is snapshotted, so a failed reinstall rolls back to the old entry
+        let mut replaces_log_entry = false;
+
         // If force is enabled, clean up FIRST (before checking if installed)
         // This ensures cleanup runs even if the check happens before installation
         if self.config.force {
@@ -154,7 +481,7 @@ impl Executor {
                     "Force reinstall requested for {} (found in log, removing)",
                     verb_name
                 );
-                self.remove_from_log(verb_name)?;
+                replaces_log_entry = true;
             } else {
                 info!(
                     "Force reinstall requested for {} (not in log, but cleanup done)",
@@ -164,9 +491,18 @@ impl Executor {
         } else {
             // Only check if installed when NOT forcing
             if self.is_installed(verb_name)? {
-                println!("{} already installed, skipping", verb_name);
-                println!("Use --force to reinstall");
-                return Ok(());
+                if let Some(age) = self.stale_installation_age(verb_name)? {
+                    info!(
+                        "{} was installed {} days ago, reinstalling (--reinstall-if-older-than)",
+                        verb_name,
+                        age.as_secs() / 86400
+                    );
+                    replaces_log_entry = true;
+                } else {
+                    println!("{} already installed, skipping", verb_name);
+                    println!("Use --force to reinstall");
+                    return Ok(());
+                }
             }
         }
 
@@ -177,21 +513,128 @@ impl Executor {
         if !self.config.force && !metadata.conflicts.is_empty() {
             for conflict in &metadata.conflicts {
                 if self.is_installed(conflict)? {
+                    // Uninstall the conflicting verb first, then retry this one
                     return Err(WinetricksError::VerbConflict {
                         verb: verb_name.to_string(),
                         conflicting: conflict.clone(),
+                        resolution: Some(format!(
+                            "winetricks uninstall {} && winetricks {}",
+                            conflict, verb_name
+                        )),
                     });
                 }
             }
         }
 
+        // Make sure there is enough room for downloading and extracting
+        self.check_disk_space(verb_name).await?;
+
+        // Snapshot prefix state so a failed multi-step installation can be rolled back
+        let snapshot = self.snapshot_prefix_state(verb_name)?;
+        if replaces_log_entry {
+            self.remove_from_log(verb_name)?;
+        }
+        self.report_progress(InstallProgress::Started);
+
+        let result = match self
+            .install_verb_steps(verb_name, &metadata, start_time)
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                warn!("Installation of {} failed, rolling back: {}", verb_name, e);
+                if let Err(restore_err) = self.restore_prefix_state(&snapshot) {
+                    warn!(
+                        "Warning: Failed to roll back {}: {}",
+                        verb_name, restore_err
+                    );
+                }
+                Err(WinetricksError::RolledBack {
+                    verb: verb_name.to_string(),
+                    cause: Box::new(e),
+                })
+            }
+        };
+
+        // Long installations (dotnet48...) are easy to lose track of in a terminal
+        let error = result.as_ref().err().map(|e| e.to_string());
+        crate::notify::notify_desktop(
+            verb_name,
+            result.is_ok(),
+            start_time.elapsed(),
+            error.as_deref(),
+        )
+        .await;
+
+        result
+    }
+
+    /// Whether the wineprefix is (or will be created as) 64-bit
+    fn prefix_is_win64(&self) -> Result<bool> {
+        if let Some(ref arch) = self.config.winearch {
+            return Ok(arch == "win64");
+        }
+        let wineprefix = self.config.wineprefix();
+        if Wine::prefix_exists(&wineprefix) {
+            return Ok(Wine::prefix_arch(&wineprefix)? == "win64");
+        }
+        // Wine creates 64-bit prefixes by default
+        Ok(true)
+    }
+
+    /// Run the installation steps for a verb (downloads, prerequisites, installer, post steps)
+    /// Called by install_verb() inside a transaction that is rolled back on failure
+    async fn install_verb_steps(
+        &mut self,
+        verb_name: &str,
+        metadata: &VerbMetadata,
+        start_time: Instant,
+    ) -> Result<()> {
         // Download files if needed
         let cache_dir = self.config.cache_dir.join(verb_name);
         std::fs::create_dir_all(&cache_dir)?;
 
-        for file in &metadata.files {
+        // DVD/CD-ROM verbs: files come from the mounted disc (w_mount)
+        if metadata.media.is_disc() {
+            let disc = self.locate_install_disc(metadata)?;
+            for file in metadata.files.iter().filter(|f| f.url.is_none()) {
+                let on_disc = disc.join(&file.filename);
+                let cached = cache_dir.join(&file.filename);
+                if on_disc.exists() && !cached.exists() {
+                    info!("Copying {} from disc", file.filename);
+                    std::fs::copy(&on_disc, &cached)?;
+                }
+            }
+        }
+
+        // Files for the other architecture (VerbFile::arch) aren't needed
+        let win64 = self.prefix_is_win64()?;
+        let downloads: Vec<&VerbFile> = metadata
+            .files
+            .iter()
+            .filter(|f| f.url.is_some() && f.applies_to(win64))
+            .collect();
+        let download_count = downloads.len();
+        for (index, file) in downloads.into_iter().enumerate() {
             if let Some(ref url) = file.url {
+                self.report_progress(InstallProgress::Downloading {
+                    filename: file.filename.clone(),
+                    index: index + 1,
+                    count: download_count,
+                    received: 0,
+                    total: 0,
+                });
                 info!("Downloading {} from {}", file.filename, url);
+                // Forward byte counts to the install_verb_with_progress callback
+                let on_progress = |received: u64, total: u64| {
+                    self.report_progress(InstallProgress::Downloading {
+                        filename: file.filename.clone(),
+                        index: index + 1,
+                        count: download_count,
+                        received,
+                        total,
+                    })
+                };
                 let _downloaded = self
                     .downloader
                     .download(
@@ -199,10 +642,14 @@ impl Executor {
                         &cache_dir.join(&file.filename),
                         file.sha256.as_deref(),
                         true,
+                        self.progress.as_ref().map(|_| &on_progress as _),
+                        self.config.country.as_deref(),
                     )
-                    .await?;
+                    .await
+                    .with_context(|| format!("While downloading {}", file.filename))?;
             }
         }
+        self.report_progress(InstallProgress::Installing);
 
         // Handle .NET specific prerequisites (from original winetricks)
         if verb_name == "dotnet45" {
@@ -289,12 +736,11 @@ impl Executor {
 
             // 5. Wait for wineserver BEFORE installation (critical for dotnet35)
             info!("Waiting for wineserver before .NET 3.5 installation...");
-            let wineprefix_str = self.config.wineprefix().to_string_lossy().to_string();
-            let wineserver_status = std::process::Command::new(&self.wine.wineserver_bin)
-                .arg("-w")
-                .env("WINEPREFIX", &wineprefix_str)
-                .status();
-            if let Err(e) = wineserver_status {
+            if let Err(e) = self
+                .wine
+                .wineserver_wait_timeout(&self.config.wineprefix(), self.config.wineserver_timeout)
+                .await
+            {
                 warn!("Warning: Failed to wait for wineserver: {}", e);
             }
         }
@@ -310,9 +756,8 @@ impl Executor {
             info!("Preparing for vcrun2005 installation (will remove wine builtin manifests)...");
         }
 
-        // Apply wine bug workarounds if needed (w_workaround_wine_bug)
-        // This can be called by individual verbs to conditionally apply fixes
-        // For now, we have the infrastructure but individual verbs would need to call it
+        // Warn about Wine bugs this verb works around (w_workaround_wine_bug)
+        self.warn_wine_bug_workarounds(verb_name, metadata);
 
         // Check if this is a DirectX d3dx9 verb (needs special handling)
         let is_d3dx9_verb = verb_name.starts_with("d3dx9") || verb_name == "d3dx9";
@@ -323,20 +768,16 @@ impl Executor {
             self.ensure_directx_redistributable().await?;
         }
 
-        // Handle corefonts (meta-verb that installs multiple individual font verbs)
-        if verb_name == "corefonts" {
-            info!("Installing corefonts (MS Arial, Courier, Times fonts)...");
-            return self.install_corefonts().await;
-        }
-
-        // Handle allfonts (meta-verb that installs all available font verbs)
-        if verb_name == "allfonts" {
-            info!("Installing allfonts (all available fonts)...");
-            return self.install_allfonts().await;
+        // mspaint's download is a Windows Update package to extract, not an installer to run
+        if verb_name == "mspaint" {
+            return self.install_mspaint().await;
         }
 
         // Handle verbs with empty files arrays (need special handling)
-        if metadata.files.is_empty() {
+        // Meta-verbs (corefonts, allfonts, ...) only w_call other verbs, which
+        // execute_verb_installation() handles below
+        if metadata.files.is_empty() && metadata.calls.is_empty() && metadata.calls_after.is_empty()
+        {
             // GitHub-based DLLs that download from releases
             let github_dlls = vec![
                 (
@@ -390,22 +831,6 @@ impl Executor {
                 }
             }
 
-            // Meta-verbs that install multiple components
-            if verb_name == "allcodecs" {
-                info!("Installing allcodecs (all codec components)...");
-                return self.install_allcodecs().await;
-            }
-
-            if verb_name == "cjkfonts" {
-                info!("Installing cjkfonts (CJK font components)...");
-                return self.install_cjkfonts().await;
-            }
-
-            if verb_name == "pptfonts" {
-                info!("Installing pptfonts (PowerPoint font components)...");
-                return self.install_pptfonts().await;
-            }
-
             // Special cases
             if verb_name == "directx9" {
                 // DirectX 9 is deprecated/no-op in modern winetricks
@@ -422,11 +847,6 @@ impl Executor {
                 return Ok(());
             }
 
-            if verb_name == "mspaint" {
-                // mspaint uses Windows Update installer that needs extraction
-                return self.install_mspaint().await;
-            }
-
             // Settings verbs (Windows version, registry tweaks, etc.)
             // These don't need files, they just modify registry/config
             if metadata.category == VerbCategory::Settings {
@@ -434,7 +854,7 @@ impl Executor {
                 // Settings verbs are handled by execute_verb_installation
                 // which will detect they have no files and skip file processing
                 // But we still need to handle the actual setting change
-                return self.install_settings_verb(verb_name, &metadata).await;
+                return self.install_settings_verb(verb_name, metadata).await;
             }
 
             // If we get here, it's an unknown verb with no files
@@ -448,8 +868,9 @@ impl Executor {
         }
 
         // Execute verb installation
-        self.execute_verb_installation(&metadata, &cache_dir, is_vcrun_verb)
-            .await?;
+        self.execute_verb_installation(metadata, &cache_dir, is_vcrun_verb)
+            .await
+            .with_context(|| format!("While installing {}", verb_name))?;
 
         // Handle VC++ Redistributables post-installation steps
         if is_vcrun_verb {
@@ -497,7 +918,8 @@ impl Executor {
             // Set registry key to avoid popup on WINEPREFIX updates
             // Original winetricks: "${WINE}" reg add "HKLM\\Software\\Microsoft\\.NETFramework" /v OnlyUseLatestCLR /t REG_DWORD /d 0001 /f
             let wineprefix_str = self.config.wineprefix().to_string_lossy().to_string();
-            let reg_status = std::process::Command::new(&self.wine.wine_bin)
+            let reg_status = self
+                .wine_command(&self.wine.wine_bin)
                 .arg("reg")
                 .arg("add")
                 .arg("HKLM\\Software\\Microsoft\\.NETFramework")
@@ -526,7 +948,8 @@ impl Executor {
                 .map(|a| a == "win64")
                 .unwrap_or(false)
             {
-                let reg_status = std::process::Command::new(&self.wine.wine_bin)
+                let reg_status = self
+                    .wine_command(&self.wine.wine_bin)
                     .arg("reg")
                     .arg("add")
                     .arg("HKLM\\Software\\Wow6432Node\\.NETFramework")
@@ -576,23 +999,49 @@ impl Executor {
         }
 
         // Verify installation
-        if let Some(ref installed_file) = metadata.installed_file {
+        if let (true, Some(installed_file)) = (
+            verb_name.starts_with("dotnet"),
+            metadata.installed_file.as_ref(),
+        ) {
             info!("Verifying installation: {}", installed_file);
 
             // For .NET Framework, do comprehensive verification
-            if verb_name.starts_with("dotnet") {
-                if !self.verify_dotnet_installation(verb_name, installed_file)? {
-                    return Err(WinetricksError::Verb(format!(
-                        "Installation verification failed for {}. The installer may have failed silently.",
-                        verb_name
-                    )));
+            if !self
+                .verify_dotnet_installation(verb_name, installed_file)
+                .context("While verifying installation")?
+            {
+                return Err(WinetricksError::Verb(format!(
+                    "Installation verification failed for {}. The installer may have failed silently.",
+                    verb_name
+                )));
+            }
+        } else {
+            // For other verbs, either installed_file or installed_exe being present counts
+            let checks: Vec<&String> = metadata
+                .installed_file
+                .iter()
+                .chain(metadata.installed_exe.iter())
+                .collect();
+            if !checks.is_empty() {
+                let mut verified = false;
+                for path in &checks {
+                    info!("Verifying installation: {}", path);
+                    if self
+                        .verify_file_exists(path)
+                        .context("While verifying installation")?
+                    {
+                        verified = true;
+                        break;
+                    }
                 }
-            } else {
-                // For other verbs, check the installed_file path
-                if !self.verify_file_exists(installed_file)? {
+                if !verified {
                     warn!(
                         "Warning: Installed file not found: {}. Installation may have failed.",
-                        installed_file
+                        checks
+                            .iter()
+                            .map(|s| s.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" or ")
                     );
                     // Don't fail for non-critical verbs, but warn
                 }
@@ -606,14 +1055,11 @@ impl Executor {
             std::thread::sleep(std::time::Duration::from_secs(2));
 
             // Wait for wineserver to finish any remaining operations
-            let wineserver_status = std::process::Command::new(&self.wine.wineserver_bin)
-                .arg("-w")
-                .env(
-                    "WINEPREFIX",
-                    &self.config.wineprefix().to_string_lossy().to_string(),
-                )
-                .status();
-            if let Err(e) = wineserver_status {
+            if let Err(e) = self
+                .wine
+                .wineserver_wait_timeout(&self.config.wineprefix(), self.config.wineserver_timeout)
+                .await
+            {
                 warn!(
                     "Warning: Failed to wait for wineserver after .NET installation: {}",
                     e
@@ -648,7 +1094,7 @@ impl Executor {
 
     /// Execute verb installation logic
     async fn execute_verb_installation(
-        &self,
+        &mut self,
         metadata: &VerbMetadata,
         cache_dir: &Path,
         is_vcrun: bool,
@@ -656,9 +1102,16 @@ impl Executor {
         // This is a simplified version - real winetricks has per-verb logic
         // For now, try to detect installer type and run it
 
+        // w_call: verbs the original installs before its own installer
+        if !metadata.calls.is_empty() {
+            self.install_calls(metadata, &metadata.calls).await?;
+        }
+
+        let win64 = self.prefix_is_win64()?;
         let files: Vec<PathBuf> = metadata
             .files
             .iter()
+            .filter(|f| f.applies_to(win64))
             .map(|f| {
                 // Handle special paths like "../directx9/directx_Jun2010_redist.exe"
                 // These point to shared cache directories
@@ -729,6 +1182,24 @@ impl Executor {
                 continue;
             }
 
+            // Trust the file's magic bytes over its extension for archives
+            // (e.g. CAB files shipped with an .exe extension)
+            let ext = match detect_archive_type(&file_to_use) {
+                Some(ArchiveType::Cab) => "cab",
+                Some(ArchiveType::Zip) => "zip",
+                Some(ArchiveType::SevenZip) => "7z",
+                Some(ArchiveType::Rar) => "rar",
+                Some(ArchiveType::Pe) | None => ext,
+            };
+            if ext
+                != file_to_use
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or("")
+            {
+                info!("{:?} detected as {} by its contents", file_to_use, ext);
+            }
+
             match ext {
                 "msi" => {
                     info!("Running MSI installer: {:?}", file_to_use);
@@ -777,14 +1248,13 @@ impl Executor {
                     // Use wine64 with SYSTEM64 msiexec for 64-bit installers on win64 prefixes
                     let (wine_bin, msiexec_path) = if use_wine64 {
                         // Find wine64 binary
-                        let wine64_bin = if let Some(wine_dir) = self.wine.wine_bin.parent() {
-                            wine_dir.join("wine64")
-                        } else {
-                            which::which("wine64").map_err(|_| {
+                        let wine64_bin = match &self.wine.wine64_bin {
+                            Some(bin) => bin.clone(),
+                            None => which::which("wine64").map_err(|_| {
                                 WinetricksError::Config(
                                     "wine64 not found for 64-bit MSI installer".into(),
                                 )
-                            })?
+                            })?,
                         };
 
                         // Use SYSTEM64 msiexec.exe (64-bit)
@@ -803,7 +1273,7 @@ impl Executor {
                     };
 
                     // Use wine start /wait for MSI files (as original winetricks does)
-                    let mut cmd = std::process::Command::new(&wine_bin);
+                    let mut cmd = self.wine_command(&wine_bin);
                     cmd.env("WINEPREFIX", &wineprefix_str);
                     cmd.env(
                         "W_OPT_UNATTENDED",
@@ -826,19 +1296,21 @@ impl Executor {
                         cmd.arg(&switch);
                     }
 
-                    // Keep terminal output visible
-                    let status = cmd
-                        .status()
-                        .map_err(|e| WinetricksError::CommandExecution {
-                            command: format!("wine start /wait msiexec.exe /i {:?}", file_win_path),
-                            error: e.to_string(),
-                        })?;
+                    let mut command =
+                        format!("wine start /wait msiexec.exe /i {:?}", file_win_path);
+                    if !metadata.msi_features.is_empty() {
+                        let features = self.select_msi_features(metadata)?;
+                        let addlocal = format!("ADDLOCAL={}", features.join(","));
+                        command.push(' ');
+                        command.push_str(&addlocal);
+                        cmd.arg(addlocal);
+                    }
 
-                    if !status.success() {
-                        return Err(WinetricksError::Verb(format!(
-                            "MSI installer failed with exit code: {:?}",
-                            status.code()
-                        )));
+                    // Capture output so a failure reports msiexec's exit code and stderr
+                    let output = self.run_installer(cmd, &command, &metadata.name)?;
+
+                    if !output.success() {
+                        return Err(WinetricksError::wine_command_failed(command, &output));
                     }
                 }
                 "exe" => {
@@ -931,7 +1403,7 @@ impl Executor {
                         None
                     };
 
-                    let mut cmd = std::process::Command::new(&self.wine.wine_bin);
+                    let mut cmd = self.wine_command(&self.wine.wine_bin);
                     cmd.env("WINEPREFIX", &wineprefix_str);
                     cmd.env(
                         "W_OPT_UNATTENDED",
@@ -1043,7 +1515,7 @@ impl Executor {
                                     WinetricksError::Config("Invalid filename".into())
                                 })?;
 
-                            let mut extract_cmd = std::process::Command::new(&self.wine.wine_bin);
+                            let mut extract_cmd = self.wine_command(&self.wine.wine_bin);
                             extract_cmd.env("WINEPREFIX", &wineprefix_str);
                             extract_cmd.env("WINEDLLOVERRIDES", "fusion=b");
                             extract_cmd.current_dir(cache_dir);
@@ -1053,9 +1525,13 @@ impl Executor {
                                 .arg(&extract_dir.to_string_lossy().to_string());
 
                             info!("Extracting installer to: {:?}", extract_dir);
-                            let extract_status = extract_cmd.status()?;
+                            let extract_output = self.run_installer(
+                                extract_cmd,
+                                &format!("wine {} /x:{:?}", file_name, extract_dir),
+                                &metadata.name,
+                            )?;
 
-                            if !extract_status.success() {
+                            if !extract_output.success() {
                                 warn!("Extraction failed, falling back to original method...");
                                 if self.config.unattended {
                                     cmd.arg("/q");
@@ -1087,7 +1563,7 @@ impl Executor {
                                                 &setup_path,
                                                 &extract_dir,
                                                 wineprefix_str,
-                                                is_dotnet45,
+                                                &metadata.name,
                                             )
                                             .await;
                                     } else {
@@ -1104,7 +1580,7 @@ impl Executor {
                                             &setup_exe,
                                             &extract_dir,
                                             wineprefix_str,
-                                            is_dotnet45,
+                                            &metadata.name,
                                         )
                                         .await;
                                 }
@@ -1199,26 +1675,24 @@ impl Executor {
 
                     // Use .output() which waits for the process to complete
                     // For .NET installers, the extractor should wait for Setup.exe to finish
-                    let output = cmd
-                        .output()
-                        .map_err(|e| WinetricksError::CommandExecution {
-                            command: format!("wine {}", cmd_args.join(" ")),
-                            error: e.to_string(),
-                        })?;
+                    // Killed with its whole process tree if it outlives --timeout
+                    let output = self.run_installer(
+                        cmd,
+                        &format!("wine {}", cmd_args.join(" ")),
+                        &metadata.name,
+                    )?;
 
                     // Print captured stdout/stderr
                     if !output.stdout.is_empty() {
                         eprintln!("Installer stdout:");
-                        eprintln!("{}", String::from_utf8_lossy(&output.stdout));
+                        eprintln!("{}", output.stdout);
                     }
                     if !output.stderr.is_empty() {
                         eprintln!("Installer stderr:");
-                        eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+                        eprintln!("{}", output.stderr);
                     }
 
-                    let status = output.status;
-
-                    eprintln!("Installer finished with exit code: {:?}", status.code());
+                    eprintln!("Installer finished with exit code: {:?}", output.code());
 
                     // Restore original directory if we changed it
                     if let Some((orig_dir, _)) = cache_dir_for_cmd {
@@ -1274,11 +1748,13 @@ impl Executor {
 
                         // Wait for wineserver multiple times to ensure all operations complete
                         for i in 1..=5 {
-                            let wineserver_status =
-                                std::process::Command::new(&self.wine.wineserver_bin)
-                                    .arg("-w")
-                                    .env("WINEPREFIX", &wineprefix_str)
-                                    .status();
+                            let wineserver_status = self
+                                .wine
+                                .wineserver_wait_timeout(
+                                    &self.config.wineprefix(),
+                                    self.config.wineserver_timeout,
+                                )
+                                .await;
                             if let Err(e) = wineserver_status {
                                 if i == 1 {
                                     warn!("Warning: Failed to wait for wineserver: {}", e);
@@ -1305,7 +1781,7 @@ impl Executor {
 
                     // Check exit code - MS installers can return specific codes that indicate success
                     // Original winetricks: w_try_ms_installer handles exit codes 105, 194, 236 as non-fatal
-                    let exit_code = status.code();
+                    let exit_code = output.code();
 
                     // MS installers (including .NET) can return:
                     // 0 = success
@@ -1359,7 +1835,7 @@ impl Executor {
                         // For other MS installers, also accept non-fatal exit codes
                         match exit_code {
                             Some(0) | Some(105) | Some(194) | Some(236) => true,
-                            _ => status.success(),
+                            _ => output.success(),
                         }
                     };
 
@@ -1414,6 +1890,28 @@ impl Executor {
             }
         }
 
+        // DLLs and fonts copied straight into the prefix are only picked up after an update
+        if !files.is_empty()
+            && matches!(
+                metadata.category,
+                VerbCategory::Dlls
+                    | VerbCategory::Runtimes
+                    | VerbCategory::Codecs
+                    | VerbCategory::Fonts
+            )
+        {
+            let wineprefix = self.config.wineprefix();
+            info!("Updating wineprefix after installing {}", metadata.name);
+            if let Err(e) = self.wine.wineboot_update(&wineprefix).await {
+                warn!("wineboot --update failed after {}: {}", metadata.name, e);
+            }
+        }
+
+        // w_call: verbs the original installs after its own installer
+        if !metadata.calls_after.is_empty() {
+            self.install_calls(metadata, &metadata.calls_after).await?;
+        }
+
         Ok(())
     }
 
@@ -1423,14 +1921,14 @@ impl Executor {
         let wineprefix = self.config.wineprefix();
         std::env::set_var("WINEPREFIX", &wineprefix);
 
-        let output = std::process::Command::new(&self.wine.wine_bin)
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("winepath")
             .arg("-w")
             .arg(unix_path)
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine winepath -w {:?}", unix_path),
-                error: e.to_string(),
+            .map_err(|e| {
+                WinetricksError::command_io(format!("wine winepath -w {:?}", unix_path), e)
             })?;
 
         if output.status.success() {
@@ -1448,64 +1946,381 @@ impl Executor {
     /// Check if verb is installed
     /// Matches original winetricks behavior using word boundary matching
     pub fn is_installed(&self, verb_name: &str) -> Result<bool> {
-        let wineprefix = self.config.wineprefix();
-        let log_file = wineprefix.join("winetricks.log");
+        // Exact match (like original winetricks' grep -qw, so "dotnet" doesn't match "dotnet48")
+        Ok(install_log::read_log(&self.config.wineprefix())?
+            .iter()
+            .any(|entry| entry.verb == verb_name))
+    }
 
-        if !log_file.exists() {
-            return Ok(false);
+    /// Check that a verb's installed_file or installed_exe (either one counts) is
+    /// present in the wineprefix; verbs without either only have winetricks.log to go by
+    pub fn verify_installed_files(&self, verb_name: &str) -> Result<bool> {
+        let metadata =
+            self.registry
+                .get(verb_name)
+                .ok_or_else(|| WinetricksError::VerbNotFound {
+                    verb: verb_name.to_string(),
+                    suggestions: self.registry.suggest(verb_name, 3),
+                })?;
+
+        let checks: Vec<&str> = Self::installed_file(metadata)
+            .into_iter()
+            .chain(metadata.installed_exe.as_deref())
+            .collect();
+        if checks.is_empty() {
+            return self.is_installed(verb_name);
         }
+        for path in checks {
+            if self.verify_file_exists(path)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
 
-        let content = std::fs::read_to_string(&log_file)?;
-        // Use word boundary matching like original winetricks (grep -qw)
-        // Match verb_name as a whole word, not as part of another verb name
-        Ok(content.lines().any(|line| {
-            let trimmed = line.trim();
-            // Skip comments, flags, and commands
-            if trimmed.starts_with('#') || trimmed.starts_with('-') || trimmed.starts_with("//") {
-                return false;
-            }
-            // Skip lines with = (commands like prefix=, arch=, etc.)
-            if trimmed.contains('=') {
-                return false;
-            }
-            // Exact match (word boundary equivalent)
-            // Match whole word to avoid partial matches (e.g., "dotnet" matching "dotnet48")
-            trimmed == verb_name
-        }))
+    /// How long ago an installed verb was installed, if that is longer than
+    /// --reinstall-if-older-than allows (entries without a timestamp never count as stale)
+    fn stale_installation_age(&self, verb_name: &str) -> Result<Option<std::time::Duration>> {
+        let Some(days) = self.config.reinstall_if_older_than else {
+            return Ok(None);
+        };
+        let max_age = std::time::Duration::from_secs(u64::from(days) * 86400);
+
+        Ok(install_log::read_log(&self.config.wineprefix())?
+            .iter()
+            .filter(|entry| entry.verb == verb_name)
+            .filter_map(|entry| entry.age())
+            // The most recent installation decides
+            .min()
+            .filter(|age| *age > max_age))
     }
 
-    /// Log installation to winetricks.log
-    fn log_installation(&self, verb_name: &str) -> Result<()> {
-        let wineprefix = self.config.wineprefix();
-        let log_file = wineprefix.join("winetricks.log");
+    /// List verbs recorded in winetricks.log (skipping comments, flags, and commands)
+    fn list_installed_verbs(&self) -> Result<Vec<String>> {
+        Self::read_installed_verbs(&self.config.wineprefix())
+    }
 
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_file)?;
+    /// List verbs recorded in a wineprefix's winetricks.log
+    pub fn read_installed_verbs(wineprefix: &Path) -> Result<Vec<String>> {
+        Ok(install_log::read_log(wineprefix)?
+            .into_iter()
+            .map(|entry| entry.verb)
+            .collect())
+    }
 
-        writeln!(file, "{}", verb_name)?;
-        Ok(())
+    /// Delete the download cache of a verb (cache_dir/<verb>/)
+    /// Returns the number of bytes freed
+    pub fn cleanup_verb_cache(&self, verb_name: &str) -> Result<u64> {
+        // The name becomes a path under cache_dir, so it must not be able to leave it
+        if verb_name.is_empty()
+            || verb_name.contains(['/', '\\'])
+            || verb_name.contains("..")
+            || Path::new(verb_name).is_absolute()
+        {
+            return Err(WinetricksError::Config(format!(
+                "Invalid verb name: {}",
+                verb_name
+            )));
+        }
+        if !self.registry.exists(verb_name) {
+            return Err(WinetricksError::VerbNotFound {
+                verb: verb_name.to_string(),
+                suggestions: self.registry.suggest(verb_name, 3),
+            });
+        }
+
+        let verb_cache = self.config.cache_dir.join(verb_name);
+        if !verb_cache.is_dir() {
+            return Ok(0);
+        }
+        // A symlinked cache entry could still point outside the cache
+        let cache_dir = self.config.cache_dir.canonicalize()?;
+        if !verb_cache.canonicalize()?.starts_with(&cache_dir) {
+            return Err(WinetricksError::Config(format!(
+                "Refusing to delete {}: not inside the cache directory {}",
+                verb_cache.display(),
+                cache_dir.display()
+            )));
+        }
+
+        info!("Removing cached files for {}", verb_name);
+        let mut freed = Self::remove_dir_counting(&verb_cache)?;
+        // Files shared with the content store are only freed once the store copy goes
+        freed += self.downloader.prune_store()?;
+        Ok(freed)
     }
 
-    /// Uninstall a verb (removes from log, attempts cleanup)
-    pub async fn uninstall_verb(&mut self, verb_name: &str) -> Result<()> {
-        if !self.config.unattended {
-            info!("Uninstalling verb: {}", verb_name);
+    /// Delete cached downloads of verbs that aren't installed in any known wineprefix
+    /// (the current one and everything under prefixes_root)
+    /// Returns the number of bytes freed
+    pub fn cleanup_all_caches(&self) -> Result<u64> {
+        let mut prefixes = vec![self.config.wineprefix()];
+        if let Ok(entries) = std::fs::read_dir(&self.config.prefixes_root) {
+            prefixes.extend(
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.is_dir()),
+            );
         }
 
-        // Check if installed
-        if !self.is_installed(verb_name)? {
-            if !self.config.unattended {
-                println!("{} is not installed", verb_name);
+        let mut installed = std::collections::HashSet::new();
+        for prefix in &prefixes {
+            installed.extend(Self::read_installed_verbs(prefix)?);
+        }
+
+        let Ok(entries) = std::fs::read_dir(&self.config.cache_dir) else {
+            return Ok(0);
+        };
+
+        let mut freed = 0;
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Only verb directories: skip the content store, helper caches (ahk), and
+            // directories other verbs download into (d3dx9_* use directx9, mspaint uses vcrun6)
+            if !path.is_dir()
+                || name.starts_with('.')
+                || !self.registry.exists(&name)
+                || SHARED_CACHE_DIRS.contains(&name.as_str())
+                || installed.contains(&name)
+            {
+                continue;
             }
-            return Ok(());
+
+            info!("Removing cached files for {} (not installed)", name);
+            freed += Self::remove_dir_counting(&path)?;
         }
 
-        // Try to get metadata to see what type of verb it is
-        if let Some(metadata) = self.registry.get(verb_name) {
-            match metadata.category {
-                VerbCategory::Apps => {
+        freed += self.downloader.prune_store()?;
+        Ok(freed)
+    }
+
+    /// Remove a directory tree, returning the bytes actually freed
+    /// (hard-linked files still linked elsewhere don't count)
+    fn remove_dir_counting(dir: &Path) -> Result<u64> {
+        use std::os::unix::fs::MetadataExt;
+
+        let mut freed = 0;
+        for entry in walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let metadata = entry.metadata().map_err(std::io::Error::from)?;
+            if metadata.nlink() == 1 {
+                freed += metadata.len();
+            }
+        }
+        std::fs::remove_dir_all(dir)?;
+        Ok(freed)
+    }
+
+    /// Where a verb leaves its installed_file, when that isn't where the original
+    /// winetricks puts it
+    fn installed_file(metadata: &VerbMetadata) -> Option<&str> {
+        match metadata.name.as_str() {
+            // install_mspaint() copies it to system32 (syswow64 on win64), not C:\windows
+            "mspaint" => Some("${W_SYSTEM32_DLLS_WIN}/mspaint.exe"),
+            _ => metadata.installed_file.as_deref(),
+        }
+    }
+
+    /// List files a verb placed in the wineprefix
+    /// Built from installed_file, the verb's files, and DLL/font placement rules
+    /// (system32/syswow64 for DLLs, Fonts for fonts); only existing files are returned
+    pub fn list_installed_files(&self, verb_name: &str) -> Result<Vec<InstalledFile>> {
+        let metadata =
+            self.registry
+                .get(verb_name)
+                .ok_or_else(|| WinetricksError::VerbNotFound {
+                    verb: verb_name.to_string(),
+                    suggestions: self.registry.suggest(verb_name, 3),
+                })?;
+
+        let windows_dir = self.config.wineprefix().join("drive_c/windows");
+        let is_win64 = self
+            .config
+            .winearch
+            .as_ref()
+            .map(|a| a == "win64")
+            .unwrap_or(false);
+
+        // On 64-bit prefixes, 32-bit DLLs go to syswow64
+        let mut dll_dirs = vec![windows_dir.join("system32")];
+        if is_win64 {
+            dll_dirs.push(windows_dir.join("syswow64"));
+        }
+        let fonts_dirs = [windows_dir.join("Fonts"), windows_dir.join("fonts")];
+
+        let mut candidates = Vec::new();
+        if let Some(installed_file) = Self::installed_file(metadata) {
+            candidates.extend(self.prefix_path_candidates(installed_file));
+        }
+
+        // DLL verbs are usually named after the DLL they install (e.g. d3dcompiler_47)
+        let mut placed_names: Vec<String> =
+            metadata.files.iter().map(|f| f.filename.clone()).collect();
+        if matches!(
+            metadata.category,
+            VerbCategory::Dlls | VerbCategory::Runtimes | VerbCategory::Codecs
+        ) {
+            placed_names.push(format!("{}.dll", verb_name));
+        }
+
+        for name in &placed_names {
+            let lower = name.to_lowercase();
+            if lower.ends_with(".dll") {
+                candidates.extend(dll_dirs.iter().map(|dir| dir.join(name)));
+            } else if [".ttf", ".ttc", ".otf", ".fon"]
+                .iter()
+                .any(|ext| lower.ends_with(ext))
+            {
+                candidates.extend(fonts_dirs.iter().map(|dir| dir.join(name)));
+            }
+        }
+
+        let mut files: Vec<InstalledFile> = Vec::new();
+        for path in candidates {
+            if !path.is_file() || files.iter().any(|f| f.path == path) {
+                continue;
+            }
+            let size = std::fs::metadata(&path)?.len();
+            files.push(InstalledFile {
+                sha256: self.get_sha256sum(&path).ok(),
+                path,
+                size,
+            });
+        }
+
+        Ok(files)
+    }
+
+    /// Possible Unix locations of a Windows path template (e.g. ${W_SYSTEM32_DLLS_WIN}/foo.dll)
+    /// without calling winepath; a template may map to several directories on win64
+    fn prefix_path_candidates(&self, windows_path: &str) -> Vec<PathBuf> {
+        let drive_c = self.config.wineprefix().join("drive_c");
+        let windows_dir = drive_c.join("windows");
+        let is_win64 = self
+            .config
+            .winearch
+            .as_ref()
+            .map(|a| a == "win64")
+            .unwrap_or(false);
+
+        let programs_x86 = if is_win64 {
+            drive_c.join("Program Files (x86)")
+        } else {
+            drive_c.join("Program Files")
+        };
+        let system32_dlls = if is_win64 {
+            vec![windows_dir.join("syswow64"), windows_dir.join("system32")]
+        } else {
+            vec![windows_dir.join("system32")]
+        };
+
+        let templates: Vec<(&str, Vec<PathBuf>)> = vec![
+            ("W_SYSTEM64_DLLS_WIN64", vec![windows_dir.join("system32")]),
+            ("W_SYSTEM32_DLLS_WIN", system32_dlls),
+            (
+                "W_FONTSDIR_WIN",
+                vec![windows_dir.join("Fonts"), windows_dir.join("fonts")],
+            ),
+            ("W_WINDIR_WIN", vec![windows_dir.clone()]),
+            ("W_PROGRAMS_X86_WIN", vec![programs_x86.clone()]),
+            ("W_PROGRAMS_WIN", vec![drive_c.join("Program Files")]),
+            (
+                "W_COMMONFILES_X86_WIN",
+                vec![programs_x86.join("Common Files")],
+            ),
+            (
+                "W_COMMONFILES_WIN",
+                vec![drive_c.join("Program Files/Common Files")],
+            ),
+        ];
+
+        for (variable, bases) in templates {
+            let braced = format!("${{{}}}", variable);
+            let plain = format!("${}", variable);
+            let rest = windows_path
+                .strip_prefix(braced.as_str())
+                .or_else(|| windows_path.strip_prefix(plain.as_str()));
+            if let Some(rest) = rest {
+                let relative = rest.replace('\\', "/").trim_start_matches('/').to_string();
+                return bases.into_iter().map(|base| base.join(&relative)).collect();
+            }
+        }
+
+        // Plain Windows path (C:\...) or path relative to drive_c
+        let without_drive = windows_path
+            .strip_prefix("C:")
+            .or_else(|| windows_path.strip_prefix("c:"))
+            .unwrap_or(windows_path);
+        let relative = without_drive
+            .replace('\\', "/")
+            .trim_start_matches('/')
+            .to_string();
+        vec![drive_c.join(relative)]
+    }
+
+    /// Log installation to winetricks.log
+    fn log_installation(&self, verb_name: &str) -> Result<()> {
+        let wineprefix = self.config.wineprefix();
+        let log_file = wineprefix.join("winetricks.log");
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_file)?;
+
+        // Install time and wine version go in a trailing comment (see install_log)
+        writeln!(
+            file,
+            "{}",
+            LogEntry::now(verb_name, &self.wine.version).to_line()
+        )?;
+        Ok(())
+    }
+
+    /// Uninstall a verb (removes from log, attempts cleanup)
+    pub async fn uninstall_verb(&mut self, verb_name: &str) -> Result<()> {
+        if !self.config.unattended {
+            info!("Uninstalling verb: {}", verb_name);
+        }
+        self.check_required_tools()?;
+
+        // Check if installed
+        if !self.is_installed(verb_name)? {
+            if !self.config.unattended {
+                println!("{} is not installed", verb_name);
+            }
+            return Ok(());
+        }
+
+        // Check whether other installed verbs depend on this one
+        let installed = self.list_installed_verbs()?;
+        let dependents = self
+            .registry
+            .get_reverse_dependencies(verb_name, &installed);
+        if !dependents.is_empty() {
+            if self.config.safe {
+                return Err(WinetricksError::Verb(format!(
+                    "Cannot uninstall {}: required by installed verb(s) {} (--safe mode)",
+                    verb_name,
+                    dependents.join(", ")
+                )));
+            }
+            warn!(
+                "Warning: {} is required by installed verb(s): {}. They may stop working.",
+                verb_name,
+                dependents.join(", ")
+            );
+        }
+
+        // Try to get metadata to see what type of verb it is
+        if let Some(metadata) = self.registry.get(verb_name) {
+            match metadata.category {
+                VerbCategory::Apps => {
                     // For apps, try to find and run uninstaller
                     if !self.config.unattended {
                         println!("Attempting to uninstall application: {}", verb_name);
@@ -1518,7 +2333,10 @@ impl Executor {
                         println!("Note: Application files may still be present. Use Windows uninstaller if needed.");
                     }
                 }
-                VerbCategory::Dlls | VerbCategory::Fonts => {
+                VerbCategory::Dlls
+                | VerbCategory::Runtimes
+                | VerbCategory::Codecs
+                | VerbCategory::Fonts => {
                     // DLLs and fonts are harder to fully remove
                     if !self.config.unattended {
                         println!("Removing {} from installation log", verb_name);
@@ -1571,10 +2389,9 @@ impl Executor {
         let lines: Vec<String> = content
             .lines()
             .filter(|l| {
-                let trimmed = l.trim();
                 // Keep the line if it's not the verb we're removing
                 // Use exact match to avoid removing similar verb names
-                trimmed != verb_name
+                LogEntry::parse(l).is_none_or(|entry| entry.verb != verb_name)
             })
             .map(|l| l.to_string()) // Preserve original line (including whitespace)
             .collect();
@@ -1592,6 +2409,474 @@ impl Executor {
         Ok(())
     }
 
+    /// Snapshot winetricks.log and the registry files before installing a verb
+    fn snapshot_prefix_state(&self, verb_name: &str) -> Result<PrefixSnapshot> {
+        let wineprefix = self.config.wineprefix();
+
+        let log_file = wineprefix.join("winetricks.log");
+        let log_content = if log_file.exists() {
+            Some(std::fs::read_to_string(&log_file)?)
+        } else {
+            None
+        };
+
+        let mut registry_files = Vec::new();
+        for reg_name in ["system.reg", "user.reg", "userdef.reg"] {
+            let reg_file = wineprefix.join(reg_name);
+            let content = if reg_file.exists() {
+                Some(std::fs::read(&reg_file)?)
+            } else {
+                None
+            };
+            registry_files.push((reg_file, content));
+        }
+
+        let cache_dir = self.config.cache_dir.join(verb_name);
+        let mut cached_entries = Vec::new();
+        if cache_dir.exists() {
+            for entry in std::fs::read_dir(&cache_dir)? {
+                cached_entries.push(entry?.path());
+            }
+        }
+
+        Ok(PrefixSnapshot {
+            verb_name: verb_name.to_string(),
+            log_content,
+            registry_files,
+            cached_entries,
+        })
+    }
+
+    /// Restore the prefix state captured by snapshot_prefix_state()
+    /// Also removes files downloaded/extracted into cache_dir/<verb> during the failed attempt
+    fn restore_prefix_state(&self, snapshot: &PrefixSnapshot) -> Result<()> {
+        let wineprefix = self.config.wineprefix();
+        let wineprefix_str = wineprefix.to_string_lossy().to_string();
+
+        // Stop wineserver first, otherwise it rewrites the registry files on exit
+        eprintln!("Executing wineserver -k");
+        let _ = std::process::Command::new(&self.wine.wineserver_bin)
+            .arg("-k")
+            .env("WINEPREFIX", &wineprefix_str)
+            .status();
+
+        for (reg_file, content) in &snapshot.registry_files {
+            match content {
+                Some(data) => std::fs::write(reg_file, data)?,
+                None => {
+                    if reg_file.exists() {
+                        std::fs::remove_file(reg_file)?;
+                    }
+                }
+            }
+        }
+
+        let log_file = wineprefix.join("winetricks.log");
+        match snapshot.log_content {
+            Some(ref content) => std::fs::write(&log_file, content)?,
+            None => {
+                if log_file.exists() {
+                    std::fs::remove_file(&log_file)?;
+                }
+            }
+        }
+
+        // Remove new cache entries (keep files that were already cached before)
+        if !self.config.no_clean {
+            let cache_dir = self.config.cache_dir.join(&snapshot.verb_name);
+            if cache_dir.exists() {
+                for entry in std::fs::read_dir(&cache_dir)? {
+                    let path = entry?.path();
+                    if snapshot.cached_entries.contains(&path) {
+                        continue;
+                    }
+                    info!("Removing {}", path.display());
+                    if path.is_dir() {
+                        std::fs::remove_dir_all(&path)?;
+                    } else {
+                        std::fs::remove_file(&path)?;
+                    }
+                }
+            }
+        }
+
+        info!("Rolled back prefix state for {}", snapshot.verb_name);
+        Ok(())
+    }
+
+    /// Run the pre-installation checks for a verb without downloading or installing anything
+    /// (Wine version compatibility, conflicts, disk space, files that can't be downloaded)
+    pub async fn dry_run(&self, verb_name: &str) -> Result<DryRunReport> {
+        let metadata =
+            self.registry
+                .get(verb_name)
+                .ok_or_else(|| WinetricksError::VerbNotFound {
+                    verb: verb_name.to_string(),
+                    suggestions: self.registry.suggest(verb_name, 3),
+                })?;
+
+        let (download_bytes, total_bytes) = self.file_sizes(metadata).await?;
+        let mut report = DryRunReport {
+            verb: verb_name.to_string(),
+            installed: self.is_installed(verb_name)?,
+            download_bytes,
+            problems: Vec::new(),
+        };
+
+        if let Err(e) = self.check_package_broken(verb_name, metadata) {
+            report.problems.push(e.to_string());
+        }
+
+        if !self.config.force {
+            for conflict in &metadata.conflicts {
+                if self.is_installed(conflict)? {
+                    report
+                        .problems
+                        .push(format!("conflicts with installed verb {}", conflict));
+                }
+            }
+        }
+
+        if let Err(e) = self.check_space_for(download_bytes, total_bytes) {
+            report.problems.push(e.to_string());
+        }
+
+        let cache_dir = self.config.cache_dir.join(verb_name);
+        let win64 = self.prefix_is_win64()?;
+        for file in metadata.files.iter().filter(|f| f.applies_to(win64)) {
+            // Downloadable files were counted by file_sizes()
+            if cache_dir.join(&file.filename).exists() || file.url.is_some() {
+                continue;
+            }
+            if metadata.media.is_disc() {
+                if self.config.cdrom_path.is_none() && self.config.unattended {
+                    report.problems.push(format!(
+                        "{} must be copied from the install disc (use --cdrom)",
+                        file.filename
+                    ));
+                }
+            } else {
+                report.problems.push(format!(
+                    "{} must be downloaded manually to {}",
+                    file.filename,
+                    cache_dir.display()
+                ));
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Bytes still to download for a verb: files for the prefix architecture that aren't
+    /// cached yet (see file_sizes())
+    pub async fn estimate_download_size(&self, verb_name: &str) -> Result<u64> {
+        let metadata =
+            self.registry
+                .get(verb_name)
+                .ok_or_else(|| WinetricksError::VerbNotFound {
+                    verb: verb_name.to_string(),
+                    suggestions: self.registry.suggest(verb_name, 3),
+                })?;
+        Ok(self.file_sizes(metadata).await?.0)
+    }
+
+    /// Sizes of a verb's files for the prefix architecture: (bytes still to download,
+    /// bytes of all files). Sizes missing from the metadata are taken from the cached
+    /// file or asked from the server with a HEAD request; files whose size can't be
+    /// found count as 0
+    async fn file_sizes(&self, metadata: &VerbMetadata) -> Result<(u64, u64)> {
+        let cache_dir = self.config.cache_dir.join(&metadata.name);
+        let win64 = self.prefix_is_win64()?;
+        let mut download_bytes: u64 = 0;
+        let mut total_bytes: u64 = 0;
+        for file in metadata.files.iter().filter(|f| f.applies_to(win64)) {
+            let cached_size = std::fs::metadata(cache_dir.join(&file.filename))
+                .ok()
+                .map(|m| m.len());
+            let size = match (file.size.or(cached_size), &file.url) {
+                (Some(size), _) => size,
+                (None, Some(url)) => match self.downloader.head_request(url).await {
+                    Ok(head) => head.content_length.unwrap_or(0),
+                    Err(e) => {
+                        warn!("Could not get the size of {}: {}", file.filename, e);
+                        0
+                    }
+                },
+                (None, None) => 0,
+            };
+            total_bytes = total_bytes.saturating_add(size);
+            // Already cached files don't need to be downloaded again
+            if cached_size.is_none() && file.url.is_some() {
+                download_bytes = download_bytes.saturating_add(size);
+            }
+        }
+        Ok((download_bytes, total_bytes))
+    }
+
+    /// Look for common wineprefix corruption: missing drive_c or system32, truncated or
+    /// garbled system.reg/user.reg, an architecture other than the configured one, and
+    /// wineboot -u failing (wineserver can't start or stop cleanly)
+    pub async fn check_prefix_health(&self) -> Result<Vec<HealthIssue>> {
+        use HealthSeverity::{Error, Warn};
+
+        let wineprefix = self.config.wineprefix();
+        let mut issues = Vec::new();
+        let recreate = "Recreate the prefix (winetricks annihilate, then reinstall your verbs)";
+
+        if !wineprefix.is_dir() {
+            issues.push(HealthIssue::new(
+                Error,
+                format!("{} does not exist", wineprefix.display()),
+                "Create it with 'winetricks prefix create NAME' or run wineboot",
+            ));
+            return Ok(issues);
+        }
+
+        if !wineprefix.join("drive_c").is_dir() {
+            issues.push(HealthIssue::new(
+                Error,
+                "drive_c is missing".to_string(),
+                recreate,
+            ));
+        } else if !wineprefix.join("drive_c/windows/system32").is_dir() {
+            issues.push(HealthIssue::new(
+                Error,
+                "drive_c/windows/system32 is missing".to_string(),
+                "Run 'wineboot -u' to restore Wine's system files",
+            ));
+        }
+
+        for reg_file in ["system.reg", "user.reg"] {
+            if let Some(problem) = Self::registry_file_problem(&wineprefix.join(reg_file)) {
+                issues.push(HealthIssue::new(
+                    Error,
+                    format!("{} {}", reg_file, problem),
+                    format!(
+                        "Restore {} from a backup; otherwise {}",
+                        reg_file,
+                        recreate.to_lowercase()
+                    ),
+                ));
+            }
+        }
+
+        if let Some(ref arch) = self.config.winearch {
+            let system_reg =
+                std::fs::read_to_string(wineprefix.join("system.reg")).unwrap_or_default();
+            let prefix_arch = system_reg
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("#arch="))
+                .map(str::trim);
+            match prefix_arch {
+                Some(prefix_arch) if prefix_arch != arch => issues.push(HealthIssue::new(
+                    Error,
+                    format!(
+                        "system.reg says the prefix is {}, but {} is configured",
+                        prefix_arch, arch
+                    ),
+                    format!(
+                        "Use arch={} with this prefix, or a new prefix for {}",
+                        prefix_arch.trim_start_matches("win"),
+                        arch
+                    ),
+                )),
+                Some(_) => {}
+                None => issues.push(HealthIssue::new(
+                    Warn,
+                    "system.reg has no #arch= line".to_string(),
+                    "Run 'wineboot -u' so Wine records the architecture",
+                )),
+            }
+        }
+
+        // Only worth trying if the prefix isn't broken already
+        if !issues.iter().any(|issue| issue.severity == Error) {
+            if let Err(e) = self.wine.wineboot_update(&wineprefix).await {
+                issues.push(HealthIssue::new(
+                    Error,
+                    format!("wineboot -u failed: {}", e),
+                    "Kill leftover Wine processes (wineserver -k) and try again; if it keeps failing, check the output of 'WINEDEBUG=err+all wineboot -u'",
+                ));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    /// Why a Wine registry file (system.reg, user.reg) looks corrupt, if it does
+    fn registry_file_problem(path: &Path) -> Option<&'static str> {
+        let Ok(bytes) = std::fs::read(path) else {
+            return Some("is missing or unreadable");
+        };
+        let Ok(content) = String::from_utf8(bytes) else {
+            return Some("is not valid text");
+        };
+        if content.contains('\0') {
+            return Some("contains NUL bytes");
+        }
+        if !content.starts_with("WINE REGISTRY Version") {
+            return Some("is not a Wine registry file");
+        }
+        // Wine always finishes the file with a newline; a cut-off write doesn't
+        if !content.ends_with('\n') {
+            return Some("is truncated");
+        }
+        None
+    }
+
+    /// Dry-run a verb and, transitively, everything it installs first
+    /// Reports are in installation order (prerequisites before the verbs that need them)
+    pub async fn check_prereqs(&self, verb_name: &str) -> Result<Vec<DryRunReport>> {
+        let mut reports = Vec::new();
+        let mut visited = std::collections::HashSet::new();
+        self.check_prereqs_recursive(verb_name, &mut visited, &mut reports)
+            .await?;
+        Ok(reports)
+    }
+
+    async fn check_prereqs_recursive(
+        &self,
+        verb_name: &str,
+        visited: &mut std::collections::HashSet<String>,
+        reports: &mut Vec<DryRunReport>,
+    ) -> Result<()> {
+        if !visited.insert(verb_name.to_string()) {
+            return Ok(());
+        }
+
+        let report = self.dry_run(verb_name).await?;
+        // Installed verbs are skipped, so their prerequisites don't matter
+        if !report.installed {
+            for prerequisite in self.prerequisites(verb_name) {
+                if let Err(e) =
+                    Box::pin(self.check_prereqs_recursive(&prerequisite, visited, reports)).await
+                {
+                    reports.push(DryRunReport {
+                        verb: prerequisite,
+                        installed: false,
+                        download_bytes: 0,
+                        problems: vec![e.to_string()],
+                    });
+                }
+            }
+        }
+        reports.push(report);
+        Ok(())
+    }
+
+    /// Verbs installed before a verb: its metadata dependencies and w_call'ed verbs plus
+    /// the prerequisites install_verb_steps() handles itself (remove_mono/dotnet40 for .NET)
+    fn prerequisites(&self, verb_name: &str) -> Vec<String> {
+        let mut prerequisites: Vec<String> = Vec::new();
+        if let Some(metadata) = self.registry.get(verb_name) {
+            for verb in metadata.dependencies.iter().chain(&metadata.calls) {
+                if !prerequisites.contains(verb) {
+                    prerequisites.push(verb.clone());
+                }
+            }
+        }
+
+        let builtin: &[&str] = match verb_name {
+            "dotnet45" | "dotnet48" | "dotnet48.1" => &["remove_mono", "dotnet40"],
+            "dotnet35" | "dotnet35sp1" => &["remove_mono"],
+            _ => &[],
+        };
+        for verb in builtin {
+            if !prerequisites.iter().any(|p| p == verb) {
+                prerequisites.push(verb.to_string());
+            }
+        }
+        prerequisites
+    }
+
+    /// Check that there is enough free disk space to install a verb
+    /// Estimated from the sizes of its files (see file_sizes()) plus 2x for extraction
+    async fn check_disk_space(&self, verb_name: &str) -> Result<()> {
+        let metadata =
+            self.registry
+                .get(verb_name)
+                .ok_or_else(|| WinetricksError::VerbNotFound {
+                    verb: verb_name.to_string(),
+                    suggestions: Vec::new(),
+                })?;
+        let (download_bytes, total_bytes) = self.file_sizes(metadata).await?;
+        self.check_space_for(download_bytes, total_bytes)
+    }
+
+    /// Check that the cache has room for `download_bytes` and the wineprefix for
+    /// extracting and installing files totalling `total_bytes`
+    fn check_space_for(&self, download_bytes: u64, total_bytes: u64) -> Result<()> {
+        // No size information - nothing to estimate
+        if total_bytes == 0 {
+            return Ok(());
+        }
+
+        // Extraction (temp files + installed files) needs roughly twice the archive size
+        let extract_bytes = total_bytes.saturating_mul(2);
+
+        let wineprefix = self.config.wineprefix();
+        let (cache_available, cache_fsid) = Self::get_available_disk_space(&self.config.cache_dir)?;
+        let (prefix_available, prefix_fsid) = Self::get_available_disk_space(&wineprefix)?;
+
+        if cache_fsid == prefix_fsid {
+            // Cache and wineprefix share a filesystem - everything comes out of the same space
+            let required = download_bytes.saturating_add(extract_bytes);
+            if required > cache_available {
+                return Err(WinetricksError::InsufficientDiskSpace {
+                    required,
+                    available: cache_available,
+                });
+            }
+        } else {
+            if download_bytes > cache_available {
+                return Err(WinetricksError::InsufficientDiskSpace {
+                    required: download_bytes,
+                    available: cache_available,
+                });
+            }
+            if extract_bytes > prefix_available {
+                return Err(WinetricksError::InsufficientDiskSpace {
+                    required: extract_bytes,
+                    available: prefix_available,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get available bytes and filesystem id for the filesystem containing path
+    /// Walks up to the nearest existing ancestor (the path may not be created yet)
+    fn get_available_disk_space(path: &Path) -> Result<(u64, u64)> {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut existing = path;
+        while !existing.exists() {
+            existing = match existing.parent() {
+                Some(parent) => parent,
+                None => break,
+            };
+        }
+
+        let c_path = CString::new(existing.as_os_str().as_bytes()).map_err(|e| {
+            WinetricksError::Config(format!("Invalid path {}: {}", existing.display(), e))
+        })?;
+
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: c_path is a valid NUL-terminated string and stat is a valid out pointer
+        let ret = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+        if ret != 0 {
+            return Err(WinetricksError::Io(std::io::Error::last_os_error()));
+        }
+
+        #[allow(clippy::unnecessary_cast)]
+        let available = (stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64);
+        #[allow(clippy::unnecessary_cast)]
+        let fsid = stat.f_fsid as u64;
+        Ok((available, fsid))
+    }
+
     /// Verify that a file exists in the wineprefix (Windows path converted to Unix)
     fn verify_file_exists(&self, windows_path: &str) -> Result<bool> {
         let wineprefix = self.config.wineprefix();
@@ -1664,6 +2949,19 @@ impl Executor {
             return Ok(full_path.exists());
         }
 
+        // Handle ${W_PROGRAMS_X86_WIN}, ${W_PROGRAMS_WIN} and the Common Files variants
+        // (Program Files (x86) on 64-bit prefixes, Program Files otherwise)
+        if unix_path.starts_with("${W_PROGRAMS")
+            || unix_path.starts_with("$W_PROGRAMS")
+            || unix_path.starts_with("${W_COMMONFILES")
+            || unix_path.starts_with("$W_COMMONFILES")
+        {
+            return Ok(self
+                .prefix_path_candidates(&unix_path)
+                .iter()
+                .any(|path| path.exists()));
+        }
+
         // Try using winepath to convert if it's a simple Windows path
         if unix_path.starts_with("C:\\") || unix_path.starts_with("c:\\") {
             let wine_path = self.windows_to_unix_path(windows_path)?;
@@ -1685,15 +2983,15 @@ impl Executor {
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
 
         // Use winepath to convert Windows path to Unix
-        let output = std::process::Command::new(&self.wine.wine_bin)
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("winepath")
             .arg("-u")
             .arg(windows_path)
             .env("WINEPREFIX", &wineprefix_str)
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine winepath -u {:?}", windows_path),
-                error: e.to_string(),
+            .map_err(|e| {
+                WinetricksError::command_io(format!("wine winepath -u {:?}", windows_path), e)
             })?;
 
         if output.status.success() {
@@ -1731,7 +3029,8 @@ impl Executor {
         };
 
         // Check registry
-        let registry_check = std::process::Command::new(&self.wine.wine_bin)
+        let registry_check = self
+            .wine_command(&self.wine.wine_bin)
             .arg("reg")
             .arg("query")
             .arg(registry_key)
@@ -1888,31 +3187,64 @@ impl Executor {
     }
 
     /// Set Windows version in Wine registry
-    fn set_windows_version(&self, version: &str) -> Result<()> {
-        use std::process::Command;
+    pub fn set_windows_version(&self, version: &str) -> Result<()> {
+        self.set_windows_version_for_prefix(&self.config.wineprefix(), version)
+    }
 
-        let wineprefix = self.config.wineprefix();
+    /// Set the Windows version of a wineprefix and check that it sticks
+    /// winecfg -v alone doesn't always survive a wineserver restart, so the value is
+    /// also written to HKCU\Software\Wine directly and read back afterwards
+    pub fn set_windows_version_for_prefix(
+        &self,
+        wineprefix: &std::path::Path,
+        version: &str,
+    ) -> Result<()> {
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
+        let version = Wine::windows_version_name(version);
 
         // Use winecfg to set Windows version (matching original winetricks exactly)
         // Original winetricks: "${WINE}" winecfg -v "${winver}"
         // winecfg handles the version format correctly, avoiding the "Invalid Windows version value" error
-        eprintln!("Executing wine winecfg -v {}", version);
-        let status = Command::new(&self.wine.wine_bin)
-            .arg("winecfg")
-            .arg("-v")
-            .arg(version) // Use version name (e.g., "win7"), not hex value
-            .env("WINEPREFIX", &wineprefix_str)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine winecfg -v {}", version),
-                error: e.to_string(),
-            })?;
+        // Use version name (e.g., "win7"), not hex value
+        self.wine.set_windows_version(wineprefix, version)?;
+        if version == "default" {
+            info!("Reset Windows version to the default");
+            return Ok(());
+        }
+
+        // Same value winecfg writes: Wine only understands version names here,
+        // a hex version number would be rejected as invalid
+        let output = self.wine.run_and_capture(
+            &[
+                "reg",
+                "add",
+                "HKEY_CURRENT_USER\\Software\\Wine",
+                "/v",
+                "Version",
+                "/t",
+                "REG_SZ",
+                "/d",
+                version,
+                "/f",
+            ],
+            &[("WINEPREFIX", &wineprefix_str)],
+            None,
+        )?;
+        if !output.success() {
+            return Err(WinetricksError::wine_command_failed(
+                format!(
+                    "wine reg add \"HKEY_CURRENT_USER\\Software\\Wine\" /v Version /t REG_SZ /d {} /f",
+                    version
+                ),
+                &output,
+            ));
+        }
 
-        if !status.success() {
+        let stored = self.read_windows_version(wineprefix)?;
+        if !stored.eq_ignore_ascii_case(version) {
             return Err(WinetricksError::Config(format!(
-                "Failed to set Windows version to {}",
-                version
+                "Windows version was set to {} but the registry says {}",
+                version, stored
             )));
         }
 
@@ -1920,59 +3252,81 @@ impl Executor {
         Ok(())
     }
 
-    /// Store current Windows version (for restore later)
-    fn store_windows_version(&mut self) -> Result<()> {
-        use std::process::Command;
+    /// Windows version of the current wineprefix (winecfg name, e.g. "win7")
+    pub fn get_windows_version(&self) -> Result<String> {
+        self.read_windows_version(&self.config.wineprefix())
+    }
 
-        let wineprefix = self.config.wineprefix();
+    /// Read HKCU\Software\Wine\Version of a wineprefix
+    /// Hex version numbers written by older winetricks versions are mapped to their names
+    fn read_windows_version(&self, wineprefix: &std::path::Path) -> Result<String> {
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
 
-        // Query current Windows version from registry
-        let output = Command::new(&self.wine.wine_bin)
-            .arg("reg")
-            .arg("query")
-            .arg("HKEY_CURRENT_USER\\Software\\Wine")
-            .arg("/v")
-            .arg("Version")
-            .env("WINEPREFIX", &wineprefix_str)
-            .output();
+        let output = self.wine.run_and_capture(
+            &[
+                "reg",
+                "query",
+                "HKEY_CURRENT_USER\\Software\\Wine",
+                "/v",
+                "Version",
+            ],
+            &[("WINEPREFIX", &wineprefix_str)],
+            None,
+        )?;
 
-        if let Ok(output) = output {
-            if output.status.success() {
-                // Parse version number from output (e.g., "0x0601")
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                for line in output_str.lines() {
-                    if line.contains("REG_SZ") || line.contains("REG_DWORD") {
-                        // Extract version value (hex number)
-                        if let Some(version_val) = line.split_whitespace().last() {
-                            // Map version number back to name
-                            let version_name = match version_val {
-                                "0xa00" => "win10",
-                                "0x0603" => "win81",
-                                "0x0602" => "win8",
-                                "0x0601" => "win7",
-                                "0x0501" => "winxp",
-                                "0x0500" => "win2k",
-                                _ => {
-                                    warn!(
-                                        "Unknown Windows version number: {}, storing as win7",
-                                        version_val
-                                    );
-                                    "win7"
-                                }
-                            };
-                            self.stored_windows_version = Some(version_name.to_string());
-                            info!("Stored Windows version: {}", version_name);
-                            return Ok(());
-                        }
-                    }
+        if !output.success() {
+            return Err(WinetricksError::wine_command_failed(
+                "wine reg query \"HKEY_CURRENT_USER\\Software\\Wine\" /v Version",
+                &output,
+            ));
+        }
+
+        // "    Version    REG_SZ    win7"
+        let value = output
+            .stdout
+            .lines()
+            .find_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                match fields.as_slice() {
+                    ["Version", "REG_SZ" | "REG_DWORD", value] => Some(*value),
+                    _ => None,
                 }
+            })
+            .ok_or_else(|| {
+                WinetricksError::Config(format!(
+                    "No Windows version set in {}",
+                    wineprefix.display()
+                ))
+            })?;
+
+        let version = match value.to_lowercase().as_str() {
+            "0xa00" | "0x0a00" => "win10",
+            "0x0603" => "win81",
+            "0x0602" => "win8",
+            "0x0601" => "win7",
+            "0x0501" => "winxp",
+            "0x0500" => "win2k",
+            _ => value,
+        };
+        Ok(version.to_string())
+    }
+
+    /// Store current Windows version (for restore later)
+    fn store_windows_version(&mut self) -> Result<()> {
+        match self.get_windows_version() {
+            Ok(version) => {
+                info!("Stored Windows version: {}", version);
+                self.stored_windows_version = Some(version);
+            }
+            Err(e) => {
+                // If we can't read current version, assume win7 (most common default)
+                warn!(
+                    "Could not read current Windows version ({}), assuming win7",
+                    e
+                );
+                self.stored_windows_version = Some("win7".to_string());
             }
         }
-
-        // If we can't read current version, assume win7 (most common default)
-        warn!("Could not read current Windows version, assuming win7");
-        self.stored_windows_version = Some("win7".to_string());
         Ok(())
     }
 
@@ -2065,22 +3419,23 @@ impl Executor {
         // Try unzip first (matches original winetricks)
         if which("unzip").is_ok() {
             info!("Using unzip to extract: {:?}", zip_file);
-            let status = Command::new("unzip")
+            let command = format!("unzip -o -q -d {:?} {:?}", dest_dir, zip_file);
+            let output = Command::new("unzip")
                 .arg("-o") // Overwrite files without prompting
                 .arg("-q") // Quiet mode
                 .arg("-d") // Destination directory
                 .arg(dest_dir)
                 .arg(zip_file)
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!("unzip -o -q -d {:?} {:?}", dest_dir, zip_file),
-                    error: e.to_string(),
-                })?;
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-            if status.success() {
+            if output.status.success() {
                 return Ok(());
             }
-            warn!("unzip failed, falling back to 7z");
+            warn!(
+                "{}, falling back to 7z",
+                WinetricksError::command_failed(command, &output)
+            );
         }
 
         // Fallback to 7z (or Windows 7-Zip via Wine)
@@ -2090,13 +3445,9 @@ impl Executor {
     /// Extract CAB archive using cabextract (matching w_try_cabextract behavior)
     fn extract_cab(&self, cab_file: &Path, dest_dir: &Path) -> Result<()> {
         use std::process::Command;
-        use which::which;
 
         // cabextract is required (original winetricks dies if not found)
-        let cabextract = which("cabextract")
-            .map_err(|_| WinetricksError::Config(
-                "cabextract not found. Please install it (e.g. 'sudo apt install cabextract' or 'sudo yum install cabextract')".into()
-            ))?;
+        let cabextract = self.cabextract()?;
 
         // Original winetricks: cabextract -q -d "${W_TMP}" (uses -d flag to specify destination)
         // Show "Executing" message to match original winetricks verbose output
@@ -2106,22 +3457,17 @@ impl Executor {
             cab_file.to_string_lossy()
         );
 
-        let status = Command::new(&cabextract)
+        let command = format!("cabextract -q -d {:?} {:?}", dest_dir, cab_file);
+        let output = Command::new(&cabextract)
             .arg("-q") // Quiet mode
             .arg("-d") // Destination directory
             .arg(dest_dir) // Extract to this directory
             .arg(cab_file)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("cabextract -q -d {:?} {:?}", dest_dir, cab_file),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-        if !status.success() {
-            return Err(WinetricksError::Verb(format!(
-                "cabextract failed with exit code: {:?}",
-                status.code()
-            )));
+        if !output.status.success() {
+            return Err(WinetricksError::command_failed(command, &output));
         }
 
         Ok(())
@@ -2135,22 +3481,23 @@ impl Executor {
         // Try 7z first (matches original winetricks)
         if which("7z").is_ok() {
             info!("Using 7z to extract: {:?}", archive);
-            let status = Command::new("7z")
+            let command = format!("7z x {:?} -o{:?}", archive, dest_dir);
+            let output = Command::new("7z")
                 .arg("x") // Extract with full paths
                 .arg(archive)
                 .arg("-o") // Output directory (no space after -o)
                 .arg(dest_dir)
                 .arg("-y") // Assume yes on all queries
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!("7z x {:?} -o{:?}", archive, dest_dir),
-                    error: e.to_string(),
-                })?;
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-            if status.success() {
+            if output.status.success() {
                 return Ok(());
             }
-            warn!("7z failed, falling back to Windows 7-Zip via Wine");
+            warn!(
+                "{}, falling back to Windows 7-Zip via Wine",
+                WinetricksError::command_failed(command, &output)
+            );
         }
 
         // Fallback to Windows 7-Zip via Wine (original winetricks does this)
@@ -2164,7 +3511,9 @@ impl Executor {
             let archive_win_path = self.unix_to_wine_path(archive)?;
             let dest_win_path = self.unix_to_wine_path(dest_dir)?;
 
-            let status = std::process::Command::new(&self.wine.wine_bin)
+            let command = format!("wine 7z.exe x {:?} -o{:?}", archive_win_path, dest_win_path);
+            let output = self
+                .wine_command(&self.wine.wine_bin)
                 .arg(&sevenzip_exe)
                 .arg("x")
                 .arg(&archive_win_path)
@@ -2172,15 +3521,13 @@ impl Executor {
                 .arg(&dest_win_path)
                 .arg("-y")
                 .env("WINEPREFIX", &wineprefix_str)
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!("wine 7z.exe x {:?} -o{:?}", archive_win_path, dest_win_path),
-                    error: e.to_string(),
-                })?;
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-            if status.success() {
+            if output.status.success() {
                 return Ok(());
             }
+            warn!("{}", WinetricksError::command_failed(command, &output));
         }
 
         // If we get here, we need to install 7zip first
@@ -2205,22 +3552,23 @@ impl Executor {
                 WinetricksError::Config(format!("Failed to change to dest directory: {}", e))
             })?;
 
-            let status = Command::new("unrar")
+            let command = format!("unrar x {:?}", rar_file);
+            let output = Command::new("unrar")
                 .arg("x") // Extract with full paths
                 .arg(rar_file)
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!("unrar x {:?}", rar_file),
-                    error: e.to_string(),
-                })?;
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
             // Restore directory
             std::env::set_current_dir(original_dir)?;
 
-            if status.success() {
+            if output.status.success() {
                 return Ok(());
             }
-            warn!("unrar failed, falling back to 7z");
+            warn!(
+                "{}, falling back to 7z",
+                WinetricksError::command_failed(command, &output)
+            );
         }
 
         // Fallback to 7z (or Windows 7-Zip)
@@ -2260,22 +3608,21 @@ impl Executor {
         let dll_win_path = self.unix_to_wine_path(dll_path)?;
 
         // Use regsvr32 to register DLL
-        let status = std::process::Command::new(&self.wine.wine_bin)
+        let command = format!("wine regsvr32 /s {:?}", dll_win_path);
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("regsvr32")
             .arg("/s") // Silent mode
             .arg(&dll_win_path)
             .env("WINEPREFIX", &wineprefix_str)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine regsvr32 /s {:?}", dll_win_path),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-        if !status.success() {
+        if !output.status.success() {
             warn!(
-                "regsvr32 returned non-zero exit code for {}: {:?}",
+                "regsvr32 failed for {}: {}",
                 dll_name,
-                status.code()
+                WinetricksError::command_failed(command, &output)
             );
             // Don't fail - DLL registration can sometimes fail but DLL might still work
         }
@@ -2301,22 +3648,21 @@ impl Executor {
         let dll_win_path = self.unix_to_wine_path(dll_path)?;
 
         // Use regsvr32 via wine64 to register 64-bit DLL
-        let status = std::process::Command::new(&wine64_bin)
+        let command = format!("wine64 regsvr32 /s {:?}", dll_win_path);
+        let output = self
+            .wine_command(&wine64_bin)
             .arg("regsvr32")
             .arg("/s") // Silent mode
             .arg(&dll_win_path)
             .env("WINEPREFIX", &wineprefix_str)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine64 regsvr32 /s {:?}", dll_win_path),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-        if !status.success() {
+        if !output.status.success() {
             warn!(
-                "regsvr64 returned non-zero exit code for {}: {:?}",
+                "regsvr64 failed for {}: {}",
                 dll_name,
-                status.code()
+                WinetricksError::command_failed(command, &output)
             );
         }
 
@@ -2648,22 +3994,22 @@ impl Executor {
         let reg_file_win_path = self.unix_to_wine_path(reg_path)?;
 
         // Import registry file using regedit
-        let status = std::process::Command::new(&self.wine.wine_bin)
+        let command = format!("wine regedit /S {:?}", reg_file_win_path);
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("regedit")
             .arg("/S") // Silent mode
             .arg(&reg_file_win_path)
             .env("WINEPREFIX", &wineprefix_str)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine regedit /S {:?}", reg_file_win_path),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-        if !status.success() {
-            return Err(WinetricksError::Verb(format!(
+        if !output.status.success() {
+            warn!(
                 "Failed to register font replacement: {} -> {}",
                 alias, font_name
-            )));
+            );
+            return Err(WinetricksError::command_failed(command, &output));
         }
 
         info!("Registered font replacement: {} -> {}", alias, font_name);
@@ -2682,17 +4028,11 @@ impl Executor {
     ) -> Result<()> {
         use std::fs;
         use std::process::Command;
-        use which::which;
 
         let wineprefix = self.config.wineprefix();
 
         // cabextract is required
-        let cabextract = which("cabextract").map_err(|_| {
-            WinetricksError::Config(
-                "cabextract not found. Please install it (e.g. 'sudo apt install cabextract')"
-                    .into(),
-            )
-        })?;
+        let cabextract = self.cabextract()?;
 
         if is_64bit {
             // 64-bit: Extract a11 to C:\windows\temp\win64, then extract msvcp140.dll to system32
@@ -2709,26 +4049,22 @@ impl Executor {
                 vcredist_exe.to_string_lossy()
             );
 
-            let status = Command::new(&cabextract)
+            let command = format!(
+                "cabextract -q --directory {:?} {:?} -F a11",
+                temp_win64, vcredist_exe
+            );
+            let output = Command::new(&cabextract)
                 .arg("-q")
                 .arg("--directory")
                 .arg(&temp_win64)
                 .arg(vcredist_exe)
                 .arg("-F")
                 .arg("a11")
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!(
-                        "cabextract -q --directory {:?} {:?} -F a11",
-                        temp_win64, vcredist_exe
-                    ),
-                    error: e.to_string(),
-                })?;
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-            if !status.success() {
-                return Err(WinetricksError::Verb(
-                    "Failed to extract 'a11' CAB from VC++ Redistributables installer".into(),
-                ));
+            if !output.status.success() {
+                return Err(WinetricksError::command_failed(command, &output));
             }
 
             let a11_cab = temp_win64.join("a11");
@@ -2746,26 +4082,22 @@ impl Executor {
                 a11_cab.to_string_lossy()
             );
 
-            let status = Command::new(&cabextract)
+            let command = format!(
+                "cabextract -q --directory {:?} {:?} -F msvcp140.dll",
+                system32_dlls, a11_cab
+            );
+            let output = Command::new(&cabextract)
                 .arg("-q")
                 .arg("--directory")
                 .arg(&system32_dlls)
                 .arg(&a11_cab)
                 .arg("-F")
                 .arg("msvcp140.dll")
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!(
-                        "cabextract -q --directory {:?} {:?} -F msvcp140.dll",
-                        system32_dlls, a11_cab
-                    ),
-                    error: e.to_string(),
-                })?;
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-            if !status.success() {
-                warn!(
-                    "Warning: Failed to extract msvcp140.dll from 'a11' CAB (may not be critical)"
-                );
+            if !output.status.success() {
+                warn!("Warning: Failed to extract msvcp140.dll from 'a11' CAB (may not be critical): {}", WinetricksError::command_failed(command, &output));
             }
         } else {
             // 32-bit: Extract a10 to C:\windows\temp\win32, then extract msvcp140.dll to syswow64
@@ -2794,26 +4126,22 @@ impl Executor {
                 vcredist_exe.to_string_lossy()
             );
 
-            let status = Command::new(&cabextract)
+            let command = format!(
+                "cabextract -q --directory {:?} {:?} -F a10",
+                temp_win32, vcredist_exe
+            );
+            let output = Command::new(&cabextract)
                 .arg("-q")
                 .arg("--directory")
                 .arg(&temp_win32)
                 .arg(vcredist_exe)
                 .arg("-F")
                 .arg("a10")
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!(
-                        "cabextract -q --directory {:?} {:?} -F a10",
-                        temp_win32, vcredist_exe
-                    ),
-                    error: e.to_string(),
-                })?;
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-            if !status.success() {
-                return Err(WinetricksError::Verb(
-                    "Failed to extract 'a10' CAB from VC++ Redistributables installer".into(),
-                ));
+            if !output.status.success() {
+                return Err(WinetricksError::command_failed(command, &output));
             }
 
             let a10_cab = temp_win32.join("a10");
@@ -2834,26 +4162,22 @@ impl Executor {
                 a10_cab.to_string_lossy()
             );
 
-            let status = Command::new(&cabextract)
+            let command = format!(
+                "cabextract -q --directory {:?} {:?} -F msvcp140.dll",
+                dll_dest, a10_cab
+            );
+            let output = Command::new(&cabextract)
                 .arg("-q")
                 .arg("--directory")
                 .arg(&dll_dest)
                 .arg(&a10_cab)
                 .arg("-F")
                 .arg("msvcp140.dll")
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!(
-                        "cabextract -q --directory {:?} {:?} -F msvcp140.dll",
-                        dll_dest, a10_cab
-                    ),
-                    error: e.to_string(),
-                })?;
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-            if !status.success() {
-                warn!(
-                    "Warning: Failed to extract msvcp140.dll from 'a10' CAB (may not be critical)"
-                );
+            if !output.status.success() {
+                warn!("Warning: Failed to extract msvcp140.dll from 'a10' CAB (may not be critical): {}", WinetricksError::command_failed(command, &output));
             }
         }
 
@@ -2868,7 +4192,6 @@ impl Executor {
     fn extract_vcredist_dlls(&self, vcredist_exe: &Path, cache_dir: &Path) -> Result<()> {
         use std::fs;
         use std::process::Command;
-        use which::which;
 
         let wineprefix = self.config.wineprefix();
         let system32_dlls = wineprefix.join("drive_c/windows/system32");
@@ -2880,34 +4203,25 @@ impl Executor {
         fs::create_dir_all(&temp_win32)?;
 
         // cabextract is required
-        let cabextract = which("cabextract").map_err(|_| {
-            WinetricksError::Config(
-                "cabextract not found. Please install it (e.g. 'sudo apt install cabextract')"
-                    .into(),
-            )
-        })?;
+        let cabextract = self.cabextract()?;
 
         info!("Extracting 'a10' CAB from VC++ Redistributables installer...");
         // Extract the 'a10' CAB file from the installer
-        let status = Command::new(&cabextract)
+        let command = format!(
+            "cabextract --directory {:?} {:?} -F a10",
+            temp_win32, vcredist_exe
+        );
+        let output = Command::new(&cabextract)
             .arg("--directory")
             .arg(&temp_win32)
             .arg(vcredist_exe)
             .arg("-F")
             .arg("a10")
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!(
-                    "cabextract --directory {:?} {:?} -F a10",
-                    temp_win32, vcredist_exe
-                ),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-        if !status.success() {
-            return Err(WinetricksError::Verb(
-                "Failed to extract 'a10' CAB from VC++ Redistributables installer".into(),
-            ));
+        if !output.status.success() {
+            return Err(WinetricksError::command_failed(command, &output));
         }
 
         let a10_cab = temp_win32.join("a10");
@@ -2919,44 +4233,46 @@ impl Executor {
 
         // Extract msvcp140.dll from the 'a10' CAB
         info!("Extracting msvcp140.dll to system32...");
-        let status = Command::new(&cabextract)
+        let command = format!(
+            "cabextract --directory {:?} {:?} -F msvcp140.dll",
+            system32_dlls, a10_cab
+        );
+        let output = Command::new(&cabextract)
             .arg("--directory")
             .arg(&system32_dlls)
             .arg(&a10_cab)
             .arg("-F")
             .arg("msvcp140.dll")
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!(
-                    "cabextract --directory {:?} {:?} -F msvcp140.dll",
-                    system32_dlls, a10_cab
-                ),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-        if !status.success() {
-            warn!("Warning: Failed to extract msvcp140.dll from 'a10' CAB (may not be critical)");
+        if !output.status.success() {
+            warn!(
+                "Warning: Failed to extract msvcp140.dll from 'a10' CAB (may not be critical): {}",
+                WinetricksError::command_failed(command, &output)
+            );
         }
 
         // Extract ucrtbase.dll from the 'a10' CAB (for vcrun2015 and vcrun2017)
         info!("Extracting ucrtbase.dll to system32...");
-        let status = Command::new(&cabextract)
+        let command = format!(
+            "cabextract --directory {:?} {:?} -F ucrtbase.dll",
+            system32_dlls, a10_cab
+        );
+        let output = Command::new(&cabextract)
             .arg("--directory")
             .arg(&system32_dlls)
             .arg(&a10_cab)
             .arg("-F")
             .arg("ucrtbase.dll")
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!(
-                    "cabextract --directory {:?} {:?} -F ucrtbase.dll",
-                    system32_dlls, a10_cab
-                ),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-        if !status.success() {
-            warn!("Warning: Failed to extract ucrtbase.dll from 'a10' CAB (may not be critical)");
+        if !output.status.success() {
+            warn!(
+                "Warning: Failed to extract ucrtbase.dll from 'a10' CAB (may not be critical): {}",
+                WinetricksError::command_failed(command, &output)
+            );
         }
 
         info!("Successfully extracted VC++ Redistributables DLLs");
@@ -3017,260 +4333,133 @@ impl Executor {
             let sha256 = "8746ee1a84a083a90e37899d71d50d5c7c015e69688a466aa80447f011780c0d";
 
             self.downloader
-                .download(url, &directx_file, Some(sha256), true)
-                .await?;
+                .download(
+                    url,
+                    &directx_file,
+                    Some(sha256),
+                    true,
+                    None,
+                    self.config.country.as_deref(),
+                )
+                .await
+                .context("While downloading directx_Jun2010_redist.exe")?;
         }
 
         Ok(())
     }
 
     /// Extract specific d3dx9 DLL from DirectX redistributable (helper_d3dx9_xx)
+    /// The redistributable holds one sub-CAB per DLL version and architecture
+    /// (e.g. jun2010_d3dx9_43_x86.cab); the "d3dx9" verb extracts all of them
     fn extract_d3dx9_dll(
         &self,
         metadata: &VerbMetadata,
         directx_redist: &Path,
         cache_dir: &Path,
     ) -> Result<()> {
-        use glob::glob;
         use std::fs;
-        use std::process::Command;
-        use which::which;
-
-        let wineprefix = self.config.wineprefix();
-        let system32_dlls = wineprefix.join("drive_c/windows/system32");
-        fs::create_dir_all(&system32_dlls)?;
 
         // Extract DLL version number from verb name (e.g., "d3dx9_43" -> "43", "d3dx9" -> extract all)
-        let dll_version = if metadata.name == "d3dx9" {
-            None // Extract all d3dx9 DLLs
-        } else if metadata.name.starts_with("d3dx9_") {
-            Some(metadata.name.strip_prefix("d3dx9_").unwrap_or(""))
-        } else {
-            return Err(WinetricksError::Verb(format!(
-                "Invalid d3dx9 verb name: {}",
-                metadata.name
-            )));
-        };
-
-        // Create temp directory for extraction
-        let temp_dir = cache_dir.join("d3dx9_extract");
-        fs::create_dir_all(&temp_dir)?;
-
-        // cabextract is required
-        let cabextract = which("cabextract").map_err(|_| {
-            WinetricksError::Config(
-                "cabextract not found. Please install it (e.g. 'sudo apt install cabextract')"
-                    .into(),
-            )
-        })?;
-
-        if let Some(version) = dll_version {
-            // Extract specific DLL version (e.g., d3dx9_43)
-            let dll_name = format!("d3dx9_{}", version);
-            info!("Extracting {} from DirectX redistributable...", dll_name);
-
-            // Extract CAB files matching d3dx9_XX x86 pattern
-            // Original winetricks: w_try_cabextract -d "${W_TMP}" -L -F "*${dllname}*x86*" "${W_CACHE}"/directx9/${DIRECTX_NAME}
-            let status = Command::new(&cabextract)
-                .arg("-d")
-                .arg(&temp_dir)
-                .arg("-L") // List contents
-                .arg("-F")
-                .arg(&format!("*{}*x86*", dll_name))
-                .arg(directx_redist)
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!(
-                        "cabextract -d {:?} -L -F *{}*x86* {:?}",
-                        temp_dir, dll_name, directx_redist
-                    ),
-                    error: e.to_string(),
-                })?;
-
-            if !status.success() {
+        let dll_name = match metadata.name.strip_prefix("d3dx9_") {
+            Some(version) if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) => {
+                metadata.name.as_str()
+            }
+            _ if metadata.name == "d3dx9" => "d3dx9",
+            _ => {
                 return Err(WinetricksError::Verb(format!(
-                    "Failed to extract {} CAB files from DirectX redistributable",
-                    dll_name
+                    "Invalid d3dx9 verb name: {}",
+                    metadata.name
                 )));
             }
-
-            // Extract DLL from CAB files
-            // Original winetricks: for x in "${W_TMP}"/*.cab; do w_try_cabextract -d "${W_SYSTEM32_DLLS}" -L -F "${dllname}.dll" "${x}"; done
-            let cab_pattern = temp_dir.join("*.cab");
-            if let Ok(entries) = glob(&cab_pattern.to_string_lossy()) {
-                for cab_entry in entries {
-                    if let Ok(cab_file) = cab_entry {
-                        let status = Command::new(&cabextract)
-                            .arg("-d")
-                            .arg(&system32_dlls)
-                            .arg("-L")
-                            .arg("-F")
-                            .arg(&format!("{}.dll", dll_name))
-                            .arg(&cab_file)
-                            .status()
-                            .map_err(|e| WinetricksError::CommandExecution {
-                                command: format!(
-                                    "cabextract -d {:?} -L -F {}.dll {:?}",
-                                    system32_dlls, dll_name, cab_file
-                                ),
-                                error: e.to_string(),
-                            })?;
-
-                        if status.success() {
-                            info!("Extracted {}.dll to system32", dll_name);
-                        }
-                    }
-                }
-            }
-
-            // On win64 prefixes, also extract 64-bit version
-            if self
-                .config
-                .winearch
-                .as_ref()
-                .map(|a| a == "win64")
-                .unwrap_or(false)
-            {
-                let system64_dlls = wineprefix.join("drive_c/windows/system32"); // 64-bit DLLs go in system32 on win64
-                fs::create_dir_all(&system64_dlls)?;
-
-                // Extract CAB files matching d3dx9_XX x64 pattern
-                let status = Command::new(&cabextract)
-                    .arg("-d")
-                    .arg(&temp_dir)
-                    .arg("-L")
-                    .arg("-F")
-                    .arg(&format!("*{}*x64*", dll_name))
-                    .arg(directx_redist)
-                    .status()
-                    .map_err(|e| WinetricksError::CommandExecution {
-                        command: format!(
-                            "cabextract -d {:?} -L -F *{}*x64* {:?}",
-                            temp_dir, dll_name, directx_redist
-                        ),
-                        error: e.to_string(),
-                    })?;
-
-                if status.success() {
-                    // Extract DLL from x64 CAB files
-                    let x64_cab_pattern = temp_dir.join("*x64.cab");
-                    if let Ok(entries) = glob(&x64_cab_pattern.to_string_lossy()) {
-                        for cab_entry in entries {
-                            if let Ok(cab_file) = cab_entry {
-                                let _status = Command::new(&cabextract)
-                                    .arg("-d")
-                                    .arg(&system64_dlls)
-                                    .arg("-L")
-                                    .arg("-F")
-                                    .arg(&format!("{}.dll", dll_name))
-                                    .arg(&cab_file)
-                                    .status();
-                                // Don't fail on error, just warn
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Set DLL override to native
-            // Original winetricks: w_override_dlls native "${dllname}"
-            if let Err(e) = self.set_dll_override(&dll_name, "native") {
-                warn!(
-                    "Warning: Failed to set DLL override for {}: {}",
-                    dll_name, e
-                );
-            }
+        };
+        let all_versions = metadata.name == "d3dx9";
+        let dll_pattern = if all_versions {
+            "d3dx9*.dll".to_string()
         } else {
-            // Extract all d3dx9 DLLs (for "d3dx9" verb)
-            info!("Extracting all d3dx9 DLLs from DirectX redistributable...");
+            format!("{}.dll", dll_name)
+        };
+        info!("Extracting {} from DirectX redistributable...", dll_pattern);
 
-            // Extract all d3dx9 x86 CAB files
-            let status = Command::new(&cabextract)
-                .arg("-d")
-                .arg(&temp_dir)
-                .arg("-L")
-                .arg("-F")
-                .arg("*d3dx9*x86*")
-                .arg(directx_redist)
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!(
-                        "cabextract -d {:?} -L -F *d3dx9*x86* {:?}",
-                        temp_dir, directx_redist
-                    ),
-                    error: e.to_string(),
-                })?;
+        // cabextract is required
+        let cabextract = self.cabextract()?;
+
+        // 32-bit DLLs go to syswow64 on win64 (W_SYSTEM32_DLLS), 64-bit ones to system32
+        let windows_dir = self.config.wineprefix().join("drive_c/windows");
+        let targets = if self.prefix_is_win64()? {
+            vec![
+                ("x86", windows_dir.join("syswow64")),
+                ("x64", windows_dir.join("system32")),
+            ]
+        } else {
+            vec![("x86", windows_dir.join("system32"))]
+        };
 
-            if status.success() {
-                // Extract all d3dx9*.dll from CAB files
-                let cab_pattern = temp_dir.join("*.cab");
-                if let Ok(entries) = glob(&cab_pattern.to_string_lossy()) {
-                    for cab_entry in entries {
-                        if let Ok(cab_file) = cab_entry {
-                            let _status = Command::new(&cabextract)
-                                .arg("-d")
-                                .arg(&system32_dlls)
-                                .arg("-L")
-                                .arg("-F")
-                                .arg("d3dx9*.dll")
-                                .arg(&cab_file)
-                                .status();
-                            // Continue even if one fails
-                        }
-                    }
-                }
+        for (arch, dll_dir) in targets {
+            fs::create_dir_all(&dll_dir)?;
+
+            // Start from an empty directory so sub-CABs of another architecture
+            // (or an earlier run) aren't picked up
+            let temp_dir = cache_dir.join("d3dx9_extract");
+            if temp_dir.exists() {
+                fs::remove_dir_all(&temp_dir)?;
             }
+            fs::create_dir_all(&temp_dir)?;
 
-            // Set DLL overrides for all d3dx9 versions (as original winetricks does)
-            let d3dx9_versions = vec![
-                "d3dx9_24", "d3dx9_25", "d3dx9_26", "d3dx9_27", "d3dx9_28", "d3dx9_29", "d3dx9_30",
-                "d3dx9_31", "d3dx9_32", "d3dx9_33", "d3dx9_34", "d3dx9_35", "d3dx9_36", "d3dx9_37",
-                "d3dx9_38", "d3dx9_39", "d3dx9_40", "d3dx9_41", "d3dx9_42", "d3dx9_43",
-            ];
+            // Original winetricks: w_try_cabextract -d "${W_TMP}" -L -F "*${dllname}*x86*" "${W_CACHE}"/directx9/${DIRECTX_NAME}
+            Self::run_cabextract(
+                &cabextract,
+                &temp_dir,
+                &format!("*{}*{}*", dll_name, arch),
+                directx_redist,
+            )?;
+
+            let mut sub_cabs: Vec<PathBuf> = fs::read_dir(&temp_dir)?
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("cab"))
+                .collect();
+            sub_cabs.sort();
+            if sub_cabs.is_empty() {
+                return Err(WinetricksError::Verb(format!(
+                    "No {} {} CAB found in {}",
+                    dll_name,
+                    arch,
+                    directx_redist.display()
+                )));
+            }
 
-            for dll in &d3dx9_versions {
-                if let Err(e) = self.set_dll_override(dll, "native") {
-                    warn!("Warning: Failed to set DLL override for {}: {}", dll, e);
-                }
+            // Original winetricks: for x in "${W_TMP}"/*.cab; do w_try_cabextract -d "${W_SYSTEM32_DLLS}" -L -F "${dllname}.dll" "${x}"; done
+            for sub_cab in &sub_cabs {
+                Self::run_cabextract(&cabextract, &dll_dir, &dll_pattern, sub_cab)?;
             }
+            fs::remove_dir_all(&temp_dir)?;
 
-            // On win64, also extract 64-bit DLLs
-            if self
-                .config
-                .winearch
-                .as_ref()
-                .map(|a| a == "win64")
-                .unwrap_or(false)
-            {
-                let system64_dlls = wineprefix.join("drive_c/windows/system32");
-                fs::create_dir_all(&system64_dlls)?;
-
-                let status = Command::new(&cabextract)
-                    .arg("-d")
-                    .arg(&temp_dir)
-                    .arg("-L")
-                    .arg("-F")
-                    .arg("*d3dx9*x64*")
-                    .arg(directx_redist)
-                    .status();
+            if !all_versions && !dll_dir.join(&dll_pattern).exists() {
+                return Err(WinetricksError::Verb(format!(
+                    "{} not found in the {} CABs of {}",
+                    dll_pattern,
+                    arch,
+                    directx_redist.display()
+                )));
+            }
+            info!(
+                "Extracted {} ({}) to {}",
+                dll_pattern,
+                arch,
+                dll_dir.display()
+            );
+        }
 
-                if status.is_ok() && status.unwrap().success() {
-                    let x64_cab_pattern = temp_dir.join("*x64.cab");
-                    if let Ok(entries) = glob(&x64_cab_pattern.to_string_lossy()) {
-                        for cab_entry in entries {
-                            if let Ok(cab_file) = cab_entry {
-                                let _status = Command::new(&cabextract)
-                                    .arg("-d")
-                                    .arg(&system64_dlls)
-                                    .arg("-L")
-                                    .arg("-F")
-                                    .arg("d3dx9*.dll")
-                                    .arg(&cab_file)
-                                    .status();
-                            }
-                        }
-                    }
-                }
+        // Set DLL override to native
+        // Original winetricks: w_override_dlls native "${dllname}"
+        let overrides: Vec<String> = if all_versions {
+            (24..=43).map(|n| format!("d3dx9_{}", n)).collect()
+        } else {
+            vec![dll_name.to_string()]
+        };
+        for dll in &overrides {
+            if let Err(e) = self.set_dll_override(dll, "native") {
+                warn!("Warning: Failed to set DLL override for {}: {}", dll, e);
             }
         }
 
@@ -3278,260 +4467,252 @@ impl Executor {
         Ok(())
     }
 
-    /// Check if a package is broken in the current Wine version (w_package_broken)
-    /// Takes bug_link, bad_version (optional), and good_version (optional)
-    /// If broken and --force is not set, shows warning and returns error
-    fn check_package_broken(&self, verb_name: &str, _metadata: &VerbMetadata) -> Result<()> {
-        // For now, hardcode known broken packages from original winetricks
-        // TODO: Add broken package info to JSON metadata files
-        let broken_info = self.get_broken_info(verb_name);
-
-        if let Some((bug_link, bad_version, good_version)) = broken_info {
-            // Build version range to check
-            let version_range = if let (Some(bad), Some(good)) = (bad_version, good_version) {
-                // Both versions known: broken from bad_version to good_version
-                format!("{},{}", bad, good)
-            } else if let Some(bad) = bad_version {
-                // Only bad version known: broken from bad_version onwards
-                format!("{},", bad)
-            } else {
-                // No version info: always broken
-                return self.handle_package_broken(verb_name, bug_link, None, good_version);
-            };
+    /// cabextract -d <dest> -L -F <pattern> <archive>, failing if cabextract does
+    fn run_cabextract(cabextract: &Path, dest: &Path, pattern: &str, archive: &Path) -> Result<()> {
+        let command = format!("cabextract -d {:?} -L -F {} {:?}", dest, pattern, archive);
+        let output = std::process::Command::new(cabextract)
+            .arg("-d")
+            .arg(dest)
+            .arg("-L")
+            .arg("-F")
+            .arg(pattern)
+            .arg(archive)
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-            // Check if we're in the problematic range
-            if let Ok(is_broken) = self.wine.version_in_range(&version_range) {
-                if is_broken {
-                    return self.handle_package_broken(
-                        verb_name,
-                        bug_link,
-                        bad_version,
-                        good_version,
-                    );
-                }
-            }
+        if !output.status.success() {
+            return Err(WinetricksError::command_failed(command, &output));
         }
+        Ok(())
+    }
 
+    /// Write a Windows shortcut (.lnk) to a local file, e.g. C:\\windows\\notepad.exe
+    /// Minimal MS-SHLLINK file: header plus a LinkInfo with the target's local path
+    fn write_shortcut(lnk: &Path, target: &str) -> Result<()> {
+        // LinkInfo: header, VolumeID (fixed drive, empty label), LocalBasePath, CommonPathSuffix
+        const LINK_INFO_HEADER_SIZE: u32 = 0x1C;
+        const VOLUME_ID_SIZE: u32 = 0x11;
+        let local_base_path_offset = LINK_INFO_HEADER_SIZE + VOLUME_ID_SIZE;
+        let suffix_offset = local_base_path_offset + target.len() as u32 + 1;
+        let link_info_size = suffix_offset + 1;
+
+        let mut data: Vec<u8> = Vec::new();
+        // ShellLinkHeader
+        data.extend_from_slice(&0x4Cu32.to_le_bytes());
+        data.extend_from_slice(&[
+            0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x46,
+        ]); // CLSID 00021401-0000-0000-C000-000000000046
+        data.extend_from_slice(&0x02u32.to_le_bytes()); // HasLinkInfo
+        data.extend_from_slice(&0x20u32.to_le_bytes()); // FILE_ATTRIBUTE_ARCHIVE
+        data.extend_from_slice(&[0; 24]); // creation/access/write times
+        data.extend_from_slice(&0u32.to_le_bytes()); // file size
+        data.extend_from_slice(&0i32.to_le_bytes()); // icon index
+        data.extend_from_slice(&1u32.to_le_bytes()); // SW_SHOWNORMAL
+        data.extend_from_slice(&[0; 12]); // hotkey, reserved
+
+        // LinkInfo
+        data.extend_from_slice(&link_info_size.to_le_bytes());
+        data.extend_from_slice(&LINK_INFO_HEADER_SIZE.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes()); // VolumeIDAndLocalBasePath
+        data.extend_from_slice(&LINK_INFO_HEADER_SIZE.to_le_bytes()); // VolumeID offset
+        data.extend_from_slice(&local_base_path_offset.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes()); // no CommonNetworkRelativeLink
+        data.extend_from_slice(&suffix_offset.to_le_bytes());
+        data.extend_from_slice(&VOLUME_ID_SIZE.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes()); // DRIVE_FIXED
+        data.extend_from_slice(&0u32.to_le_bytes()); // serial number
+        data.extend_from_slice(&0x10u32.to_le_bytes()); // volume label offset
+        data.push(0); // empty volume label
+        data.extend_from_slice(target.as_bytes());
+        data.push(0);
+        data.push(0); // empty CommonPathSuffix
+
+        data.extend_from_slice(&0u32.to_le_bytes()); // TerminalBlock
+
+        if let Some(parent) = lnk.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(lnk, data)?;
         Ok(())
     }
 
-    /// Handle package broken warning/error
-    fn handle_package_broken(
-        &self,
-        verb_name: &str,
-        bug_link: &str,
-        bad_version: Option<&str>,
-        good_version: Option<&str>,
-    ) -> Result<()> {
-        let current_version = &self.wine.version_stripped;
+    /// Check if a package is broken in the current Wine version (w_package_broken)
+    /// Uses the broken_wine_versions ranges from the verb metadata
+    /// If broken and --force is not set, returns a KnownBroken error
+    fn check_package_broken(&self, verb_name: &str, metadata: &VerbMetadata) -> Result<()> {
+        // Broken version ranges are maintained in the verb's JSON metadata
+        if metadata.broken_wine_versions.is_empty() {
+            return Ok(());
+        }
 
-        let message = match (bad_version, good_version) {
-            (Some(bad), Some(good)) => {
-                format!(
-                    "Package ({}) is broken in wine-{}. Broken since version {}. Use >= {}. See {} for more information. Use --force to try anyway.",
-                    verb_name, current_version, bad, good, bug_link
-                )
-            }
-            (Some(bad), None) => {
-                format!(
-                    "Package ({}) is broken in wine-{}. Broken since version {}. See {} for more information. Use --force to try anyway.",
-                    verb_name, current_version, bad, bug_link
-                )
-            }
-            (None, Some(good)) => {
-                format!(
-                    "Package ({}) is broken in wine-{}. Use >= {}. See {} for more information. Use --force to try anyway.",
-                    verb_name, current_version, good, bug_link
-                )
-            }
-            (None, None) => {
-                format!(
-                    "Package ({}) is broken. See {} for more information. Use --force to try anyway.",
-                    verb_name, bug_link
-                )
+        let version = match self.wine.get_version() {
+            Ok(version) => version,
+            Err(e) => {
+                warn!(
+                    "Can't check whether {} is broken in wine-{}: {}",
+                    verb_name, self.wine.version_stripped, e
+                );
+                return Ok(());
             }
         };
 
+        match metadata
+            .broken_wine_versions
+            .iter()
+            .find(|range| range.contains(&version))
+        {
+            Some(range) => self.handle_package_broken(verb_name, range),
+            None => Ok(()),
+        }
+    }
+
+    /// Handle package broken warning/error
+    fn handle_package_broken(&self, verb_name: &str, range: &WineVersionRange) -> Result<()> {
+        let current_version = self.wine.version_stripped.clone();
+
+        let mut hint = String::new();
+        if !range.min.is_unbounded() {
+            hint.push_str(&format!("Broken since version {}. ", range.min));
+        }
+        if let Some(ref max) = range.max {
+            hint.push_str(&format!("Use > {}. ", max));
+        }
+        if let Some(ref workaround) = range.workaround {
+            hint.push_str(&format!("{} ", workaround));
+        }
+        if let Some(ref bug_url) = range.bug_url {
+            hint.push_str(&format!("See {} for more information. ", bug_url));
+        }
+        hint.push_str("Use --force to try anyway.");
+
         if self.config.force {
-            warn!("{}", message);
+            warn!(
+                "Package ({}) is broken in wine-{}. {}",
+                verb_name, current_version, hint
+            );
             warn!("Continuing anyway due to --force flag");
             Ok(())
         } else {
-            Err(WinetricksError::Verb(message))
-        }
-    }
-
-    /// Get broken package info for a verb (hardcoded for now, matches original winetricks)
-    fn get_broken_info(&self, verb_name: &str) -> Option<(&str, Option<&str>, Option<&str>)> {
-        // This is a simplified version - in reality, this should come from JSON metadata
-        // For now, hardcode known broken packages from original winetricks
-        match verb_name {
-            // Examples from original winetricks:
-            // w_package_broken "https://bugs.winehq.org/show_bug.cgi?id=49532" 5.12 5.18
-            // w_package_broken "https://bugs.winehq.org/show_bug.cgi?id=52722" 7.5 7.6
-            // etc.
-            _ => None,
+            Err(WinetricksError::KnownBroken {
+                verb: verb_name.to_string(),
+                wine_version: current_version,
+                workaround_hint: hint,
+            })
         }
     }
 
-    /// Install corefonts meta-verb (installs all individual font verbs)
-    /// Original winetricks: load_corefonts() calls w_call for each font
-    async fn install_corefonts(&mut self) -> Result<()> {
-        use std::fs;
-
-        // List of individual font verbs that make up corefonts
-        // Original winetricks: w_call andale, arial, comicsans, courier, georgia, impact, times, trebuchet, verdana, webdings
-        let corefonts_verbs = vec![
-            "andale",
-            "arial",
-            "comicsans",
-            "courier",
-            "georgia",
-            "impact",
-            "times",
-            "trebuchet",
-            "verdana",
-            "webdings",
-        ];
-
-        info!("Installing corefonts components...");
-
-        // Install each individual font verb
-        for font_verb in &corefonts_verbs {
-            // Check if already installed (skip if so, unless --force)
-            if !self.config.force && self.is_installed(font_verb).unwrap_or(false) {
-                info!("{} is already installed, skipping", font_verb);
-                continue;
-            }
+    /// Install the verbs a verb w_calls (e.g. the individual fonts of corefonts, the
+    /// codecs of allcodecs, the CJK fonts of cjkfonts)
+    /// For meta-verbs (verbs without files of their own, like allcodecs or allfonts),
+    /// one verb failing (e.g. a CDN outage for one font) doesn't stop the others; this
+    /// only fails if none of them could be installed, and prints which ones are missing
+    /// if only some could. For any other verb the called verbs are prerequisites of its
+    /// own installer, so the first one failing fails the verb
+    async fn install_calls(&mut self, metadata: &VerbMetadata, calls: &[String]) -> Result<()> {
+        let meta_verb = metadata.files.is_empty();
+        info!(
+            "Installing {} components: {}",
+            metadata.name,
+            calls.join(", ")
+        );
 
-            // Install individual font verb (using install_verb_internal to avoid re-checking and logging)
-            if let Err(e) = self.install_verb_internal(font_verb).await {
+        self.calling.push(metadata.name.clone());
+        let mut installed: Vec<&str> = Vec::new();
+        let mut already_installed: Vec<&str> = Vec::new();
+        let mut failures: Vec<(String, WinetricksError)> = Vec::new();
+        for called_verb in calls {
+            if self.calling.contains(called_verb) {
                 warn!(
-                    "Warning: Failed to install {} (may not be critical): {}",
-                    font_verb, e
+                    "Skipping {} for {}: it is already being installed ({})",
+                    called_verb,
+                    metadata.name,
+                    self.calling.join(" -> ")
                 );
-                // Continue installing other fonts even if one fails
-            } else {
-                info!("Successfully installed {}", font_verb);
+                continue;
             }
-        }
-
-        // Create marker file after all fonts are installed
-        // Original winetricks: touch "${W_FONTSDIR_UNIX}/corefonts.installed"
-        let wineprefix = self.config.wineprefix();
-        let windows_dir = wineprefix.join("drive_c/windows");
-        let fonts_dir_unix = if windows_dir.join("Fonts").exists() {
-            windows_dir.join("Fonts")
-        } else if windows_dir.join("fonts").exists() {
-            windows_dir.join("fonts")
-        } else {
-            // Create Fonts directory if it doesn't exist
-            let fonts_dir = windows_dir.join("Fonts");
-            fs::create_dir_all(&fonts_dir)?;
-            fonts_dir
-        };
-
-        let marker_file = fonts_dir_unix.join("corefonts.installed");
-        fs::File::create(&marker_file)?;
-        info!("Created corefonts marker file: {:?}", marker_file);
-
-        // Log corefonts installation
-        self.log_installation("corefonts")?;
-
-        Ok(())
-    }
-
-    /// Install allfonts meta-verb (installs all individual font verbs)
-    /// Original winetricks: load_allfonts() calls w_call for each font
-    async fn install_allfonts(&mut self) -> Result<()> {
-        use std::fs;
-
-        // List of all individual font verbs (excluding meta-verbs like corefonts, allfonts, cjkfonts, pptfonts)
-        // Based on files/json/fonts/*.json
-        let allfonts_verbs = vec![
-            "andale",
-            "arial",
-            "baekmuk",
-            "calibri",
-            "cambria",
-            "candara",
-            "comicsans",
-            "consolas",
-            "constantia",
-            "corbel",
-            "courier",
-            "droid",
-            "eufonts",
-            "fakechinese",
-            "fakejapanese",
-            "fakejapanese_ipamona",
-            "fakejapanese_vlgothic",
-            "fakekorean",
-            "georgia",
-            "impact",
-            "ipamona",
-            "liberation",
-            "lucida",
-            "meiryo",
-            "micross",
-            "opensymbol",
-            "sourcehansans",
-            "tahoma",
-            "takao",
-            "times",
-            "trebuchet",
-            "uff",
-            "unifont",
-            "verdana",
-            "vlgothic",
-            "webdings",
-            "wenquanyi",
-            "wenquanyizenhei",
-        ];
-
-        info!("Installing allfonts components...");
 
-        // Install each individual font verb
-        for font_verb in &allfonts_verbs {
             // Check if already installed (skip if so, unless --force)
-            if !self.config.force && self.is_installed(font_verb).unwrap_or(false) {
-                info!("{} is already installed, skipping", font_verb);
+            if !self.config.force && self.is_installed(called_verb).unwrap_or(false) {
+                info!("{} is already installed, skipping", called_verb);
+                already_installed.push(called_verb);
                 continue;
             }
 
-            // Install individual font verb (using install_verb_internal to avoid re-checking and logging)
-            if let Err(e) = self.install_verb_internal(font_verb).await {
-                warn!(
-                    "Warning: Failed to install {} (may not be critical): {}",
-                    font_verb, e
-                );
-                // Continue installing other fonts even if one fails
-            } else {
-                info!("Successfully installed {}", font_verb);
+            // Boxed: install_verb_internal() may recurse back into here for nested meta-verbs
+            match Box::pin(self.install_verb_internal(called_verb)).await {
+                Ok(()) => {
+                    info!("Successfully installed {}", called_verb);
+                    installed.push(called_verb);
+                }
+                Err(e) if !meta_verb => {
+                    self.calling.pop();
+                    return Err(e).with_context(|| {
+                        format!(
+                            "While installing {}, needed by {}",
+                            called_verb, metadata.name
+                        )
+                    });
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to install {}, continuing with the rest of {}: {}",
+                        called_verb, metadata.name, e
+                    );
+                    failures.push((called_verb.clone(), e));
+                }
             }
         }
+        self.calling.pop();
 
-        // Create marker file after all fonts are installed
-        // Original winetricks: touch "${W_FONTSDIR_UNIX}/allfonts.installed"
-        let wineprefix = self.config.wineprefix();
-        let windows_dir = wineprefix.join("drive_c/windows");
-        let fonts_dir_unix = if windows_dir.join("Fonts").exists() {
-            windows_dir.join("Fonts")
-        } else if windows_dir.join("fonts").exists() {
-            windows_dir.join("fonts")
-        } else {
-            // Create Fonts directory if it doesn't exist
-            let fonts_dir = windows_dir.join("Fonts");
-            fs::create_dir_all(&fonts_dir)?;
-            fonts_dir
-        };
-
-        let marker_file = fonts_dir_unix.join("allfonts.installed");
-        fs::File::create(&marker_file)?;
-        info!("Created allfonts marker file: {:?}", marker_file);
+        if installed.is_empty() && already_installed.is_empty() && !failures.is_empty() {
+            let details: Vec<String> = failures
+                .iter()
+                .map(|(verb, e)| format!("{}: {}", verb, e))
+                .collect();
+            return Err(WinetricksError::Verb(format!(
+                "{}: nothing could be installed ({})",
+                metadata.name,
+                details.join("; ")
+            )));
+        }
+        if !failures.is_empty() {
+            // Partial success: say exactly what is and isn't there
+            println!(
+                "{} partially installed ({} of {} components):",
+                metadata.name,
+                installed.len() + already_installed.len(),
+                calls.len()
+            );
+            if !installed.is_empty() {
+                println!("  Installed:         {}", installed.join(", "));
+            }
+            if !already_installed.is_empty() {
+                println!("  Already installed: {}", already_installed.join(", "));
+            }
+            for (verb, e) in &failures {
+                println!("  Failed:            {}: {}", verb, e);
+            }
+        }
 
-        // Log allfonts installation
-        self.log_installation("allfonts")?;
+        // Meta-verbs record themselves with a marker file
+        // Original winetricks: touch "${W_FONTSDIR_UNIX}/corefonts.installed"
+        if let Some(ref installed_file) = metadata.installed_file {
+            if installed_file.ends_with(".installed") {
+                let candidates = self.prefix_path_candidates(installed_file);
+                let marker = candidates
+                    .iter()
+                    .find(|path| path.parent().map(|p| p.is_dir()).unwrap_or(false))
+                    .or(candidates.first());
+                if let Some(marker) = marker {
+                    if let Some(parent) = marker.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::File::create(marker)?;
+                    info!("Created {} marker file: {:?}", metadata.name, marker);
+                }
+            }
+        }
 
         Ok(())
     }
@@ -3550,9 +4731,15 @@ impl Executor {
         let cache_dir = self.config.cache_dir.join(verb_name);
         fs::create_dir_all(&cache_dir)?;
 
-        // Get latest GitHub release URL
-        info!("Getting latest {} release from GitHub...", repo);
-        let release_url = self.get_github_latest_release(org, repo).await?;
+        // Get the release URL (latest, or the tag pinned in config.toml)
+        let pinned_tag = self.config.github_pins.get(verb_name).cloned();
+        match pinned_tag {
+            Some(ref tag) => info!("Getting {} release {} from GitHub...", repo, tag),
+            None => info!("Getting latest {} release from GitHub...", repo),
+        }
+        let release_url = self
+            .get_github_release(org, repo, pinned_tag.as_deref())
+            .await?;
 
         // Extract filename from URL (e.g., "vkd3d-proton-2.8.tar.zst" or "vkd3d-proton-2.8.tar.gz")
         let filename = release_url
@@ -3565,8 +4752,9 @@ impl Executor {
         // Download the release
         info!("Downloading {} from: {}", repo, release_url);
         self.downloader
-            .download(&release_url, &archive_file, None, true)
-            .await?;
+            .download(&release_url, &archive_file, None, true, None, None)
+            .await
+            .with_context(|| format!("While downloading {}", filename))?;
 
         // Extract archive file
         info!("Extracting {} archive...", repo);
@@ -3577,7 +4765,10 @@ impl Executor {
         fs::create_dir_all(&extract_dir)?;
 
         // Handle different archive formats
-        let status = if filename.ends_with(".tar.zst") {
+        let (command, output) = if filename.ends_with(".zip") {
+            self.extract_zip(&archive_file, &extract_dir)?;
+            (String::new(), None)
+        } else if filename.ends_with(".tar.zst") {
             // Use zstd to decompress, then tar to extract
             // zstd -d <file.tar.zst | tar xf - -C <dest>
             eprintln!(
@@ -3594,13 +4785,9 @@ impl Executor {
                 .arg(&archive_file)
                 .stdout(std::process::Stdio::piped());
 
-            let mut zstd_process =
-                zstd_cmd
-                    .spawn()
-                    .map_err(|e| WinetricksError::CommandExecution {
-                        command: format!("zstd -d -c {:?}", archive_file),
-                        error: e.to_string(),
-                    })?;
+            let mut zstd_process = zstd_cmd.spawn().map_err(|e| {
+                WinetricksError::command_io(format!("zstd -d -c {:?}", archive_file), e)
+            })?;
 
             let mut tar_cmd = Command::new("tar");
             tar_cmd
@@ -3612,17 +4799,15 @@ impl Executor {
                     WinetricksError::Config("Failed to create pipe for zstd".into())
                 })?);
 
-            let tar_output = tar_cmd
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!("tar xf - -C {:?}", extract_dir),
-                    error: e.to_string(),
-                })?;
+            let command = format!("tar xf - -C {:?}", extract_dir);
+            let output = tar_cmd
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
             // Wait for zstd to finish
             let _ = zstd_process.wait();
 
-            tar_output
+            (command, Some(output))
         } else if filename.ends_with(".tar.gz") {
             // Use tar to extract (tar xzf)
             eprintln!(
@@ -3630,28 +4815,27 @@ impl Executor {
                 archive_file.to_string_lossy(),
                 extract_dir.to_string_lossy()
             );
-            Command::new("tar")
+            let command = format!("tar xzf {:?} -C {:?}", archive_file, extract_dir);
+            let output = Command::new("tar")
                 .arg("xzf")
                 .arg(&archive_file)
                 .arg("-C")
                 .arg(&extract_dir)
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!("tar xzf {:?} -C {:?}", archive_file, extract_dir),
-                    error: e.to_string(),
-                })?
+                .output()
+                .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
+            (command, Some(output))
         } else {
             return Err(WinetricksError::Verb(format!(
-                "Unsupported archive format: {} (expected .tar.zst or .tar.gz)",
+                "Unsupported archive format: {} (expected .tar.zst, .tar.gz or .zip)",
                 filename
             )));
         };
 
-        if !status.success() {
-            return Err(WinetricksError::Verb(format!(
-                "Failed to extract {} archive",
-                repo
-            )));
+        if let Some(output) = output {
+            if !output.status.success() {
+                warn!("Failed to extract {} archive", repo);
+                return Err(WinetricksError::command_failed(command, &output));
+            }
         }
 
         // Find DLL files in extracted directory
@@ -3665,25 +4849,33 @@ impl Executor {
             .map(|a| a == "win64")
             .unwrap_or(false);
 
-        // Find the extracted directory (should be <repo>-* or similar)
-        let extracted_dirs: Vec<_> = fs::read_dir(&extract_dir)?
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_dir())
-            .collect();
-
-        if extracted_dirs.is_empty() {
-            return Err(WinetricksError::Verb(format!(
-                "No extracted directory found in {} archive",
-                repo
-            )));
-        }
-
-        let extracted_dir = &extracted_dirs[0].path();
-
         // Try different directory structures (x32/x64, x86/x64, win32/win64, etc.)
         let arch_dirs_32 = vec!["x32", "x86", "win32", "32"];
         let arch_dirs_64 = vec!["x64", "amd64", "win64", "64"];
 
+        // Find the extracted directory (should be <repo>-* or similar; some zip
+        // archives have the architecture directories at the top level instead)
+        let has_arch_dirs = |dir: &Path| {
+            arch_dirs_32
+                .iter()
+                .chain(arch_dirs_64.iter())
+                .any(|arch_dir| dir.join(arch_dir).is_dir())
+        };
+        let extracted_dir = if has_arch_dirs(&extract_dir) {
+            extract_dir.clone()
+        } else {
+            fs::read_dir(&extract_dir)?
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .find(|p| p.is_dir())
+                .ok_or_else(|| {
+                    WinetricksError::Verb(format!(
+                        "No extracted directory found in {} archive",
+                        repo
+                    ))
+                })?
+        };
+
         // Copy 32-bit DLLs to syswow64 (or system32 on 32-bit prefix)
         let dll_dest_32 = if is_win64 {
             wineprefix.join("drive_c/windows/syswow64")
@@ -3712,303 +4904,382 @@ impl Executor {
             let dll_dest_64 = wineprefix.join("drive_c/windows/system32");
             fs::create_dir_all(&dll_dest_64)?;
 
-            for arch_dir in &arch_dirs_64 {
-                let src_dir = extracted_dir.join(arch_dir);
-                if src_dir.exists() {
-                    for dll_name in dll_names {
-                        let src_dll = src_dir.join(dll_name);
-                        if src_dll.exists() {
-                            let dest_dll = dll_dest_64.join(dll_name);
-                            fs::copy(&src_dll, &dest_dll)?;
-                            info!("Copied {} to {:?}", dll_name, dest_dll);
-                        }
-                    }
-                    break;
-                }
-            }
-        }
-
-        // Set DLL overrides to native (strip .dll/.exe extension)
-        for dll_name in dll_names {
-            let dll_base = dll_name.trim_end_matches(".dll").trim_end_matches(".exe");
-            if let Err(e) = self.set_dll_override(dll_base, "native") {
-                warn!(
-                    "Warning: Failed to set DLL override for {}: {}",
-                    dll_base, e
-                );
-            }
-        }
-
-        // Log installation
-        self.log_installation(verb_name)?;
-
-        info!("Successfully installed {}", verb_name);
-        Ok(())
-    }
-
-    /// Install allcodecs (meta-verb that installs all codec components)
-    async fn install_allcodecs(&mut self) -> Result<()> {
-        // List of codec verbs (dirac, ffdshow, icodecs, cinepak, l3codecx, xvid)
-        let codec_verbs = vec!["dirac", "ffdshow", "icodecs", "cinepak", "l3codecx", "xvid"];
-
-        info!("Installing allcodecs components...");
-
-        for codec_verb in &codec_verbs {
-            if !self.config.force && self.is_installed(codec_verb).unwrap_or(false) {
-                info!("{} is already installed, skipping", codec_verb);
-                continue;
-            }
-
-            if let Err(e) = self.install_verb_internal(codec_verb).await {
-                warn!(
-                    "Warning: Failed to install {} (may not be critical): {}",
-                    codec_verb, e
-                );
-            } else {
-                info!("Successfully installed {}", codec_verb);
-            }
-        }
-
-        self.log_installation("allcodecs")?;
-        Ok(())
-    }
-
-    /// Install cjkfonts (meta-verb that installs CJK font components)
-    async fn install_cjkfonts(&mut self) -> Result<()> {
-        // List of CJK font verbs
-        let cjk_verbs = vec![
-            "baekmuk",
-            "fakechinese",
-            "fakejapanese",
-            "fakejapanese_ipamona",
-            "fakejapanese_vlgothic",
-            "fakekorean",
-            "ipamona",
-            "meiryo",
-            "sourcehansans",
-            "takao",
-            "vlgothic",
-            "wenquanyi",
-            "wenquanyizenhei",
-        ];
-
-        info!("Installing cjkfonts components...");
-
-        for font_verb in &cjk_verbs {
-            if !self.config.force && self.is_installed(font_verb).unwrap_or(false) {
-                info!("{} is already installed, skipping", font_verb);
-                continue;
-            }
-
-            if let Err(e) = self.install_verb_internal(font_verb).await {
-                warn!(
-                    "Warning: Failed to install {} (may not be critical): {}",
-                    font_verb, e
-                );
-            } else {
-                info!("Successfully installed {}", font_verb);
-            }
-        }
-
-        self.log_installation("cjkfonts")?;
-        Ok(())
-    }
-
-    /// Install pptfonts (meta-verb that installs PowerPoint font components)
-    async fn install_pptfonts(&mut self) -> Result<()> {
-        // PowerPoint fonts are typically: calibri, cambria, candara, consolas, constantia, corbel
-        let ppt_verbs = vec![
-            "calibri",
-            "cambria",
-            "candara",
-            "consolas",
-            "constantia",
-            "corbel",
-        ];
-
-        info!("Installing pptfonts components...");
-
-        for font_verb in &ppt_verbs {
-            if !self.config.force && self.is_installed(font_verb).unwrap_or(false) {
-                info!("{} is already installed, skipping", font_verb);
-                continue;
+            for arch_dir in &arch_dirs_64 {
+                let src_dir = extracted_dir.join(arch_dir);
+                if src_dir.exists() {
+                    for dll_name in dll_names {
+                        let src_dll = src_dir.join(dll_name);
+                        if src_dll.exists() {
+                            let dest_dll = dll_dest_64.join(dll_name);
+                            fs::copy(&src_dll, &dest_dll)?;
+                            info!("Copied {} to {:?}", dll_name, dest_dll);
+                        }
+                    }
+                    break;
+                }
             }
+        }
 
-            if let Err(e) = self.install_verb_internal(font_verb).await {
+        // Set DLL overrides to native (strip .dll/.exe extension)
+        for dll_name in dll_names {
+            let dll_base = dll_name.trim_end_matches(".dll").trim_end_matches(".exe");
+            if let Err(e) = self.set_dll_override(dll_base, "native") {
                 warn!(
-                    "Warning: Failed to install {} (may not be critical): {}",
-                    font_verb, e
+                    "Warning: Failed to set DLL override for {}: {}",
+                    dll_base, e
                 );
-            } else {
-                info!("Successfully installed {}", font_verb);
             }
         }
 
-        self.log_installation("pptfonts")?;
+        // Log installation
+        self.log_installation(verb_name)?;
+
+        info!("Successfully installed {}", verb_name);
         Ok(())
     }
 
-    /// Install settings verb (Windows version, registry tweaks, etc.)
+    /// Install mspaint from the Windows XP security update KB978706
+    /// Extracts mspaint.exe with cabextract (the update is a CAB self-extractor), copies
+    /// it and the MFC DLLs it needs into system32, and adds a Start Menu shortcut
     async fn install_mspaint(&mut self) -> Result<()> {
         use std::fs;
-        use std::process::Command;
-        use which::which;
 
-        info!("Installing mspaint (Windows Update installer)");
+        info!("Installing mspaint (Windows Update package)");
 
-        // Load metadata from registry
         let metadata = self
             .registry
             .get("mspaint")
-            .ok_or_else(|| WinetricksError::VerbNotFound("mspaint".to_string()))?
+            .ok_or_else(|| WinetricksError::VerbNotFound {
+                verb: "mspaint".to_string(),
+                suggestions: Vec::new(),
+            })?
             .clone();
+        let file_info = metadata.files.first().ok_or_else(|| {
+            WinetricksError::Verb("mspaint has no download in its metadata".into())
+        })?;
+
         let cache_dir = self.config.cache_dir.join("mspaint");
         fs::create_dir_all(&cache_dir)?;
+        let package = cache_dir.join(&file_info.filename);
+        if !package.exists() {
+            let url = file_info
+                .url
+                .as_ref()
+                .ok_or_else(|| WinetricksError::Verb("mspaint file has no URL".into()))?;
+            info!("Downloading mspaint update package...");
+            self.downloader
+                .download(
+                    url,
+                    &package,
+                    file_info.sha256.as_deref(),
+                    true,
+                    None,
+                    self.config.country.as_deref(),
+                )
+                .await
+                .with_context(|| format!("While downloading {}", file_info.filename))?;
+        }
 
-        // Download file
-        let file_info = &metadata.files[0];
-        let file_path = cache_dir.join(&file_info.filename);
+        let cabextract = self.cabextract()?;
 
-        if !file_path.exists() {
-            if let Some(ref url) = file_info.url {
-                info!("Downloading mspaint installer...");
-                self.downloader
-                    .download(url, &file_path, file_info.sha256.as_deref(), true)
-                    .await?;
-            } else {
-                return Err(WinetricksError::Verb("mspaint file has no URL".into()));
+        // mspaint.exe is 32-bit: syswow64 on win64 (W_SYSTEM32_DLLS), system32 otherwise
+        let windows_dir = self.config.wineprefix().join("drive_c/windows");
+        let system32 = if self.prefix_is_win64()? {
+            windows_dir.join("syswow64")
+        } else {
+            windows_dir.join("system32")
+        };
+        fs::create_dir_all(&system32)?;
+
+        // XP's mspaint needs mfc42(u).dll (Wine bug 657), which vcrun6's vcredist.exe ships
+        let vcredist = self.config.cache_dir.join("vcrun6").join("vcredist.exe");
+        if vcredist.exists() {
+            info!("Extracting mfc42*.dll from {}", vcredist.display());
+            if let Err(e) = Self::run_cabextract(&cabextract, &system32, "mfc42*.dll", &vcredist) {
+                warn!("Failed to extract mfc42*.dll (may not be critical): {}", e);
             }
+        } else if !system32.join("mfc42u.dll").exists() {
+            warn!("mfc42u.dll not found; install vcrun6 if mspaint doesn't start");
         }
 
-        let wineprefix = self.config.wineprefix();
-        let wineprefix_str = wineprefix.to_string_lossy().to_string();
+        // Start from an empty directory so files of an earlier run aren't picked up
+        let temp_dir = cache_dir.join("mspaint_extract");
+        if temp_dir.exists() {
+            fs::remove_dir_all(&temp_dir)?;
+        }
+        fs::create_dir_all(&temp_dir)?;
 
-        // Step 1: Extract mfc42*.dll from vcredist.exe (if vcrun6 is available)
-        let vcrun6_cache = self.config.cache_dir.join("vcrun6");
-        let vcredist_exe = vcrun6_cache.join("vcredist.exe");
-        if vcredist_exe.exists() {
-            let cabextract = which("cabextract")
-                .map_err(|_| WinetricksError::Config("cabextract not found".into()))?;
+        // Original winetricks: w_try "$WINE" "$file1" /q /x:"$W_TMP_WIN"/"$file1"
+        Self::run_cabextract(&cabextract, &temp_dir, "*mspaint.exe", &package)?;
 
-            let syswow64 = wineprefix.join("drive_c/windows/syswow64");
-            fs::create_dir_all(&syswow64)?;
+        // Like the original winetricks, prefer the GDR (security fixes only) build
+        let extracted = ["sp3gdr/mspaint.exe", "sp3qfe/mspaint.exe", "mspaint.exe"]
+            .iter()
+            .map(|p| temp_dir.join(p))
+            .find(|p| p.exists())
+            .ok_or_else(|| {
+                WinetricksError::Verb(format!("mspaint.exe not found in {}", package.display()))
+            })?;
 
-            info!("Extracting mfc42*.dll from vcredist.exe...");
-            eprintln!(
-                "Executing cabextract -q {} -d {} -F mfc42*.dll",
-                vcredist_exe.to_string_lossy(),
-                syswow64.to_string_lossy()
-            );
+        let dest = system32.join("mspaint.exe");
+        fs::copy(&extracted, &dest)?;
+        info!("Copied mspaint.exe to {}", dest.display());
 
-            let status = Command::new(&cabextract)
-                .arg("-q")
-                .arg(&vcredist_exe)
-                .arg("-d")
-                .arg(&syswow64)
-                .arg("-F")
-                .arg("mfc42*.dll")
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!(
-                        "cabextract -q {} -d {} -F mfc42*.dll",
-                        vcredist_exe.to_string_lossy(),
-                        syswow64.to_string_lossy()
-                    ),
-                    error: e.to_string(),
-                })?;
+        if !self.config.no_clean {
+            let _ = fs::remove_dir_all(&temp_dir);
+        }
 
-            if !status.success() {
-                warn!("Warning: Failed to extract mfc42*.dll (may not be critical)");
-            }
+        // Start Menu > Programs > Accessories > Paint (winemenubuilder turns it into a
+        // .desktop entry)
+        let shortcut = self.config.wineprefix().join(
+            "drive_c/ProgramData/Microsoft/Windows/Start Menu/Programs/Accessories/Paint.lnk",
+        );
+        let target = format!(
+            "C:\\windows\\{}\\mspaint.exe",
+            system32.file_name().unwrap_or_default().to_string_lossy()
+        );
+        if let Err(e) = Self::write_shortcut(&shortcut, &target) {
+            warn!("Failed to create mspaint shortcut: {}", e);
         }
 
-        // Step 2: Extract Windows Update installer with /q /x:
-        let temp_dir = wineprefix.join("drive_c/windows/temp");
-        fs::create_dir_all(&temp_dir)?;
-        let extract_dest = temp_dir.join(&file_info.filename);
+        self.log_installation("mspaint")?;
+        Ok(())
+    }
+
+    /// Apply a settings verb (w_metadata ... settings)
+    /// Windows version verbs go through winecfg, everything else is a registry tweak
+    async fn install_settings_verb(
+        &mut self,
+        verb_name: &str,
+        _metadata: &VerbMetadata,
+    ) -> Result<()> {
+        // Windows version verbs: map winetricks verb names to winecfg -v names
+        // Original winetricks: winver verbs call w_set_winver with the verb name,
+        // except the server releases which use winecfg's own spelling
+        let win_version = match verb_name {
+            "win2k3" => Some("win2003"),
+            "win2k8" => Some("win2008"),
+            "win2k8r2" => Some("win2008r2"),
+            "win11" | "win10" | "win81" | "win8" | "win7" | "vista" | "winxp" | "win2k"
+            | "winme" | "win98" | "win95" | "nt40" | "nt351" | "win31" | "win30" | "win20" => {
+                Some(verb_name)
+            }
+            _ => None,
+        };
 
-        let file_win_path = self.unix_to_wine_path(&file_path)?;
-        let extract_dest_win = self.unix_to_wine_path(&extract_dest)?;
+        if let Some(win_version) = win_version {
+            self.set_windows_version(win_version)?;
+            self.log_installation(verb_name)?;
+            return Ok(());
+        }
 
-        info!("Extracting Windows Update installer...");
-        eprintln!(
-            "Executing wine {} /q /x:{}",
-            file_win_path, extract_dest_win
-        );
+        // key=value settings (ddr=opengl, videomemorysize=512, csmt=off, ...)
+        if let Some((key, value)) = verb_name.split_once('=') {
+            const D3D_KEY: &str = "HKEY_CURRENT_USER\\Software\\Wine\\Direct3D";
+            const X11_KEY: &str = "HKEY_CURRENT_USER\\Software\\Wine\\X11 Driver";
 
-        let mut extract_cmd = std::process::Command::new(&self.wine.wine_bin);
-        extract_cmd.env("WINEPREFIX", &wineprefix_str);
-        extract_cmd.current_dir(&cache_dir);
-        extract_cmd.arg(&file_win_path);
-        extract_cmd.arg("/q");
-        extract_cmd.arg("/x:").arg(&extract_dest_win);
+            match (key, value) {
+                ("renderer", _) => {
+                    let renderer = if value == "default" {
+                        None
+                    } else {
+                        Some(value)
+                    };
+                    self.config.set_renderer_in_registry(renderer)?;
+                }
+                ("ddr", "gdi" | "opengl") => {
+                    self.set_registry_value(D3D_KEY, "DirectDrawRenderer", "REG_SZ", value)?;
+                }
+                ("videomemorysize" | "videomemsize", "default") => {
+                    self.delete_registry_value(D3D_KEY, "VideoMemorySize")?;
+                }
+                ("videomemorysize" | "videomemsize", size) if size.parse::<u32>().is_ok() => {
+                    self.set_registry_value(D3D_KEY, "VideoMemorySize", "REG_SZ", size)?;
+                }
+                ("csmt", "on" | "off") => {
+                    let enabled = if value == "on" { "1" } else { "0" };
+                    self.set_registry_value(D3D_KEY, "csmt", "REG_DWORD", enabled)?;
+                }
+                ("orm", "backbuffer" | "fbo") => {
+                    self.set_registry_value(D3D_KEY, "OffscreenRenderingMode", "REG_SZ", value)?;
+                }
+                ("glsl", "enabled" | "disabled") => {
+                    self.set_registry_value(D3D_KEY, "UseGLSL", "REG_SZ", value)?;
+                }
+                ("multisampling", "enabled" | "disabled") => {
+                    self.set_registry_value(D3D_KEY, "Multisampling", "REG_SZ", value)?;
+                }
+                ("grabfullscreen" | "usetakefocus", "y" | "n") => {
+                    let name = if key == "grabfullscreen" {
+                        "GrabFullscreen"
+                    } else {
+                        "UseTakeFocus"
+                    };
+                    let data = if value == "y" { "Y" } else { "N" };
+                    self.set_registry_value(X11_KEY, name, "REG_SZ", data)?;
+                }
+                ("hidewineexports", "enable" | "disable") => {
+                    let data = if value == "enable" { "Y" } else { "N" };
+                    self.set_registry_value(
+                        "HKEY_CURRENT_USER\\Software\\Wine",
+                        "HideWineExports",
+                        "REG_SZ",
+                        data,
+                    )?;
+                }
+                _ => {
+                    // Skipped rather than failed (and not logged as installed), so one
+                    // unknown setting doesn't stop the rest of a command line
+                    warn!(
+                        "Skipping settings verb {}: unsupported value for setting '{}'",
+                        verb_name, key
+                    );
+                    return Ok(());
+                }
+            }
 
-        let extract_status =
-            extract_cmd
-                .status()
-                .map_err(|e| WinetricksError::CommandExecution {
-                    command: format!("wine {} /q /x:{}", file_win_path, extract_dest_win),
-                    error: e.to_string(),
-                })?;
+            info!("Applied setting {}", verb_name);
+            self.log_installation(verb_name)?;
+            return Ok(());
+        }
 
-        if !extract_status.success() {
-            return Err(WinetricksError::Verb(
-                "Failed to extract Windows Update installer".into(),
-            ));
+        match verb_name {
+            "nocrashdialog" => {
+                // Original winetricks: load_nocrashdialog
+                self.set_registry_value(
+                    "HKEY_CURRENT_USER\\Software\\Wine\\WineDbg",
+                    "ShowCrashDialog",
+                    "REG_DWORD",
+                    "0",
+                )?;
+            }
+            "heapcheck" => {
+                // Original winetricks: load_heapcheck
+                self.set_registry_value(
+                    "HKEY_LOCAL_MACHINE\\System\\CurrentControlSet\\Control\\Session Manager",
+                    "GlobalFlag",
+                    "REG_DWORD",
+                    "0x00200030",
+                )?;
+            }
+            "forcemono" => {
+                // Original winetricks: load_forcemono
+                self.set_dll_override("mscoree", "native")?;
+                self.set_dll_override("mscorsvw.exe", "")?;
+            }
+            "native_mdac" => {
+                // Original winetricks: load_native_mdac
+                for dll in ["odbc32", "odbccp32", "oledb32"] {
+                    self.set_dll_override(dll, "native,builtin")?;
+                }
+            }
+            "native_oleaut32" => {
+                self.set_dll_override("oleaut32", "native,builtin")?;
+            }
+            "isolate_home" | "sandbox" => {
+                // Original winetricks: w_skip_windows ... then replaces the
+                // profile folder symlinks with plain directories
+                self.unlink_user_dirs()?;
+                if verb_name == "sandbox" {
+                    // sandbox also removes the Z: drive mapping to /
+                    let z_drive = self.config.wineprefix().join("dosdevices/z:");
+                    if z_drive.is_symlink() {
+                        std::fs::remove_file(&z_drive)?;
+                        info!("Removed {:?}", z_drive);
+                    }
+                }
+            }
+            "fontfix" => {
+                // Original winetricks only checks for known-broken host fonts
+                info!("fontfix: nothing to change in this wineprefix");
+            }
+            "good" => {
+                // Test verb that always succeeds
+            }
+            "bad" => {
+                // Test verb that always fails
+                return Err(WinetricksError::Verb("bad: this verb always fails".into()));
+            }
+            _ => {
+                warn!(
+                    "Skipping settings verb {}: it has no handler in this version of winetricks",
+                    verb_name
+                );
+                return Ok(());
+            }
         }
 
-        // Step 3: Copy mspaint.exe from extracted directory
-        let extracted_mspaint = extract_dest.join("SP3GDR/mspaint.exe");
-        let dest_mspaint = wineprefix.join("drive_c/windows/mspaint.exe");
+        info!("Applied setting {}", verb_name);
+        self.log_installation(verb_name)?;
+        Ok(())
+    }
 
-        if !extracted_mspaint.exists() {
-            return Err(WinetricksError::Verb(
-                "mspaint.exe not found in extracted files".into(),
+    /// Write a registry value with `wine reg add`
+    fn set_registry_value(
+        &self,
+        key: &str,
+        name: &str,
+        value_type: &str,
+        data: &str,
+    ) -> Result<()> {
+        let wineprefix = self.config.wineprefix();
+        let wineprefix_str = wineprefix.to_string_lossy().to_string();
+
+        let output = self.wine.run_and_capture(
+            &[
+                "reg", "add", key, "/v", name, "/t", value_type, "/d", data, "/f",
+            ],
+            &[("WINEPREFIX", &wineprefix_str)],
+            None,
+        )?;
+
+        if !output.success() {
+            return Err(WinetricksError::wine_command_failed(
+                format!(
+                    "wine reg add \"{}\" /v {} /t {} /d {} /f",
+                    key, name, value_type, data
+                ),
+                &output,
             ));
         }
+        Ok(())
+    }
 
-        fs::create_dir_all(dest_mspaint.parent().unwrap())?;
-        fs::copy(&extracted_mspaint, &dest_mspaint)?;
-        info!("Copied mspaint.exe to {:?}", dest_mspaint);
+    /// Delete a registry value with `wine reg delete`
+    /// A missing value is not an error (it already has Wine's default)
+    fn delete_registry_value(&self, key: &str, name: &str) -> Result<()> {
+        let wineprefix = self.config.wineprefix();
+        let wineprefix_str = wineprefix.to_string_lossy().to_string();
 
-        self.log_installation("mspaint")?;
+        self.wine.run_and_capture(
+            &["reg", "delete", key, "/v", name, "/f"],
+            &[("WINEPREFIX", &wineprefix_str)],
+            None,
+        )?;
         Ok(())
     }
 
-    async fn install_settings_verb(
-        &mut self,
-        verb_name: &str,
-        _metadata: &VerbMetadata,
-    ) -> Result<()> {
-        // Handle Windows version settings
-        if verb_name.starts_with("win") {
-            let version = verb_name.strip_prefix("win").unwrap_or(verb_name);
-            // Map verb names to Windows version strings
-            let win_version = match version {
-                "7" => "win7",
-                "8" => "win8",
-                "81" => "win8.1",
-                "10" => "win10",
-                "11" => "win11",
-                "xp" => "winxp",
-                "2k" => "win2k",
-                "95" => "win95",
-                "98" => "win98",
-                "me" => "winme",
-                _ => version,
-            };
-            self.set_windows_version(win_version)?;
-            info!("Set Windows version to: {}", win_version);
-        } else {
-            // Other settings verbs (fontfix, forcemono, etc.) may need specific handling
-            // For now, just log the installation
-            info!(
-                "Settings verb {} installed (specific handling may be needed)",
-                verb_name
-            );
+    /// Replace symlinks in the Wine user profile (Desktop, Documents, ...) with real directories
+    /// Used by isolate_home and sandbox so the prefix no longer writes into $HOME
+    fn unlink_user_dirs(&self) -> Result<()> {
+        use std::fs;
+
+        let users_dir = self.config.wineprefix().join("drive_c/users");
+        let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+        let profile = users_dir.join(&user);
+        if !profile.is_dir() {
+            warn!("User profile {:?} not found, nothing to isolate", profile);
+            return Ok(());
         }
 
-        self.log_installation(verb_name)?;
+        for entry in fs::read_dir(&profile)? {
+            let path = entry?.path();
+            if path.is_symlink() {
+                fs::remove_file(&path)?;
+                fs::create_dir_all(&path)?;
+                info!("Replaced symlink {:?} with a directory", path);
+            }
+        }
         Ok(())
     }
 
@@ -4133,13 +5404,13 @@ impl Executor {
     fn append_path(&self, new_path: &str) -> Result<()> {
         use std::fs;
         use std::io::Write;
-        use std::process::Command;
 
         let wineprefix = self.config.wineprefix();
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
 
         // Get current PATH from registry
-        let output = Command::new(&self.wine.wine_bin)
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("reg")
             .arg("query")
             .arg("HKLM\\System\\CurrentControlSet\\Control\\Session Manager\\Environment")
@@ -4147,10 +5418,7 @@ impl Executor {
             .arg("PATH")
             .env("WINEPREFIX", &wineprefix_str)
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: "wine reg query for PATH".to_string(),
-                error: e.to_string(),
-            })?;
+            .map_err(|e| WinetricksError::command_io("wine reg query for PATH", e))?;
 
         // Parse current PATH (handle both REG_SZ and REG_EXPAND_SZ)
         let current_path = String::from_utf8_lossy(&output.stdout);
@@ -4199,7 +5467,8 @@ impl Executor {
     }
 
     /// Common DLL override handling (matching w_common_override_dll behavior)
-    /// Removes wine builtin manifests for specific packages (comctl32, vcrun2005)
+    /// Removes conflicting wine builtin manifests from winsxs, sets the overrides, and on
+    /// win64 prefixes mirrors them under Wow6432Node so 32-bit and 64-bit loading agree
     fn common_override_dll(
         &self,
         verb_name: &str,
@@ -4207,44 +5476,40 @@ impl Executor {
         dll_names: &[&str],
     ) -> Result<()> {
         use std::fs;
+        use std::io::Write;
 
         let wineprefix = self.config.wineprefix();
         let windows_dir = wineprefix.join("drive_c/windows");
 
-        // Remove wine builtin manifests for specific packages (original winetricks does this)
-        match verb_name {
-            "comctl32" => {
-                // Remove comctl32 manifests
-                let manifest_paths = vec![
-                    windows_dir.join("winsxs/manifests/amd64_microsoft.windows.common-controls_6595b64144ccf1df_6.0.2600.2982_none_deadbeef.manifest"),
-                    windows_dir.join("winsxs/manifests/x86_microsoft.windows.common-controls_6595b64144ccf1df_6.0.2600.2982_none_deadbeef.manifest"),
-                ];
-
-                for manifest_path in manifest_paths {
-                    if manifest_path.exists() {
-                        info!("Removing wine builtin manifest: {:?}", manifest_path);
-                        let _ = fs::remove_file(&manifest_path);
-                    }
-                }
-            }
-            "vcrun2005" => {
-                // Remove vcrun2005 manifests
-                let manifest_paths = vec![
-                    windows_dir.join("winsxs/manifests/amd64_microsoft.vc80.atl_1fc8b3b9a1e18e3b_8.0.50727.4053_none_deadbeef.manifest"),
-                    windows_dir.join("winsxs/manifests/amd64_microsoft.vc80.crt_1fc8b3b9a1e18e3b_8.0.50727.4053_none_deadbeef.manifest"),
-                    windows_dir.join("winsxs/manifests/x86_microsoft.vc80.atl_1fc8b3b9a1e18e3b_8.0.50727.4053_none_deadbeef.manifest"),
-                ];
-
-                for manifest_path in manifest_paths {
-                    if manifest_path.exists() {
-                        info!("Removing wine builtin manifest: {:?}", manifest_path);
-                        let _ = fs::remove_file(&manifest_path);
+        // Side-by-side assemblies whose wine builtin manifests shadow the native DLLs
+        let mut assemblies: Vec<&str> = dll_names
+            .iter()
+            .filter_map(|dll| match *dll {
+                "comctl32" => Some("microsoft.windows.common-controls"),
+                "msvcr80" | "msvcp80" => Some("microsoft.vc80.crt"),
+                "mfc80" => Some("microsoft.vc80.mfc"),
+                "atl80" => Some("microsoft.vc80.atl"),
+                _ => None,
+            })
+            .collect();
+        if verb_name == "vcrun2005" {
+            assemblies.push("microsoft.vc80.atl");
+        }
+
+        // Remove wine builtin manifests (wine marks them with a "deadbeef" public key hash)
+        let manifests_dir = windows_dir.join("winsxs/manifests");
+        if !assemblies.is_empty() {
+            if let Ok(entries) = fs::read_dir(&manifests_dir) {
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().to_lowercase();
+                    if name.contains("_deadbeef")
+                        && assemblies.iter().any(|assembly| name.contains(assembly))
+                    {
+                        info!("Removing wine builtin manifest: {:?}", entry.path());
+                        let _ = fs::remove_file(entry.path());
                     }
                 }
             }
-            _ => {
-                // No special manifest handling for other packages
-            }
         }
 
         // Set DLL overrides for all specified DLLs
@@ -4252,6 +5517,41 @@ impl Executor {
             self.set_dll_override(dll_name, override_type)?;
         }
 
+        // On win64, also set the overrides in the Wow6432Node view
+        let is_win64 = self
+            .config
+            .winearch
+            .as_ref()
+            .map(|a| a == "win64")
+            .unwrap_or(false);
+        if is_win64 && !dll_names.is_empty() {
+            let temp_dir = windows_dir.join("temp");
+            fs::create_dir_all(&temp_dir)?;
+            let reg_file = temp_dir.join("common_override_dll.reg");
+
+            let mut reg_content = String::from(
+                "REGEDIT4\n\n[HKEY_CURRENT_USER\\Software\\Wow6432Node\\Wine\\DllOverrides]\n",
+            );
+            for dll_name in dll_names {
+                reg_content.push_str(&format!("\"{}\"=\"{}\"\n", dll_name, override_type));
+            }
+
+            let mut file = fs::File::create(&reg_file)?;
+            file.write_all(reg_content.as_bytes())?;
+            file.sync_all()?;
+
+            let result = self.regedit64(&reg_file);
+            let _ = fs::remove_file(&reg_file);
+            result?;
+
+            info!(
+                "Set Wow6432Node DLL overrides for {}: {} = {}",
+                verb_name,
+                dll_names.join(" "),
+                override_type
+            );
+        }
+
         Ok(())
     }
 
@@ -4339,6 +5639,34 @@ impl Executor {
         Ok(should_apply)
     }
 
+    /// Warn about Wine bugs from the verb metadata that affect the current Wine version
+    fn warn_wine_bug_workarounds(&self, verb_name: &str, metadata: &VerbMetadata) {
+        for workaround in &metadata.wine_bug_workarounds {
+            let bug_number = workaround.bug_number.to_string();
+            let description = if workaround.description.is_empty() {
+                None
+            } else {
+                Some(workaround.description.as_str())
+            };
+
+            // No version range means the bug affects all Wine versions ("0," matches any)
+            let ranges: Vec<&str> = match workaround.affected_versions {
+                Some(ref versions) => versions.split_whitespace().collect(),
+                None => vec!["0,"],
+            };
+            let affected = self
+                .workaround_wine_bug(&bug_number, description, &ranges)
+                .unwrap_or(false);
+
+            if affected {
+                warn!(
+                    "{} is affected by Wine bug {} (https://bugs.winehq.org/show_bug.cgi?id={})",
+                    verb_name, workaround.bug_number, workaround.bug_number
+                );
+            }
+        }
+    }
+
     /// Helper function for VB6 SP6 extractions (matching helper_vb6sp6 behavior)
     /// Extracts specific files from VB6 SP6 archive to destination directory
     fn helper_vb6sp6(&self, dest_dir: &Path, files: &[&str]) -> Result<()> {
@@ -4365,29 +5693,24 @@ impl Executor {
 
     /// Remove all DLL overrides from registry (matching w_override_no_dlls behavior)
     pub fn override_no_dlls(&self) -> Result<()> {
-        use std::process::Command;
-
         let wineprefix = self.config.wineprefix();
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
 
         // Delete the entire DllOverrides registry key
         // Original winetricks: w_try_regedit /d "HKEY_CURRENT_USER\\Software\\Wine\\DllOverrides"
-        let status = Command::new(&self.wine.wine_bin)
+        let command = "wine regedit /d DllOverrides";
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("regedit")
             .arg("/S") // Silent mode
             .arg("/d")
             .arg("HKEY_CURRENT_USER\\Software\\Wine\\DllOverrides")
             .env("WINEPREFIX", &wineprefix_str)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: "wine regedit /d DllOverrides".to_string(),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command, e))?;
 
-        if !status.success() {
-            return Err(WinetricksError::Config(
-                "Failed to remove DLL overrides".into(),
-            ));
+        if !output.status.success() {
+            return Err(WinetricksError::command_failed(command, &output));
         }
 
         info!("Removed all DLL overrides");
@@ -4398,7 +5721,6 @@ impl Executor {
     pub fn override_dlls(&self, override_type: &str, dll_names: &[&str]) -> Result<()> {
         use std::fs;
         use std::io::Write;
-        use std::process::Command;
 
         // Handle disabled mode (empty string)
         let mode = if override_type == "disabled" {
@@ -4437,15 +5759,15 @@ impl Executor {
 
         // Convert to Wine path and import
         let reg_file_str = reg_file.to_string_lossy().to_string();
-        let output = Command::new(&self.wine.wine_bin)
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("winepath")
             .arg("-w")
             .arg(&reg_file_str)
             .env("WINEPREFIX", &wineprefix_str)
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine winepath -w {:?}", reg_file_str),
-                error: e.to_string(),
+            .map_err(|e| {
+                WinetricksError::command_io(format!("wine winepath -w {:?}", reg_file_str), e)
             })?;
 
         // Import registry file using regedit32/regedit64 (matching original winetricks)
@@ -4963,7 +6285,6 @@ impl Executor {
     ) -> Result<()> {
         use std::fs;
         use std::io::Write;
-        use std::process::Command;
 
         // Map mode shortcuts to full names
         let mode = match override_type {
@@ -5008,37 +6329,33 @@ impl Executor {
 
         // Convert to Wine path and import
         let reg_file_str = reg_file.to_string_lossy().to_string();
-        let output = Command::new(&self.wine.wine_bin)
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("winepath")
             .arg("-w")
             .arg(&reg_file_str)
             .env("WINEPREFIX", &wineprefix_str)
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine winepath -w {:?}", reg_file_str),
-                error: e.to_string(),
+            .map_err(|e| {
+                WinetricksError::command_io(format!("wine winepath -w {:?}", reg_file_str), e)
             })?;
 
         let reg_file_win = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
-        let status = Command::new(&self.wine.wine_bin)
+        let command = format!("wine regedit /S {:?}", reg_file_win);
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("regedit")
             .arg("/S")
             .arg(&reg_file_win)
             .env("WINEPREFIX", &wineprefix_str)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine regedit /S {:?}", reg_file_win),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
         let _ = fs::remove_file(&reg_file);
 
-        if !status.success() {
-            return Err(WinetricksError::Config(format!(
-                "Failed to set app DLL overrides for {}",
-                app_name
-            )));
+        if !output.status.success() {
+            return Err(WinetricksError::command_failed(command, &output));
         }
 
         info!(
@@ -5097,98 +6414,68 @@ impl Executor {
 
     /// Import registry file using 32-bit regedit (matching w_try_regedit32 behavior)
     pub fn regedit32(&self, reg_file: &Path) -> Result<()> {
-        use std::process::Command;
-
-        let wineprefix = self.config.wineprefix();
-        let wineprefix_str = wineprefix.to_string_lossy().to_string();
-
-        // Convert to Wine Windows path
-        let reg_file_win = self.unix_to_wine_path(reg_file)?;
-
-        // On win64, use syswow64\regedit.exe (32-bit regedit)
-        // On win32, use C:\windows\regedit.exe
-        // Original winetricks: w_try_regedit32 uses C:\windows\syswow64\regedit.exe for win64
-        let regedit_exe = if self
-            .config
-            .winearch
-            .as_ref()
-            .map(|a| a == "win64")
-            .unwrap_or(false)
-        {
-            // Use 32-bit regedit from syswow64 (matches original winetricks)
-            "C:\\windows\\syswow64\\regedit.exe".to_string()
+        // On win64 the 32-bit regedit lives in syswow64, on win32 in system32
+        let regedit_exe = if self.prefix_is_win64()? {
+            "C:\\windows\\syswow64\\regedit.exe"
         } else {
-            "C:\\windows\\regedit.exe".to_string()
+            "C:\\windows\\system32\\regedit.exe"
         };
 
-        let mut cmd = Command::new(&self.wine.wine_bin);
-        cmd.arg(&regedit_exe);
-
-        if self.config.unattended {
-            cmd.arg("/S"); // Silent mode
-        }
-
-        cmd.arg(&reg_file_win);
-        cmd.env("WINEPREFIX", &wineprefix_str);
-
-        let status = cmd
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine {} /S {:?}", regedit_exe, reg_file_win),
-                error: e.to_string(),
-            })?;
-
-        if !status.success() {
-            return Err(WinetricksError::Config(
-                "Failed to import registry file (32-bit)".into(),
-            ));
-        }
-
-        Ok(())
+        self.run_regedit(&self.wine.wine_bin, "wine", regedit_exe, reg_file)
     }
 
     /// Import registry file using 64-bit regedit (matching w_try_regedit64 behavior)
     pub fn regedit64(&self, reg_file: &Path) -> Result<()> {
-        use std::process::Command;
-        use which::which;
-
-        let wineprefix = self.config.wineprefix();
-        let wineprefix_str = wineprefix.to_string_lossy().to_string();
-
-        // Convert to Wine Windows path
-        let reg_file_win = self.unix_to_wine_path(reg_file)?;
-
-        // Find wine64 binary
-        let wine64_bin = if let Some(wine_bin) = self.wine.wine_bin.parent() {
-            wine_bin.join("wine64")
-        } else {
-            which("wine64").map_err(|_| WinetricksError::Config("wine64 not found".into()))?
+        // WoW64 builds of Wine (9.0+) have no wine64; wine runs 64-bit programs itself
+        let wine64_bin = self
+            .wine
+            .wine_bin
+            .parent()
+            .map(|dir| dir.join("wine64"))
+            .filter(|bin| bin.is_file())
+            .or_else(|| which::which("wine64").ok());
+        let (wine_bin, wine_name) = match wine64_bin {
+            Some(ref bin) => (bin, "wine64"),
+            None => (&self.wine.wine_bin, "wine"),
         };
 
-        // Use C:\windows\regedit.exe via wine64 (64-bit)
-        let regedit_exe = "C:\\windows\\regedit.exe";
+        self.run_regedit(
+            wine_bin,
+            wine_name,
+            "C:\\windows\\system32\\regedit.exe",
+            reg_file,
+        )
+    }
 
-        let mut cmd = Command::new(&wine64_bin);
-        cmd.arg(regedit_exe);
+    /// Silently import a registry file with the given wine binary and regedit.exe
+    fn run_regedit(
+        &self,
+        wine_bin: &Path,
+        wine_name: &str,
+        regedit_exe: &str,
+        reg_file: &Path,
+    ) -> Result<()> {
+        let wineprefix = self.config.wineprefix();
 
-        if self.config.unattended {
-            cmd.arg("/S"); // Silent mode
-        }
+        // Convert to Wine Windows path
+        let reg_file_win = self.unix_to_wine_path(reg_file)?;
 
-        cmd.arg(&reg_file_win);
-        cmd.env("WINEPREFIX", &wineprefix_str);
+        let mut cmd = self.wine_command(wine_bin);
+        cmd.arg(regedit_exe)
+            .arg("/S")
+            .arg(&reg_file_win)
+            .env("WINEPREFIX", &wineprefix);
 
-        let status = cmd
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine64 {} /S {:?}", regedit_exe, reg_file_win),
-                error: e.to_string(),
-            })?;
+        // Same wording as the original winetricks' w_try
+        let command = format!("{} {} /S {}", wine_name, regedit_exe, reg_file_win);
+        info!("Executing {}", command);
 
-        if !status.success() {
-            return Err(WinetricksError::Config(
-                "Failed to import registry file (64-bit)".into(),
-            ));
+        let output = self
+            .wine
+            .capture_command(cmd, &command, None)?
+            .ok_or_else(|| WinetricksError::command_io(command.as_str(), "timed out"))?;
+        if !output.success() {
+            return Err(WinetricksError::wine_command_failed(command, &output));
         }
 
         Ok(())
@@ -5284,21 +6571,66 @@ impl Executor {
 
     /// Get latest GitHub release URL (matching w_get_github_latest_release behavior)
     pub async fn get_github_latest_release(&self, org: &str, repo: &str) -> Result<String> {
-        use std::fs;
+        self.get_github_release(org, repo, None).await
+    }
 
-        let temp_dir = dirs::cache_dir()
-            .ok_or_else(|| WinetricksError::Config("Could not determine cache directory".into()))?
-            .join("winetricks");
-        fs::create_dir_all(&temp_dir)?;
+    /// Get the download URL of a GitHub release (the latest one, or the given tag)
+    /// Lookups are cached in <cache>/github_releases.json for a day to stay clear of
+    /// the GitHub API rate limit
+    pub async fn get_github_release(
+        &self,
+        org: &str,
+        repo: &str,
+        tag: Option<&str>,
+    ) -> Result<String> {
+        // The tag ends up in the API URL path
+        if let Some(tag) = tag {
+            if tag.is_empty()
+                || !tag
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'))
+            {
+                return Err(WinetricksError::Config(format!(
+                    "Invalid release tag '{}' for {}/{}",
+                    tag, org, repo
+                )));
+            }
+        }
 
-        let release_json = temp_dir.join("release.json");
+        let cache_key = format!("{}/{}@{}", org, repo, tag.unwrap_or("latest"));
+        let cache_file = self.config.cache_dir.join("github_releases.json");
+        let mut cache: std::collections::HashMap<String, CachedRelease> =
+            std::fs::read_to_string(&cache_file)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default();
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if let Some(cached) = cache.get(&cache_key) {
+            if now.saturating_sub(cached.fetched_at) < GITHUB_RELEASE_CACHE_TTL.as_secs() {
+                info!(
+                    "Using cached GitHub release URL for {}: {}",
+                    cache_key, cached.url
+                );
+                return Ok(cached.url.clone());
+            }
+        }
 
-        // Download latest release JSON from GitHub API
-        let api_url = format!(
-            "https://api.github.com/repos/{}/{}/releases/latest",
-            org, repo
-        );
-        let client = reqwest::Client::new();
+        // Download release JSON from GitHub API
+        let api_url = match tag {
+            Some(tag) => format!(
+                "https://api.github.com/repos/{}/{}/releases/tags/{}",
+                org, repo, tag
+            ),
+            None => format!(
+                "https://api.github.com/repos/{}/{}/releases/latest",
+                org, repo
+            ),
+        };
+        let client = crate::download::http_client_builder(self.config.torify)?.build()?;
         let response = client
             .get(&api_url)
             .header("Accept", "application/vnd.github.v3+json")
@@ -5310,6 +6642,14 @@ impl Executor {
             })?;
 
         // Check response status
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            if let Some(tag) = tag {
+                return Err(WinetricksError::Config(format!(
+                    "{}/{} has no release tagged '{}' (see https://github.com/{}/{}/releases)",
+                    org, repo, tag, org, repo
+                )));
+            }
+        }
         if !response.status().is_success() {
             let status = response.status();
             let error_text = response
@@ -5341,58 +6681,90 @@ impl Executor {
             ))
         })?;
 
-        // Prefer .tar.zst or .tar.gz assets (vkd3d-proton uses .tar.zst)
-        if let Some(assets) = json.get("assets").and_then(|a| a.as_array()) {
-            // Try to find .tar.zst first, then .tar.gz, then any asset
-            for ext in &[".tar.zst", ".tar.gz", ".zip"] {
-                for asset in assets {
-                    if let Some(name) = asset.get("name").and_then(|n| n.as_str()) {
-                        if name.ends_with(ext) {
-                            if let Some(url) =
-                                asset.get("browser_download_url").and_then(|u| u.as_str())
-                            {
-                                info!(
-                                    "Got latest GitHub release URL for {}/{}: {}",
-                                    org, repo, url
-                                );
-                                return Ok(url.to_string());
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Fallback: get first asset
-            if let Some(first_asset) = assets.first() {
-                if let Some(url) = first_asset
-                    .get("browser_download_url")
+        let url = json
+            .get("assets")
+            .and_then(|a| a.as_array())
+            .and_then(|assets| Self::select_release_asset(assets))
+            // Fallback: source tarball
+            .or_else(|| {
+                json.get("tarball_url")
                     .and_then(|u| u.as_str())
-                {
-                    info!(
-                        "Got latest GitHub release URL for {}/{}: {}",
-                        org, repo, url
-                    );
-                    return Ok(url.to_string());
-                }
-            }
+                    .map(String::from)
+            })
+            .ok_or_else(|| {
+                WinetricksError::Config(format!(
+                    "No download URL found in GitHub release for {}/{}",
+                    org, repo
+                ))
+            })?;
+        info!("Got GitHub release URL for {}: {}", cache_key, url);
+
+        cache.insert(
+            cache_key,
+            CachedRelease {
+                url: url.clone(),
+                fetched_at: now,
+            },
+        );
+        if let Err(e) = std::fs::write(&cache_file, serde_json::to_string_pretty(&cache)?) {
+            warn!("Failed to update {:?}: {}", cache_file, e);
         }
 
-        // Fallback: try tarball_url or zipball_url
-        if let Some(tarball) = json.get("tarball_url").and_then(|u| u.as_str()) {
-            return Ok(tarball.to_string());
+        Ok(url)
+    }
+
+    /// Pick the download URL of a release's Windows build from its assets
+    /// Archives (.tar.zst, then .tar.gz, then .zip) are preferred, x86_64 builds first
+    /// when a release ships several platforms; dxvk's Linux-only "native" builds are skipped
+    fn select_release_asset(assets: &[serde_json::Value]) -> Option<String> {
+        let named: Vec<(&str, &str)> = assets
+            .iter()
+            .filter_map(|asset| {
+                Some((
+                    asset.get("name")?.as_str()?,
+                    asset.get("browser_download_url")?.as_str()?,
+                ))
+            })
+            .collect();
+
+        let archives: Vec<(&str, &str)> = named
+            .iter()
+            .copied()
+            .filter(|(name, _)| {
+                [".tar.zst", ".tar.gz", ".zip"]
+                    .iter()
+                    .any(|ext| name.ends_with(ext))
+            })
+            .collect();
+        let x86_64: Vec<(&str, &str)> = archives
+            .iter()
+            .copied()
+            .filter(|(name, _)| name.contains("x86_64"))
+            .collect();
+        let candidates = if x86_64.is_empty() {
+            archives
+                .into_iter()
+                .filter(|(name, _)| !name.contains("native"))
+                .collect()
+        } else {
+            x86_64
+        };
+
+        for ext in &[".tar.zst", ".tar.gz", ".zip"] {
+            if let Some((_, url)) = candidates.iter().find(|(name, _)| name.ends_with(ext)) {
+                return Some(url.to_string());
+            }
         }
 
-        Err(WinetricksError::Config(format!(
-            "No download URL found in GitHub release for {}/{}",
-            org, repo
-        )))
+        // Fallback: first asset
+        named.first().map(|(_, url)| url.to_string())
     }
 
     /// Get latest GitHub prerelease URL (matching w_get_github_latest_prerelease behavior)
     pub async fn get_github_latest_prerelease(&self, org: &str, repo: &str) -> Result<String> {
         // Download releases list from GitHub API
         let api_url = format!("https://api.github.com/repos/{}/{}/releases", org, repo);
-        let client = reqwest::Client::new();
+        let client = crate::download::http_client_builder(self.config.torify)?.build()?;
         let response = client
             .get(&api_url)
             .header("Accept", "application/vnd.github.v3+json")
@@ -5446,7 +6818,7 @@ impl Executor {
             "https://gitlab.com/api/v4/projects/{}/releases",
             format!("{}/{}", org, repo)
         );
-        let client = reqwest::Client::new();
+        let client = crate::download::http_client_builder(self.config.torify)?.build()?;
         let response = client.get(&api_url).send().await.map_err(|e| {
             WinetricksError::Config(format!("Failed to fetch GitLab releases: {}", e))
         })?;
@@ -5546,12 +6918,108 @@ impl Executor {
         Ok(None)
     }
 
+    /// Ask the user a question, falling back to the terminal when there is no
+    /// zenity/kdialog (empty answer if neither is available)
+    fn prompt_user(&self, prompt: &str) -> Result<String> {
+        use std::io::{BufRead, IsTerminal};
+
+        match self.question(prompt)? {
+            Some(answer) => Ok(answer),
+            None if !self.config.unattended && std::io::stdin().is_terminal() => {
+                print!("{} ", prompt);
+                std::io::stdout().flush()?;
+                let mut answer = String::new();
+                std::io::stdin().lock().read_line(&mut answer)?;
+                Ok(answer.trim().to_string())
+            }
+            None => Ok(String::new()),
+        }
+    }
+
+    /// Optional MSI features to install for a verb: all of them when unattended,
+    /// otherwise the ones the user picks (Enter for all)
+    fn select_msi_features(&self, metadata: &VerbMetadata) -> Result<Vec<String>> {
+        let all = metadata.msi_features.clone();
+        if self.config.unattended {
+            return Ok(all);
+        }
+
+        println!("Optional features of {}:", metadata.name);
+        for feature in &all {
+            println!("  {}", feature);
+        }
+        let answer = self.prompt_user("Features to install (comma-separated, Enter for all):")?;
+
+        let mut selected = Vec::new();
+        for feature in answer.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            match all.iter().find(|f| f.eq_ignore_ascii_case(feature)) {
+                Some(known) if !selected.contains(known) => selected.push(known.clone()),
+                Some(_) => {}
+                None => warn!(
+                    "Ignoring unknown feature '{}' of {}",
+                    feature, metadata.name
+                ),
+            }
+        }
+        if selected.is_empty() {
+            return Ok(all);
+        }
+        info!(
+            "Installing {} features: {}",
+            metadata.name,
+            selected.join(", ")
+        );
+        Ok(selected)
+    }
+
+    /// Find the mounted install disc for a DVD/CD-ROM verb (matching w_mount behavior)
+    /// Uses --cdrom if given, otherwise asks the user for the mount point
+    fn locate_install_disc(&mut self, metadata: &VerbMetadata) -> Result<PathBuf> {
+        let disc_name = if metadata.media == MediaType::Dvd {
+            "DVD"
+        } else {
+            "CD-ROM"
+        };
+
+        if let Some(ref path) = self.config.cdrom_path {
+            if path.is_dir() {
+                return Ok(path.clone());
+            }
+            return Err(WinetricksError::Verb(format!(
+                "--cdrom path {} is not a mounted directory",
+                path.display()
+            )));
+        }
+
+        if self.config.unattended {
+            return Err(WinetricksError::Verb(format!(
+                "{} requires the {} {}; pass --cdrom=PATH with its mount point",
+                metadata.name, metadata.title, disc_name
+            )));
+        }
+
+        let prompt = format!(
+            "Please insert and mount the {} {}, then enter its mount point:",
+            metadata.title, disc_name
+        );
+        let path = PathBuf::from(self.prompt_user(&prompt)?);
+        if path.as_os_str().is_empty() || !path.is_dir() {
+            return Err(WinetricksError::Verb(format!(
+                "No mounted {} found for {} at {:?}",
+                disc_name, metadata.name, path
+            )));
+        }
+
+        // Remember it for the rest of this run (multi-disc verbs, dependencies)
+        self.config.cdrom_path = Some(path.clone());
+        Ok(path)
+    }
+
     /// Execute AutoHotkey script (matching w_ahk_do behavior)
     /// Downloads AutoHotkeyU32.exe if not present, then executes the script
     pub fn ahk_do(&self, script: &str) -> Result<()> {
         use std::fs;
         use std::io::Write;
-        use std::process::Command;
 
         let wineprefix = self.config.wineprefix();
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
@@ -5604,20 +7072,17 @@ impl Executor {
         let ahk_file_win = self.unix_to_wine_path(&ahk_file)?;
 
         // Run AutoHotkey script
-        let status = Command::new(&self.wine.wine_bin)
+        let command = format!("wine AutoHotkeyU32.exe {:?}", ahk_file_win);
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg(&autohotkey_exe_win)
             .arg(&ahk_file_win)
             .env("WINEPREFIX", &wineprefix_str)
-            .status()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine AutoHotkeyU32.exe {:?}", ahk_file_win),
-                error: e.to_string(),
-            })?;
+            .output()
+            .map_err(|e| WinetricksError::command_io(command.as_str(), e))?;
 
-        if !status.success() {
-            return Err(WinetricksError::Config(
-                "AutoHotkey script execution failed".into(),
-            ));
+        if !output.status.success() {
+            return Err(WinetricksError::command_failed(command, &output));
         }
 
         info!("Executed AutoHotkey script");
@@ -5628,7 +7093,6 @@ impl Executor {
     /// Note: This requires uTorrent to be installed in the Wine prefix
     pub async fn download_torrent(&self, verb_name: &str, torrent_files: &[&str]) -> Result<()> {
         use std::fs;
-        use std::process::Command;
 
         let wineprefix = self.config.wineprefix();
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
@@ -5662,7 +7126,8 @@ impl Executor {
             let torrent_win = self.unix_to_wine_path(&torrent_path)?;
 
             // Launch uTorrent (non-blocking)
-            let _ = Command::new(&self.wine.wine_bin)
+            let _ = self
+                .wine_command(&self.wine.wine_bin)
                 .arg(&torrent_win)
                 .env("WINEPREFIX", &wineprefix_str)
                 .spawn();
@@ -5681,7 +7146,6 @@ impl Executor {
     pub fn get_sha256sum(&self, file: &Path) -> Result<String> {
         use sha2::{Digest, Sha256};
         use std::fs::File;
-        use std::io::Read;
 
         if !file.exists() {
             warn!("File does not exist: {:?}", file);
@@ -5691,11 +7155,9 @@ impl Executor {
             )));
         }
 
+        // Hash in chunks rather than reading the (possibly huge) installer into memory
         let mut hasher = Sha256::new();
-        let mut f = File::open(file)?;
-        let mut buffer = Vec::new();
-        f.read_to_end(&mut buffer)?;
-        hasher.update(&buffer);
+        std::io::copy(&mut File::open(file)?, &mut hasher)?;
 
         let hash = hasher.finalize();
         Ok(format!("{:x}", hash))
@@ -5744,21 +7206,19 @@ impl Executor {
 
     /// Expand Windows environment variable (matching w_expand_env behavior)
     pub fn expand_env(&self, var_name: &str) -> Result<String> {
-        use std::process::Command;
-
         let wineprefix = self.config.wineprefix();
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
 
         // Use cmd.exe /c "echo %VAR%" to expand environment variable
-        let output = Command::new(&self.wine.wine_bin)
+        let output = self
+            .wine_command(&self.wine.wine_bin)
             .arg("cmd.exe")
             .arg("/c")
             .arg(&format!("echo %{}%", var_name))
             .env("WINEPREFIX", &wineprefix_str)
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine cmd.exe /c echo %{}%", var_name),
-                error: e.to_string(),
+            .map_err(|e| {
+                WinetricksError::command_io(format!("wine cmd.exe /c echo %{}%", var_name), e)
             })?;
 
         if output.status.success() {
@@ -5826,15 +7286,29 @@ impl Executor {
         Ok(None)
     }
 
+    /// Command running the given wine binary in the active wineprefix, with the
+    /// environment Proton needs for it (see Wine::proton_env())
+    fn wine_command(&self, wine_bin: &Path) -> std::process::Command {
+        let wineprefix = self.config.wineprefix();
+        let mut cmd = std::process::Command::new(wine_bin);
+        cmd.envs(self.wine.proton_env(&wineprefix))
+            .env("WINEPREFIX", wineprefix);
+        cmd
+    }
+
+    /// cabextract binary: the configured one, else the one in PATH
+    fn cabextract(&self) -> Result<PathBuf> {
+        match &self.config.cabextract {
+            Some(path) => Ok(path.clone()),
+            None => crate::tools::require("cabextract"),
+        }
+    }
+
     /// Verify cabextract is available (matching w_verify_cabextract_available behavior)
     pub fn verify_cabextract_available(&self) -> Result<()> {
         use std::process::Command;
-        use which::which;
 
-        let cabextract = which("cabextract")
-            .map_err(|_| WinetricksError::Config(
-                "Cannot find cabextract. Please install it (e.g. 'sudo apt install cabextract' or 'sudo yum install cabextract')".into()
-            ))?;
+        let cabextract = self.cabextract()?;
 
         // Test cabextract with -q -v
         let status = Command::new(&cabextract).arg("-q").arg("-v").output();
@@ -6091,41 +7565,75 @@ impl Executor {
         Ok(())
     }
 
+    /// .NET Framework version a dotnet verb installs: its directory under
+    /// Microsoft.NET/Framework(64) and its key under HKLM\Software\Microsoft\NET Framework Setup\NDP
+    /// 4.5 and later update 4.0 in place. None for .NET Core/5+ verbs, which don't use these
+    fn dotnet_framework_version(verb_name: &str) -> Option<(&'static str, &'static str)> {
+        let version = verb_name.strip_prefix("dotnet")?;
+        if version.starts_with("11") {
+            Some(("v1.1.4322", "v1.1.4322"))
+        } else if version.starts_with("20") {
+            Some(("v2.0.50727", "v2.0.50727"))
+        } else if version.starts_with("30") {
+            Some(("v3.0", "v3.0"))
+        } else if version.starts_with("35") {
+            Some(("v3.5", "v3.5"))
+        } else if version.starts_with('4') {
+            Some(("v4.0.30319", "v4"))
+        } else {
+            None
+        }
+    }
+
     /// Clean up partial .NET installation before force reinstall
+    /// Removes the version's Framework and Framework64 directories and NDP registry keys,
+    /// then stops the prefix's wineserver so nothing keeps the old files loaded
     fn cleanup_dotnet_installation(&self, verb_name: &str) -> Result<()> {
+        let Some((framework_version, ndp_key)) = Self::dotnet_framework_version(verb_name) else {
+            info!(
+                "{} isn't a .NET Framework verb, nothing to clean up",
+                verb_name
+            );
+            return Ok(());
+        };
+
         let wineprefix = self.config.wineprefix();
         let wineprefix_str = wineprefix.to_string_lossy().to_string();
 
         info!("Cleaning up partial .NET installation for {}...", verb_name);
 
-        // Remove .NET Framework directory (but keep system32/mscoree.dll for now as it might be needed)
-        let framework_dir = wineprefix.join("drive_c/windows/Microsoft.NET/Framework/v4.0.30319");
-        if framework_dir.exists() {
-            warn!("Removing partial Framework/v4.0.30319/ directory...");
-            if let Err(e) = std::fs::remove_dir_all(&framework_dir) {
-                warn!("Warning: Failed to remove Framework directory: {}", e);
+        // Remove .NET Framework directories (but keep system32/mscoree.dll for now as it might be needed)
+        for framework in ["Framework", "Framework64"] {
+            let framework_dir = wineprefix
+                .join("drive_c/windows/Microsoft.NET")
+                .join(framework)
+                .join(framework_version);
+            if framework_dir.exists() {
+                warn!(
+                    "Removing partial {}/{}/ directory...",
+                    framework, framework_version
+                );
+                if let Err(e) = std::fs::remove_dir_all(&framework_dir) {
+                    warn!("Warning: Failed to remove {} directory: {}", framework, e);
+                }
             }
         }
 
-        // Remove registry entries
-        // Remove v4/Full key
-        let _ = std::process::Command::new(&self.wine.wine_bin)
-            .arg("reg")
-            .arg("delete")
-            .arg("HKLM\\Software\\Microsoft\\NET Framework Setup\\NDP\\v4\\Full")
-            .arg("/f")
-            .env("WINEPREFIX", &wineprefix_str)
-            .status();
-
-        // Remove v3.5 key if applicable
-        if verb_name.contains("35") {
-            let _ = std::process::Command::new(&self.wine.wine_bin)
+        // Remove registry entries (32-bit installers write to Wow6432Node in 64-bit prefixes)
+        // reg delete fails if the key doesn't exist, which is fine
+        for software in ["HKLM\\Software", "HKLM\\Software\\Wow6432Node"] {
+            let key = format!(
+                "{}\\Microsoft\\NET Framework Setup\\NDP\\{}",
+                software, ndp_key
+            );
+            let _ = self
+                .wine_command(&self.wine.wine_bin)
                 .arg("reg")
                 .arg("delete")
-                .arg("HKLM\\Software\\Microsoft\\NET Framework Setup\\NDP\\v3.5")
+                .arg(&key)
                 .arg("/f")
                 .env("WINEPREFIX", &wineprefix_str)
-                .status();
+                .output();
         }
 
         // Remove marker files
@@ -6142,129 +7650,107 @@ impl Executor {
             }
         }
 
+        // Stop the prefix's wineserver so no process still references the removed files
+        let _ = std::process::Command::new(&self.wine.wineserver_bin)
+            .arg("-k")
+            .env("WINEPREFIX", &wineprefix_str)
+            .status();
+
         info!("Cleanup complete. Installation will proceed as fresh install.");
         Ok(())
     }
 
-    /// Run Setup.exe directly for .NET 4.5 (after manual extraction)
+    /// Run the Setup.exe of an extracted .NET Framework 4.5 installer
+    /// Waits for wineserver, checks that mscorlib.dll was installed and removes the
+    /// extracted files (unless --no-clean); fails if Setup.exe exits unsuccessfully
     async fn run_setup_exe_directly(
         &self,
         setup_exe: &Path,
         extract_dir: &Path,
         wineprefix_str: String,
-        _is_dotnet45: bool,
+        verb_name: &str,
     ) -> Result<()> {
-        use std::process::Command;
-
-        info!("Running Setup.exe directly for .NET 4.5...");
+        let wineprefix = PathBuf::from(&wineprefix_str);
 
         // Convert Setup.exe path to Wine Windows path
+        info!("Running {:?} directly", setup_exe);
         let setup_exe_str = setup_exe.to_string_lossy().to_string();
-        let output = Command::new(&self.wine.wine_bin)
-            .arg("winepath")
-            .arg("-w")
-            .arg(&setup_exe_str)
-            .env("WINEPREFIX", &wineprefix_str)
-            .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine winepath -w {:?}", setup_exe_str),
-                error: e.to_string(),
-            })?;
+        let winepath = self.wine.run_and_capture(
+            &["winepath", "-w", &setup_exe_str],
+            &[("WINEPREFIX", &wineprefix_str)],
+            None,
+        )?;
+        if !winepath.success() {
+            return Err(WinetricksError::wine_command_failed(
+                format!("wine winepath -w {:?}", setup_exe_str),
+                &winepath,
+            ));
+        }
+        let setup_exe_win = winepath.stdout.trim().to_string();
 
-        let setup_exe_win = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-        // Run Setup.exe with /quiet /norestart flags for unattended mode
-        // /quiet suppresses ALL GUI dialogs from the installer
-        let mut setup_cmd = Command::new(&self.wine.wine_bin);
-        setup_cmd.env("WINEPREFIX", &wineprefix_str);
-        setup_cmd.env("WINEDLLOVERRIDES", "fusion=b");
-        setup_cmd.current_dir(extract_dir);
-        setup_cmd.arg(&setup_exe_win);
-
-        // .NET Framework Setup.exe flags:
-        // /quiet - Suppresses ALL UI including error dialogs (theoretical, but may still show some)
-        // /passive - Shows progress bar, minimal UI, but may still show blocking dialogs
-        // /norestart - Don't restart
-        // /repair - Repair an existing installation (but this also shows UI)
-        //
-        // Problem: Even /quiet can show blocking dialogs when .NET is already installed
-        // Solution: Try /quiet /norestart first. If that fails, the user will see the dialog.
-        // For truly unattended, we could check if installed first, but that's complex.
-        //
-        // Note: .NET Framework installers have a known limitation where they may show
-        // dialogs even with /quiet when detecting existing installations.
+        // Like the extraction, with Wine's builtin fusion.dll
+        let mut setup_cmd = self.wine_command(&self.wine.wine_bin);
+        setup_cmd
+            .env("WINEPREFIX", &wineprefix_str)
+            .env("WINEDLLOVERRIDES", "fusion=b")
+            .env("DOTNET_INSTALL_PATH", &wineprefix_str)
+            .current_dir(extract_dir)
+            .arg(&setup_exe_win);
+        // Interactive installs show the installer's own UI
+        // Note: .NET installers may still show an "already installed" dialog with /q
         if self.config.unattended {
-            // Always use /quiet for unattended - it's the most silent option
-            // If it shows a dialog, that's a limitation of the .NET installer itself
-            setup_cmd.arg("/quiet").arg("/norestart");
-            info!("Running Setup.exe with /quiet /norestart (unattended mode)");
-            if self.config.force {
-                warn!("Note: .NET installers may still show dialogs about already-installed status even with /quiet");
-                warn!("This is a limitation of the .NET Framework installer, not winetricks");
-            }
-        } else {
-            // Even in interactive mode, use /passive for less intrusive GUI
-            setup_cmd.arg("/passive").arg("/norestart");
-            info!("Running Setup.exe in passive mode (minimal GUI)");
+            setup_cmd.arg("/q");
         }
+        setup_cmd.arg("/norestart");
 
         let setup_args: Vec<String> = setup_cmd
             .get_args()
             .map(|a| a.to_string_lossy().to_string())
             .collect();
-        eprintln!(
-            "Running Setup.exe: {} {}",
-            self.wine.wine_bin.to_string_lossy(),
-            setup_args.join(" ")
+        let command = format!("wine {}", setup_args.join(" "));
+        info!("Running {} (this may take 5-10 minutes)", command);
+        let setup_output = self.run_installer(setup_cmd, &command, verb_name)?;
+
+        // 3010 means success, but a reboot is needed (wineboot takes care of that)
+        info!(
+            "Setup.exe finished with exit code {}",
+            setup_output.exit_code
         );
-        eprintln!("This may take 5-10 minutes...");
+        if !setup_output.success() && setup_output.exit_code != 3010 {
+            return Err(WinetricksError::wine_command_failed(command, &setup_output))
+                .with_context(|| format!("Setup.exe of {} failed", verb_name));
+        }
 
-        let setup_output = setup_cmd
-            .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("wine {}", setup_args.join(" ")),
-                error: e.to_string(),
-            })?;
+        info!("Waiting for wineserver after Setup.exe...");
+        self.wine
+            .wineserver_wait_timeout(&wineprefix, self.config.wineserver_timeout)
+            .await?;
 
-        if !setup_output.stdout.is_empty() {
-            eprintln!("Setup.exe stdout:");
-            eprintln!("{}", String::from_utf8_lossy(&setup_output.stdout));
-        }
-        if !setup_output.stderr.is_empty() {
-            eprintln!("Setup.exe stderr:");
-            eprintln!("{}", String::from_utf8_lossy(&setup_output.stderr));
-        }
-
-        let setup_exit = setup_output.status.code();
-        eprintln!("Setup.exe finished with exit code: {:?}", setup_exit);
-
-        // .NET Framework installer exit codes:
-        // 0 = Success
-        // 1603 = Fatal error (but can be false positive)
-        // 3010 = Success, reboot required
-        // Other codes may indicate various states (already installed, cancelled by user dialog, etc.)
-        // Note: Even with /quiet, the installer may show a blocking dialog if .NET is already installed
-        // This is a known limitation of the .NET Framework installer - it cannot be suppressed
-        if let Some(code) = setup_exit {
-            if code != 0 && code != 3010 {
-                warn!("Setup.exe returned exit code {} - installation may have failed or been cancelled", code);
-                if self.config.force {
-                    warn!("With --force, this might indicate .NET is already installed and the installer showed a dialog");
-                    warn!("This is expected behavior - the .NET installer cannot suppress 'already installed' dialogs even with /quiet");
-                }
-            }
+        // Setup.exe exits successfully in some cases where it installed nothing
+        let (framework_dir, _) =
+            Self::dotnet_framework_version(verb_name).unwrap_or(("v4.0.30319", "v4"));
+        let mscorlib = wineprefix
+            .join("drive_c/windows/Microsoft.NET/Framework")
+            .join(framework_dir)
+            .join("mscorlib.dll");
+        info!("Checking for {:?}", mscorlib);
+        if !mscorlib.exists() {
+            return Err(WinetricksError::Verb(format!(
+                "Setup.exe of {} finished, but {} was not installed",
+                verb_name,
+                mscorlib.display()
+            )));
         }
 
-        // Wait for wineserver after Setup.exe completes
-        info!("Waiting for wineserver after Setup.exe...");
-        std::thread::sleep(std::time::Duration::from_secs(5));
-        for i in 1..=3 {
-            let _ = Command::new(&self.wine.wineserver_bin)
-                .arg("-w")
-                .env("WINEPREFIX", &wineprefix_str)
-                .status();
-            if i < 3 {
-                std::thread::sleep(std::time::Duration::from_secs(2));
+        if self.config.no_clean {
+            info!(
+                "Keeping extracted installer in {:?} (--no-clean)",
+                extract_dir
+            );
+        } else {
+            info!("Removing extracted installer {:?}", extract_dir);
+            if let Err(e) = std::fs::remove_dir_all(extract_dir) {
+                warn!("Failed to remove {:?}: {}", extract_dir, e);
             }
         }
 
@@ -6285,10 +7771,7 @@ impl Executor {
         let output = Command::new(&pgrep)
             .arg(process_name)
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("pgrep {}", process_name),
-                error: e.to_string(),
-            })?;
+            .map_err(|e| WinetricksError::command_io(format!("pgrep {}", process_name), e))?;
 
         if output.status.success() {
             let stdout_str = String::from_utf8_lossy(&output.stdout);
@@ -6314,3 +7797,430 @@ impl Executor {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verb::VerbCategory;
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+
+    /// Executor for a win64 prefix in `root`, without detecting Wine
+    fn test_executor(root: &Path, registry: VerbRegistry) -> Executor {
+        let mut config = Config::new().unwrap();
+        config.wineprefix = Some(root.join("prefix"));
+        config.cache_dir = root.join("cache");
+        config.winearch = Some("win64".to_string());
+        config.skip_wine_check = true;
+        config.unattended = true;
+        Executor {
+            downloader: DownloadManager::new(config.cache_dir.clone()).unwrap(),
+            config,
+            wine: Wine::placeholder(),
+            registry,
+            stored_windows_version: None,
+            progress: None,
+            calling: Vec::new(),
+        }
+    }
+
+    /// Write a shell script to `path` and make it executable
+    fn write_script(path: &Path, script: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, script).unwrap();
+        std::fs::set_permissions(path, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+    }
+
+    /// Point the executor at a wine stand-in that keeps the prefix's Windows version
+    /// in $WINEPREFIX/version (winecfg -v and reg add/query/delete of Version)
+    fn use_version_stub_wine(executor: &mut Executor, root: &Path) {
+        let wine = root.join("bin/wine");
+        write_script(
+            &wine,
+            r#"#!/bin/sh
+version="$WINEPREFIX/version"
+case "$1 $2" in
+"winecfg -v") echo "$3" > "$version" ;;
+"reg add") while [ $# -gt 1 ]; do [ "$1" = /d ] && echo "$2" > "$version"; shift; done ;;
+"reg query") [ -f "$version" ] && echo "    Version    REG_SZ    $(cat "$version")" ;;
+"reg delete") [ -f "$version" ] && rm "$version" ;;
+*) exit 1 ;;
+esac
+"#,
+        );
+        std::fs::create_dir_all(executor.config.wineprefix()).unwrap();
+        executor.wine.wine_bin = wine;
+    }
+
+    /// Serve `body` for every request on a local port; returns the base URL
+    fn serve(body: &'static [u8]) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                if !request.starts_with(b"HEAD") {
+                    let _ = stream.write_all(body);
+                }
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn installed_paths_resolve_to_their_prefix_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = test_executor(dir.path(), VerbRegistry::new());
+        let drive_c = dir.path().join("prefix/drive_c");
+        for file in [
+            "windows/syswow64/d3dx9_43.dll",
+            "windows/Fonts/arial.ttf",
+            "Program Files (x86)/7-Zip/7zFM.exe",
+            "Program Files/Common Files/System/ado/msado15.dll",
+        ] {
+            std::fs::create_dir_all(drive_c.join(file).parent().unwrap()).unwrap();
+            std::fs::write(drive_c.join(file), b"").unwrap();
+        }
+
+        // DLLs: syswow64 then system32 on win64
+        assert_eq!(
+            executor.prefix_path_candidates("${W_SYSTEM32_DLLS_WIN}/d3dx9_43.dll"),
+            [
+                drive_c.join("windows/syswow64/d3dx9_43.dll"),
+                drive_c.join("windows/system32/d3dx9_43.dll")
+            ]
+        );
+        assert!(executor
+            .verify_file_exists("${W_SYSTEM32_DLLS_WIN}/d3dx9_43.dll")
+            .unwrap());
+        assert!(!executor
+            .verify_file_exists("${W_SYSTEM32_DLLS_WIN}/d3dx9_42.dll")
+            .unwrap());
+
+        // Fonts
+        assert!(executor
+            .verify_file_exists("${W_FONTSDIR_WIN}/arial.ttf")
+            .unwrap());
+        assert!(!executor
+            .verify_file_exists("${W_FONTSDIR_WIN}/times.ttf")
+            .unwrap());
+
+        // Programs: Program Files (x86) for 32-bit programs on win64
+        assert!(executor
+            .verify_file_exists("${W_PROGRAMS_X86_WIN}/7-Zip/7zFM.exe")
+            .unwrap());
+        assert!(!executor
+            .verify_file_exists("${W_PROGRAMS_WIN}/7-Zip/7zFM.exe")
+            .unwrap());
+        assert!(executor
+            .verify_file_exists("$W_COMMONFILES_WIN\\System\\ado\\msado15.dll")
+            .unwrap());
+
+        // ...and everything in system32 and Program Files on win32
+        executor.config.winearch = Some("win32".to_string());
+        assert_eq!(
+            executor.prefix_path_candidates("${W_SYSTEM32_DLLS_WIN}/d3dx9_43.dll"),
+            [drive_c.join("windows/system32/d3dx9_43.dll")]
+        );
+        assert_eq!(
+            executor.prefix_path_candidates("${W_PROGRAMS_X86_WIN}/7-Zip/7zFM.exe"),
+            [drive_c.join("Program Files/7-Zip/7zFM.exe")]
+        );
+    }
+
+    #[test]
+    fn write_shortcut_writes_a_shell_link_to_the_local_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let lnk = dir.path().join("Start Menu/Paint.lnk");
+        let target = "C:\\windows\\syswow64\\mspaint.exe";
+        Executor::write_shortcut(&lnk, target).unwrap();
+
+        let data = std::fs::read(&lnk).unwrap();
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize
+        };
+        // ShellLinkHeader: size, LinkCLSID, LinkFlags with HasLinkInfo only
+        assert_eq!(u32_at(0), 0x4C);
+        assert_eq!(
+            data[4..20],
+            [
+                0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x46
+            ]
+        );
+        assert_eq!(u32_at(20), 0x02);
+
+        // LinkInfo right after the header, then the 4-byte TerminalBlock
+        let link_info = &data[0x4C..];
+        let link_info_size = u32_at(0x4C);
+        assert_eq!(data.len(), 0x4C + link_info_size + 4);
+        assert_eq!(data[data.len() - 4..], [0; 4]);
+        assert_eq!(u32_at(0x4C + 8), 1); // VolumeIDAndLocalBasePath
+
+        let volume_id = u32_at(0x4C + 12);
+        assert_eq!(u32_at(0x4C + volume_id + 4), 3); // DRIVE_FIXED
+        let local_base_path = u32_at(0x4C + 16);
+        let path_len = link_info[local_base_path..]
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap();
+        assert_eq!(
+            &link_info[local_base_path..local_base_path + path_len],
+            target.as_bytes()
+        );
+        let suffix = u32_at(0x4C + 24);
+        assert_eq!(suffix, local_base_path + path_len + 1);
+        assert_eq!(link_info[suffix], 0);
+    }
+
+    #[tokio::test]
+    async fn install_mspaint_downloads_and_extracts_into_syswow64() {
+        const PACKAGE: &[u8] = b"KB978706 self-extractor";
+        let dir = tempfile::tempdir().unwrap();
+
+        // cabextract stand-in that "extracts" sp3gdr/mspaint.exe when asked for it
+        let cabextract = dir.path().join("bin/cabextract");
+        write_script(
+            &cabextract,
+            "#!/bin/sh\n\
+             while [ $# -gt 1 ]; do\n\
+             case \"$1\" in -d) dest=$2; shift;; -F) pattern=$2; shift;; esac\n\
+             shift\n\
+             done\n\
+             case \"$pattern\" in *mspaint.exe) mkdir -p \"$dest/sp3gdr\" && \
+             echo paint > \"$dest/sp3gdr/mspaint.exe\";; esac\n",
+        );
+
+        let metadata: VerbMetadata = serde_json::from_value(serde_json::json!({
+            "name": "mspaint",
+            "category": "apps",
+            "title": "MS Paint",
+            "files": [{
+                "filename": "windowsxp-kb978706-x86-enu.exe",
+                "url": format!("{}/windowsxp-kb978706-x86-enu.exe", serve(PACKAGE)),
+                "sha256": format!("{:x}", Sha256::digest(PACKAGE)),
+            }],
+            "installed_file": "${W_WINDIR_WIN}/mspaint.exe",
+        }))
+        .unwrap();
+        let mut registry = VerbRegistry::new();
+        registry
+            .register("mspaint".to_string(), metadata, VerbCategory::Apps)
+            .unwrap();
+
+        let mut executor = test_executor(dir.path(), registry);
+        executor.config.cabextract = Some(cabextract);
+        executor.install_mspaint().await.unwrap();
+
+        let prefix = dir.path().join("prefix");
+        let cached = dir
+            .path()
+            .join("cache/mspaint/windowsxp-kb978706-x86-enu.exe");
+        assert_eq!(std::fs::read(cached).unwrap(), PACKAGE);
+        let installed = prefix.join("drive_c/windows/syswow64/mspaint.exe");
+        assert_eq!(std::fs::read_to_string(&installed).unwrap(), "paint\n");
+        assert!(prefix
+            .join("drive_c/ProgramData/Microsoft/Windows/Start Menu/Programs/Accessories/Paint.lnk")
+            .is_file());
+        assert!(!dir.path().join("cache/mspaint/mspaint_extract").exists());
+        assert!(std::fs::read_to_string(prefix.join("winetricks.log"))
+            .unwrap()
+            .contains("mspaint"));
+
+        // Found where install_mspaint() put it, not at the metadata's C:\windows path
+        let files = executor.list_installed_files("mspaint").unwrap();
+        assert!(files.iter().any(|f| f.path == installed));
+    }
+
+    #[test]
+    fn windows_version_aliases_are_set_under_their_winecfg_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = test_executor(dir.path(), VerbRegistry::new());
+        use_version_stub_wine(&mut executor, dir.path());
+
+        // What dotnet45 sets after its installer ran
+        executor.set_windows_version("win2k3").unwrap();
+        assert_eq!(executor.get_windows_version().unwrap(), "win2003");
+        assert!(executor.set_windows_version("win2k5").is_err());
+    }
+
+    #[test]
+    fn unset_winver_removes_the_windows_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = test_executor(dir.path(), VerbRegistry::new());
+        use_version_stub_wine(&mut executor, dir.path());
+
+        executor.set_windows_version("win7").unwrap();
+        executor.unset_winver().unwrap();
+        assert!(executor.get_windows_version().is_err());
+        // Nothing left to delete is fine too
+        executor.unset_winver().unwrap();
+    }
+
+    #[tokio::test]
+    async fn disk_space_check_asks_the_server_for_missing_sizes() {
+        // 1 EiB, more than any test machine has free
+        const SIZE: u64 = 1 << 60;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/huge.exe", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let _ = stream.read(&mut [0; 1024]);
+                let _ = stream.write_all(
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        SIZE
+                    )
+                    .as_bytes(),
+                );
+            }
+        });
+
+        let metadata: VerbMetadata = serde_json::from_value(serde_json::json!({
+            "name": "huge",
+            "category": "apps",
+            "title": "Huge",
+            "files": [{"filename": "huge.exe", "url": url}],
+        }))
+        .unwrap();
+        assert!(metadata.files[0].size.is_none());
+        let mut registry = VerbRegistry::new();
+        registry
+            .register("huge".to_string(), metadata, VerbCategory::Apps)
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let executor = test_executor(dir.path(), registry);
+
+        assert!(matches!(
+            executor.check_disk_space("huge").await,
+            Err(WinetricksError::InsufficientDiskSpace { .. })
+        ));
+        let report = executor.dry_run("huge").await.unwrap();
+        assert_eq!(report.download_bytes, SIZE);
+        assert!(!report.is_ready());
+    }
+
+    #[test]
+    fn a_logged_install_without_its_installed_file_does_not_verify() {
+        let metadata: VerbMetadata = serde_json::from_value(serde_json::json!({
+            "name": "7zip",
+            "category": "apps",
+            "title": "7-Zip",
+            "installed_exe": "${W_PROGRAMS_X86_WIN}/7-Zip/7zFM.exe",
+        }))
+        .unwrap();
+        let mut registry = VerbRegistry::new();
+        registry
+            .register("7zip".to_string(), metadata, VerbCategory::Apps)
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let executor = test_executor(dir.path(), registry);
+        std::fs::create_dir_all(dir.path().join("prefix")).unwrap();
+
+        // The installer reported success, but put nothing where the metadata says
+        executor.log_installation("7zip").unwrap();
+        assert!(executor.is_installed("7zip").unwrap());
+        assert!(!executor.verify_installed_files("7zip").unwrap());
+        let result = crate::sandbox::verification_result(
+            &executor,
+            "7zip",
+            Ok(()),
+            std::time::Duration::ZERO,
+        );
+        assert!(result.success && !result.passed());
+
+        let exe = dir
+            .path()
+            .join("prefix/drive_c/Program Files (x86)/7-Zip/7zFM.exe");
+        std::fs::create_dir_all(exe.parent().unwrap()).unwrap();
+        std::fs::write(&exe, b"MZ").unwrap();
+        assert!(executor.verify_installed_files("7zip").unwrap());
+    }
+
+    #[tokio::test]
+    async fn only_meta_verbs_survive_a_failed_call() {
+        let verb = |name: &str, files: serde_json::Value| -> VerbMetadata {
+            serde_json::from_value(serde_json::json!({
+                "name": name,
+                "category": "dlls",
+                "title": name,
+                "files": files,
+                "calls": ["done", "nosuchverb"],
+            }))
+            .unwrap()
+        };
+        let meta = verb("allthings", serde_json::json!([]));
+        let installer = verb(
+            "withinstaller",
+            serde_json::json!([{"filename": "setup.exe", "url": "http://127.0.0.1:9/setup.exe"}]),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = test_executor(dir.path(), VerbRegistry::new());
+        std::fs::create_dir_all(dir.path().join("prefix")).unwrap();
+        executor.log_installation("done").unwrap();
+
+        // allcodecs-style: the verbs that could be installed are kept
+        executor.install_calls(&meta, &meta.calls).await.unwrap();
+
+        // A prerequisite of an installer failing fails the installer
+        let e = executor
+            .install_calls(&installer, &installer.calls)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("nosuchverb"), "{}", e);
+        assert!(executor.calling.is_empty());
+    }
+
+    #[test]
+    fn proton_commands_get_the_compat_data_path_of_their_own_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = test_executor(dir.path(), VerbRegistry::new());
+        let wine = dir
+            .path()
+            .join("steam/steamapps/common/Proton 9.0/files/bin/wine");
+        write_script(
+            &wine,
+            "#!/bin/sh\necho \"$STEAM_COMPAT_DATA_PATH\" > \"$WINEPREFIX/compat\"\n",
+        );
+        executor.wine.wine_bin = wine;
+        executor.wine.proton = true;
+
+        // Both the executor's own commands and Wine's follow the active prefix
+        for appid in ["100", "200"] {
+            let compat_data = dir.path().join("compatdata").join(appid);
+            let prefix = compat_data.join("pfx");
+            std::fs::create_dir_all(&prefix).unwrap();
+            executor.config.wineprefix = Some(prefix.clone());
+
+            let status = executor
+                .wine_command(&executor.wine.wine_bin)
+                .status()
+                .unwrap();
+            assert!(status.success());
+            let recorded = std::fs::read_to_string(prefix.join("compat")).unwrap();
+            assert_eq!(recorded.trim(), compat_data.to_str().unwrap());
+            std::fs::remove_file(prefix.join("compat")).unwrap();
+
+            let prefix_str = prefix.to_string_lossy();
+            executor
+                .wine
+                .run_and_capture(&[], &[("WINEPREFIX", &prefix_str)], None)
+                .unwrap();
+            let recorded = std::fs::read_to_string(prefix.join("compat")).unwrap();
+            assert_eq!(recorded.trim(), compat_data.to_str().unwrap());
+        }
+    }
+}
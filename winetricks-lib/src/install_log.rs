@@ -0,0 +1,160 @@
+//! winetricks.log reading and writing
+//!
+//! Like the original winetricks, the log lists one installed verb per line. Entries
+//! written by this version carry the install time and wine version in a trailing
+//! comment, e.g. `dxvk # 2026-10-15T09:20:38Z wine-9.0`, which `grep -w` still matches.
+
+use crate::error::Result;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Log file name inside a wineprefix
+pub const LOG_FILE_NAME: &str = "winetricks.log";
+
+/// An installed verb recorded in winetricks.log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub verb: String,
+    /// When the verb was installed (None for entries written by the original winetricks)
+    pub installed_at: Option<SystemTime>,
+    /// Wine version used for the installation (e.g. "wine-9.0")
+    pub wine_version: Option<String>,
+}
+
+impl LogEntry {
+    /// New entry for a verb installed right now
+    pub fn now(verb: &str, wine_version: &str) -> Self {
+        Self {
+            verb: verb.to_string(),
+            installed_at: Some(SystemTime::now()),
+            wine_version: (!wine_version.is_empty()).then(|| wine_version.to_string()),
+        }
+    }
+
+    /// Parse a log line
+    /// Returns None for empty lines, comments, flags (-q) and commands (prefix=foo)
+    pub fn parse(line: &str) -> Option<Self> {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || trimmed.starts_with('-') || trimmed.starts_with("//") {
+            return None;
+        }
+
+        let (verb, comment) = match trimmed.split_once('#') {
+            Some((verb, comment)) => (verb.trim(), comment.trim()),
+            None => (trimmed, ""),
+        };
+        if verb.is_empty() || verb.contains('=') || verb.contains(char::is_whitespace) {
+            return None;
+        }
+
+        // "<timestamp> <wine version>"; the version may contain spaces ("wine-9.0 (Staging)")
+        let (timestamp, wine_version) = match comment.split_once(' ') {
+            Some((timestamp, version)) => (timestamp, version.trim()),
+            None => (comment, ""),
+        };
+
+        Some(Self {
+            verb: verb.to_string(),
+            installed_at: parse_timestamp(timestamp),
+            wine_version: (!wine_version.is_empty()).then(|| wine_version.to_string()),
+        })
+    }
+
+    /// Format the entry as a log line (without newline)
+    pub fn to_line(&self) -> String {
+        let mut line = self.verb.clone();
+        if let Some(installed_at) = self.installed_at {
+            line.push_str(" # ");
+            line.push_str(&format_timestamp(installed_at));
+            if let Some(ref version) = self.wine_version {
+                line.push(' ');
+                line.push_str(version);
+            }
+        }
+        line
+    }
+
+    /// Time since installation (None if unknown)
+    pub fn age(&self) -> Option<Duration> {
+        self.installed_at
+            .map(|t| SystemTime::now().duration_since(t).unwrap_or_default())
+    }
+}
+
+/// Read the entries of a wineprefix's winetricks.log (empty if there is no log)
+pub fn read_log(wineprefix: &Path) -> Result<Vec<LogEntry>> {
+    let log_file = wineprefix.join(LOG_FILE_NAME);
+    if !log_file.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&log_file)?;
+    Ok(content.lines().filter_map(LogEntry::parse).collect())
+}
+
+/// Format a time as an ISO 8601 UTC timestamp (2026-10-15T09:20:38Z)
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86400, secs % 86400);
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Parse an ISO 8601 UTC timestamp written by format_timestamp()
+pub fn parse_timestamp(timestamp: &str) -> Option<SystemTime> {
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut date_parts = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (
+        date_parts.next()??,
+        date_parts.next()??,
+        date_parts.next()??,
+    );
+    let mut time_parts = time.splitn(3, ':').map(|p| p.parse::<u64>().ok());
+    let (hour, minute, second) = (
+        time_parts.next()??,
+        time_parts.next()??,
+        time_parts.next()??,
+    );
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86400 + hour * 3600 + minute * 60 + second))
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of days_from_civil(): (year, month, day)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
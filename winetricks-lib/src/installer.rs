@@ -178,3 +178,44 @@ pub fn detect_from_file(file_path: &Path) -> Option<InstallerType> {
 
     None
 }
+
+/// Archive/executable format detected from a file's magic bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveType {
+    /// Microsoft Cabinet (MSCF)
+    Cab,
+    /// Windows executable (MZ)
+    Pe,
+    /// ZIP (PK)
+    Zip,
+    /// 7-Zip (7z\xBC\xAF\x27\x1C)
+    SevenZip,
+    /// RAR (Rar!)
+    Rar,
+}
+
+/// Detect archive type from the first bytes of a file
+/// Many Microsoft downloads are CAB archives with an .exe extension, so this
+/// is more reliable than the file extension
+pub fn detect_archive_type(path: &Path) -> Option<ArchiveType> {
+    use std::io::Read;
+
+    let mut magic = [0u8; 8];
+    let mut file = std::fs::File::open(path).ok()?;
+    let bytes_read = file.read(&mut magic).ok()?;
+    let magic = &magic[..bytes_read];
+
+    if magic.starts_with(b"MSCF") {
+        Some(ArchiveType::Cab)
+    } else if magic.starts_with(b"7z\xBC\xAF\x27\x1C") {
+        Some(ArchiveType::SevenZip)
+    } else if magic.starts_with(b"Rar!") {
+        Some(ArchiveType::Rar)
+    } else if magic.starts_with(b"PK\x03\x04") {
+        Some(ArchiveType::Zip)
+    } else if magic.starts_with(b"MZ") {
+        Some(ArchiveType::Pe)
+    } else {
+        None
+    }
+}
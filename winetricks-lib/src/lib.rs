@@ -7,12 +7,27 @@ pub mod config;
 pub mod download;
 pub mod error;
 pub mod executor;
+pub mod install_log;
 pub mod installer;
+mod notify;
+pub mod sandbox;
+pub mod self_update;
+pub mod tools;
 pub mod verb;
 pub mod wine;
 
-pub use config::Config;
-pub use error::{Result, WinetricksError};
-pub use executor::Executor;
-pub use verb::{MediaType, Verb, VerbCategory, VerbFile, VerbMetadata, VerbRegistry};
-pub use wine::Wine;
+pub use config::{Config, OutputFormat};
+pub use error::{Result, ResultExt, WinetricksError};
+pub use executor::{
+    DryRunReport, Executor, HealthIssue, HealthSeverity, InstallProgress, InstalledFile,
+};
+pub use install_log::LogEntry;
+pub use sandbox::{Sandbox, VerificationResult};
+pub use tools::ToolAvailability;
+pub use verb::{
+    ArchSupport, ManualDownloadInfo, MediaType, RegistryDiff, Verb, VerbCategory, VerbChange,
+    VerbFile, VerbMetadata, VerbRegistry, WineBugWorkaround, WineVersionRange,
+};
+pub use wine::{
+    CommandOutput, PrefixExportManifest, Wine, WineInstallType, WineProcess, WineVersion,
+};
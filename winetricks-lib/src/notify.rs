@@ -0,0 +1,91 @@
+//! Desktop notifications (org.freedesktop.Notifications over D-Bus)
+//!
+//! Only compiled in with the `notify` feature; otherwise notify_desktop() does nothing.
+
+use std::time::Duration;
+
+/// Tell the desktop that a verb installation finished
+/// Failures (no session bus, no notification daemon) are logged and otherwise ignored
+pub(crate) async fn notify_desktop(
+    verb_name: &str,
+    success: bool,
+    duration: Duration,
+    error: Option<&str>,
+) {
+    #[cfg(feature = "notify")]
+    {
+        let (summary, body) = notification_text(verb_name, success, duration, error);
+        if let Err(e) = send_notification(&summary, &body).await {
+            tracing::debug!("Desktop notification not sent: {}", e);
+        }
+    }
+
+    #[cfg(not(feature = "notify"))]
+    let _ = (verb_name, success, duration, error);
+}
+
+#[cfg(feature = "notify")]
+fn notification_text(
+    verb_name: &str,
+    success: bool,
+    duration: Duration,
+    error: Option<&str>,
+) -> (String, String) {
+    let secs = duration.as_secs();
+    let elapsed = if secs >= 60 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    };
+
+    if success {
+        (
+            format!("{} installed", verb_name),
+            format!(
+                "winetricks finished installing {} in {}",
+                verb_name, elapsed
+            ),
+        )
+    } else {
+        (
+            format!("{} failed to install", verb_name),
+            format!(
+                "winetricks gave up on {} after {}: {}",
+                verb_name,
+                elapsed,
+                error.unwrap_or("unknown error")
+            ),
+        )
+    }
+}
+
+/// Call org.freedesktop.Notifications.Notify on the session bus
+#[cfg(feature = "notify")]
+async fn send_notification(summary: &str, body: &str) -> zbus::Result<()> {
+    use std::collections::HashMap;
+    use zbus::zvariant::Value;
+
+    let connection = zbus::Connection::session().await?;
+    let hints: HashMap<&str, Value> = HashMap::new();
+
+    // Notify(app_name, replaces_id, app_icon, summary, body, actions, hints, expire_timeout)
+    connection
+        .call_method(
+            Some("org.freedesktop.Notifications"),
+            "/org/freedesktop/Notifications",
+            Some("org.freedesktop.Notifications"),
+            "Notify",
+            &(
+                "winetricks",
+                0u32,
+                "wine",
+                summary,
+                body,
+                Vec::<&str>::new(),
+                hints,
+                -1i32,
+            ),
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,184 @@
+//! Throwaway wineprefixes for test installs (--isolate-sandbox)
+//!
+//! A sandbox is a tmpfs mounted at /tmp/wt_sandbox_XXXX. Mounting needs root, so
+//! without it the sandbox falls back to a temp dir in /dev/shm (itself a ramdisk on
+//! most systems) or /tmp. Everything is unmounted and deleted when the sandbox is dropped.
+
+use crate::config::Config;
+use crate::error::{Result, WinetricksError};
+use crate::executor::Executor;
+use crate::wine::Wine;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use tracing::{info, warn};
+
+/// Outcome of installing a verb into a sandbox
+#[derive(Debug, Clone)]
+pub struct VerificationResult {
+    pub verb: String,
+    /// The installation finished without error
+    pub success: bool,
+    /// The verb's installed file/exe was present afterwards
+    pub installed: bool,
+    /// Why the installation failed
+    pub error: Option<String>,
+    pub duration: Duration,
+}
+
+impl VerificationResult {
+    /// Whether the verb installed cleanly
+    pub fn passed(&self) -> bool {
+        self.success && self.installed
+    }
+
+    /// Process exit code for the result (0 passed, 1 failed)
+    pub fn exit_code(&self) -> i32 {
+        if self.passed() {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+/// Temporary directory for a throwaway wineprefix, tmpfs-backed when possible
+pub struct Sandbox {
+    dir: TempDir,
+    mounted: bool,
+}
+
+impl Sandbox {
+    /// Create a sandbox, mounting a tmpfs on it if allowed
+    pub fn create() -> Result<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("wt_sandbox_")
+            .tempdir_in(std::env::temp_dir())?;
+
+        let mounted = Command::new("mount")
+            .args(["-t", "tmpfs", "tmpfs"])
+            .arg(dir.path())
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false);
+        if mounted {
+            info!("Mounted tmpfs sandbox at {}", dir.path().display());
+            return Ok(Self { dir, mounted });
+        }
+
+        // No permission to mount - use the /dev/shm ramdisk instead, if there is one
+        let shm = Path::new("/dev/shm");
+        if shm.is_dir() {
+            if let Ok(shm_dir) = tempfile::Builder::new()
+                .prefix("wt_sandbox_")
+                .tempdir_in(shm)
+            {
+                warn!(
+                    "Could not mount tmpfs, using sandbox {}",
+                    shm_dir.path().display()
+                );
+                return Ok(Self {
+                    dir: shm_dir,
+                    mounted: false,
+                });
+            }
+        }
+
+        warn!(
+            "Could not mount tmpfs, using sandbox {} on disk",
+            dir.path().display()
+        );
+        Ok(Self { dir, mounted })
+    }
+
+    /// Root directory of the sandbox
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Wineprefix inside the sandbox
+    pub fn prefix(&self) -> PathBuf {
+        self.dir.path().join("prefix")
+    }
+
+    /// Whether the sandbox is a tmpfs mount
+    pub fn is_tmpfs(&self) -> bool {
+        self.mounted
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        // Unmount before TempDir removes the (then empty) mount point
+        if self.mounted {
+            match Command::new("umount").arg(self.dir.path()).status() {
+                Ok(status) if status.success() => {}
+                _ => warn!("Could not unmount sandbox {}", self.dir.path().display()),
+            }
+        }
+    }
+}
+
+/// Install a verb into a fresh prefix in a new sandbox and check that it installed
+/// Installation errors are reported in the result; errors setting up the sandbox
+/// or the prefix are returned
+pub async fn verify_in_sandbox(config: &Config, verb_name: &str) -> Result<VerificationResult> {
+    let sandbox = Sandbox::create()?;
+    let prefix = sandbox.prefix();
+    let arch = config
+        .winearch
+        .clone()
+        .unwrap_or_else(|| "win64".to_string());
+
+    let wine = Wine::detect()?;
+    info!(
+        "Creating sandbox WINEPREFIX \"{}\" with WINEARCH={}",
+        prefix.display(),
+        arch
+    );
+    wine.create_prefix(&prefix, &arch).await?;
+
+    let mut sandbox_config = config.clone();
+    sandbox_config.wineprefix = Some(prefix.clone());
+    sandbox_config.winearch = Some(arch);
+    sandbox_config.unattended = true;
+    sandbox_config.isolate = false;
+
+    let start = Instant::now();
+    let mut executor = Executor::new(sandbox_config).await?;
+    let outcome = match executor.install_verb(verb_name).await {
+        // A typo isn't a failed install
+        Err(e @ WinetricksError::VerbNotFound { .. }) => return Err(e),
+        other => other,
+    };
+    let duration = start.elapsed();
+
+    let result = verification_result(&executor, verb_name, outcome, duration);
+
+    // Stop the prefix's wineserver so nothing holds files in the sandbox open
+    let _ = Command::new(&wine.wineserver_bin)
+        .arg("-k")
+        .env("WINEPREFIX", &prefix)
+        .status();
+
+    Ok(result)
+}
+
+/// Result of an installation that returned `outcome`
+/// The installer returning success isn't enough, the verb's files must be there too
+pub(crate) fn verification_result(
+    executor: &Executor,
+    verb_name: &str,
+    outcome: Result<()>,
+    duration: Duration,
+) -> VerificationResult {
+    let installed = outcome.is_ok() && executor.verify_installed_files(verb_name).unwrap_or(false);
+    VerificationResult {
+        verb: verb_name.to_string(),
+        success: outcome.is_ok(),
+        installed,
+        error: outcome.err().map(|e| e.to_string()),
+        duration,
+    }
+}
@@ -0,0 +1,270 @@
+//! Self-update from GitHub releases (--self-update, --update-rollback)
+//!
+//! Releases carry a binary per platform (`winetricks-<target>`, e.g.
+//! winetricks-x86_64-unknown-linux-gnu) next to a `.sha256` checksum file. Updating
+//! replaces the running executable and keeps the previous one as `winetricks.bak`.
+
+use crate::download::{http_client_builder, DownloadManager};
+use crate::error::{Result, WinetricksError};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// GitHub API endpoint of the latest release
+pub const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/ryzendew/winetricks/releases/latest";
+
+/// A release binary for this platform
+#[derive(Debug, Clone)]
+pub struct Release {
+    /// Release tag (e.g. "v0.2.0")
+    pub tag: String,
+    /// Asset file name (e.g. "winetricks-x86_64-unknown-linux-gnu")
+    pub asset_name: String,
+    pub binary_url: String,
+    /// URL of the asset's sha256sum-style checksum file
+    pub checksum_url: String,
+}
+
+impl Release {
+    /// Version number of the release (the tag without its leading "v")
+    pub fn version(&self) -> &str {
+        self.tag.trim_start_matches('v')
+    }
+}
+
+/// Whether version `candidate` is newer than `current`, compared as semver:
+/// major.minor.patch numerically, a pre-release (1.0.0-rc.1) before its release, and
+/// build metadata (+...) ignored. Versions that don't parse are never newer
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    match (parse_version(candidate), parse_version(current)) {
+        (Some(candidate), Some(current)) => compare_versions(&candidate, &current).is_gt(),
+        _ => false,
+    }
+}
+
+/// (major, minor, patch) and pre-release identifiers of a semver version
+type Version<'a> = ([u64; 3], Vec<&'a str>);
+
+fn parse_version(version: &str) -> Option<Version<'_>> {
+    let version = version.split('+').next()?;
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, pre.split('.').collect()),
+        None => (version, Vec::new()),
+    };
+    let mut numbers = core.split('.').map(|n| n.parse::<u64>().ok());
+    let parsed = [numbers.next()??, numbers.next()??, numbers.next()??];
+    numbers.next().is_none().then_some((parsed, pre))
+}
+
+fn compare_versions(a: &Version, b: &Version) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    a.0.cmp(&b.0)
+        .then_with(|| match (a.1.is_empty(), b.1.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            // Numeric identifiers compare numerically and sort before alphanumeric ones
+            (false, false) => {
+                for (x, y) in a.1.iter().zip(&b.1) {
+                    let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+                        (Ok(x), Ok(y)) => x.cmp(&y),
+                        (Ok(_), Err(_)) => Ordering::Less,
+                        (Err(_), Ok(_)) => Ordering::Greater,
+                        (Err(_), Err(_)) => x.cmp(y),
+                    };
+                    if order.is_ne() {
+                        return order;
+                    }
+                }
+                a.1.len().cmp(&b.1.len())
+            }
+        })
+}
+
+/// Target triple release binaries are named after (e.g. x86_64-unknown-linux-gnu)
+pub fn platform_target() -> String {
+    format!("{}-unknown-linux-gnu", std::env::consts::ARCH)
+}
+
+/// Backup of the executable kept by the last update (winetricks.bak)
+pub fn backup_path(exe: &Path) -> PathBuf {
+    exe.with_extension("bak")
+}
+
+/// Look up the latest release and its binary for this platform
+/// (through Tor with torify, see download::http_client_builder)
+pub async fn latest_release(torify: bool) -> Result<Release> {
+    let response = http_client_builder(torify)?
+        .build()?
+        .get(LATEST_RELEASE_URL)
+        .header("Accept", "application/vnd.github.v3+json")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(WinetricksError::Download(format!(
+            "Failed to fetch the latest release from {}: HTTP {}",
+            LATEST_RELEASE_URL,
+            response.status()
+        )));
+    }
+    let json: serde_json::Value = response.json().await?;
+
+    let tag = json
+        .get("tag_name")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let assets = json
+        .get("assets")
+        .and_then(|a| a.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let asset_url = |name: &str| {
+        assets
+            .iter()
+            .find(|asset| asset.get("name").and_then(|n| n.as_str()) == Some(name))
+            .and_then(|asset| asset.get("browser_download_url"))
+            .and_then(|u| u.as_str())
+            .map(String::from)
+    };
+
+    let asset_name = format!("winetricks-{}", platform_target());
+    let binary_url = asset_url(&asset_name).ok_or_else(|| {
+        WinetricksError::Download(format!(
+            "Release {} has no {} binary",
+            tag,
+            platform_target()
+        ))
+    })?;
+    // Never install a binary that can't be verified
+    let checksum_url = asset_url(&format!("{}.sha256", asset_name)).ok_or_else(|| {
+        WinetricksError::Download(format!(
+            "Release {} has no checksum for {}",
+            tag, asset_name
+        ))
+    })?;
+
+    Ok(Release {
+        tag,
+        asset_name,
+        binary_url,
+        checksum_url,
+    })
+}
+
+/// Download a release, verify its checksum and replace `exe` with it
+/// The replaced executable is kept as winetricks.bak (see rollback())
+pub async fn install_release(release: &Release, exe: &Path, torify: bool) -> Result<()> {
+    let checksum_file = http_client_builder(torify)?
+        .build()?
+        .get(&release.checksum_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    // sha256sum format: "<hash>  <file name>"
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .filter(|hash| hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| {
+            WinetricksError::Download(format!("Invalid checksum file {}", release.checksum_url))
+        })?
+        .to_lowercase();
+
+    // Download next to the executable, so the final rename stays on one filesystem
+    let exe_dir = exe.parent().unwrap_or(Path::new("."));
+    let download_dir = tempfile::Builder::new()
+        .prefix(".winetricks-update")
+        .tempdir_in(exe_dir)
+        .map_err(|e| file_error("create a download directory in", exe_dir, e))?;
+    let downloader =
+        DownloadManager::new(download_dir.path().to_path_buf())?.with_torify(torify)?;
+    let new_exe = downloader
+        .download(
+            &release.binary_url,
+            &release.asset_name,
+            Some(&expected),
+            true,
+            None,
+            None,
+        )
+        .await?;
+
+    std::fs::set_permissions(&new_exe, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| file_error("make executable", &new_exe, e))?;
+
+    let backup = backup_path(exe);
+    std::fs::rename(exe, &backup).map_err(|e| file_error("back up", exe, e))?;
+    if let Err(e) = std::fs::rename(&new_exe, exe) {
+        // Put the old executable back rather than leaving none at all
+        let _ = std::fs::rename(&backup, exe);
+        return Err(file_error("replace", exe, e));
+    }
+
+    info!(
+        "Updated {} to {} (previous version kept as {})",
+        exe.display(),
+        release.tag,
+        backup.display()
+    );
+    Ok(())
+}
+
+/// Swap the executable with the winetricks.bak kept by the last update
+/// (running it again undoes the rollback)
+pub fn rollback(exe: &Path) -> Result<()> {
+    let backup = backup_path(exe);
+    if !backup.is_file() {
+        return Err(WinetricksError::Config(format!(
+            "No backup to roll back to ({} not found)",
+            backup.display()
+        )));
+    }
+
+    let swap = exe.with_extension("rollback");
+    std::fs::rename(exe, &swap).map_err(|e| file_error("move", exe, e))?;
+    if let Err(e) = std::fs::rename(&backup, exe) {
+        let _ = std::fs::rename(&swap, exe);
+        return Err(file_error("restore", &backup, e));
+    }
+    std::fs::rename(&swap, &backup).map_err(|e| file_error("back up", &swap, e))?;
+    Ok(())
+}
+
+/// Error for a failed file operation, with a hint when it lacked permission
+fn file_error(action: &str, path: &Path, e: std::io::Error) -> WinetricksError {
+    if e.kind() == std::io::ErrorKind::PermissionDenied {
+        WinetricksError::Config(format!(
+            "Permission denied trying to {} {} (try running with sudo or as root)",
+            action,
+            path.display()
+        ))
+    } else {
+        WinetricksError::Config(format!("Failed to {} {}: {}", action, path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_strictly_newer_versions_are_newer() {
+        assert!(is_newer("0.2.0", "0.1.0"));
+        assert!(is_newer("0.10.0", "0.9.3"));
+        assert!(is_newer("1.0.0", "1.0.0-rc.2"));
+        assert!(is_newer("1.0.0-rc.10", "1.0.0-rc.2"));
+        assert!(is_newer("1.0.0-rc.1", "1.0.0-beta"));
+
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.1.0+build.5", "0.1.0"));
+        assert!(!is_newer("0.1.0", "0.2.0"));
+        assert!(!is_newer("1.0.0-rc.1", "1.0.0"));
+        assert!(!is_newer("nightly", "0.1.0"));
+        assert!(!is_newer("1.2", "0.1.0"));
+    }
+}
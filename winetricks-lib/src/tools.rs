@@ -0,0 +1,117 @@
+//! External tools winetricks runs (cabextract, wine, 7z, ...)
+
+use crate::error::{Result, WinetricksError};
+use crate::wine::Wine;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use which::which;
+
+/// Tools installations can't do without
+pub const REQUIRED_TOOLS: &[&str] = &["cabextract", "wine", "wineserver"];
+
+/// Tools only some archive types need (with fallbacks for most of them)
+pub const OPTIONAL_TOOLS: &[&str] = &["7z", "unrar", "unzip"];
+
+/// Which external tools were found, and where
+#[derive(Debug, Clone, Default)]
+pub struct ToolAvailability {
+    /// Path of every tool that was found
+    pub found: HashMap<&'static str, PathBuf>,
+    /// Optional tools that are not installed
+    pub missing_optional: Vec<&'static str>,
+}
+
+impl ToolAvailability {
+    /// Whether a tool was found
+    pub fn has(&self, tool: &str) -> bool {
+        self.found.contains_key(tool)
+    }
+}
+
+/// Check for the required and optional external tools
+/// Fails with WinetricksError::MissingTools (including install instructions) if a
+/// required tool is missing; missing optional tools are only logged
+pub fn check_required_tools() -> Result<ToolAvailability> {
+    let mut availability = ToolAvailability::default();
+    let mut missing_required = Vec::new();
+
+    // wine/wineserver may also be a custom build next to the WINEPREFIX (see Wine::detect)
+    let custom_wine = Wine::find_custom_wine(&Wine::get_wineprefix());
+
+    for &tool in REQUIRED_TOOLS {
+        let custom = custom_wine
+            .as_ref()
+            .and_then(|(wine, wineserver)| match tool {
+                "wine" => Some(wine.clone()),
+                "wineserver" => Some(wineserver.clone()),
+                _ => None,
+            });
+        match custom.or_else(|| which(tool).ok()) {
+            Some(path) => {
+                availability.found.insert(tool, path);
+            }
+            None => missing_required.push(tool.to_string()),
+        }
+    }
+
+    for &tool in OPTIONAL_TOOLS {
+        match which(tool) {
+            Ok(path) => {
+                availability.found.insert(tool, path);
+            }
+            Err(_) => {
+                tracing::debug!("Optional tool {} not found", tool);
+                availability.missing_optional.push(tool);
+            }
+        }
+    }
+
+    if !missing_required.is_empty() {
+        return Err(WinetricksError::MissingTools {
+            tools: missing_required,
+        });
+    }
+    Ok(availability)
+}
+
+/// Find a required tool in PATH (MissingTools error with install instructions if absent)
+pub fn require(tool: &str) -> Result<PathBuf> {
+    which(tool).map_err(|_| WinetricksError::MissingTools {
+        tools: vec![tool.to_string()],
+    })
+}
+
+/// Distro package providing a tool: (apt, dnf, pacman)
+fn packages(tool: &str) -> (&str, &str, &str) {
+    match tool {
+        "wine" | "wineserver" => ("wine", "wine", "wine"),
+        "7z" => ("p7zip-full", "p7zip", "p7zip"),
+        other => (other, other, other),
+    }
+}
+
+/// "apt install a b / dnf install a b / pacman -S a b" for the given tools
+pub fn install_hint(tools: &[String]) -> String {
+    let mut apt: Vec<&str> = Vec::new();
+    let mut dnf: Vec<&str> = Vec::new();
+    let mut pacman: Vec<&str> = Vec::new();
+    for tool in tools {
+        let (a, d, p) = packages(tool);
+        // wine and wineserver come from the same package
+        if !apt.contains(&a) {
+            apt.push(a);
+        }
+        if !dnf.contains(&d) {
+            dnf.push(d);
+        }
+        if !pacman.contains(&p) {
+            pacman.push(p);
+        }
+    }
+    format!(
+        "apt install {} / dnf install {} / pacman -S {}",
+        apt.join(" "),
+        dnf.join(" "),
+        pacman.join(" ")
+    )
+}
@@ -1,10 +1,18 @@
 //! Verb system for winetricks packages
 
-use crate::error::{Result, WinetricksError};
+use crate::error::{Result, ResultExt, WinetricksError};
+use crate::wine::WineVersion;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
+
+/// How long fetching a metadata bundle may take to connect (see load_from_url)
+const METADATA_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long fetching a metadata bundle may take in total
+const METADATA_TIMEOUT: Duration = Duration::from_secs(60);
 
 /// Verb categories
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -19,6 +27,10 @@ pub enum VerbCategory {
     Settings,
     #[serde(rename = "benchmarks")]
     Benchmarks,
+    #[serde(rename = "codecs")]
+    Codecs,
+    #[serde(rename = "runtimes")]
+    Runtimes,
     #[serde(rename = "download")]
     Download,
     #[serde(rename = "manual-download")]
@@ -26,6 +38,19 @@ pub enum VerbCategory {
 }
 
 impl VerbCategory {
+    /// Every category, in the order verbs are listed
+    pub const ALL: [VerbCategory; 9] = [
+        VerbCategory::Apps,
+        VerbCategory::Benchmarks,
+        VerbCategory::Codecs,
+        VerbCategory::Dlls,
+        VerbCategory::Fonts,
+        VerbCategory::Runtimes,
+        VerbCategory::Settings,
+        VerbCategory::Download,
+        VerbCategory::ManualDownload,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             VerbCategory::Apps => "apps",
@@ -33,6 +58,8 @@ impl VerbCategory {
             VerbCategory::Fonts => "fonts",
             VerbCategory::Settings => "settings",
             VerbCategory::Benchmarks => "benchmarks",
+            VerbCategory::Codecs => "codecs",
+            VerbCategory::Runtimes => "runtimes",
             VerbCategory::Download => "download",
             VerbCategory::ManualDownload => "manual-download",
         }
@@ -49,6 +76,8 @@ impl FromStr for VerbCategory {
             "fonts" => Ok(VerbCategory::Fonts),
             "settings" => Ok(VerbCategory::Settings),
             "benchmarks" => Ok(VerbCategory::Benchmarks),
+            "codecs" => Ok(VerbCategory::Codecs),
+            "runtimes" => Ok(VerbCategory::Runtimes),
             "download" => Ok(VerbCategory::Download),
             "manual-download" => Ok(VerbCategory::ManualDownload),
             _ => Err(format!("Unknown category: {}", s)),
@@ -64,6 +93,49 @@ pub enum MediaType {
     Download,
     #[serde(rename = "manual_download")]
     ManualDownload,
+    /// Installed from a DVD (w_mount)
+    #[serde(rename = "dvd")]
+    Dvd,
+    /// Installed from a CD-ROM (w_mount)
+    #[serde(rename = "cd")]
+    CdRom,
+}
+
+/// Prefix architectures a verb works in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ArchSupport {
+    /// 32-bit prefixes only (16-bit tools, old x86-only DLLs)
+    #[serde(rename = "x86")]
+    X86Only,
+    /// 64-bit prefixes only
+    #[serde(rename = "x64")]
+    X64Only,
+    #[default]
+    #[serde(rename = "both")]
+    Both,
+}
+
+impl ArchSupport {
+    /// Whether the verb works in both 32-bit and 64-bit prefixes (the default)
+    pub fn is_both(&self) -> bool {
+        *self == ArchSupport::Both
+    }
+}
+
+impl MediaType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaType::Download => "download",
+            MediaType::ManualDownload => "manual_download",
+            MediaType::Dvd => "dvd",
+            MediaType::CdRom => "cd",
+        }
+    }
+
+    /// Whether the verb installs from a mounted disc rather than a download
+    pub fn is_disc(&self) -> bool {
+        matches!(self, MediaType::Dvd | MediaType::CdRom)
+    }
 }
 
 /// Verb metadata
@@ -78,6 +150,10 @@ pub struct VerbMetadata {
     /// Display title
     pub title: String,
 
+    /// Longer-form description (from the "# Notes:" comment block)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
     /// Publisher name
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub publisher: Option<String>,
@@ -86,10 +162,19 @@ pub struct VerbMetadata {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub year: Option<String>,
 
+    /// Homepage of the software the verb installs (from the "# Homepage:" comment)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+
     /// Media type
     #[serde(default)]
     pub media: MediaType,
 
+    /// Prefix architectures the verb works in (only x86/x64-only verbs, which are marked
+    /// by hand, have the field)
+    #[serde(default, skip_serializing_if = "ArchSupport::is_both")]
+    pub architecture: ArchSupport,
+
     /// Download files
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub files: Vec<VerbFile>,
@@ -105,6 +190,86 @@ pub struct VerbMetadata {
     /// Conflicting verbs
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub conflicts: Vec<String>,
+
+    /// Verbs this verb depends on (prerequisites)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+
+    /// Verbs the original load_* function runs itself (w_call) before its own
+    /// installation step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<String>,
+
+    /// Verbs the original load_* function w_calls after its own installation step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls_after: Vec<String>,
+
+    /// Free-form tags for finer filtering than the category (e.g. "directx", "gaming")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Page to buy or download the files of a manual download verb (w_download_manual)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_url: Option<String>,
+
+    /// How to get the files of a manual download verb (from the "# Manual download:" comment)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_instructions: Option<String>,
+
+    /// Wine versions this verb is known to be broken in (matching w_package_broken)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub broken_wine_versions: Vec<WineVersionRange>,
+
+    /// Wine bugs worked around by this verb (matching w_workaround_wine_bug)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wine_bug_workarounds: Vec<WineBugWorkaround>,
+
+    /// Optional features of the verb's MSI package (msiexec ADDLOCAL=); users can pick
+    /// which ones to install unless running unattended
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub msi_features: Vec<String>,
+}
+
+/// Wine bug worked around by a verb
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WineBugWorkaround {
+    /// WineHQ bug number
+    pub bug_number: u32,
+
+    /// Description of the workaround
+    #[serde(default)]
+    pub description: String,
+
+    /// Affected Wine version ranges (e.g. ",5.18" or "4.0,6.0", space separated)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub affected_versions: Option<String>,
+}
+
+/// Range of Wine versions a verb is known to be broken in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WineVersionRange {
+    /// First broken Wine version (inclusive, broken since forever if not set)
+    #[serde(default, skip_serializing_if = "WineVersion::is_unbounded")]
+    pub min: WineVersion,
+
+    /// Last broken Wine version (inclusive, still broken if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<WineVersion>,
+
+    /// Related Wine bug URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bug_url: Option<String>,
+
+    /// Workaround hint shown to the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workaround: Option<String>,
+}
+
+impl WineVersionRange {
+    /// Whether the given Wine version falls within this range
+    pub fn contains(&self, version: &WineVersion) -> bool {
+        *version >= self.min && self.max.as_ref().is_none_or(|max| version <= max)
+    }
 }
 
 /// File to download
@@ -120,6 +285,41 @@ pub struct VerbFile {
     /// SHA256 checksum
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sha256: Option<String>,
+
+    /// Size in bytes (if known, used for disk space checks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+
+    /// Architecture the file is for ("x86" or "x64"), None if it's for both
+    /// x64 files are only used in 64-bit prefixes; x86 ones in every prefix, since a
+    /// 64-bit prefix runs 32-bit programs too (WoW64) and needs both
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+}
+
+impl VerbFile {
+    /// Whether the file is used in a 64-bit (or 32-bit) prefix
+    pub fn applies_to(&self, win64: bool) -> bool {
+        match self.arch.as_deref() {
+            Some("x64") => win64,
+            _ => true,
+        }
+    }
+}
+
+/// What the user has to fetch by hand for a manual download verb
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManualDownloadInfo {
+    /// Files to put into the verb's cache directory
+    pub files: Vec<VerbFile>,
+
+    /// Page to buy or download the files from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_url: Option<String>,
+
+    /// Instructions for getting the files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
 }
 
 /// Verb registry
@@ -139,9 +339,80 @@ impl VerbRegistry {
     }
 
     /// Load verbs from metadata directory
+    /// The JSON files are parsed in parallel; if any of them can't be loaded, the
+    /// directory is loaded again sequentially so the error names the offending file
     pub fn load_from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
-        let mut registry = Self::new();
+        use rayon::prelude::*;
+        use std::sync::Mutex;
+
         let dir = dir.as_ref();
+        let files = Self::verb_files(dir)?;
+
+        let registry = Mutex::new(Self::new());
+        let loaded = files.par_iter().try_for_each(|(file_path, category)| {
+            let metadata = Self::load_verb_metadata(file_path)?;
+            registry.lock().unwrap_or_else(|e| e.into_inner()).register(
+                metadata.name.clone(),
+                metadata,
+                *category,
+            )
+        });
+        if loaded.is_err() {
+            return Self::load_from_dir_sequential(&files);
+        }
+
+        let mut registry = registry.into_inner().unwrap_or_else(|e| e.into_inner());
+        // Registration order depends on thread scheduling
+        for names in registry.by_category.values_mut() {
+            names.sort();
+        }
+        Ok(registry)
+    }
+
+    /// Load verbs from `dir`, then the verbs in `overlay_dir` (if it exists) on top
+    /// of them: an overlay verb replaces the verb of the same name
+    pub fn load_with_overlay<P: AsRef<Path>, Q: AsRef<Path>>(
+        dir: P,
+        overlay_dir: Q,
+    ) -> Result<Self> {
+        let mut registry = Self::load_from_dir(dir)?;
+        if overlay_dir.as_ref().is_dir() {
+            registry.merge(Self::load_from_dir(overlay_dir)?);
+        }
+        Ok(registry)
+    }
+
+    /// Add the verbs of `overlay`, replacing verbs of the same name (in any category)
+    pub fn merge(&mut self, overlay: VerbRegistry) {
+        for (name, metadata) in overlay.verbs {
+            if let Some(old) = self.verbs.remove(&name) {
+                if let Some(names) = self.by_category.get_mut(&old.category) {
+                    names.retain(|n| n != &name);
+                }
+            }
+            let names = self.by_category.entry(metadata.category).or_default();
+            names.push(name.clone());
+            names.sort();
+            self.verbs.insert(name, metadata);
+        }
+    }
+
+    /// Load verbs one file at a time, adding the file path to errors
+    fn load_from_dir_sequential(files: &[(PathBuf, VerbCategory)]) -> Result<Self> {
+        let mut registry = Self::new();
+        for (file_path, category) in files {
+            let metadata = Self::load_verb_metadata(file_path)
+                .with_context(|| format!("While loading {}", file_path.display()))?;
+            registry
+                .register(metadata.name.clone(), metadata, *category)
+                .with_context(|| format!("While loading {}", file_path.display()))?;
+        }
+        Ok(registry)
+    }
+
+    /// The verb JSON files in a metadata directory (<category>/<verb>.json)
+    fn verb_files(dir: &Path) -> Result<Vec<(PathBuf, VerbCategory)>> {
+        let mut files = Vec::new();
 
         // Scan all category directories
         for category_dir in std::fs::read_dir(dir)? {
@@ -159,28 +430,153 @@ impl VerbRegistry {
 
             let category = VerbCategory::from_str(category_name).map_err(WinetricksError::Verb)?;
 
-            // Load all JSON files in category directory
+            // All JSON files in category directory
             for entry in std::fs::read_dir(&path)? {
-                let entry = entry?;
-                let file_path = entry.path();
+                let file_path = entry?.path();
 
                 if file_path.extension().and_then(|s| s.to_str()) != Some("json") {
                     continue;
                 }
+                if file_path.file_stem().and_then(|n| n.to_str()).is_none() {
+                    return Err(WinetricksError::Verb("Invalid verb filename".into()));
+                }
+                files.push((file_path, category));
+            }
+        }
 
-                let verb_name = file_path
-                    .file_stem()
-                    .and_then(|n| n.to_str())
-                    .ok_or_else(|| WinetricksError::Verb("Invalid verb filename".into()))?;
+        Ok(files)
+    }
+
+    /// Load verbs from a remote JSON bundle (an array of verb metadata objects)
+    /// With torify the bundle is fetched through Tor (see download::http_client_builder)
+    pub async fn load_from_url(url: &str, torify: bool) -> Result<Self> {
+        Self::load_from_url_if_modified(url, None, torify)
+            .await?
+            .map(|(registry, _)| registry)
+            .ok_or_else(|| {
+                WinetricksError::Download(format!("Unexpected 304 Not Modified from {}", url))
+            })
+    }
+
+    /// Like load_from_url, but revalidating a copy fetched earlier with its ETag
+    /// Returns None if the server says that copy is still current, otherwise the
+    /// registry and the ETag of the new bundle
+    pub async fn load_from_url_if_modified(
+        url: &str,
+        etag: Option<&str>,
+        torify: bool,
+    ) -> Result<Option<(Self, Option<String>)>> {
+        let client = crate::download::http_client_builder(torify)?
+            .connect_timeout(METADATA_CONNECT_TIMEOUT)
+            .timeout(METADATA_TIMEOUT)
+            .build()?;
+        let mut request = client.get(url);
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let response = request.send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(WinetricksError::Download(format!(
+                "Failed to fetch verb metadata from {}: HTTP {}",
+                url,
+                response.status()
+            )));
+        }
+
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let registry = Self::import_json(&response.text().await?)?;
+        Ok(Some((registry, etag)))
+    }
+
+    /// Serialize all verbs to a single JSON array (sorted by name)
+    pub fn export_json(&self) -> Result<String> {
+        let mut verbs: Vec<&VerbMetadata> = self.verbs.values().collect();
+        verbs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(serde_json::to_string_pretty(&verbs)?)
+    }
+
+    /// Markdown reference of all verbs: a section per category with a table of its
+    /// verbs (name, title, publisher, year, media), sorted by name
+    pub fn to_markdown(&self) -> String {
+        // Table cells can't contain pipes or line breaks
+        let cell = |value: &str| value.replace('|', "\\|").replace('\n', " ");
 
-                let metadata = Self::load_verb_metadata(&file_path)?;
-                registry.register(verb_name.to_string(), metadata, category)?;
+        let mut markdown = String::from("# Winetricks verbs\n");
+        for category in VerbCategory::ALL {
+            let verbs: Vec<&VerbMetadata> = self.iter_category(category).collect();
+            if verbs.is_empty() {
+                continue;
+            }
+
+            markdown.push_str(&format!("\n## {}\n\n", category.as_str()));
+            markdown.push_str("| Name | Title | Publisher | Year | Media |\n");
+            markdown.push_str("|------|-------|-----------|------|-------|\n");
+            for verb in verbs {
+                markdown.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    cell(&verb.name),
+                    cell(&verb.title),
+                    cell(verb.publisher.as_deref().unwrap_or("")),
+                    cell(verb.year.as_deref().unwrap_or("")),
+                    verb.media.as_str()
+                ));
             }
         }
+        markdown
+    }
+
+    /// Load verbs from a JSON array of verb metadata objects (export_json output or a bundle)
+    /// Each entry carries its own category, so no directory layout is needed
+    pub fn import_json(content: &str) -> Result<Self> {
+        let verbs: Vec<VerbMetadata> = serde_json::from_str(content)?;
+
+        let mut registry = Self::new();
+        for metadata in verbs {
+            let category = metadata.category;
+            registry.register(metadata.name.clone(), metadata, category)?;
+        }
 
         Ok(registry)
     }
 
+    /// Write every verb as <dir>/<category>/<name>.json (the layout load_from_dir reads)
+    /// Verb files in the category directories that aren't in this registry (verbs that
+    /// were dropped or moved to another category) are removed
+    pub fn write_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        for metadata in self.verbs.values() {
+            let category_dir = dir.as_ref().join(metadata.category.as_str());
+            std::fs::create_dir_all(&category_dir)?;
+            std::fs::write(
+                category_dir.join(format!("{}.json", metadata.name)),
+                serde_json::to_string_pretty(metadata)?,
+            )?;
+        }
+
+        for category in VerbCategory::ALL {
+            let Ok(entries) = std::fs::read_dir(dir.as_ref().join(category.as_str())) else {
+                continue;
+            };
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+                let name = path.file_stem().and_then(|n| n.to_str()).unwrap_or("");
+                if self.get(name).map(|verb| verb.category) != Some(category) {
+                    std::fs::remove_file(&path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Load verb metadata from JSON file
     fn load_verb_metadata<P: AsRef<Path>>(path: P) -> Result<VerbMetadata> {
         let content = std::fs::read_to_string(path.as_ref())?;
@@ -231,6 +627,96 @@ impl VerbRegistry {
         self.verbs.values().collect()
     }
 
+    /// List verbs of every category, sorted by name
+    pub fn list_all(&self) -> Vec<&VerbMetadata> {
+        let mut verbs = self.list();
+        verbs.sort_by(|a, b| a.name.cmp(&b.name));
+        verbs
+    }
+
+    /// List verbs installed from the given media, sorted by name
+    pub fn list_by_media_type(&self, media: MediaType) -> Vec<&VerbMetadata> {
+        self.list_all()
+            .into_iter()
+            .filter(|v| v.media == media)
+            .collect()
+    }
+
+    /// List verbs whose files have to be downloaded by hand, sorted by name
+    pub fn get_verbs_needing_manual_download(&self) -> Vec<&VerbMetadata> {
+        self.list_by_media_type(MediaType::ManualDownload)
+    }
+
+    /// Files, purchase page and instructions of a manual download verb
+    /// Returns None for unknown verbs and verbs that download (or install from disc) by themselves
+    pub fn get_manual_download_info(&self, verb_name: &str) -> Option<ManualDownloadInfo> {
+        let verb = self.get(verb_name)?;
+        if verb.media != MediaType::ManualDownload {
+            return None;
+        }
+        Some(ManualDownloadInfo {
+            files: verb.files.clone(),
+            purchase_url: verb.purchase_url.clone(),
+            instructions: verb.download_instructions.clone(),
+        })
+    }
+
+    /// List verbs with the given tag (case-insensitive), sorted by name
+    pub fn list_by_tag(&self, tag: &str) -> Vec<&VerbMetadata> {
+        self.list_all()
+            .into_iter()
+            .filter(|v| v.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            .collect()
+    }
+
+    /// Verbs whose installed_file is the given file (e.g. "msvcp140.dll",
+    /// case-insensitive), sorted by name
+    /// Helps map a Windows "DLL not found" error to the verb that provides the DLL
+    pub fn get_by_installed_file(&self, filename: &str) -> Vec<&VerbMetadata> {
+        let file_name = |path: &str| path.rsplit(['/', '\\']).next().unwrap_or(path).to_string();
+        let wanted = file_name(filename.trim());
+        self.list_all()
+            .into_iter()
+            .filter(|v| {
+                // installed_file may list alternatives separated by '|'
+                v.installed_file.as_deref().is_some_and(|installed| {
+                    installed
+                        .split('|')
+                        .any(|path| file_name(path).eq_ignore_ascii_case(&wanted))
+                })
+            })
+            .collect()
+    }
+
+    /// All tags used by any verb, sorted
+    pub fn list_tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self
+            .verbs
+            .values()
+            .flat_map(|v| v.tags.iter().map(String::as_str))
+            .collect();
+        tags.sort_unstable();
+        tags.dedup();
+        tags
+    }
+
+    /// Iterate over all verbs, by category (in `VerbCategory::ALL` order) then name
+    pub fn iter(&self) -> impl Iterator<Item = &VerbMetadata> + '_ {
+        VerbCategory::ALL
+            .into_iter()
+            .flat_map(move |category| self.iter_category(category))
+    }
+
+    /// Iterate over the verbs of one category, sorted by name
+    pub fn iter_category(
+        &self,
+        category: VerbCategory,
+    ) -> impl Iterator<Item = &VerbMetadata> + '_ {
+        let mut verbs = self.list_by_category(category);
+        verbs.sort_by(|a, b| a.name.cmp(&b.name));
+        verbs.into_iter()
+    }
+
     /// List verbs by category
     pub fn list_by_category(&self, category: VerbCategory) -> Vec<&VerbMetadata> {
         self.by_category
@@ -248,6 +734,177 @@ impl VerbRegistry {
     pub fn exists(&self, name: &str) -> bool {
         self.verbs.contains_key(name)
     }
+
+    /// Get installed verbs that list `verb_name` as a dependency
+    /// (used to avoid breaking dependents when uninstalling)
+    pub fn get_reverse_dependencies(&self, verb_name: &str, installed: &[String]) -> Vec<String> {
+        let mut dependents: Vec<String> = installed
+            .iter()
+            .filter(|name| name.as_str() != verb_name)
+            .filter(|name| {
+                self.verbs
+                    .get(name.as_str())
+                    .map(|metadata| metadata.dependencies.iter().any(|dep| dep == verb_name))
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+
+        dependents.sort();
+        dependents.dedup();
+        dependents
+    }
+
+    /// Find pairs of installed verbs that conflict with each other
+    /// Each pair is reported once, ordered alphabetically
+    pub fn find_conflicts(&self, installed: &[String]) -> Vec<(String, String)> {
+        let mut pairs: Vec<(String, String)> = Vec::new();
+
+        for verb_name in installed {
+            let Some(metadata) = self.verbs.get(verb_name) else {
+                continue;
+            };
+
+            for conflict in &metadata.conflicts {
+                if conflict == verb_name || !installed.contains(conflict) {
+                    continue;
+                }
+
+                let pair = if verb_name < conflict {
+                    (verb_name.clone(), conflict.clone())
+                } else {
+                    (conflict.clone(), verb_name.clone())
+                };
+                if !pairs.contains(&pair) {
+                    pairs.push(pair);
+                }
+            }
+        }
+
+        pairs.sort();
+        pairs
+    }
+
+    /// Suggest up to `n` known verb names closest to a misspelled one
+    /// (Levenshtein distance, ignoring matches that are too far off to be useful)
+    pub fn suggest(&self, misspelled: &str, n: usize) -> Vec<String> {
+        let misspelled = misspelled.to_lowercase();
+        let max_distance = (misspelled.chars().count() / 2).max(2);
+
+        let mut candidates: Vec<(usize, &String)> = self
+            .verbs
+            .keys()
+            .map(|name| (levenshtein(&misspelled, &name.to_lowercase()), name))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect();
+
+        candidates.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+        candidates
+            .into_iter()
+            .take(n)
+            .map(|(_, name)| name.clone())
+            .collect()
+    }
+
+    /// Compare two registries (e.g. the local cache and a freshly downloaded bundle)
+    /// Verbs are matched by name; all lists are sorted by verb name
+    pub fn diff(old: &VerbRegistry, new: &VerbRegistry) -> RegistryDiff {
+        let mut diff = RegistryDiff::default();
+
+        for (name, new_metadata) in &new.verbs {
+            match old.verbs.get(name) {
+                None => diff.added.push(name.clone()),
+                Some(old_metadata) => {
+                    let change = VerbChange::between(old_metadata, new_metadata);
+                    if !change.fields.is_empty() {
+                        diff.changed.push((name.clone(), change));
+                    }
+                }
+            }
+        }
+        diff.removed = old
+            .verbs
+            .keys()
+            .filter(|name| !new.verbs.contains_key(*name))
+            .cloned()
+            .collect();
+
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort_by(|a, b| a.0.cmp(&b.0));
+        diff
+    }
+}
+
+/// Differences between two verb registries (see VerbRegistry::diff)
+#[derive(Debug, Clone, Default)]
+pub struct RegistryDiff {
+    /// Verbs only in the new registry
+    pub added: Vec<String>,
+    /// Verbs only in the old registry
+    pub removed: Vec<String>,
+    /// Verbs in both whose metadata differs
+    pub changed: Vec<(String, VerbChange)>,
+}
+
+impl RegistryDiff {
+    /// Whether the registries have the same verbs with the same metadata
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Which metadata fields of a verb differ between two registries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerbChange {
+    /// JSON field names (e.g. "files", "installed_file"), sorted
+    pub fields: Vec<String>,
+}
+
+impl VerbChange {
+    /// Compare two versions of a verb field by field, by their JSON form
+    fn between(old: &VerbMetadata, new: &VerbMetadata) -> Self {
+        let to_map = |metadata: &VerbMetadata| match serde_json::to_value(metadata) {
+            Ok(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        let (old, new) = (to_map(old), to_map(new));
+
+        // Fields skipped when empty are missing from one side only
+        let mut fields: Vec<String> = old
+            .keys()
+            .chain(new.keys())
+            .filter(|key| old.get(*key) != new.get(*key))
+            .cloned()
+            .collect();
+        fields.sort();
+        fields.dedup();
+        Self { fields }
+    }
+}
+
+impl std::fmt::Display for VerbChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.fields.join(", "))
+    }
+}
+
+/// Levenshtein edit distance between two strings
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b_chars.len()).collect();
+    let mut curr = vec![0; b_chars.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b_chars.len()]
 }
 
 /// Verb executor (placeholder for now)
@@ -261,3 +918,55 @@ impl Verb {
         Self { metadata }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn x86_files_apply_to_win64_prefixes_too() {
+        let file = |arch: Option<&str>| VerbFile {
+            filename: "vc_redist.exe".to_string(),
+            url: None,
+            sha256: None,
+            size: None,
+            arch: arch.map(str::to_string),
+        };
+        assert!(file(Some("x86")).applies_to(false));
+        assert!(file(Some("x86")).applies_to(true));
+        assert!(!file(Some("x64")).applies_to(false));
+        assert!(file(Some("x64")).applies_to(true));
+        assert!(file(None).applies_to(false));
+        assert!(file(None).applies_to(true));
+    }
+
+    #[test]
+    fn overlay_verbs_survive_rewriting_the_base_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let base_dir = dir.path().join("base");
+        let overlay_dir = dir.path().join("overlay");
+        let verbs = |json: &str| VerbRegistry::import_json(json).unwrap();
+
+        let base = verbs(
+            r#"[{"name": "vcrun2019", "category": "dlls", "title": "Built-in"},
+                {"name": "corefonts", "category": "fonts", "title": "Core fonts"}]"#,
+        );
+        base.write_to_dir(&base_dir).unwrap();
+        verbs(
+            r#"[{"name": "vcrun2019", "category": "runtimes", "title": "Imported"},
+                {"name": "mytool", "category": "apps", "title": "My tool"}]"#,
+        )
+        .write_to_dir(&overlay_dir)
+        .unwrap();
+
+        // A metadata refresh rewrites (and prunes) the base directory only
+        base.write_to_dir(&base_dir).unwrap();
+
+        let registry = VerbRegistry::load_with_overlay(&base_dir, &overlay_dir).unwrap();
+        assert_eq!(registry.get("vcrun2019").unwrap().title, "Imported");
+        assert_eq!(registry.get("mytool").unwrap().title, "My tool");
+        assert!(registry.get("corefonts").is_some());
+        assert!(registry.list_by_category(VerbCategory::Dlls).is_empty());
+        assert_eq!(registry.list_by_category(VerbCategory::Runtimes).len(), 1);
+    }
+}
@@ -1,10 +1,65 @@
 //! Wine interface for detecting and managing Wine installations
 
 use crate::error::{Result, WinetricksError};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
 use which::which;
 
+/// Registry key holding per-DLL overrides
+const DLL_OVERRIDES_KEY: &str = "HKEY_CURRENT_USER\\Software\\Wine\\DllOverrides";
+const DRIVERS_KEY: &str = "HKEY_CURRENT_USER\\Software\\Wine\\Drivers";
+
+/// Windows versions `winecfg -v` accepts
+pub const WINDOWS_VERSIONS: &[&str] = &[
+    "win11",
+    "win10",
+    "win81",
+    "win8",
+    "win2008r2",
+    "win7",
+    "win2008",
+    "vista",
+    "win2003",
+    "winxp64",
+    "winxp",
+    "win2k",
+    "winme",
+    "win98",
+    "win95",
+    "nt40",
+    "nt351",
+    "win31",
+    "win30",
+    "win20",
+];
+
+/// Audio drivers for HKCU\Software\Wine\Drivers\Audio ("disabled" turns sound off)
+pub const AUDIO_DRIVERS: &[&str] = &["pulse", "alsa", "oss", "coreaudio", "disabled"];
+
+/// Graphics drivers for HKCU\Software\Wine\Drivers\Graphics ("default" lets Wine pick)
+pub const GRAPHICS_DRIVERS: &[&str] = &["x11", "wayland", "mac", "default"];
+
+/// Default time to wait for wineserver to finish (wineserver -w)
+pub const DEFAULT_WINESERVER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Manifest stored in prefix exports (see Wine::export_prefix)
+pub const EXPORT_MANIFEST_NAME: &str = "winetricks_export.json";
+
+/// What a prefix export contains
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PrefixExportManifest {
+    /// Verbs listed in the prefix's winetricks.log
+    pub verbs: Vec<String>,
+    /// Wine version of the exporting system (e.g. "wine-9.0")
+    pub wine_version: String,
+    /// Prefix architecture (win32 or win64)
+    pub arch: String,
+    /// When the export was made (ISO 8601 UTC)
+    pub exported_at: String,
+}
+
 /// Wine installation and version information
 #[derive(Debug, Clone)]
 pub struct Wine {
@@ -22,18 +77,344 @@ pub struct Wine {
 
     /// Architecture (win32 or win64)
     pub arch: String,
+
+    /// Verbosity for run_and_capture (0: hide stderr, 2+: mirror stderr to the terminal)
+    pub verbosity: u8,
+
+    /// Whether wine_bin belongs to a Steam Proton build (see is_proton())
+    pub proton: bool,
+
+    /// wine64 next to wine_bin, if the installation has one (older WoW64 builds)
+    pub wine64_bin: Option<PathBuf>,
+
+    /// Where the installation was found
+    pub install_type: WineInstallType,
+}
+
+/// Where Wine::detect() found a Wine installation, in detection order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WineInstallType {
+    /// The $WINE environment variable
+    Env,
+    /// A Wine build belonging to the WINEPREFIX (see find_custom_wine())
+    Custom,
+    /// wine in PATH
+    System,
+    /// A Proton build in Steam's compatibilitytools.d
+    Proton,
+    /// Flatpak runtime (/app/bin/wine)
+    Flatpak,
+    /// /opt/wine-staging (WineHQ staging packages)
+    Staging,
+    /// A Wine package in the Nix store
+    Nix,
+}
+
+impl WineInstallType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WineInstallType::Env => "env",
+            WineInstallType::Custom => "custom",
+            WineInstallType::System => "system",
+            WineInstallType::Proton => "proton",
+            WineInstallType::Flatpak => "flatpak",
+            WineInstallType::Staging => "staging",
+            WineInstallType::Nix => "nix",
+        }
+    }
+}
+
+impl std::fmt::Display for WineInstallType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Wine version number (e.g. "9.0", "8.21"), compared component by component
+/// The default (0) is older than every real version
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct WineVersion(Vec<u32>);
+
+impl WineVersion {
+    /// Whether this is the default version, i.e. no lower bound
+    pub fn is_unbounded(&self) -> bool {
+        self.0.iter().all(|n| *n == 0)
+    }
+}
+
+impl std::str::FromStr for WineVersion {
+    type Err = WinetricksError;
+
+    /// Accepts both "9.0" and `wine --version` output like "wine-9.0-rc1 (Staging)"
+    fn from_str(s: &str) -> Result<Self> {
+        Wine::strip_version(s)
+            .split('.')
+            .map(|n| {
+                n.trim()
+                    .parse::<u32>()
+                    .map_err(|_| WinetricksError::InvalidWineVersion(s.to_string()))
+            })
+            .collect::<Result<Vec<u32>>>()
+            .map(WineVersion)
+    }
+}
+
+impl TryFrom<String> for WineVersion {
+    type Error = WinetricksError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<WineVersion> for String {
+    fn from(version: WineVersion) -> Self {
+        version.to_string()
+    }
+}
+
+impl std::fmt::Display for WineVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self.0.iter().map(u32::to_string).collect();
+        f.write_str(&parts.join("."))
+    }
+}
+
+/// A process running in a wineprefix (see Wine::list_running_processes)
+#[derive(Debug, Clone)]
+pub struct WineProcess {
+    pub pid: u32,
+    /// Executable name (e.g. "explorer.exe", "wineserver")
+    pub name: String,
+    /// Executable path as the process sees it (a Windows path for Windows programs)
+    pub exe: String,
+}
+
+/// Captured result of a wine command (see Wine::run_and_capture)
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// Process exit code (-1 if wine was killed by a signal)
+    pub exit_code: i32,
+}
+
+impl CommandOutput {
+    /// Whether wine exited with status 0
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+
+    /// Exit code, or None if wine was killed by a signal
+    pub fn code(&self) -> Option<i32> {
+        (self.exit_code >= 0).then_some(self.exit_code)
+    }
 }
 
 impl Wine {
     /// Detect Wine installation
-    /// Checks for custom Wine in WINEPREFIX first, then falls back to PATH
+    /// Tries, in order: $WINE, a custom Wine in WINEPREFIX (see find_custom_wine()), PATH,
+    /// Proton builds in Steam's compatibilitytools.d, Flatpak (/app/bin/wine),
+    /// /opt/wine-staging and the Nix store
     pub fn detect() -> Result<Self> {
-        // Check if WINEPREFIX has a custom Wine installation
-        // Common locations: WINEPREFIX/bin/wine, WINEPREFIX/wine/bin/wine, WINEPREFIX/../ElementalWarriorWine/bin/wine
         let wineprefix = Self::get_wineprefix();
-        let mut wine_bin = None;
-        let mut wineserver_bin = None;
+        let (wine_bin, wineserver_bin, install_type) = Self::find_wine(&wineprefix)?;
+
+        let version = Self::query_version(&wine_bin)?;
+        let version_stripped = Self::strip_version(&version);
 
+        // Detect architecture by checking if wineserver is 64-bit
+        // This is a simplified check - real winetricks does more complex detection
+        // For now, default to win32 (will be improved later)
+        let arch = "win32".to_string();
+
+        let wine64_bin = wine_bin
+            .parent()
+            .map(|dir| dir.join("wine64"))
+            .filter(|bin| bin.is_file());
+
+        tracing::debug!("Using {} Wine at {:?}", install_type, wine_bin);
+        let wine = Self {
+            proton: Self::is_proton(&wine_bin),
+            wine_bin,
+            wineserver_bin,
+            version,
+            version_stripped,
+            arch,
+            verbosity: 0,
+            wine64_bin,
+            install_type,
+        };
+        Ok(wine)
+    }
+
+    /// Wine at /usr/bin/wine, without checking that it exists or running it
+    /// (Config::skip_wine_check); anything that runs wine will fail
+    pub fn placeholder() -> Self {
+        Self {
+            wine_bin: PathBuf::from("/usr/bin/wine"),
+            wineserver_bin: PathBuf::from("/usr/bin/wineserver"),
+            version: "wine-0.0".to_string(),
+            version_stripped: "0.0".to_string(),
+            arch: "win32".to_string(),
+            verbosity: 0,
+            proton: false,
+            wine64_bin: None,
+            install_type: WineInstallType::System,
+        }
+    }
+
+    /// Find wine and wineserver (see detect() for the search order)
+    fn find_wine(wineprefix: &Path) -> Result<(PathBuf, PathBuf, WineInstallType)> {
+        // $WINE may be a path or a command name (e.g. WINE=wine-staging)
+        if let Some(env_wine) = std::env::var_os("WINE").filter(|v| !v.is_empty()) {
+            let wine_bin = which(&env_wine).map_err(|_| {
+                WinetricksError::Wine(format!("$WINE ({:?}) is not an executable", env_wine))
+            })?;
+            let wineserver_bin = std::env::var_os("WINESERVER")
+                .filter(|v| !v.is_empty())
+                .and_then(|v| which(v).ok())
+                .or_else(|| Self::wineserver_for(&wine_bin))
+                .ok_or_else(|| {
+                    WinetricksError::Wine(format!(
+                        "No wineserver found for $WINE ({:?}), set $WINESERVER",
+                        wine_bin
+                    ))
+                })?;
+            return Ok((wine_bin, wineserver_bin, WineInstallType::Env));
+        }
+
+        if let Some((wine_bin, wineserver_bin)) = Self::find_custom_wine(wineprefix) {
+            return Ok((wine_bin, wineserver_bin, WineInstallType::Custom));
+        }
+
+        if let Ok(wine_bin) = which("wine") {
+            let wineserver_bin = which("wineserver")
+                .map_err(|_| WinetricksError::Wine("wineserver binary not found in PATH".into()))?;
+            return Ok((wine_bin, wineserver_bin, WineInstallType::System));
+        }
+
+        Self::fallback_wine_paths()
+            .into_iter()
+            .filter(|(wine_bin, _)| wine_bin.is_file())
+            .find_map(|(wine_bin, install_type)| {
+                let wineserver_bin = wine_bin.parent()?.join("wineserver");
+                wineserver_bin
+                    .is_file()
+                    .then_some((wine_bin, wineserver_bin, install_type))
+            })
+            .ok_or_else(|| {
+                WinetricksError::Wine(
+                    "wine binary not found in PATH, Steam compatibilitytools.d, /app/bin, \
+                     /opt/wine-staging or /nix/store (set $WINE to use another)"
+                        .into(),
+                )
+            })
+    }
+
+    /// wineserver belonging to a wine binary: next to it, or in PATH
+    fn wineserver_for(wine_bin: &Path) -> Option<PathBuf> {
+        wine_bin
+            .parent()
+            .map(|dir| dir.join("wineserver"))
+            .filter(|bin| bin.is_file())
+            .or_else(|| which("wineserver").ok())
+    }
+
+    /// Candidate wine binaries outside PATH, in priority order
+    fn fallback_wine_paths() -> Vec<(PathBuf, WineInstallType)> {
+        let mut candidates = Vec::new();
+
+        // Proton ships wine as <proton dir>/files/bin/wine (older builds: dist/bin/wine)
+        if let Some(home) = dirs::home_dir() {
+            let tools_dir = home
+                .join(".local")
+                .join("share")
+                .join("Steam")
+                .join("compatibilitytools.d");
+            let mut proton_dirs: Vec<PathBuf> = std::fs::read_dir(&tools_dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|entry| entry.path())
+                        .filter(|dir| dir.join("proton").is_file())
+                        .collect()
+                })
+                .unwrap_or_default();
+            // Newest build first (e.g. GE-Proton9-20 before GE-Proton9-9)
+            proton_dirs.sort_by_cached_key(|dir| {
+                std::cmp::Reverse(Self::version_sort_key(
+                    &dir.file_name().unwrap_or_default().to_string_lossy(),
+                ))
+            });
+            for dir in proton_dirs {
+                for dist in ["files", "dist"] {
+                    candidates.push((
+                        dir.join(dist).join("bin").join("wine"),
+                        WineInstallType::Proton,
+                    ));
+                }
+            }
+        }
+
+        candidates.push((PathBuf::from("/app/bin/wine"), WineInstallType::Flatpak));
+        candidates.push((
+            PathBuf::from("/opt/wine-staging/bin/wine"),
+            WineInstallType::Staging,
+        ));
+
+        // Store paths are <hash>-<name>-<version>; only look inside Wine packages
+        let mut nix_wines: Vec<PathBuf> = std::fs::read_dir("/nix/store")
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|entry| entry.file_name().to_string_lossy().contains("-wine"))
+                    .map(|entry| entry.path().join("bin").join("wine"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        nix_wines.sort();
+        candidates.extend(nix_wines.into_iter().map(|bin| (bin, WineInstallType::Nix)));
+
+        candidates
+    }
+
+    /// Split a name into text and numbers, so names sort by their version numbers
+    /// (GE-Proton9-9 before GE-Proton9-20)
+    fn version_sort_key(name: &str) -> Vec<NamePart> {
+        let mut parts = Vec::new();
+        let mut rest = name;
+        while let Some(first) = rest.chars().next() {
+            let is_digit = first.is_ascii_digit();
+            let end = rest
+                .find(|c: char| c.is_ascii_digit() != is_digit)
+                .unwrap_or(rest.len());
+            let (part, tail) = rest.split_at(end);
+            parts.push(match part.parse() {
+                Ok(number) if is_digit => NamePart::Number(number),
+                _ => NamePart::Text(part.to_string()),
+            });
+            rest = tail;
+        }
+        parts
+    }
+
+    /// Look for a custom Wine installation belonging to a WINEPREFIX
+    /// Common locations: WINEPREFIX/bin/wine, WINEPREFIX/wine/bin/wine, WINEPREFIX/../ElementalWarriorWine/bin/wine
+    /// Returns (wine, wineserver) if both exist
+    pub fn find_custom_wine(wineprefix: &Path) -> Option<(PathBuf, PathBuf)> {
         // Try common custom Wine locations
         let mut custom_wine_paths = vec![
             wineprefix.join("bin").join("wine"),
@@ -46,60 +427,101 @@ impl Wine {
             custom_wine_paths.push(parent.join("wine").join("bin").join("wine"));
         }
 
-        for wine_path in &custom_wine_paths {
-            if wine_path.exists() && wine_path.is_file() {
-                let potential_wineserver =
-                    wine_path.parent().and_then(|p| Some(p.join("wineserver")));
-
-                if let Some(ws_path) = potential_wineserver {
+        for wine_path in custom_wine_paths {
+            if wine_path.is_file() {
+                if let Some(ws_path) = wine_path.parent().map(|p| p.join("wineserver")) {
                     if ws_path.exists() {
-                        wine_bin = Some(wine_path.clone());
-                        wineserver_bin = Some(ws_path);
-                        break;
+                        return Some((wine_path, ws_path));
                     }
                 }
             }
         }
+        None
+    }
 
-        // Fall back to PATH if no custom Wine found
-        let wine_bin = match wine_bin {
-            Some(bin) => bin,
-            None => which("wine")
-                .map_err(|_| WinetricksError::Wine("wine binary not found in PATH".into()))?,
+    /// Check whether a wine binary is part of a Proton build
+    /// Proton ships wine as <proton dir>/files/bin/wine (older builds: dist/bin/wine),
+    /// next to the `proton` launcher script
+    pub fn is_proton(bin: &Path) -> bool {
+        let Some(dist_dir) = bin.parent().and_then(|bin_dir| bin_dir.parent()) else {
+            return false;
         };
+        let is_dist = dist_dir
+            .file_name()
+            .map(|name| name == "files" || name == "dist")
+            .unwrap_or(false);
+        is_dist
+            && dist_dir
+                .parent()
+                .map(|proton_dir| proton_dir.join("proton").is_file())
+                .unwrap_or(false)
+    }
 
-        let wineserver_bin = match wineserver_bin {
-            Some(bin) => bin,
-            None => which("wineserver")
-                .map_err(|_| WinetricksError::Wine("wineserver binary not found in PATH".into()))?,
-        };
+    /// Environment Proton's wine expects (STEAM_COMPAT_CLIENT_INSTALL_PATH,
+    /// STEAM_COMPAT_DATA_PATH) for the given wineprefix, to set on each command
+    /// run in it; empty for other Wine builds
+    /// A STEAM_COMPAT_CLIENT_INSTALL_PATH set by the user is left alone
+    pub fn proton_env(&self, wineprefix: &Path) -> Vec<(&'static str, PathBuf)> {
+        let mut env = Vec::new();
+        if !self.proton {
+            return env;
+        }
 
-        let version = Self::get_version(&wine_bin)?;
-        let version_stripped = Self::strip_version(&version);
+        if std::env::var_os("STEAM_COMPAT_CLIENT_INSTALL_PATH").is_none() {
+            // Official Proton lives in <steam>/steamapps/common, custom builds
+            // (e.g. GE-Proton) in <steam>/compatibilitytools.d
+            let steam_root = self
+                .wine_bin
+                .ancestors()
+                .find(|dir| {
+                    dir.file_name()
+                        .map(|name| name == "steamapps" || name == "compatibilitytools.d")
+                        .unwrap_or(false)
+                })
+                .and_then(|dir| dir.parent())
+                .map(Path::to_path_buf)
+                .or_else(Self::steam_root);
+            match steam_root {
+                Some(root) => env.push(("STEAM_COMPAT_CLIENT_INSTALL_PATH", root)),
+                None => tracing::warn!(
+                    "Proton detected at {:?}, but no Steam installation was found",
+                    self.wine_bin
+                ),
+            }
+        }
 
-        // Detect architecture by checking if wineserver is 64-bit
-        // This is a simplified check - real winetricks does more complex detection
-        // For now, default to win32 (will be improved later)
-        let arch = "win32".to_string();
+        // Proton keeps the wineprefix in <compatdata>/<appid>/pfx
+        let compat_data = if wineprefix.file_name().map(|n| n == "pfx").unwrap_or(false) {
+            wineprefix.parent().unwrap_or(wineprefix)
+        } else {
+            wineprefix
+        };
+        env.push(("STEAM_COMPAT_DATA_PATH", compat_data.to_path_buf()));
+        env
+    }
 
-        Ok(Self {
-            wine_bin,
-            wineserver_bin,
-            version,
-            version_stripped,
-            arch,
-        })
+    /// Locate the Steam installation (native or Flatpak)
+    pub fn steam_root() -> Option<PathBuf> {
+        let home = dirs::home_dir()?;
+        [
+            home.join(".steam").join("steam"),
+            home.join(".local").join("share").join("Steam"),
+            home.join(".var")
+                .join("app")
+                .join("com.valvesoftware.Steam")
+                .join("data")
+                .join("Steam"),
+        ]
+        .into_iter()
+        .find(|dir| dir.join("steamapps").is_dir())
     }
 
-    /// Get wine version
-    fn get_version(wine_bin: &PathBuf) -> Result<String> {
+    /// Run `wine --version`
+    fn query_version(wine_bin: &PathBuf) -> Result<String> {
         let output = Command::new(wine_bin)
             .arg("--version")
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("{:?} --version", wine_bin),
-                error: e.to_string(),
-            })?;
+            .map_err(|e| WinetricksError::command_io(format!("{:?} --version", wine_bin), e))?;
 
         let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
@@ -126,6 +548,372 @@ impl Wine {
             .to_string()
     }
 
+    /// Wait for wineserver to finish (wineserver -w), giving up after timeout
+    /// On timeout the hung wineserver is killed so later steps don't block forever
+    pub async fn wineserver_wait_timeout(&self, prefix: &Path, timeout: Duration) -> Result<()> {
+        let mut child = tokio::process::Command::new(&self.wineserver_bin)
+            .arg("-w")
+            .env("WINEPREFIX", prefix)
+            .spawn()
+            .map_err(|e| WinetricksError::command_io(format!("{:?} -w", self.wineserver_bin), e))?;
+
+        match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(WinetricksError::command_io(
+                format!("{:?} -w", self.wineserver_bin),
+                e,
+            )),
+            Err(_) => {
+                // Timed out - stop waiting and kill the hung wineserver
+                let _ = child.kill().await;
+                let _ = tokio::process::Command::new(&self.wineserver_bin)
+                    .arg("-k")
+                    .env("WINEPREFIX", prefix)
+                    .status()
+                    .await;
+                Err(WinetricksError::WineserverTimeout {
+                    timeout_secs: timeout.as_secs(),
+                })
+            }
+        }
+    }
+
+    /// Create a wineprefix with the given architecture (win32 or win64)
+    /// Runs wineboot with WINEPREFIX/WINEARCH set, then waits for wineserver to finish
+    pub async fn create_prefix(&self, path: &Path, arch: &str) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let status = tokio::process::Command::new(&self.wine_bin)
+            .arg("wineboot")
+            .env("WINEPREFIX", path)
+            .env("WINEARCH", arch)
+            .envs(self.proton_env(path))
+            .status()
+            .await
+            .map_err(|e| {
+                WinetricksError::command_io(
+                    format!("WINEARCH={} {:?} wineboot", arch, self.wine_bin),
+                    e,
+                )
+            })?;
+
+        if !status.success() {
+            return Err(WinetricksError::Wine(format!(
+                "wineboot failed to create prefix {:?} (exit status: {})",
+                path, status
+            )));
+        }
+
+        self.wineserver_wait_timeout(path, DEFAULT_WINESERVER_TIMEOUT)
+            .await
+    }
+
+    /// Run wineboot --update in a prefix (after placing DLLs or fonts in it directly),
+    /// then wait for wineserver to finish
+    pub async fn wineboot_update(&self, prefix: &Path) -> Result<()> {
+        let command = format!("{:?} wineboot --update", self.wine_bin);
+        let output = tokio::process::Command::new(&self.wine_bin)
+            .arg("wineboot")
+            .arg("--update")
+            .env("WINEPREFIX", prefix)
+            .envs(self.proton_env(prefix))
+            .output()
+            .await
+            .map_err(|e| WinetricksError::command_io(&command, e))?;
+
+        if !output.status.success() {
+            return Err(WinetricksError::command_failed(command, &output));
+        }
+
+        self.wineserver_wait_timeout(prefix, DEFAULT_WINESERVER_TIMEOUT)
+            .await
+    }
+
+    /// Check if a wineprefix has been initialized (drive_c/windows exists)
+    pub fn prefix_exists(path: &Path) -> bool {
+        path.join("drive_c/windows").is_dir()
+    }
+
+    /// List initialized wineprefixes: the default one (WINEPREFIX or ~/.wine) and the
+    /// named prefixes under prefixes_root, sorted by path
+    pub fn list_prefixes(prefixes_root: &Path) -> Vec<PathBuf> {
+        let mut prefixes = Self::prefixes_in(prefixes_root);
+
+        let default_prefix = Self::get_wineprefix();
+        if Self::prefix_exists(&default_prefix) && !prefixes.contains(&default_prefix) {
+            prefixes.push(default_prefix);
+        }
+
+        prefixes.sort();
+        prefixes
+    }
+
+    /// Initialized wineprefixes directly inside a directory, sorted by path
+    /// Proton's <appid>/pfx layout (Steam compatdata) is found as well
+    pub fn prefixes_in(root: &Path) -> Vec<PathBuf> {
+        let mut prefixes: Vec<PathBuf> = std::fs::read_dir(root)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter_map(|p| {
+                        if Self::prefix_exists(&p) {
+                            Some(p)
+                        } else {
+                            let pfx = p.join("pfx");
+                            Self::prefix_exists(&pfx).then_some(pfx)
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        prefixes.sort();
+        prefixes
+    }
+
+    /// List the processes running in a wineprefix (wineserver, Wine services and
+    /// Windows programs), sorted by pid
+    /// wineserver can't list its clients and pgrep can't match on environment
+    /// variables, so this looks for processes with WINEPREFIX set to the prefix in /proc
+    /// that are Wine's (see is_wine_process); winetricks itself and the shells that
+    /// started it inherit WINEPREFIX too and are skipped
+    pub fn list_running_processes(prefix: &Path) -> Result<Vec<WineProcess>> {
+        let prefix = prefix
+            .canonicalize()
+            .unwrap_or_else(|_| prefix.to_path_buf());
+        let own_chain = Self::process_chain(std::process::id());
+
+        let mut processes = Vec::new();
+        for entry in std::fs::read_dir("/proc")? {
+            let entry = entry?;
+            let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|s| s.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            if own_chain.contains(&pid) {
+                continue;
+            }
+
+            // Other users' processes (and ones that exited meanwhile) can't be read
+            let Ok(environ) = std::fs::read(entry.path().join("environ")) else {
+                continue;
+            };
+            let in_prefix = environ
+                .split(|b| *b == 0)
+                .filter_map(|var| var.strip_prefix(b"WINEPREFIX="))
+                .any(|value| {
+                    let value = Path::new(std::str::from_utf8(value).unwrap_or_default());
+                    value == prefix || value.canonicalize().is_ok_and(|v| v == prefix)
+                });
+            if !in_prefix {
+                continue;
+            }
+
+            // Wine puts the Windows path of the program in argv[0]
+            let cmdline = std::fs::read(entry.path().join("cmdline")).unwrap_or_default();
+            let exe = cmdline
+                .split(|b| *b == 0)
+                .next()
+                .map(|arg| String::from_utf8_lossy(arg).to_string())
+                .unwrap_or_default();
+            let binary = std::fs::read_link(entry.path().join("exe")).unwrap_or_default();
+            if !Self::is_wine_process(&binary, &exe) {
+                continue;
+            }
+            let name = exe
+                .rsplit(['/', '\\'])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            processes.push(WineProcess { pid, name, exe });
+        }
+
+        processes.sort_by_key(|p| p.pid);
+        Ok(processes)
+    }
+
+    /// Whether a process is Wine's: running one of Wine's own binaries (wineserver,
+    /// wine-preloader, ...) or a Windows program, which Wine starts with its Windows
+    /// path (C:\...) as argv[0]
+    fn is_wine_process(binary: &Path, argv0: &str) -> bool {
+        let binary_name = binary
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        let windows_path = argv0.as_bytes();
+        matches!(
+            binary_name,
+            "wine" | "wine64" | "wine-preloader" | "wine64-preloader" | "wineserver"
+        ) || (windows_path.len() > 2
+            && windows_path[0].is_ascii_alphabetic()
+            && windows_path[1] == b':'
+            && windows_path[2] == b'\\')
+    }
+
+    /// A process and its ancestors, from /proc/<pid>/stat
+    fn process_chain(pid: u32) -> Vec<u32> {
+        let mut chain = vec![pid];
+        let mut pid = pid;
+        while let Some(parent) = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .ok()
+            // "<pid> (<comm>) <state> <ppid> ...", comm may contain spaces and parentheses
+            .and_then(|stat| {
+                stat.rsplit_once(')')
+                    .and_then(|(_, rest)| rest.split_whitespace().nth(1)?.parse::<u32>().ok())
+            })
+            .filter(|parent| *parent > 0 && !chain.contains(parent))
+        {
+            chain.push(parent);
+            pid = parent;
+        }
+        chain
+    }
+
+    /// Kill every process running in a wineprefix (SIGKILL)
+    /// Returns the number of processes killed
+    pub fn kill_prefix_processes(prefix: &Path) -> Result<u32> {
+        let mut killed = 0;
+        for process in Self::list_running_processes(prefix)? {
+            if unsafe { libc::kill(process.pid as libc::pid_t, libc::SIGKILL) } == 0 {
+                killed += 1;
+            } else {
+                tracing::warn!(
+                    "Failed to kill {} ({}): {}",
+                    process.name,
+                    process.pid,
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+        Ok(killed)
+    }
+
+    /// Read the architecture of an existing wineprefix from system.reg (#arch=win32|win64)
+    pub fn prefix_arch(path: &Path) -> Result<String> {
+        let system_reg = path.join("system.reg");
+        let content = std::fs::read_to_string(&system_reg).map_err(|e| {
+            WinetricksError::Wine(format!("Failed to read {:?}: {}", system_reg, e))
+        })?;
+
+        if let Some(arch) = content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("#arch="))
+        {
+            return Ok(arch.trim().to_string());
+        }
+
+        // Older prefixes don't record the arch; syswow64 only exists in 64-bit prefixes
+        if path.join("drive_c/windows/syswow64").is_dir() {
+            Ok("win64".to_string())
+        } else {
+            Ok("win32".to_string())
+        }
+    }
+
+    /// Total size in bytes of the files inside a wineprefix
+    pub fn prefix_size(path: &Path) -> u64 {
+        walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| e.metadata().ok())
+            .map(|m| m.len())
+            .sum()
+    }
+
+    /// Write a wineprefix to a .tar.gz with a winetricks_export.json manifest
+    /// The archive is streamed to disk; symlinks (dosdevices, the user's folders) are
+    /// stored as links rather than followed
+    pub fn export_prefix(&self, prefix: &Path, output: &Path) -> Result<PrefixExportManifest> {
+        if !Self::prefix_exists(prefix) {
+            return Err(WinetricksError::Config(format!(
+                "Wineprefix does not exist: {}",
+                prefix.display()
+            )));
+        }
+
+        let manifest = PrefixExportManifest {
+            verbs: crate::install_log::read_log(prefix)?
+                .into_iter()
+                .map(|entry| entry.verb)
+                .collect(),
+            wine_version: self.version.clone(),
+            arch: Self::prefix_arch(prefix)?,
+            exported_at: crate::install_log::format_timestamp(std::time::SystemTime::now()),
+        };
+        let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+
+        let file = std::io::BufWriter::new(std::fs::File::create(output)?);
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        let mut archive = tar::Builder::new(encoder);
+        archive.follow_symlinks(false);
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest_json.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        );
+        header.set_cksum();
+        archive.append_data(&mut header, EXPORT_MANIFEST_NAME, manifest_json.as_slice())?;
+        archive.append_dir_all(".", prefix)?;
+
+        // Finish the tar stream, then the gzip stream, then flush the file
+        archive
+            .into_inner()?
+            .finish()?
+            .into_inner()
+            .map_err(|e| e.into_error())?;
+        Ok(manifest)
+    }
+
+    /// Extract a prefix export made by export_prefix() into dest (which must not exist)
+    pub fn import_prefix(input: &Path, dest: &Path) -> Result<PrefixExportManifest> {
+        if dest.exists() {
+            return Err(WinetricksError::Config(format!(
+                "{} already exists; choose another name or delete it first",
+                dest.display()
+            )));
+        }
+
+        let file = std::io::BufReader::new(std::fs::File::open(input)?);
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+        archive.set_preserve_permissions(true);
+        // unpack() refuses entries that would land outside dest
+        if let Err(e) = archive.unpack(dest) {
+            let _ = std::fs::remove_dir_all(dest);
+            return Err(e.into());
+        }
+
+        let manifest_path = dest.join(EXPORT_MANIFEST_NAME);
+        let manifest = std::fs::read(&manifest_path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok());
+        let Some(manifest) = manifest else {
+            let _ = std::fs::remove_dir_all(dest);
+            return Err(WinetricksError::Config(format!(
+                "{} is not a winetricks prefix export (no valid {})",
+                input.display(),
+                EXPORT_MANIFEST_NAME
+            )));
+        };
+        std::fs::remove_file(&manifest_path)?;
+        Ok(manifest)
+    }
+
+    /// Parsed version of this Wine installation
+    pub fn get_version(&self) -> Result<WineVersion> {
+        self.version_stripped.parse()
+    }
+
     /// Check if wine version is >= specified version
     pub fn version_ge(&self, version: &str) -> Result<bool> {
         self.compare_version(version, |a, b| a >= b)
@@ -170,34 +958,21 @@ impl Wine {
     /// Compare wine version with another version using a custom comparison function
     fn compare_version<F>(&self, other: &str, cmp: F) -> Result<bool>
     where
-        F: Fn(&[u32], &[u32]) -> bool,
+        F: Fn(&WineVersion, &WineVersion) -> bool,
     {
-        let self_version = Self::parse_version(&self.version_stripped)?;
-        let other_version = Self::parse_version(other)?;
+        let self_version = self.get_version()?;
+        let other_version: WineVersion = other.parse()?;
         Ok(cmp(&self_version, &other_version))
     }
 
-    /// Parse version string into a vector of version numbers
-    /// e.g., "8.0" -> [8, 0], "5.12" -> [5, 12]
-    fn parse_version(version: &str) -> Result<Vec<u32>> {
-        version
-            .split('.')
-            .map(|s| {
-                s.trim()
-                    .parse::<u32>()
-                    .map_err(|_| WinetricksError::InvalidWineVersion(version.to_string()))
-            })
-            .collect()
-    }
-
     /// Execute a wine command
     pub fn exec(&self, args: &[&str]) -> Result<std::process::Output> {
         let output = Command::new(&self.wine_bin)
             .args(args)
+            .envs(self.proton_env(&Self::get_wineprefix()))
             .output()
-            .map_err(|e| WinetricksError::CommandExecution {
-                command: format!("{:?} {:?}", self.wine_bin, args),
-                error: e.to_string(),
+            .map_err(|e| {
+                WinetricksError::command_io(format!("{:?} {:?}", self.wine_bin, args), e)
             })?;
 
         if !output.status.success() {
@@ -210,6 +985,348 @@ impl Wine {
         Ok(output)
     }
 
+    /// Run a wine command and capture its output
+    /// stderr is only shown on the terminal with verbosity >= 2 (wine is very chatty);
+    /// with a timeout, a command that runs too long is killed and reported as an error
+    pub fn run_and_capture(
+        &self,
+        args: &[&str],
+        env: &[(&str, &str)],
+        timeout: Option<Duration>,
+    ) -> Result<CommandOutput> {
+        let command = format!("wine {}", args.join(" "));
+        if self.verbosity >= 1 {
+            eprintln!("Executing {}", command);
+        }
+
+        let mut cmd = Command::new(&self.wine_bin);
+        cmd.args(args).envs(env.iter().copied());
+
+        self.capture_command(cmd, &command, timeout)?
+            .ok_or_else(|| {
+                WinetricksError::command_io(
+                    command,
+                    format!(
+                        "timed out after {} seconds",
+                        timeout.unwrap_or_default().as_secs()
+                    ),
+                )
+            })
+    }
+
+    /// Run an already set up wine command (binary, environment, working directory)
+    /// and capture its output, like run_and_capture()
+    /// Returns None if it ran longer than the timeout; the command and everything it
+    /// started (e.g. an installer's Setup.exe) is killed then
+    pub fn capture_command(
+        &self,
+        cmd: Command,
+        command: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Option<CommandOutput>> {
+        self.capture_command_with_log(cmd, command, timeout, &|_| {})
+    }
+
+    /// Like capture_command(), passing each stderr line to `on_stderr` as it is
+    /// written (e.g. to show an installer's output live)
+    pub fn capture_command_with_log(
+        &self,
+        mut cmd: Command,
+        command: &str,
+        timeout: Option<Duration>,
+        on_stderr: &dyn Fn(&str),
+    ) -> Result<Option<CommandOutput>> {
+        use std::io::{BufRead, BufReader, Read};
+        use std::os::unix::process::CommandExt;
+        use std::process::Stdio;
+
+        let wineprefix = cmd
+            .get_envs()
+            .find(|(key, _)| *key == "WINEPREFIX")
+            .and_then(|(_, value)| value)
+            .map(PathBuf::from);
+        if let Some(wineprefix) = wineprefix {
+            cmd.envs(self.proton_env(&wineprefix));
+        }
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // Own process group, so the whole process tree can be killed on timeout
+        if timeout.is_some() {
+            cmd.process_group(0);
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| WinetricksError::command_io(command, e))?;
+
+        // Drain both pipes on their own threads so a full pipe can't stall wine
+        let mut stdout_pipe = child.stdout.take();
+        let stdout_reader = std::thread::spawn(move || {
+            let mut stdout = String::new();
+            if let Some(ref mut pipe) = stdout_pipe {
+                let _ = pipe.read_to_string(&mut stdout);
+            }
+            stdout
+        });
+        let stderr_pipe = child.stderr.take();
+        let mirror_stderr = self.verbosity >= 2;
+        // Lines are handed back to this thread, which calls on_stderr while polling
+        let (line_tx, line_rx) = std::sync::mpsc::channel::<String>();
+        let stderr_reader = std::thread::spawn(move || {
+            let mut stderr = String::new();
+            if let Some(pipe) = stderr_pipe {
+                for line in BufReader::new(pipe).lines().map_while(|l| l.ok()) {
+                    if mirror_stderr {
+                        eprintln!("{}", line);
+                    }
+                    stderr.push_str(&line);
+                    stderr.push('\n');
+                    let _ = line_tx.send(line);
+                }
+            }
+            stderr
+        });
+
+        let started = std::time::Instant::now();
+        let status = loop {
+            line_rx.try_iter().for_each(|line| on_stderr(&line));
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if timeout.is_some_and(|t| started.elapsed() >= t) {
+                tracing::warn!(
+                    "{} still running after {} seconds, killing it",
+                    command,
+                    timeout.unwrap_or_default().as_secs()
+                );
+                // A negative pid signals the whole process group
+                unsafe {
+                    libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+                }
+                let _ = child.wait();
+                return Ok(None);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        };
+
+        let stderr = stderr_reader.join().unwrap_or_default();
+        line_rx.try_iter().for_each(|line| on_stderr(&line));
+        Ok(Some(CommandOutput {
+            stdout: stdout_reader.join().unwrap_or_default(),
+            stderr,
+            exit_code: status.code().unwrap_or(-1),
+        }))
+    }
+
+    /// Read the DLL override for a DLL (e.g. "native,builtin"), if one is set
+    /// Queries HKCU\Software\Wine\DllOverrides with `wine reg query`
+    pub fn get_dll_override(&self, prefix: &Path, dll: &str) -> Result<Option<String>> {
+        let prefix_str = prefix.to_string_lossy().to_string();
+        let output = self.run_and_capture(
+            &["reg", "query", DLL_OVERRIDES_KEY, "/v", dll],
+            &[("WINEPREFIX", &prefix_str)],
+            None,
+        )?;
+
+        // reg query fails when the value doesn't exist
+        if !output.success() {
+            return Ok(None);
+        }
+
+        Ok(Self::parse_reg_sz_values(&output.stdout)
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(dll))
+            .map(|(_, value)| value))
+    }
+
+    /// List all DLL overrides in a prefix (DLL name -> override)
+    pub fn list_dll_overrides(&self, prefix: &Path) -> Result<HashMap<String, String>> {
+        let prefix_str = prefix.to_string_lossy().to_string();
+        let output = self.run_and_capture(
+            &["reg", "query", DLL_OVERRIDES_KEY],
+            &[("WINEPREFIX", &prefix_str)],
+            None,
+        )?;
+
+        // A prefix without any overrides has no DllOverrides key
+        if !output.success() {
+            return Ok(HashMap::new());
+        }
+
+        Ok(Self::parse_reg_sz_values(&output.stdout)
+            .into_iter()
+            .collect())
+    }
+
+    /// Run winecfg with command line options (e.g. ["-v", "win10"]) in a prefix
+    pub fn run_winecfg_command(&self, prefix: &Path, args: &[&str]) -> Result<()> {
+        let prefix_str = prefix.to_string_lossy().to_string();
+        let mut winecfg_args = vec!["winecfg"];
+        winecfg_args.extend_from_slice(args);
+
+        let output = self.run_and_capture(&winecfg_args, &[("WINEPREFIX", &prefix_str)], None)?;
+        if !output.success() {
+            return Err(WinetricksError::wine_command_failed(
+                format!("wine {}", winecfg_args.join(" ")),
+                &output,
+            ));
+        }
+        Ok(())
+    }
+
+    /// winecfg name of a Windows version, for the winetricks spellings of the server
+    /// releases (win2k3 is winecfg's win2003)
+    pub fn windows_version_name(version: &str) -> &str {
+        match version {
+            "win2k3" => "win2003",
+            "win2k8" => "win2008",
+            "win2k8r2" => "win2008r2",
+            _ => version,
+        }
+    }
+
+    /// Set the Windows version of a prefix (winecfg -v), one of WINDOWS_VERSIONS
+    /// (or an alias, see windows_version_name()); "default" removes the prefix's
+    /// version so Wine falls back to its own default
+    pub fn set_windows_version(&self, prefix: &Path, version: &str) -> Result<()> {
+        let version = Self::windows_version_name(version);
+        if version == "default" {
+            return self.unset_windows_version(prefix);
+        }
+        Self::check_known_value("Windows version", version, WINDOWS_VERSIONS)?;
+        self.run_winecfg_command(prefix, &["-v", version])
+    }
+
+    /// Delete HKCU\Software\Wine\Version (winecfg has no option for this)
+    fn unset_windows_version(&self, prefix: &Path) -> Result<()> {
+        let prefix_str = prefix.to_string_lossy().to_string();
+        let env = [("WINEPREFIX", prefix_str.as_str())];
+        let delete = [
+            "reg",
+            "delete",
+            "HKEY_CURRENT_USER\\Software\\Wine",
+            "/v",
+            "Version",
+            "/f",
+        ];
+        let output = self.run_and_capture(&delete, &env, None)?;
+        if output.success() {
+            return Ok(());
+        }
+
+        // reg delete also fails when there is no version to delete
+        let query = [
+            "reg",
+            "query",
+            "HKEY_CURRENT_USER\\Software\\Wine",
+            "/v",
+            "Version",
+        ];
+        if !self.run_and_capture(&query, &env, None)?.success() {
+            return Ok(());
+        }
+        Err(WinetricksError::wine_command_failed(
+            format!("wine {}", delete.join(" ")),
+            &output,
+        ))
+    }
+
+    /// Set the audio driver of a prefix, one of AUDIO_DRIVERS
+    /// winecfg has no option for this, so the value is written like its Audio tab does
+    pub fn set_audio_driver(&self, prefix: &Path, driver: &str) -> Result<()> {
+        Self::check_known_value("audio driver", driver, AUDIO_DRIVERS)?;
+        // An empty driver list disables sound
+        let data = if driver == "disabled" { "" } else { driver };
+        self.set_driver(prefix, "Audio", Some(data))
+    }
+
+    /// Set the graphics driver of a prefix, one of GRAPHICS_DRIVERS
+    pub fn set_graphics_driver(&self, prefix: &Path, driver: &str) -> Result<()> {
+        Self::check_known_value("graphics driver", driver, GRAPHICS_DRIVERS)?;
+        let data = (driver != "default").then_some(driver);
+        self.set_driver(prefix, "Graphics", data)
+    }
+
+    /// Read the graphics driver of a prefix (e.g. "x11" or "wayland"), if one is set
+    pub fn get_graphics_driver(&self, prefix: &Path) -> Result<Option<String>> {
+        let prefix_str = prefix.to_string_lossy().to_string();
+        let output = self.run_and_capture(
+            &["reg", "query", DRIVERS_KEY, "/v", "Graphics"],
+            &[("WINEPREFIX", &prefix_str)],
+            None,
+        )?;
+
+        // reg query fails when the value doesn't exist
+        if !output.success() {
+            return Ok(None);
+        }
+
+        Ok(Self::parse_reg_sz_values(&output.stdout)
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Graphics"))
+            .map(|(_, value)| value)
+            .filter(|value| !value.is_empty()))
+    }
+
+    /// Write (or with None delete) a value under HKCU\Software\Wine\Drivers
+    fn set_driver(&self, prefix: &Path, name: &str, data: Option<&str>) -> Result<()> {
+        let prefix_str = prefix.to_string_lossy().to_string();
+        let args = match data {
+            Some(data) => vec![
+                "reg",
+                "add",
+                DRIVERS_KEY,
+                "/v",
+                name,
+                "/t",
+                "REG_SZ",
+                "/d",
+                data,
+                "/f",
+            ],
+            None => vec!["reg", "delete", DRIVERS_KEY, "/v", name, "/f"],
+        };
+
+        let output = self.run_and_capture(&args, &[("WINEPREFIX", &prefix_str)], None)?;
+        // Deleting a value that isn't set is fine
+        if !output.success() && data.is_some() {
+            return Err(WinetricksError::wine_command_failed(
+                format!("wine {}", args.join(" ")),
+                &output,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Config error for values outside a known-valid set (instead of writing junk to the registry)
+    fn check_known_value(what: &str, value: &str, known: &[&str]) -> Result<()> {
+        if known.contains(&value) {
+            return Ok(());
+        }
+        Err(WinetricksError::Config(format!(
+            "Unknown {} '{}' (expected one of: {})",
+            what,
+            value,
+            known.join(", ")
+        )))
+    }
+
+    /// Parse `    name    REG_SZ    value` lines from `wine reg query` output
+    fn parse_reg_sz_values(stdout: &str) -> Vec<(String, String)> {
+        stdout
+            .lines()
+            .filter_map(|line| {
+                let (name, value) = line.split_once("REG_SZ")?;
+                let name = name.trim();
+                if name.is_empty() {
+                    return None;
+                }
+                Some((name.to_string(), value.trim().to_string()))
+            })
+            .collect()
+    }
+
     /// Get wine prefix path
     pub fn get_wineprefix() -> PathBuf {
         std::env::var("WINEPREFIX")
@@ -218,3 +1335,76 @@ impl Wine {
             .unwrap_or_else(|| dirs::home_dir().unwrap().join(".wine"))
     }
 }
+
+/// Part of a name split by Wine::version_sort_key (numbers sort before text)
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum NamePart {
+    Number(u64),
+    Text(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proton_builds_sort_by_version_number() {
+        let mut names = [
+            "GE-Proton9-9",
+            "GE-Proton10-1",
+            "GE-Proton9-20",
+            "GE-Proton9-2",
+            "Proton-stable",
+        ];
+        names.sort_by_cached_key(|name| std::cmp::Reverse(Wine::version_sort_key(name)));
+        assert_eq!(
+            names,
+            [
+                "Proton-stable",
+                "GE-Proton10-1",
+                "GE-Proton9-20",
+                "GE-Proton9-9",
+                "GE-Proton9-2",
+            ]
+        );
+    }
+
+    #[test]
+    fn wine_processes_are_wine_binaries_or_windows_programs() {
+        for binary in [
+            "/usr/bin/wineserver",
+            "/usr/lib/wine/wine64-preloader",
+            "/opt/wine-staging/bin/wine",
+        ] {
+            assert!(
+                Wine::is_wine_process(Path::new(binary), "wine"),
+                "{}",
+                binary
+            );
+        }
+        assert!(Wine::is_wine_process(
+            Path::new("/usr/lib/wine/wine-preloader"),
+            "C:\\windows\\system32\\services.exe"
+        ));
+        assert!(Wine::is_wine_process(
+            Path::new("/usr/bin/wine-preloader-custom"),
+            "Z:\\home\\user\\setup.exe"
+        ));
+
+        // Shells, editors and winetricks itself only inherited WINEPREFIX
+        assert!(!Wine::is_wine_process(Path::new("/usr/bin/bash"), "bash"));
+        assert!(!Wine::is_wine_process(
+            Path::new("/usr/bin/winetricks"),
+            "winetricks"
+        ));
+        assert!(!Wine::is_wine_process(Path::new("/usr/bin/vim"), "C:"));
+    }
+
+    #[test]
+    fn process_chain_includes_the_parent() {
+        let chain = Wine::process_chain(std::process::id());
+        assert_eq!(chain[0], std::process::id());
+        assert!(chain.len() > 1);
+        assert!(chain[1..].contains(&std::os::unix::process::parent_id()));
+    }
+}